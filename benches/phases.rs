@@ -0,0 +1,95 @@
+//! Time the phases of reading and writing a GDTF file separately, over all
+//! example files.
+//!
+//! Geometries and DMX modes are parsed in one call, so their time is part of
+//! `parse`. Compare against `metadata`, which skips both.
+
+use std::io::Cursor;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use example_files::examples_iter;
+use opengdtf::{archive::GdtfArchive, name::Name, parse, parse_gdtf_metadata, serialize_gdtf};
+
+fn example_archives() -> Vec<Vec<u8>> {
+    examples_iter()
+        .map(|entry| std::fs::read(entry.path()).unwrap())
+        .collect()
+}
+
+fn phases(c: &mut Criterion) {
+    let archives = example_archives();
+    let descriptions: Vec<String> = archives
+        .iter()
+        .filter_map(|a| {
+            GdtfArchive::new(Cursor::new(a))
+                .and_then(|mut a| a.description())
+                .ok()
+        })
+        .collect();
+    let parsed: Vec<_> = archives
+        .iter()
+        .filter_map(|a| parse(Cursor::new(a)).ok())
+        .collect();
+
+    c.bench_function("unzip description", |b| {
+        b.iter(|| {
+            for a in &archives {
+                black_box(GdtfArchive::new(Cursor::new(a)).and_then(|mut a| a.description())).ok();
+            }
+        })
+    });
+    c.bench_function("xml", |b| {
+        b.iter(|| {
+            for d in &descriptions {
+                black_box(roxmltree::Document::parse(d)).ok();
+            }
+        })
+    });
+    c.bench_function("metadata", |b| {
+        b.iter(|| {
+            for a in &archives {
+                black_box(parse_gdtf_metadata(Cursor::new(a))).ok();
+            }
+        })
+    });
+    c.bench_function("parse", |b| {
+        b.iter(|| {
+            for a in &archives {
+                black_box(parse(Cursor::new(a))).ok();
+            }
+        })
+    });
+    // mostly valid names, like in real files, plus names of a large pixel bar
+    // in case there are no example files
+    let names: Vec<String> = parsed
+        .iter()
+        .flat_map(|p| p.gdtf.dmx_modes().iter())
+        .flat_map(|mode| mode.all_channels())
+        .flat_map(|ch| [ch.name.to_string(), ch.attribute.to_string()])
+        .chain((1..=1000).map(|i| format!("Pixel {i}_ColorAdd_R")))
+        .collect();
+    c.bench_function("names", |b| {
+        b.iter(|| {
+            for n in &names {
+                black_box(Name::try_from(n.as_str())).ok();
+            }
+        })
+    });
+    c.bench_function("integrity", |b| {
+        b.iter(|| {
+            for p in &parsed {
+                black_box(p.gdtf.integrity_report());
+            }
+        })
+    });
+    c.bench_function("serialize", |b| {
+        b.iter(|| {
+            for p in &parsed {
+                black_box(serialize_gdtf(&p.gdtf));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, phases);
+criterion_main!(benches);
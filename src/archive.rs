@@ -0,0 +1,276 @@
+//! Lazy access to the files of a GDTF archive, and to GDTF archives nested in
+//! another zip archive.
+
+use std::{
+    io::{Cursor, Read, Seek},
+    path::Path,
+};
+
+use zip::{read::ZipFile, result::ZipError, DateTime, ZipArchive};
+
+use crate::{
+    resources::{names_in_archive_order, ResourceMap},
+    ArchiveError, Error, ParseOptions, ParsedGdtf,
+};
+
+/// Metadata of a file in an archive, as listed in the zip central directory
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    /// path inside the archive
+    pub name: String,
+    /// uncompressed size in bytes
+    pub size: u64,
+    /// compressed size in bytes
+    pub compressed_size: u64,
+    pub crc32: u32,
+    /// in the local time of the archive creator, zip has no time zones
+    pub last_modified: DateTime,
+}
+
+/// An opened GDTF archive.
+///
+/// Only the central directory of the zip is read on construction. The
+/// description and resource files are read when requested.
+pub struct GdtfArchive<R: Read + Seek> {
+    zip: ZipArchive<R>,
+    /// paths in archive order
+    names: Vec<String>,
+}
+
+impl<R: Read + Seek> GdtfArchive<R> {
+    pub fn new(reader: R) -> Result<Self, Error> {
+        let (zip, names) = open_zip(reader)?;
+        Ok(Self { zip, names })
+    }
+
+    /// Paths of all files in the archive, in archive order
+    pub fn file_names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    /// Metadata of all files in the archive, in archive order. Nothing is
+    /// decompressed.
+    pub fn entries(&mut self) -> Result<Vec<ArchiveEntry>, Error> {
+        let zip = &mut self.zip;
+        (0..zip.len())
+            .map(|i| {
+                let file = zip.by_index_raw(i).map_err(ArchiveError::InvalidZip)?;
+                Ok(ArchiveEntry {
+                    name: file.name().to_owned(),
+                    size: file.size(),
+                    compressed_size: file.compressed_size(),
+                    crc32: file.crc32(),
+                    last_modified: file.last_modified(),
+                })
+            })
+            .collect()
+    }
+
+    /// Read the content of `description.xml`
+    pub fn description(&mut self) -> Result<String, Error> {
+        let mut description_file = self
+            .zip
+            .by_name("description.xml")
+            .map_err(ArchiveError::DescriptionXmlMissing)?;
+
+        let size: usize = description_file.size().try_into().unwrap_or(0);
+        let mut description = String::with_capacity(size);
+
+        description_file
+            .read_to_string(&mut description)
+            .map_err(ArchiveError::InvalidDescriptionXml)?;
+        Ok(description)
+    }
+
+    /// Get a reader for a single file in the archive, which is decompressed
+    /// while reading
+    pub fn file(&mut self, path: &str) -> Result<ZipFile<'_>, ZipError> {
+        self.zip.by_name(path)
+    }
+
+    /// Read and parse `description.xml`. The metadata of all files in the
+    /// archive is included in the result.
+    pub fn parse(&mut self, options: &ParseOptions) -> Result<ParsedGdtf, Error> {
+        let archive_entries = self.entries()?;
+        let description = self.description()?;
+        let mut parsed = crate::parser::parse_description(description, options)?;
+        parsed.archive_entries = archive_entries;
+        Ok(parsed)
+    }
+
+    /// Keep the archive open for extracting typed resources
+    pub fn into_resource_map(self) -> ResourceMap<R> {
+        ResourceMap::with_names(self.zip, self.names)
+    }
+}
+
+/// A zip archive containing GDTF files, like a show file bundle.
+///
+/// Nested GDTF files are read into memory when opened, nothing is extracted
+/// to disk.
+pub struct GdtfBundle<R: Read + Seek> {
+    zip: ZipArchive<R>,
+    /// paths in archive order
+    names: Vec<String>,
+}
+
+impl<R: Read + Seek> GdtfBundle<R> {
+    pub fn new(reader: R) -> Result<Self, Error> {
+        let (zip, names) = open_zip(reader)?;
+        Ok(Self { zip, names })
+    }
+
+    /// Paths of all files with the extension `.gdtf`, in archive order
+    pub fn gdtf_names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str).filter(|name| {
+            matches!(
+                Path::new(name).extension().and_then(|e| e.to_str()),
+                Some(e) if e.eq_ignore_ascii_case("gdtf")
+            )
+        })
+    }
+
+    /// Read a nested GDTF file into memory and open it
+    pub fn open(&mut self, path: &str) -> Result<GdtfArchive<Cursor<Vec<u8>>>, Error> {
+        let mut file = self.zip.by_name(path).map_err(ArchiveError::InvalidZip)?;
+        let mut buf = Vec::with_capacity(file.size().try_into().unwrap_or(0));
+        file.read_to_end(&mut buf)
+            .map_err(|source| ArchiveError::InvalidNestedGdtf {
+                path: path.to_owned(),
+                source,
+            })?;
+        GdtfArchive::new(Cursor::new(buf))
+    }
+
+    /// Parse all nested GDTF files one after another. Yields the path of each
+    /// file with its result.
+    pub fn parse_all<'a>(
+        &'a mut self,
+        options: &'a ParseOptions,
+    ) -> impl Iterator<Item = (String, Result<ParsedGdtf, Error>)> + 'a {
+        let names: Vec<String> = self.gdtf_names().map(str::to_owned).collect();
+        names.into_iter().map(move |name| {
+            let parsed = self.open(&name).and_then(|mut a| a.parse(options));
+            (name, parsed)
+        })
+    }
+}
+
+/// Read the central directory and the paths of all files in archive order
+fn open_zip<R: Read + Seek>(reader: R) -> Result<(ZipArchive<R>, Vec<String>), Error> {
+    let mut zip = ZipArchive::new(reader).map_err(ArchiveError::InvalidZip)?;
+    let names = names_in_archive_order(&mut zip).map_err(ArchiveError::InvalidZip)?;
+    Ok((zip, names))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::{write::FileOptions, ZipWriter};
+
+    use super::*;
+
+    #[test]
+    fn lazy_access() {
+        let mut writer = ZipWriter::new(Cursor::new(vec![]));
+        writer
+            .start_file("description.xml", FileOptions::default())
+            .unwrap();
+        writer
+            .write_all(br#"<GDTF DataVersion="1.2"><FixtureType Name="Test"/></GDTF>"#)
+            .unwrap();
+        writer
+            .start_file("wheels/gobo.png", FileOptions::default())
+            .unwrap();
+        writer.write_all(b"png").unwrap();
+        let mut cursor = writer.finish().unwrap();
+        cursor.set_position(0);
+
+        let mut archive = GdtfArchive::new(cursor).unwrap();
+        assert_eq!(
+            archive.file_names().collect::<Vec<_>>(),
+            vec!["description.xml", "wheels/gobo.png"]
+        );
+        let mut content = String::new();
+        archive
+            .file("wheels/gobo.png")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "png");
+
+        let parsed = archive.parse(&Default::default()).unwrap();
+        assert_eq!(parsed.gdtf.name, "Test");
+        let entry = parsed.archive_entries.get(1).unwrap();
+        assert_eq!(entry.name, "wheels/gobo.png");
+        assert_eq!(entry.size, 3);
+        assert_eq!(entry.crc32, 2199389072);
+        assert_eq!(parsed.archive_entries.len(), 2);
+
+        assert_eq!(archive.into_resource_map().wheel_media().count(), 1);
+    }
+
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(vec![]));
+        for (name, content) in files {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(content).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn nested_gdtf_files() {
+        let gdtf = |name: &str| {
+            zip(&[(
+                "description.xml",
+                format!(r#"<GDTF DataVersion="1.2"><FixtureType Name="{name}"/></GDTF>"#)
+                    .as_bytes(),
+            )])
+        };
+        let (spot, wash) = (gdtf("Spot"), gdtf("Wash"));
+        let bundle = zip(&[
+            ("show.xml", b"<Show/>".as_slice()),
+            ("fixtures/Spot.gdtf", spot.as_slice()),
+            ("fixtures/Wash.GDTF", wash.as_slice()),
+            ("fixtures/Broken.gdtf", b"not a zip".as_slice()),
+        ]);
+
+        let mut bundle = GdtfBundle::new(Cursor::new(bundle)).unwrap();
+        assert_eq!(
+            bundle.gdtf_names().collect::<Vec<_>>(),
+            vec![
+                "fixtures/Spot.gdtf",
+                "fixtures/Wash.GDTF",
+                "fixtures/Broken.gdtf"
+            ]
+        );
+
+        let options = Default::default();
+        let results: Vec<_> = bundle.parse_all(&options).collect();
+        let names: Vec<_> = results
+            .iter()
+            .map(|(path, parsed)| {
+                (
+                    path.as_str(),
+                    parsed.as_ref().map(|p| p.gdtf.name.to_string()).ok(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("fixtures/Spot.gdtf", Some("Spot".into())),
+                ("fixtures/Wash.GDTF", Some("Wash".into())),
+                ("fixtures/Broken.gdtf", None),
+            ]
+        );
+        assert!(matches!(
+            bundle.open("missing.gdtf"),
+            Err(Error::Archive(ArchiveError::InvalidZip(
+                ZipError::FileNotFound
+            )))
+        ));
+    }
+}
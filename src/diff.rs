@@ -0,0 +1,378 @@
+//! Structured comparison of two fixture types, e.g. to review the changes
+//! between two revisions of a file.
+//!
+//! Geometries, DMX modes, channels and channel functions are matched by name,
+//! so a renamed element is reported as removed and added. Values are compared
+//! in the form they are written to `description.xml`.
+
+use std::collections::BTreeMap;
+
+use crate::{channel::Channel, dmx_modes::DmxMode, geometries::Geometries, name::Name, Gdtf};
+
+/// Differences between two fixture types, see [`diff`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GdtfDiff {
+    /// changed attributes of the FixtureType and the DataVersion
+    pub metadata: Vec<FieldChange>,
+    pub geometries: Vec<Change>,
+    pub dmx_modes: Vec<ModeChange>,
+}
+
+impl GdtfDiff {
+    /// Whether the fixture types are equal in all compared values
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty() && self.geometries.is_empty() && self.dmx_modes.is_empty()
+    }
+}
+
+/// A changed value, named like the XML attribute it is written to. Values of
+/// channel functions are prefixed with the name of the channel function, like
+/// `Dimmer.PhysicalTo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// Change of a named element, like a geometry or a channel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added(Name),
+    Removed(Name),
+    Changed {
+        name: Name,
+        fields: Vec<FieldChange>,
+    },
+}
+
+/// Change of a DMX mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModeChange {
+    Added(Name),
+    Removed(Name),
+    Changed {
+        name: Name,
+        fields: Vec<FieldChange>,
+        channels: Vec<Change>,
+    },
+}
+
+/// Compare fixture type `a` to its revision `b`.
+///
+/// Elements are reported in the order of `a`, followed by the elements only
+/// `b` has, in the order of `b`.
+pub fn diff(a: &Gdtf, b: &Gdtf) -> GdtfDiff {
+    GdtfDiff {
+        metadata: changed_fields(&metadata(a), &metadata(b)),
+        geometries: diff_named(
+            &geometries(&a.geometries),
+            &geometries(&b.geometries),
+            |name, a, b| {
+                let fields = changed_fields(a, b);
+                (!fields.is_empty()).then_some(Change::Changed { name, fields })
+            },
+            Change::Added,
+            Change::Removed,
+        ),
+        dmx_modes: diff_named(
+            &modes(a),
+            &modes(b),
+            |name, (a_fields, a_mode), (b_fields, b_mode)| {
+                let fields = changed_fields(a_fields, b_fields);
+                let channels = diff_named(
+                    &channels(a, a_mode),
+                    &channels(b, b_mode),
+                    |name, a, b| {
+                        let fields = changed_fields(a, b);
+                        (!fields.is_empty()).then_some(Change::Changed { name, fields })
+                    },
+                    Change::Added,
+                    Change::Removed,
+                );
+                (!fields.is_empty() || !channels.is_empty()).then_some(ModeChange::Changed {
+                    name,
+                    fields,
+                    channels,
+                })
+            },
+            ModeChange::Added,
+            ModeChange::Removed,
+        ),
+    }
+}
+
+/// Values of an element as pairs of field name and value
+type Fields = Vec<(String, String)>;
+
+/// Match elements by name and report the ones only in `a`, the changed ones,
+/// and the ones only in `b`
+fn diff_named<T, C>(
+    a: &[(Name, T)],
+    b: &[(Name, T)],
+    changed: impl Fn(Name, &T, &T) -> Option<C>,
+    added: impl Fn(Name) -> C,
+    removed: impl Fn(Name) -> C,
+) -> Vec<C> {
+    let b_by_name: BTreeMap<&Name, &T> = b.iter().map(|(name, v)| (name, v)).collect();
+    let mut changes = vec![];
+    for (name, a_value) in a {
+        match b_by_name.get(name) {
+            Some(b_value) => changes.extend(changed(name.clone(), a_value, b_value)),
+            None => changes.push(removed(name.clone())),
+        }
+    }
+    changes.extend(
+        b.iter()
+            .filter(|(name, _)| !a.iter().any(|(a_name, _)| a_name == name))
+            .map(|(name, _)| added(name.clone())),
+    );
+    changes
+}
+
+fn changed_fields(a: &Fields, b: &Fields) -> Vec<FieldChange> {
+    let b_by_field: BTreeMap<&String, &String> = b.iter().map(|(k, v)| (k, v)).collect();
+    let missing = String::new();
+    let mut changes: Vec<FieldChange> = a
+        .iter()
+        .filter_map(|(field, old)| {
+            let new = b_by_field.get(field).copied().unwrap_or(&missing);
+            (old != new).then(|| FieldChange {
+                field: field.clone(),
+                old: old.clone(),
+                new: new.clone(),
+            })
+        })
+        .collect();
+    changes.extend(
+        b.iter()
+            .filter(|(field, _)| !a.iter().any(|(a_field, _)| a_field == field))
+            .map(|(field, new)| FieldChange {
+                field: field.clone(),
+                old: missing.clone(),
+                new: new.clone(),
+            }),
+    );
+    changes
+}
+
+fn fields<const N: usize>(pairs: [(&str, String); N]) -> Fields {
+    pairs.into_iter().map(|(k, v)| (k.to_owned(), v)).collect()
+}
+
+fn metadata(gdtf: &Gdtf) -> Fields {
+    fields([
+        ("DataVersion", gdtf.data_version.to_string()),
+        ("Name", gdtf.name.to_string()),
+        ("ShortName", gdtf.short_name.clone()),
+        ("LongName", gdtf.long_name.clone()),
+        ("Manufacturer", gdtf.manufacturer.clone()),
+        ("Description", gdtf.description.clone()),
+        ("FixtureTypeID", gdtf.fixture_type_id.to_string()),
+        ("Thumbnail", gdtf.thumbnail.clone().unwrap_or_default()),
+        (
+            "RefFT",
+            gdtf.ref_ft.map(|id| id.to_string()).unwrap_or_default(),
+        ),
+        ("CanHaveChildren", gdtf.can_have_children.to_string()),
+    ])
+}
+
+fn geometries(geometries: &Geometries) -> Vec<(Name, Fields)> {
+    geometries
+        .graph()
+        .node_indices()
+        .filter_map(|i| {
+            let g = geometries.get_by_index(i).ok()?;
+            let parent = geometries
+                .parent_index(i)
+                .and_then(|p| geometries.get_by_index(p).ok())
+                .map(|p| p.name.to_string())
+                .unwrap_or_default();
+            Some((
+                g.name.clone(),
+                fields([
+                    ("Type", format!("{:?}", g.t)),
+                    ("Parent", parent),
+                    ("Position", g.position.to_string()),
+                    (
+                        "Model",
+                        g.model.as_ref().map(Name::to_string).unwrap_or_default(),
+                    ),
+                ]),
+            ))
+        })
+        .collect()
+}
+
+fn modes(gdtf: &Gdtf) -> Vec<(Name, (Fields, &DmxMode))> {
+    gdtf.dmx_modes()
+        .iter()
+        .map(|mode| {
+            let geometry = mode
+                .geometry_name(&gdtf.geometries)
+                .map(Name::to_string)
+                .unwrap_or_default();
+            (
+                mode.name.clone(),
+                (
+                    fields([
+                        ("Description", mode.description.clone()),
+                        ("Geometry", geometry),
+                    ]),
+                    mode,
+                ),
+            )
+        })
+        .collect()
+}
+
+fn channels(gdtf: &Gdtf, mode: &DmxMode) -> Vec<(Name, Fields)> {
+    mode.all_channels()
+        .map(|ch| (ch.name.clone(), channel_fields(gdtf, mode, ch)))
+        .collect()
+}
+
+fn channel_fields(gdtf: &Gdtf, mode: &DmxMode, ch: &Channel) -> Fields {
+    let geometry = gdtf
+        .geometries
+        .get_by_index(ch.geometry)
+        .map(|g| g.name.to_string())
+        .unwrap_or_default();
+    let offsets = ch
+        .offsets
+        .iter()
+        .map(u16::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    let functions: Vec<_> = ch
+        .defined_functions()
+        .iter()
+        .filter_map(|i| mode.channel_functions.node_weight(*i))
+        .collect();
+    let function_names = functions
+        .iter()
+        .map(|chf| chf.name.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let mut fields = fields([
+        ("Attribute", ch.attribute.to_string()),
+        ("Geometry", geometry),
+        ("DMXBreak", ch.dmx_break.to_string()),
+        ("Offset", offsets),
+        ("Default", ch.default.to_string()),
+        (
+            "Highlight",
+            ch.highlight.map(|v| v.to_string()).unwrap_or_default(),
+        ),
+        ("ChannelFunctions", function_names),
+    ]);
+    for chf in functions {
+        let values = [
+            ("Attribute", chf.attr.clone()),
+            ("DMXFrom", chf.dmx_from.to_string()),
+            ("Default", chf.default.to_string()),
+            ("PhysicalFrom", chf.phys_from.to_string()),
+            ("PhysicalTo", chf.phys_to.to_string()),
+            (
+                "Wheel",
+                chf.wheel.as_ref().map(Name::to_string).unwrap_or_default(),
+            ),
+        ];
+        fields.extend(
+            values
+                .into_iter()
+                .map(|(attr, v)| (format!("{}.{attr}", chf.name), v)),
+        );
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::GdtfBuilder;
+
+    use super::*;
+
+    fn revision(dimmer_to: f64, with_strobe: bool) -> Gdtf {
+        let mut builder = GdtfBuilder::new("Manufacturer", "Test")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .geometry("Beam", Some("Body"))
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Beam", "Dimmer", 1, vec![1])
+            .unwrap()
+            .channel_function("Mode", "Beam_Dimmer", "Dimmer", "Dimmer", 0, 0., dimmer_to)
+            .unwrap();
+        if with_strobe {
+            builder = builder
+                .channel("Mode", "Beam", "Shutter1", 1, vec![2])
+                .unwrap();
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn diff_revisions() {
+        let a = revision(1., false);
+        assert!(diff(&a, &a).is_empty());
+        assert_eq!(
+            diff(&a, &revision(1., false))
+                .metadata
+                .first()
+                .unwrap()
+                .field,
+            "FixtureTypeID"
+        );
+
+        let mut b = revision(2., true);
+        b.fixture_type_id = a.fixture_type_id;
+        b.long_name = "Test Fixture".into();
+
+        let d = diff(&a, &b);
+        assert_eq!(
+            d.metadata,
+            vec![FieldChange {
+                field: "LongName".into(),
+                old: "Test".into(),
+                new: "Test Fixture".into(),
+            }]
+        );
+        assert!(d.geometries.is_empty());
+        let [ModeChange::Changed {
+            name,
+            fields,
+            channels,
+        }] = d.dmx_modes.as_slice()
+        else {
+            panic!("unexpected mode changes {:?}", d.dmx_modes);
+        };
+        assert_eq!(name, "Mode");
+        assert!(fields.is_empty());
+        assert_eq!(
+            channels,
+            &vec![
+                Change::Changed {
+                    name: "Beam_Dimmer".try_into().unwrap(),
+                    fields: vec![FieldChange {
+                        field: "Dimmer.PhysicalTo".into(),
+                        old: "1.000000".into(),
+                        new: "2.000000".into(),
+                    }],
+                },
+                Change::Added("Beam_Shutter1".try_into().unwrap()),
+            ]
+        );
+
+        let reversed = diff(&b, &a);
+        assert!(matches!(
+            reversed.dmx_modes.as_slice(),
+            [ModeChange::Changed { channels, .. }]
+                if matches!(channels.last(), Some(Change::Removed(name)) if name == "Beam_Shutter1")
+        ));
+    }
+}
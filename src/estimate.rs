@@ -0,0 +1,202 @@
+//! Rough estimate of the light output and power consumption of a DMX mode,
+//! e.g. for power and brightness budgeting.
+//!
+//! The estimate is approximate. Dimmer curves are taken from the physical
+//! values of the Dimmer channel functions, but the output of a beam is assumed
+//! to be proportional to its dimmer, and so is its power consumption. Emitters
+//! are not parsed yet, so color mixing is not taken into account: every beam
+//! contributes its full LuminousFlux at full level.
+
+use petgraph::graph::NodeIndex;
+
+use crate::{
+    channel::Channel, dmx_modes::DmxMode, geometries::Geometries, geometry::Type,
+    resolve::interpolate, Gdtf,
+};
+
+/// Approximate output of a DMX mode, see the [module documentation](self)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ApproximateOutput {
+    /// in lm
+    pub luminous_flux: f64,
+    /// in W, only counting the beams
+    pub power_consumption: f64,
+    /// number of beams, with one beam per instance of a template geometry
+    pub beams: usize,
+}
+
+/// Estimate the output of `mode` when all Dimmer channels are at `dmx_level`.
+///
+/// The level is scaled to the resolution of each channel, so 255 is full on
+/// for 16 bit channels as well. Dimmer channels multiply, like a master dimmer
+/// and the dimmers of individual cells. Beams without any Dimmer channel above
+/// them are assumed to follow the level linearly.
+pub fn estimate_output(gdtf: &Gdtf, mode: &DmxMode, dmx_level: u8) -> ApproximateOutput {
+    let geometries = &gdtf.geometries;
+    let dimmers: Vec<(&Channel, f64)> = mode
+        .all_channels()
+        .filter(|ch| ch.attribute == "Dimmer")
+        .map(|ch| (ch, dimmer_fraction(mode, ch, dmx_level)))
+        .collect();
+
+    let mut output = ApproximateOutput {
+        luminous_flux: 0.,
+        power_consumption: 0.,
+        beams: 0,
+    };
+    for (beam_index, reference) in beam_instances(geometries, *mode.geometry()) {
+        let Ok(geometry) = geometries.get_by_index(beam_index) else {
+            continue;
+        };
+        let Type::Beam(beam) = &geometry.t else {
+            continue;
+        };
+        let controlling: Vec<f64> = dimmers
+            .iter()
+            .filter(|(ch, _)| controls(geometries, ch, beam_index, reference))
+            .map(|(_, fraction)| *fraction)
+            .collect();
+        let fraction = if controlling.is_empty() {
+            f64::from(dmx_level) / 255.
+        } else {
+            controlling.iter().product()
+        };
+        output.luminous_flux += beam.luminous_flux * fraction;
+        output.power_consumption += beam.power_consumption * fraction;
+        output.beams += 1;
+    }
+    output
+}
+
+/// Relative output of a Dimmer channel at `dmx_level`, from 0 to 1, relative to
+/// the highest physical value of its channel functions
+fn dimmer_fraction(mode: &DmxMode, ch: &Channel, dmx_level: u8) -> f64 {
+    let value = (0..ch.bytes).fold(0u32, |value, _| value << 8 | u32::from(dmx_level));
+    let functions: Vec<_> = ch
+        .defined_functions()
+        .iter()
+        .filter_map(|i| mode.channel_functions.node_weight(*i))
+        .collect();
+    let max = functions
+        .iter()
+        .map(|chf| chf.phys_from.value().max(chf.phys_to.value()))
+        .fold(0., f64::max);
+    let Some(active) = functions
+        .iter()
+        .find(|chf| (chf.dmx_from..=chf.dmx_to).contains(&value))
+    else {
+        return 0.;
+    };
+    if max <= 0. {
+        return 0.;
+    }
+    (interpolate(active, value).value() / max).clamp(0., 1.)
+}
+
+/// All beams of the mode geometry, as pairs of the beam geometry and the
+/// GeometryReference it is instantiated by, if it is in a template
+fn beam_instances(
+    geometries: &Geometries,
+    mode_geometry: NodeIndex,
+) -> Vec<(NodeIndex, Option<NodeIndex>)> {
+    let mut instances = vec![];
+    for i in geometries.graph().node_indices() {
+        if !matches!(geometries.get_by_index(i), Ok(g) if matches!(g.t, Type::Beam(_))) {
+            continue;
+        }
+        let top_level = geometries.top_level_geometry_index(i);
+        if top_level == mode_geometry {
+            instances.push((i, None));
+        } else if geometries.is_template(top_level) {
+            instances.extend(
+                geometries
+                    .template_references(top_level)
+                    .filter(|r| geometries.top_level_geometry_index(*r) == mode_geometry)
+                    .map(|r| (i, Some(r))),
+            );
+        }
+    }
+    instances
+}
+
+/// Whether the Dimmer channel `ch` controls the instance of `beam` created by
+/// `reference`
+fn controls(
+    geometries: &Geometries,
+    ch: &Channel,
+    beam: NodeIndex,
+    reference: Option<NodeIndex>,
+) -> bool {
+    match (ch.template_geometry, reference) {
+        (Some(template_geometry), Some(reference)) => {
+            ch.reference() == Some(reference) && is_at_or_below(geometries, beam, template_geometry)
+        }
+        (Some(_), None) => false,
+        (None, Some(reference)) => is_at_or_below(geometries, reference, ch.geometry),
+        (None, None) => is_at_or_below(geometries, beam, ch.geometry),
+    }
+}
+
+fn is_at_or_below(geometries: &Geometries, geometry: NodeIndex, root: NodeIndex) -> bool {
+    geometry == root || geometries.ancestors(geometry).any(|a| a == root)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse_description;
+
+    use super::*;
+
+    #[test]
+    fn led_pixel_bar() {
+        let description = r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Bar" Manufacturer="Test" FixtureTypeID="00000000-0000-0000-0000-000000000001">
+    <Geometries>
+        <Geometry Name="Body">
+            <Beam Name="Spot" LuminousFlux="1000" PowerConsumption="50"/>
+            <GeometryReference Name="Pixel 1" Geometry="Pixel">
+                <Break DMXBreak="1" DMXOffset="2"/>
+            </GeometryReference>
+            <GeometryReference Name="Pixel 2" Geometry="Pixel">
+                <Break DMXBreak="1" DMXOffset="3"/>
+            </GeometryReference>
+        </Geometry>
+        <Geometry Name="Pixel">
+            <Beam Name="LED" LuminousFlux="200" PowerConsumption="10"/>
+        </Geometry>
+    </Geometries>
+    <DMXModes>
+        <DMXMode Name="Mode" Geometry="Body">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Body">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Name="Dimmer" Attribute="Dimmer" DMXFrom="0/1" PhysicalFrom="0" PhysicalTo="1"/>
+                    </LogicalChannel>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Pixel">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Name="Dimmer" Attribute="Dimmer" DMXFrom="0/1" PhysicalFrom="0" PhysicalTo="1"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#;
+        let gdtf = parse_description(description.into(), &Default::default())
+            .unwrap()
+            .gdtf;
+        let mode = gdtf.dmx_modes().first().unwrap();
+
+        let full = estimate_output(&gdtf, mode, 255);
+        assert_eq!(full.beams, 3);
+        assert!((full.luminous_flux - 1400.).abs() < 1e-9);
+        assert!((full.power_consumption - 70.).abs() < 1e-9);
+
+        // the master dimmer and the pixel dimmers multiply
+        let fifth = estimate_output(&gdtf, mode, 51);
+        assert!((fifth.luminous_flux - (1000. * 0.2 + 2. * 200. * 0.04)).abs() < 1e-9);
+
+        assert_eq!(estimate_output(&gdtf, mode, 0).luminous_flux, 0.);
+    }
+}
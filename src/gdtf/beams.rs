@@ -0,0 +1,121 @@
+use petgraph::graph::NodeIndex;
+
+use crate::{
+    geometry::{Beam, LampType, Type},
+    name::Name,
+    Gdtf,
+};
+
+/// Photometric data of one Beam geometry, see [`Gdtf::beams`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BeamPhotometry {
+    /// graph index of the Beam geometry in `Gdtf::geometries`
+    pub geometry: NodeIndex,
+    pub name: Name,
+    pub lamp_type: LampType,
+    /// in degrees
+    pub beam_angle: f64,
+    /// in degrees
+    pub field_angle: f64,
+    /// in lm
+    pub luminous_flux: f64,
+    /// in K
+    pub color_temperature: f64,
+}
+
+impl Gdtf {
+    /// Photometric data of all Beam geometries, ordered by graph index, so
+    /// previz tools can set up light sources.
+    ///
+    /// Beams inside a template geometry are listed once, not once per
+    /// GeometryReference. Their placement per instance is available with
+    /// [`Geometries::instance_world_matrix`](crate::geometries::Geometries::instance_world_matrix).
+    pub fn beams(&self) -> Vec<BeamPhotometry> {
+        self.geometries
+            .graph()
+            .node_indices()
+            .filter_map(|i| {
+                let geometry = self.geometries.graph().node_weight(i)?;
+                let Type::Beam(Beam {
+                    lamp_type,
+                    luminous_flux,
+                    color_temperature,
+                    beam_angle,
+                    field_angle,
+                    ..
+                }) = geometry.t
+                else {
+                    return None;
+                };
+                Some(BeamPhotometry {
+                    geometry: i,
+                    name: geometry.name.clone(),
+                    lamp_type,
+                    beam_angle,
+                    field_angle,
+                    luminous_flux,
+                    color_temperature,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{geometry::Geometry, name::IntoValidName};
+
+    use super::*;
+
+    #[test]
+    fn beams_of_all_geometries() {
+        let mut gdtf = Gdtf::default();
+        let body = gdtf
+            .geometries
+            .add_top_level(Geometry {
+                name: "Body".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        let beam = gdtf
+            .geometries
+            .add(
+                Geometry {
+                    name: "Beam".into_valid(),
+                    t: Type::Beam(Beam {
+                        lamp_type: LampType::Led,
+                        beam_angle: 12.,
+                        field_angle: 20.,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                body,
+            )
+            .unwrap();
+        gdtf.geometries
+            .add_top_level(Geometry {
+                name: "Spare".into_valid(),
+                t: Type::Beam(Beam::default()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let beams = gdtf.beams();
+        assert_eq!(beams.len(), 2);
+        assert_eq!(
+            beams.first(),
+            Some(&BeamPhotometry {
+                geometry: beam,
+                name: "Beam".into_valid(),
+                lamp_type: LampType::Led,
+                beam_angle: 12.,
+                field_angle: 20.,
+                luminous_flux: 10000.,
+                color_temperature: 6000.,
+            })
+        );
+        assert_eq!(beams.get(1).map(|b| b.name.as_str()), Some("Spare"));
+    }
+}
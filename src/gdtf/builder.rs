@@ -0,0 +1,374 @@
+use petgraph::graph::NodeIndex;
+
+use crate::{
+    channel::{Channel, DefaultSource},
+    channel_offsets::{ChannelOffsets, OffsetError},
+    checked_graph::CheckedGraphError,
+    dmx_break::Break,
+    dmx_modes::{ChannelFunction, DmxMode},
+    geometries::GeometriesError,
+    geometry::{Geometry, Type},
+    name::{Name, NameError},
+    physical_value::PhysicalValue,
+    Gdtf, GdtfError,
+};
+
+/// Fluent builder for authoring a fixture type in code.
+///
+/// Every step is validated and returns an error instead of building an
+/// invalid fixture type. Geometries, DMX modes and channels are referred to by
+/// name.
+///
+/// ```
+/// # use opengdtf::builder::GdtfBuilder;
+/// let gdtf = GdtfBuilder::new("Manufacturer", "Fixture")?
+///     .geometry("Body", None)?
+///     .geometry("Beam", Some("Body"))?
+///     .dmx_mode("Mode 1", "Body")?
+///     .channel("Mode 1", "Beam", "Dimmer", 1, vec![1, 2])?
+///     .channel_function("Mode 1", "Beam_Dimmer", "Dimmer", "Dimmer", 0, 0., 1.)?
+///     .build();
+/// assert_eq!(gdtf.dmx_mode(0)?.channels.len(), 1);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct GdtfBuilder {
+    gdtf: Gdtf,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum BuilderError {
+    #[error("{0}")]
+    InvalidName(#[from] NameError),
+    #[error("{0}")]
+    Geometries(#[from] GeometriesError),
+    #[error("{0}")]
+    Gdtf(#[from] GdtfError),
+    #[error("{0}")]
+    Offsets(#[from] OffsetError),
+    #[error("{0}")]
+    Graph(#[from] CheckedGraphError),
+    #[error("unknown geometry '{0}'")]
+    UnknownGeometry(Name),
+    #[error("unknown DMX mode '{0}'")]
+    UnknownDmxMode(Name),
+    #[error("DMX mode name '{0}' already taken")]
+    DuplicateDmxMode(Name),
+    #[error("unknown channel '{channel}' in DMX mode '{mode}'")]
+    UnknownChannel { channel: Name, mode: Name },
+    #[error("channel name '{channel}' already taken in DMX mode '{mode}'")]
+    DuplicateChannel { channel: Name, mode: Name },
+    #[error("invalid DMX break {0}")]
+    InvalidBreak(u16),
+    #[error(
+        "DMXFrom {dmx_from} must be bigger than the previous one and at most {max} for this channel"
+    )]
+    InvalidDmxFrom { dmx_from: u32, max: u32 },
+    #[error("physical value must be finite")]
+    NonFinitePhysicalValue,
+}
+
+impl GdtfBuilder {
+    pub fn new(manufacturer: impl Into<String>, name: &str) -> Result<Self, BuilderError> {
+        let mut gdtf = Gdtf {
+            name: name.try_into()?,
+            manufacturer: manufacturer.into(),
+            fixture_type_id: uuid::Uuid::new_v4(),
+            ..Default::default()
+        };
+        gdtf.short_name = gdtf.name.to_string();
+        gdtf.long_name = gdtf.name.to_string();
+        Ok(Self { gdtf })
+    }
+
+    /// Add a general geometry, top-level if `parent` is None
+    pub fn geometry(mut self, name: &str, parent: Option<&str>) -> Result<Self, BuilderError> {
+        let geometry = Geometry {
+            name: name.try_into()?,
+            t: Type::General,
+            ..Default::default()
+        };
+        match parent {
+            Some(parent) => {
+                let parent = self.geometry_index(parent)?;
+                self.gdtf.geometries.add(geometry, parent)?
+            }
+            None => self.gdtf.geometries.add_top_level(geometry)?,
+        };
+        Ok(self)
+    }
+
+    /// Add a DMX mode operating on the top-level `geometry`
+    pub fn dmx_mode(mut self, name: &str, geometry: &str) -> Result<Self, BuilderError> {
+        let name: Name = name.try_into()?;
+        if self.gdtf.dmx_modes.iter().any(|m| m.name == name) {
+            return Err(BuilderError::DuplicateDmxMode(name));
+        }
+        let geometry = self.geometry_index(geometry)?;
+        self.gdtf.add_dmx_mode(name, String::new(), geometry)?;
+        Ok(self)
+    }
+
+    /// Add a channel to a DMX mode. The channel is named
+    /// `{geometry}_{attribute}` and starts with a raw DMX channel function.
+    ///
+    /// Empty `offsets` create a virtual channel.
+    pub fn channel(
+        mut self,
+        mode: &str,
+        geometry: &str,
+        attribute: &str,
+        dmx_break: u16,
+        offsets: Vec<u16>,
+    ) -> Result<Self, BuilderError> {
+        let geometry_index = self.geometry_index(geometry)?;
+        let geometry_name = self
+            .gdtf
+            .geometries
+            .get_by_index(geometry_index)?
+            .name
+            .clone();
+        let attribute: Name = attribute.try_into()?;
+        let name: Name = format!("{geometry_name}_{attribute}").try_into()?;
+        let dmx_break: Break = dmx_break
+            .try_into()
+            .map_err(|_| BuilderError::InvalidBreak(dmx_break))?;
+        let offsets = ChannelOffsets::try_from(offsets)?;
+        let bytes = if offsets.is_empty() {
+            4 // maximum resolution for virtual channels
+        } else {
+            offsets.len() as u8
+        };
+
+        let mode_name: Name = mode.try_into()?;
+        let mode = self.mode_mut(&mode_name)?;
+        if mode.all_channels().any(|ch| ch.name == name) {
+            return Err(BuilderError::DuplicateChannel {
+                channel: name,
+                mode: mode_name,
+            });
+        }
+        let raw = mode.channel_functions.add_node(ChannelFunction {
+            name: name.clone(),
+            raw_dmx: true,
+            geometry: geometry_index,
+            template_geometry: None,
+            attr: "NoFeature".into(),
+            original_attr: "RawDMX".into(),
+            dmx_from: 0,
+            dmx_to: max_dmx_value(bytes),
+            phys_from: PhysicalValue::ZERO,
+            phys_to: PhysicalValue::ONE,
+            real_fade: 0.,
+            real_acceleration: 0.,
+            min: PhysicalValue::ZERO,
+            max: PhysicalValue::ONE,
+            default: 0,
+            wheel: None,
+            channel_sets: vec![],
+            extensions: Default::default(),
+            source: None,
+        })?;
+        let index_in_mode = mode.channels.len();
+        let id = mode.new_channel_id();
+        mode.channels.push(Channel {
+            name,
+            geometry: geometry_index,
+            template_geometry: None,
+            attribute,
+            index_in_mode,
+            id,
+            dmx_break,
+            bytes,
+            offsets,
+            channel_functions: vec![raw],
+            initial_function: None,
+            default: 0,
+            default_source: DefaultSource::RawChannelFunction,
+            highlight: None,
+            extensions: Default::default(),
+            source: None,
+        });
+        mode.index_channel_functions();
+        Ok(self)
+    }
+
+    /// Add a channel function to a channel.
+    ///
+    /// Channel functions must be added in ascending order of `dmx_from`. The
+    /// DMXTo of the previous channel function is adjusted accordingly.
+    #[allow(clippy::too_many_arguments)]
+    pub fn channel_function(
+        mut self,
+        mode: &str,
+        channel: &str,
+        name: &str,
+        attribute: &str,
+        dmx_from: u32,
+        phys_from: f64,
+        phys_to: f64,
+    ) -> Result<Self, BuilderError> {
+        let name: Name = name.try_into()?;
+        let channel: Name = channel.try_into()?;
+        let mode_name: Name = mode.try_into()?;
+        let [phys_from, phys_to] = [phys_from, phys_to].map(PhysicalValue::try_from);
+        let (Ok(phys_from), Ok(phys_to)) = (phys_from, phys_to) else {
+            return Err(BuilderError::NonFinitePhysicalValue);
+        };
+
+        let mode = self.mode_mut(&mode_name)?;
+        let Some(ch) = mode.channels.iter_mut().find(|ch| ch.name == channel) else {
+            return Err(BuilderError::UnknownChannel {
+                channel,
+                mode: mode_name,
+            });
+        };
+        let max = max_dmx_value(ch.bytes);
+
+        // the raw DMX channel function is not part of the DMX ranges
+        let previous = match ch.defined_functions().last() {
+            Some(i) => mode.channel_functions.node_weight_mut(*i),
+            None => None,
+        };
+        match previous {
+            Some(previous) if dmx_from <= previous.dmx_from || dmx_from > max => {
+                return Err(BuilderError::InvalidDmxFrom { dmx_from, max })
+            }
+            Some(previous) => previous.dmx_to = dmx_from - 1,
+            None if dmx_from > max => return Err(BuilderError::InvalidDmxFrom { dmx_from, max }),
+            None => {}
+        }
+
+        let (geometry, template_geometry) = (ch.geometry, ch.template_geometry);
+        let index = mode.channel_functions.add_node(ChannelFunction {
+            name,
+            raw_dmx: false,
+            geometry,
+            template_geometry,
+            attr: attribute.to_owned(),
+            original_attr: String::new(),
+            dmx_from,
+            dmx_to: max,
+            phys_from,
+            phys_to,
+            real_fade: 0.,
+            real_acceleration: 0.,
+            min: phys_from,
+            max: phys_to,
+            default: dmx_from,
+            wheel: None,
+            channel_sets: vec![],
+            extensions: Default::default(),
+            source: None,
+        })?;
+        ch.channel_functions.push(index);
+        mode.index_channel_functions();
+        Ok(self)
+    }
+
+    pub fn build(self) -> Gdtf {
+        self.gdtf
+    }
+
+    fn geometry_index(&self, name: &str) -> Result<NodeIndex, BuilderError> {
+        let name: Name = name.try_into()?;
+        self.gdtf
+            .geometries
+            .get_index(&name)
+            .ok_or(BuilderError::UnknownGeometry(name))
+    }
+
+    fn mode_mut(&mut self, name: &Name) -> Result<&mut DmxMode, BuilderError> {
+        self.gdtf
+            .dmx_modes
+            .iter_mut()
+            .find(|m| &m.name == name)
+            .ok_or_else(|| BuilderError::UnknownDmxMode(name.clone()))
+    }
+}
+
+pub(crate) fn max_dmx_value(bytes: u8) -> u32 {
+    u32::MAX >> (32 - 8 * u32::from(bytes.clamp(1, 4)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_fixture() {
+        let gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .geometry("Beam", Some("Body"))
+            .unwrap()
+            .dmx_mode("Mode 1", "Body")
+            .unwrap()
+            .channel("Mode 1", "Beam", "Dimmer", 1, vec![1])
+            .unwrap()
+            .channel_function("Mode 1", "Beam_Dimmer", "Closed", "Dimmer", 0, 0., 0.)
+            .unwrap()
+            .channel_function("Mode 1", "Beam_Dimmer", "Dimmer", "Dimmer", 10, 0., 1.)
+            .unwrap()
+            .build();
+
+        assert_eq!(gdtf.manufacturer, "Manufacturer");
+        let mode = gdtf.dmx_mode(0).unwrap();
+        let ch = mode.channels.first().unwrap();
+        assert_eq!(ch.name, "Beam_Dimmer");
+        assert_eq!(ch.channel_functions.len(), 3);
+        let dmx_ranges: Vec<_> = ch.functions(mode).map(|f| f.dmx_range).collect();
+        assert_eq!(dmx_ranges, vec![0..=255, 0..=9, 10..=255]);
+    }
+
+    #[test]
+    fn invalid_steps_are_rejected() {
+        let builder = || {
+            GdtfBuilder::new("Manufacturer", "Fixture")
+                .unwrap()
+                .geometry("Body", None)
+                .unwrap()
+        };
+        assert!(matches!(
+            GdtfBuilder::new("Manufacturer", "Invalid."),
+            Err(BuilderError::InvalidName(_))
+        ));
+        assert!(matches!(
+            builder().geometry("Beam", Some("Missing")),
+            Err(BuilderError::UnknownGeometry(_))
+        ));
+        assert!(matches!(
+            builder().dmx_mode("Mode", "Missing"),
+            Err(BuilderError::UnknownGeometry(_))
+        ));
+        assert!(matches!(
+            builder()
+                .dmx_mode("Mode", "Body")
+                .unwrap()
+                .dmx_mode("Mode", "Body"),
+            Err(BuilderError::DuplicateDmxMode(_))
+        ));
+        assert!(matches!(
+            builder().dmx_mode("Mode", "Body").unwrap().channel(
+                "Mode",
+                "Body",
+                "Dimmer",
+                1,
+                vec![513]
+            ),
+            Err(BuilderError::Offsets(_))
+        ));
+        assert!(matches!(
+            builder()
+                .dmx_mode("Mode", "Body")
+                .unwrap()
+                .channel("Mode", "Body", "Dimmer", 1, vec![1])
+                .unwrap()
+                .channel_function("Mode", "Body_Dimmer", "A", "Dimmer", 10, 0., 1.)
+                .unwrap()
+                .channel_function("Mode", "Body_Dimmer", "B", "Dimmer", 5, 0., 1.),
+            Err(BuilderError::InvalidDmxFrom { dmx_from: 5, .. })
+        ));
+    }
+}
@@ -1,14 +1,198 @@
-use crate::{channel_offsets::ChannelOffsets, dmx_break::Break, name::Name};
-use petgraph::graph::NodeIndex;
+use std::ops::RangeInclusive;
+
+use crate::{
+    channel_offsets::ChannelOffsets,
+    dmx_break::Break,
+    dmx_modes::{ChannelFunction, ChfId, DmxMode, ModeMaster},
+    extensions::Extensions,
+    name::Name,
+    source_location::SourceLocation,
+};
+use petgraph::{graph::NodeIndex, visit::EdgeRef, Direction::Incoming};
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Channel {
     pub name: Name,
+    /// geometry the channel belongs to; for template channels, this is the
+    /// GeometryReference the instance was created for
+    pub geometry: NodeIndex,
+    /// for template channels, the geometry in the subtree of the template the
+    /// channel belongs to; `None` for other channels. Together with
+    /// `geometry`, it identifies the instance of that geometry for the
+    /// reference, see [`Geometries::instantiate`].
+    ///
+    /// [`Geometries::instantiate`]: crate::geometries::Geometries::instantiate
+    pub template_geometry: Option<NodeIndex>,
+    /// attribute of the first logical channel
+    pub attribute: Name,
+    /// 0-based position of the DMXChannel in the DMX mode
+    pub index_in_mode: usize,
+    /// identifies the channel in its DMX mode, see [`ChannelId`]. Assigned
+    /// when the channel is created and, unlike `index_in_mode`, not changed
+    /// by edits of the mode. Template channel instances share the id of their
+    /// template channel.
+    ///
+    /// [`ChannelId`]: crate::dmx_modes::ChannelId
+    pub id: usize,
     pub dmx_break: Break,
     /// only between 1 to 4 bytes are supported
     pub bytes: u8,
     pub offsets: ChannelOffsets,
-    /// first one must always be the Raw DMX Channel Function
-    pub channel_functions: Vec<NodeIndex>,
+    /// graph indices in the channel functions of the mode, first one must
+    /// always be the Raw DMX Channel Function, see
+    /// [`Channel::defined_functions`] to skip it
+    pub(crate) channel_functions: Vec<NodeIndex>,
+    /// channel function referenced by InitialFunction, `None` if absent or
+    /// invalid
+    pub(crate) initial_function: Option<NodeIndex>,
     pub default: u32,
+    /// which channel function `default` was taken from
+    pub default_source: DefaultSource,
+    /// DMX value for the highlight function of consoles, `None` if absent
+    pub highlight: Option<u32>,
+    /// attributes of the DMXChannel that are not represented by other fields
+    pub extensions: Extensions,
+    /// where this was parsed from, `None` if not parsed from a file
+    pub source: Option<SourceLocation>,
+}
+
+/// The channel function that determines the default value of a channel
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DefaultSource {
+    /// the channel function referenced by InitialFunction
+    InitialFunction,
+    /// the first channel function after the raw DMX channel function
+    FirstChannelFunction,
+    /// the raw DMX channel function, whose default is 0
+    #[default]
+    RawChannelFunction,
+    /// the `Default` attribute of the DMXChannel, which only GDTF 1.0 defines
+    DmxChannelDefault,
+}
+
+impl Channel {
+    /// For template channels, the GeometryReference the channel was
+    /// instantiated for, `None` for other channels
+    pub fn reference(&self) -> Option<NodeIndex> {
+        self.template_geometry.map(|_| self.geometry)
+    }
+
+    /// The implicit raw DMX channel function, which covers the whole DMX
+    /// range. It is the target of ModeMasters that reference the channel.
+    pub(crate) fn raw_function(&self) -> Option<NodeIndex> {
+        self.channel_functions.first().copied()
+    }
+
+    /// The channel functions defined in the file, without the raw DMX channel
+    /// function
+    pub(crate) fn defined_functions(&self) -> &[NodeIndex] {
+        self.channel_functions.get(1..).unwrap_or_default()
+    }
+
+    /// The channel function referenced by InitialFunction, `None` if the
+    /// channel has none
+    pub fn initial_function<'a>(&self, mode: &'a DmxMode) -> Option<&'a ChannelFunction> {
+        mode.channel_functions.node_weight(self.initial_function?)
+    }
+
+    /// Iterate over the channel functions of the channel in `mode`, starting
+    /// with the raw DMX channel function, with their DMX range and the
+    /// ModeMasters they depend on.
+    ///
+    /// Yields nothing if the channel is not part of `mode`.
+    pub fn functions<'a>(&'a self, mode: &'a DmxMode) -> impl Iterator<Item = FunctionEntry<'a>> {
+        self.channel_functions.iter().filter_map(move |i| {
+            let id = mode.chf_id(*i)?;
+            let chf = mode.channel_functions.node_weight(*i)?;
+            let mode_masters = mode
+                .channel_functions
+                .edges_directed(*i, Incoming)
+                .filter_map(|e| Some((mode.chf_id(e.source())?, e.weight())))
+                .collect();
+            Some(FunctionEntry {
+                id,
+                chf,
+                dmx_range: chf.dmx_from..=chf.dmx_to,
+                mode_masters,
+            })
+        })
+    }
+
+    /// Split `value` into the bytes of the channel, most significant first.
+    /// Yields the offset and the byte value, nothing for virtual channels.
+    pub fn dmx_bytes(&self, value: u32) -> impl Iterator<Item = (u16, u8)> + '_ {
+        let len = self.offsets.len();
+        self.offsets
+            .iter()
+            .enumerate()
+            .map(move |(byte_index, offset)| {
+                (*offset, (value >> (8 * (len - 1 - byte_index))) as u8)
+            })
+    }
+}
+
+/// A channel function of a channel, see [`Channel::functions`]
+#[derive(Debug, Clone)]
+pub struct FunctionEntry<'a> {
+    pub id: ChfId,
+    pub chf: &'a ChannelFunction,
+    pub dmx_range: RangeInclusive<u32>,
+    /// each master channel function with the DMX range of the master in
+    /// which this channel function is active
+    pub mode_masters: Vec<(ChfId, &'a ModeMaster)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::GdtfBuilder;
+
+    #[test]
+    fn functions_with_mode_masters() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Control", 1, vec![1])
+            .unwrap()
+            .channel("Mode", "Body", "Dimmer", 1, vec![2])
+            .unwrap()
+            .channel_function("Mode", "Body_Dimmer", "Closed", "Dimmer", 0, 0., 0.)
+            .unwrap()
+            .channel_function("Mode", "Body_Dimmer", "Dimmer", "Dimmer", 10, 0., 1.)
+            .unwrap()
+            .build();
+        let mode = gdtf.dmx_mode_mut(0).unwrap();
+        let ids: Vec<_> = mode
+            .all_channels()
+            .flat_map(|ch| ch.functions(mode).map(|f| f.id))
+            .collect();
+        let control = *ids.first().unwrap();
+        let dimmer = *ids.last().unwrap();
+        mode.add_mode_master(control, dimmer, 0, 127).unwrap();
+
+        let channel = mode.channels.get(1).unwrap();
+        let functions: Vec<_> = channel
+            .functions(mode)
+            .map(|f| {
+                let masters: Vec<_> = f
+                    .mode_masters
+                    .iter()
+                    .map(|(i, m)| (*i, m.from, m.to))
+                    .collect();
+                (f.chf.name.to_string(), f.dmx_range, masters)
+            })
+            .collect();
+        assert_eq!(
+            functions,
+            [
+                ("Body_Dimmer".into(), 0..=255, vec![]),
+                ("Closed".into(), 0..=9, vec![]),
+                ("Dimmer".into(), 10..=255, vec![(control, 0, 127)]),
+            ]
+        );
+    }
 }
@@ -0,0 +1,188 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{channel::Channel, dmx_modes::DmxMode};
+
+/// Maximum length of a channel label in characters. Many consoles can't
+/// display more in their channel views.
+pub const MAX_LABEL_LEN: usize = 8;
+
+/// Strategy for the short label of a channel, before it is shortened to
+/// [`MAX_LABEL_LEN`] and numbered, see [`DmxMode::channel_labels`].
+///
+/// Implemented for closures, so a custom strategy can be passed as
+/// `|ch: &Channel| ...`.
+pub trait LabelStrategy {
+    fn label(&self, channel: &Channel) -> String;
+}
+
+impl<F: Fn(&Channel) -> String> LabelStrategy for F {
+    fn label(&self, channel: &Channel) -> String {
+        self(channel)
+    }
+}
+
+/// Label channels by the pretty name GDTF defines for the attribute of their
+/// first logical channel, e.g. `Dim` for `Dimmer` or `G1 <>` for `Gobo1Pos`.
+///
+/// Attributes without a known pretty name are labelled with their name,
+/// without underscores.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PrettyAttribute;
+
+impl LabelStrategy for PrettyAttribute {
+    fn label(&self, channel: &Channel) -> String {
+        let attribute = channel.attribute.as_str();
+        pretty_attribute(attribute).unwrap_or_else(|| attribute.replace('_', ""))
+    }
+}
+
+/// Pretty names of the standard attributes from GDTF 1.2, Annex A. `{n}` is
+/// replaced by the number of attributes like `Gobo(n)`.
+const PRETTY_NAMES: &[(&str, &str)] = &[
+    ("Dimmer", "Dim"),
+    ("Pan", "P"),
+    ("Tilt", "T"),
+    ("PanRotate", "P Rotate"),
+    ("TiltRotate", "T Rotate"),
+    ("ColorAdd_R", "R"),
+    ("ColorAdd_G", "G"),
+    ("ColorAdd_B", "B"),
+    ("ColorAdd_C", "C"),
+    ("ColorAdd_M", "M"),
+    ("ColorAdd_Y", "Y"),
+    ("ColorAdd_W", "W"),
+    ("ColorAdd_WW", "WW"),
+    ("ColorAdd_CW", "CW"),
+    ("ColorAdd_UV", "UV"),
+    ("ColorSub_C", "C"),
+    ("ColorSub_M", "M"),
+    ("ColorSub_Y", "Y"),
+    ("CTO", "CTO"),
+    ("CTB", "CTB"),
+    ("Zoom", "Zoom"),
+    ("Iris", "Iris"),
+    ("Focus{n}", "Focus{n}"),
+    ("Gobo{n}", "G{n}"),
+    ("Gobo{n}Pos", "G{n} <>"),
+    ("Gobo{n}PosRotate", "G{n} <>R"),
+    ("Color{n}", "C{n}"),
+    ("Shutter{n}", "Sh{n}"),
+    ("Shutter{n}Strobe", "Strobe{n}"),
+    ("Prism{n}", "Prism{n}"),
+    ("Frost{n}", "Frost{n}"),
+    ("Effects{n}", "FX{n}"),
+    ("Control{n}", "Ctrl{n}"),
+];
+
+fn pretty_attribute(attribute: &str) -> Option<String> {
+    // split the first number off, e.g. `Gobo1Pos` into `Gobo{n}Pos` and `1`
+    let (pattern, n) = match attribute.find(|c: char| c.is_ascii_digit()) {
+        Some(start) => {
+            let (prefix, rest) = attribute.split_at(start);
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let (n, suffix) = rest.split_at(end);
+            (format!("{prefix}{{n}}{suffix}"), n)
+        }
+        None => (attribute.to_owned(), ""),
+    };
+    PRETTY_NAMES
+        .iter()
+        .find(|(name, _)| *name == pattern)
+        .map(|(_, pretty)| pretty.replace("{n}", n))
+}
+
+impl DmxMode {
+    /// Short labels for all channels, in the order of
+    /// [`DmxMode::all_channels`].
+    ///
+    /// Labels are at most [`MAX_LABEL_LEN`] characters long. Labels that occur
+    /// more than once are numbered in channel order, e.g. `Dim1` and `Dim2`,
+    /// shortening them further if needed, so all labels are unique.
+    pub fn channel_labels(&self, strategy: &impl LabelStrategy) -> Vec<(&Channel, String)> {
+        let labels: Vec<_> = self
+            .all_channels()
+            .map(|ch| (ch, truncate(strategy.label(ch).trim(), MAX_LABEL_LEN)))
+            .collect();
+
+        let mut counts = HashMap::<String, usize>::new();
+        for (_, label) in &labels {
+            *counts.entry(label.clone()).or_default() += 1;
+        }
+
+        let mut taken: HashSet<String> = labels
+            .iter()
+            .filter(|(_, label)| counts.get(label) == Some(&1))
+            .map(|(_, label)| label.clone())
+            .collect();
+        let mut next_number = HashMap::<String, usize>::new();
+        labels
+            .into_iter()
+            .map(|(ch, label)| {
+                if counts.get(&label) == Some(&1) {
+                    return (ch, label);
+                }
+                let number = next_number.entry(label.clone()).or_insert(1);
+                loop {
+                    let suffix = number.to_string();
+                    *number += 1;
+                    let base = truncate(&label, MAX_LABEL_LEN.saturating_sub(suffix.len()));
+                    let numbered = format!("{}{suffix}", base.trim_end());
+                    if taken.insert(numbered.clone()) {
+                        return (ch, numbered);
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    s.chars().take(max_chars).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::GdtfBuilder;
+
+    use super::*;
+
+    #[test]
+    fn numbered_pretty_labels() {
+        let gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .geometry("Cell 1", Some("Body"))
+            .unwrap()
+            .geometry("Cell 2", Some("Body"))
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Pan", 1, vec![1])
+            .unwrap()
+            .channel("Mode", "Cell 1", "Dimmer", 1, vec![2])
+            .unwrap()
+            .channel("Mode", "Cell 2", "Dimmer", 1, vec![3])
+            .unwrap()
+            .channel("Mode", "Body", "Gobo1Pos", 1, vec![4])
+            .unwrap()
+            .channel("Mode", "Body", "VeryLongVendorAttribute", 1, vec![5])
+            .unwrap()
+            .build();
+        let mode = gdtf.dmx_modes().first().unwrap();
+
+        let labels = |labels: Vec<(&Channel, String)>| -> Vec<String> {
+            labels.into_iter().map(|(_, label)| label).collect()
+        };
+        assert_eq!(
+            labels(mode.channel_labels(&PrettyAttribute)),
+            ["P", "Dim1", "Dim2", "G1 <>", "VeryLong"]
+        );
+        assert_eq!(
+            labels(mode.channel_labels(&|_: &Channel| "Channel".to_owned())),
+            ["Channel1", "Channel2", "Channel3", "Channel4", "Channel5"]
+        );
+    }
+}
@@ -7,6 +7,8 @@ use derive_more::IntoIterator;
 /// Values go from 1 to 512. Empty indicates a virtual channel. The maximum
 /// number of supported bytes per channel is 4. Duplicates are disallowed.
 #[derive(Default, Debug, IntoIterator, derive_more::Deref, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Vec<u16>"))]
 pub struct ChannelOffsets(Vec<u16>);
 
 #[derive(Debug, thiserror::Error)]
@@ -65,10 +67,19 @@ impl FromStr for ChannelOffsets {
 }
 
 impl ChannelOffsets {
-    /// Adds `value` to all elements of the channel offset
-    pub fn add_all(mut self, value: u16) -> Result<Self, OffsetError> {
-        self.0.iter_mut().for_each(|v| *v += value);
-        self.0.try_into()
+    /// Adds `value` to all elements of the channel offset. Offsets that end up
+    /// beyond 512, e.g. in the next universe, are an error.
+    pub fn add_all(self, value: u32) -> Result<Self, OffsetError> {
+        self.0
+            .into_iter()
+            .map(|v| {
+                u32::from(v)
+                    .checked_add(value)
+                    .and_then(|v| u16::try_from(v).ok())
+                    .ok_or(OffsetError::OutsideRange)
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .try_into()
     }
 }
 
@@ -78,7 +89,7 @@ mod tests {
 
     #[test]
     fn test_creation() -> Result<(), OffsetError> {
-        assert_eq!(ChannelOffsets::default().0, []);
+        assert_eq!(ChannelOffsets::default().0, Vec::<u16>::new());
         assert_eq!(ChannelOffsets::try_from(vec![])?, ChannelOffsets::default());
         assert_eq!(
             ChannelOffsets::from_str("1,4")?,
@@ -110,4 +121,22 @@ mod tests {
         ));
         Ok(())
     }
+
+    #[test]
+    fn add_all() -> Result<(), OffsetError> {
+        let offsets = ChannelOffsets::try_from(vec![1, 2])?;
+        assert_eq!(
+            offsets.clone().add_all(510)?,
+            ChannelOffsets(vec![511, 512])
+        );
+        assert!(matches!(
+            offsets.clone().add_all(511),
+            Err(OffsetError::OutsideRange)
+        ));
+        assert!(matches!(
+            offsets.add_all(u32::MAX),
+            Err(OffsetError::OutsideRange)
+        ));
+        Ok(())
+    }
 }
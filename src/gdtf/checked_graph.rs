@@ -33,6 +33,87 @@ where
     }
 }
 
+/// Serialized as the node weights in index order and the edges with the
+/// indices of the nodes they connect
+#[cfg(feature = "serde")]
+impl<N, E, Ty, Ix> serde::Serialize for CheckedGraph<N, E, Ty, Ix>
+where
+    N: serde::Serialize,
+    E: serde::Serialize,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        #[derive(serde::Serialize)]
+        struct Edge<'a, E> {
+            from: usize,
+            to: usize,
+            weight: &'a E,
+        }
+
+        let edges: Vec<_> = self
+            .0
+            .raw_edges()
+            .iter()
+            .map(|e| Edge {
+                from: e.source().index(),
+                to: e.target().index(),
+                weight: &e.weight,
+            })
+            .collect();
+        let mut s = serializer.serialize_struct("CheckedGraph", 2)?;
+        s.serialize_field("nodes", &self.0.node_weights().collect::<Vec<_>>())?;
+        s.serialize_field("edges", &edges)?;
+        s.end()
+    }
+}
+
+/// Rebuilds the graph from the serialized form, edges to missing nodes are an
+/// error
+#[cfg(feature = "serde")]
+impl<'de, N, E, Ty, Ix> serde::Deserialize<'de> for CheckedGraph<N, E, Ty, Ix>
+where
+    N: serde::Deserialize<'de>,
+    E: serde::Deserialize<'de>,
+    Ty: EdgeType,
+    Ix: IndexType,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(serde::Deserialize)]
+        struct Edge<E> {
+            from: usize,
+            to: usize,
+            weight: E,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Flat<N, E> {
+            nodes: Vec<N>,
+            edges: Vec<Edge<E>>,
+        }
+
+        let flat = Flat::<N, E>::deserialize(deserializer)?;
+        let mut graph = Self::default();
+        for node in flat.nodes {
+            graph.add_node(node).map_err(Error::custom)?;
+        }
+        for edge in flat.edges {
+            graph
+                .add_edge(
+                    NodeIndex::new(edge.from),
+                    NodeIndex::new(edge.to),
+                    edge.weight,
+                )
+                .map_err(Error::custom)?;
+        }
+        Ok(graph)
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum CheckedGraphError {
     #[error("graph is at the maximum number of nodes")]
@@ -79,10 +160,12 @@ where
     delegate! {
         to self.0 {
             pub fn node_weight(&self, a: NodeIndex<Ix>) -> Option<&N>;
+            pub fn node_weight_mut(&mut self, a: NodeIndex<Ix>) -> Option<&mut N>;
             pub fn edge_weight(&self, e: EdgeIndex<Ix>) -> Option<&E>;
             pub fn neighbors_directed(&self, a: NodeIndex<Ix>, dir: Direction) -> Neighbors<E, Ix>;
             pub fn edges_directed(&self, a: NodeIndex<Ix>, dir: Direction) -> Edges<E, Ty, Ix>;
             pub fn find_edge(&self, a: NodeIndex<Ix>, b: NodeIndex<Ix>) -> Option<EdgeIndex<Ix>>;
+            pub fn remove_edge(&mut self, e: EdgeIndex<Ix>) -> Option<E>;
             pub fn node_count(&self) -> usize;
             pub fn node_weights(&self) -> impl Iterator<Item=&N>;
         }
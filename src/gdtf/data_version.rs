@@ -1,9 +1,15 @@
-#[derive(Debug, PartialEq, strum::EnumString, strum::Display)]
+/// GDTF version a file is written in. Versions are ordered from oldest to
+/// newest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, strum::EnumString, strum::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DataVersion {
     #[strum(to_string = "1.0")]
+    #[cfg_attr(feature = "serde", serde(rename = "1.0"))]
     V1_0,
     #[strum(to_string = "1.1")]
+    #[cfg_attr(feature = "serde", serde(rename = "1.1"))]
     V1_1,
     #[strum(to_string = "1.2")]
+    #[cfg_attr(feature = "serde", serde(rename = "1.2"))]
     V1_2,
 }
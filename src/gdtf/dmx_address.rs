@@ -0,0 +1,137 @@
+use std::{fmt::Display, str::FromStr};
+
+/// Absolute DMX address, counting across universes.
+///
+/// Universe 1, address 1 corresponds to absolute address 1. Universe 2,
+/// address 1 corresponds to absolute address 513.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u32"))]
+pub struct DmxAddress(u32);
+
+#[derive(Debug, thiserror::Error)]
+pub enum DmxAddressError {
+    #[error("absolute DMX address must be bigger than 0")]
+    Zero,
+    #[error("universe must be bigger than 0")]
+    ZeroUniverse,
+    #[error("DMX address {0} not between 1 and 512")]
+    AddressOutsideRange(u16),
+    #[error("DMX address overflows")]
+    Overflow,
+    #[error("invalid DMX address '{0}', expected absolute address or Universe.Address")]
+    Invalid(String),
+}
+
+impl TryFrom<u32> for DmxAddress {
+    type Error = DmxAddressError;
+
+    fn try_from(absolute: u32) -> Result<Self, Self::Error> {
+        if absolute == 0 {
+            return Err(DmxAddressError::Zero);
+        }
+        Ok(DmxAddress(absolute))
+    }
+}
+
+impl Default for DmxAddress {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// Parses the GDTF DMXAddress format, either an absolute address like `513`
+/// or `Universe.Address` like `2.1`
+impl FromStr for DmxAddress {
+    type Err = DmxAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DmxAddressError::Invalid(s.to_owned());
+        match s.split_once('.') {
+            Some((universe, address)) => DmxAddress::new(
+                universe.parse().map_err(|_| invalid())?,
+                address.parse().map_err(|_| invalid())?,
+            ),
+            None => s.parse::<u32>().map_err(|_| invalid())?.try_into(),
+        }
+    }
+}
+
+impl DmxAddress {
+    /// Create from 1-based universe and address in that universe
+    pub fn new(universe: u32, address: u16) -> Result<Self, DmxAddressError> {
+        if universe == 0 {
+            return Err(DmxAddressError::ZeroUniverse);
+        }
+        if !(1..=512).contains(&address) {
+            return Err(DmxAddressError::AddressOutsideRange(address));
+        }
+        (universe - 1)
+            .checked_mul(512)
+            .and_then(|v| v.checked_add(address.into()))
+            .map(DmxAddress)
+            .ok_or(DmxAddressError::Overflow)
+    }
+
+    pub fn absolute(&self) -> u32 {
+        self.0
+    }
+
+    /// 1-based universe
+    pub fn universe(&self) -> u32 {
+        (self.0 - 1) / 512 + 1
+    }
+
+    /// 1-based address inside the universe
+    pub fn address(&self) -> u16 {
+        ((self.0 - 1) % 512 + 1) as u16
+    }
+
+    /// Add `offset` addresses, possibly moving into the next universe
+    pub fn add(&self, offset: u32) -> Result<Self, DmxAddressError> {
+        self.0
+            .checked_add(offset)
+            .map(DmxAddress)
+            .ok_or(DmxAddressError::Overflow)
+    }
+}
+
+impl Display for DmxAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{:03}", self.universe(), self.address())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn universe_and_address() {
+        let a = DmxAddress::new(2, 1).unwrap();
+        assert_eq!(a.absolute(), 513);
+        assert_eq!(a.universe(), 2);
+        assert_eq!(a.address(), 1);
+        assert_eq!(format!("{a}"), "2.001");
+
+        let b = DmxAddress::new(1, 512).unwrap();
+        assert_eq!(b.universe(), 1);
+        assert_eq!(b.address(), 512);
+        assert_eq!(b.add(1).unwrap(), a);
+
+        assert!(DmxAddress::new(0, 1).is_err());
+        assert!(DmxAddress::new(1, 513).is_err());
+        assert!(DmxAddress::try_from(0).is_err());
+    }
+
+    #[test]
+    fn parse_absolute_and_universe_address() {
+        assert_eq!("513".parse::<DmxAddress>().unwrap().absolute(), 513);
+        assert_eq!("2.1".parse::<DmxAddress>().unwrap().absolute(), 513);
+        assert_eq!("1.512".parse::<DmxAddress>().unwrap().absolute(), 512);
+        assert!("0".parse::<DmxAddress>().is_err());
+        assert!("1.513".parse::<DmxAddress>().is_err());
+        assert!("-1".parse::<DmxAddress>().is_err());
+        assert!("1.2.3".parse::<DmxAddress>().is_err());
+    }
+}
@@ -4,7 +4,19 @@ use std::str::FromStr;
 // TODO we could remove all of this and replace with u16 if we just used 0-based breaks instead of 1-based
 
 /// DMX Break, which is an unsigned integer bigger than 0
-#[derive(derive_more::Display, derive_more::DebugCustom, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(
+    derive_more::Display,
+    derive_more::DebugCustom,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u16"))]
 pub struct Break(u16);
 
 impl TryFrom<u16> for Break {
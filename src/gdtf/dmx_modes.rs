@@ -1,9 +1,31 @@
+use std::collections::HashMap;
+
 use getset::Getters;
-use petgraph::{graph::NodeIndex, Directed};
+use petgraph::{
+    graph::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
+    Directed,
+    Direction::{Incoming, Outgoing},
+};
 
-use crate::{channel::Channel, checked_graph::CheckedGraph, name::Name, Gdtf, GdtfError, Problem};
+use crate::{
+    builder::max_dmx_value,
+    channel::Channel,
+    checked_graph::{CheckedGraph, CheckedGraphError},
+    dmx_address::DmxAddress,
+    dmx_break::Break,
+    extensions::Extensions,
+    geometries::{Geometries, GeometriesError},
+    macros::FtMacro,
+    name::{IntoValidName, Name},
+    physical_value::PhysicalValue,
+    relations::Relation,
+    source_location::SourceLocation,
+    ChannelNaming, Gdtf, GdtfError, Problem, SubfixtureNaming,
+};
 
 #[derive(Debug, Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[getset(get = "pub")]
 pub struct DmxMode {
     pub name: Name,
@@ -14,8 +36,39 @@ pub struct DmxMode {
     pub channels: Vec<Channel>, // main channels (not template/subfixture)
     // TODO pub?
     pub subfixtures: Vec<Subfixture>, // template/subfixture channels kept here
-    // TODO pub?
-    pub channel_functions: ChannelFunctions,
+    /// channel functions of all channels with ModeMasters as edges, use
+    /// [`ChfId`]s to refer to them from outside the crate
+    #[getset(skip)]
+    pub(crate) channel_functions: ChannelFunctions,
+    pub relations: Vec<Relation>,
+    pub macros: Vec<FtMacro>,
+    /// attributes that are not represented by other fields
+    pub extensions: Extensions,
+    /// where this was parsed from, `None` if not parsed from a file
+    pub source: Option<SourceLocation>,
+    /// how the names of channels were synthesized, see
+    /// [`ParseOptions::channel_naming`](crate::ParseOptions::channel_naming).
+    /// [`Gdtf::rename_geometry`] updates names made with it.
+    channel_naming: ChannelNaming,
+    /// how subfixtures were named, see
+    /// [`ParseOptions::subfixture_naming`](crate::ParseOptions::subfixture_naming).
+    /// [`Gdtf::rename_geometry`] updates names made with it.
+    subfixture_naming: SubfixtureNaming,
+    /// lower bound for the [`Channel::id`] of the next new channel, so ids of
+    /// removed channels are not reused
+    #[getset(skip)]
+    next_channel_id: usize,
+    /// subfixtures held back by [`ParseOptions::keep_template_channels`] until
+    /// [`DmxMode::instantiate_templates`] is called
+    ///
+    /// [`ParseOptions::keep_template_channels`]: crate::ParseOptions::keep_template_channels
+    #[getset(skip)]
+    uninstantiated: Vec<Subfixture>,
+    /// [`ChfId`] of each graph index in `channel_functions` that belongs to
+    /// a channel, see [`DmxMode::index_channel_functions`]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[getset(skip)]
+    chf_ids: HashMap<NodeIndex, ChfId>,
 }
 
 impl Gdtf {
@@ -37,11 +90,734 @@ impl Gdtf {
             channels: Default::default(),
             subfixtures: Default::default(),
             channel_functions: Default::default(),
+            relations: Default::default(),
+            macros: Default::default(),
+            extensions: Default::default(),
+            source: None,
+            channel_naming: Default::default(),
+            subfixture_naming: Default::default(),
+            next_channel_id: 0,
+            uninstantiated: Default::default(),
+            chf_ids: Default::default(),
         });
         Ok(self.dmx_modes.len() - 1)
     }
 }
 
+impl DmxMode {
+    /// Name of the mode geometry as it is stored in `geometries`. For parsed
+    /// files, this is the name after deduplication, like everywhere else.
+    pub fn geometry_name<'a>(
+        &self,
+        geometries: &'a Geometries,
+    ) -> Result<&'a Name, GeometriesError> {
+        geometries.get_by_index(self.geometry).map(|g| &g.name)
+    }
+
+    /// Iterate over all channels, first the main channels, then the channels of
+    /// each subfixture.
+    pub fn all_channels(&self) -> impl Iterator<Item = &Channel> {
+        self.channels
+            .iter()
+            .chain(self.subfixtures.iter().flat_map(|sf| sf.channels.iter()))
+    }
+
+    /// All channels, main and subfixture channels, whose attribute is
+    /// `attribute`, in the order of [`DmxMode::all_channels`].
+    ///
+    /// Matches the attribute itself, not the channel name, so template
+    /// channels are found regardless of how their instances are named.
+    pub fn find_channels_by_attribute<'a>(
+        &'a self,
+        attribute: &'a str,
+    ) -> impl Iterator<Item = &'a Channel> + 'a {
+        self.all_channels()
+            .filter(move |ch| ch.attribute == attribute)
+    }
+
+    /// Iterate over all channels with their [`ChannelId`], in the order of
+    /// [`DmxMode::all_channels`]
+    pub fn channels_with_ids(&self) -> impl Iterator<Item = (ChannelId, &Channel)> {
+        let main = self.channels.iter().map(|ch| {
+            let id = ChannelId {
+                subfixture: None,
+                channel: ch.id,
+            };
+            (id, ch)
+        });
+        let subfixtures = self.subfixtures.iter().flat_map(|sf| {
+            sf.channels.iter().map(move |ch| {
+                let id = ChannelId {
+                    subfixture: Some(sf.id),
+                    channel: ch.id,
+                };
+                (id, ch)
+            })
+        });
+        main.chain(subfixtures)
+    }
+
+    /// The channel identified by `id`, `None` if it doesn't exist. Finds
+    /// template channels that are not instantiated yet as well.
+    pub fn channel_by_id(&self, id: ChannelId) -> Option<&Channel> {
+        let channels = match id.subfixture {
+            None => &self.channels,
+            Some(sf) => &self.all_subfixtures().find(|s| s.id == sf)?.channels,
+        };
+        channels.iter().find(|ch| ch.id == id.channel)
+    }
+
+    /// The channel function identified by `id`, `None` if it doesn't exist
+    pub fn channel_function_by_id(&self, id: ChfId) -> Option<&ChannelFunction> {
+        self.channel_functions.node_weight(self.chf_index(id)?)
+    }
+
+    /// The [`ChfId`] of the channel function with given graph index in
+    /// `channel_functions`, `None` if no channel has it
+    pub(crate) fn chf_id(&self, index: NodeIndex) -> Option<ChfId> {
+        self.chf_ids.get(&index).copied()
+    }
+
+    /// Graph index in `channel_functions` of the channel function identified
+    /// by `id`
+    pub(crate) fn chf_index(&self, id: ChfId) -> Option<NodeIndex> {
+        self.channel_by_id(id.channel)?
+            .channel_functions
+            .get(id.index)
+            .copied()
+    }
+
+    /// Rebuild the lookup from channel functions to their [`ChfId`]. The
+    /// methods of `DmxMode` keep it up to date; call this after moving,
+    /// renumbering or removing channels through `channels` or `subfixtures`
+    /// directly.
+    pub fn index_channel_functions(&mut self) {
+        let main = self.channels.iter().map(|ch| (None, ch));
+        let subfixtures = self
+            .all_subfixtures()
+            .flat_map(|sf| sf.channels.iter().map(move |ch| (Some(sf.id), ch)));
+        self.chf_ids = main
+            .chain(subfixtures)
+            .flat_map(|(subfixture, ch)| {
+                let channel = ChannelId {
+                    subfixture,
+                    channel: ch.id,
+                };
+                ch.channel_functions
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, i)| (*i, ChfId { channel, index }))
+            })
+            .collect();
+    }
+
+    /// The template channels of the mode as written in the file, each with
+    /// the channels instantiated from it, sorted by `index_in_mode`.
+    ///
+    /// This view is for consumers that want the abstract channel with the list
+    /// of references instead of one copy per subfixture. It includes template
+    /// channels that are not instantiated yet, see
+    /// [`DmxMode::instantiate_templates`].
+    pub fn template_channels(&self) -> Vec<TemplateChannel<'_>> {
+        let mut templates: Vec<TemplateChannel> = vec![];
+        for sf in self.all_subfixtures() {
+            for channel in sf.channels.iter() {
+                let Some(template_geometry) = channel.template_geometry else {
+                    continue;
+                };
+                let instance = TemplateInstance {
+                    subfixture: sf.id,
+                    reference: sf.geometry,
+                    channel,
+                };
+                match templates
+                    .iter_mut()
+                    .find(|t| t.index_in_mode == channel.index_in_mode)
+                {
+                    Some(t) => t.instances.push(instance),
+                    None => templates.push(TemplateChannel {
+                        index_in_mode: channel.index_in_mode,
+                        template_geometry,
+                        attribute: &channel.attribute,
+                        instances: vec![instance],
+                    }),
+                }
+            }
+        }
+        templates.sort_by_key(|t| t.index_in_mode);
+        templates
+    }
+
+    /// Whether all template channels are instantiated, i.e. part of
+    /// `subfixtures`. Only `false` for modes parsed with
+    /// [`ParseOptions::keep_template_channels`] before
+    /// [`DmxMode::instantiate_templates`] is called.
+    ///
+    /// [`ParseOptions::keep_template_channels`]: crate::ParseOptions::keep_template_channels
+    pub fn templates_instantiated(&self) -> bool {
+        self.uninstantiated.is_empty()
+    }
+
+    /// Add the instances of template channels that were kept back by
+    /// [`ParseOptions::keep_template_channels`] to `subfixtures`, one
+    /// subfixture per GeometryReference. Does nothing if they are already
+    /// instantiated.
+    ///
+    /// Until then, the instances are only visible through
+    /// [`DmxMode::template_channels`] and not part of `subfixtures`,
+    /// [`DmxMode::all_channels`] or anything built on it, like footprints and
+    /// overlaps. They are still written when serializing.
+    ///
+    /// [`ParseOptions::keep_template_channels`]: crate::ParseOptions::keep_template_channels
+    pub fn instantiate_templates(&mut self) {
+        if self.uninstantiated.is_empty() {
+            return;
+        }
+        self.subfixtures.append(&mut self.uninstantiated);
+        self.subfixtures.sort_by_key(|sf| sf.id.index);
+    }
+
+    /// Hold back all subfixtures until [`DmxMode::instantiate_templates`]
+    pub(crate) fn keep_templates_uninstantiated(&mut self) {
+        self.uninstantiated.append(&mut self.subfixtures);
+    }
+
+    /// Subfixtures including those that are not instantiated yet
+    pub(crate) fn all_subfixtures(&self) -> impl Iterator<Item = &Subfixture> {
+        self.subfixtures.iter().chain(self.uninstantiated.iter())
+    }
+
+    /// Channels of the subfixtures that are not instantiated yet
+    pub(crate) fn uninstantiated_channels(&self) -> impl Iterator<Item = &Channel> {
+        self.uninstantiated.iter().flat_map(|sf| sf.channels.iter())
+    }
+
+    pub(crate) fn all_subfixtures_mut(&mut self) -> impl Iterator<Item = &mut Subfixture> {
+        self.subfixtures
+            .iter_mut()
+            .chain(self.uninstantiated.iter_mut())
+    }
+
+    /// Number of DMX addresses occupied in each break, i.e. the highest
+    /// offset of any channel in that break. Sorted by break.
+    pub fn footprints(&self) -> Vec<(Break, u16)> {
+        let mut footprints: Vec<(Break, u16)> = vec![];
+        for ch in self.all_channels() {
+            let Some(highest) = ch.offsets.iter().max() else {
+                continue; // virtual channel
+            };
+            match footprints.iter_mut().find(|(b, _)| *b == ch.dmx_break) {
+                Some((_, footprint)) => *footprint = (*footprint).max(*highest),
+                None => footprints.push((ch.dmx_break, *highest)),
+            }
+        }
+        footprints.sort_by_key(|(b, _)| *b.value());
+        footprints
+    }
+
+    /// Summary of the DMX addresses the mode occupies
+    pub fn footprint(&self) -> Footprint {
+        let mut footprint = Footprint::default();
+        for ch in self.all_channels() {
+            let Some(highest) = ch.offsets.iter().max() else {
+                footprint.virtual_channels += 1;
+                continue;
+            };
+            match footprint
+                .breaks
+                .iter_mut()
+                .find(|b| b.dmx_break == ch.dmx_break)
+            {
+                Some(b) => {
+                    b.channel_count += 1;
+                    b.highest_offset = b.highest_offset.max(*highest);
+                }
+                None => footprint.breaks.push(BreakFootprint {
+                    dmx_break: ch.dmx_break,
+                    channel_count: 1,
+                    highest_offset: *highest,
+                }),
+            }
+        }
+        footprint.breaks.sort_by_key(|b| *b.dmx_break.value());
+        footprint
+    }
+
+    /// Every DMX address offset that is occupied by more than one channel.
+    ///
+    /// `first` is the channel that comes first in [`DmxMode::all_channels`].
+    pub fn overlapping_offsets(&self) -> Vec<Overlap<'_>> {
+        let mut occupied: HashMap<(Break, u16), &Channel> = HashMap::new();
+        let mut overlaps = vec![];
+        for ch in self.all_channels() {
+            for offset in ch.offsets.iter() {
+                if let Some(first) = occupied.insert((ch.dmx_break, *offset), ch) {
+                    overlaps.push(Overlap {
+                        dmx_break: ch.dmx_break,
+                        offset: *offset,
+                        first,
+                        second: ch,
+                    });
+                }
+            }
+        }
+        overlaps
+    }
+
+    /// DMX data with every channel at its default value, including subfixture
+    /// channels.
+    ///
+    /// Contains the bytes of each break, starting at offset 1, sorted by
+    /// break. Addresses not occupied by any channel are 0.
+    pub fn default_frame(&self) -> Vec<(Break, Vec<u8>)> {
+        self.frame(|ch| ch.default)
+    }
+
+    /// Like [`DmxMode::default_frame`], but channels with a highlight value
+    /// use it instead of their default.
+    pub fn highlight_frame(&self) -> Vec<(Break, Vec<u8>)> {
+        self.frame(|ch| ch.highlight.unwrap_or(ch.default))
+    }
+
+    fn frame(&self, value: impl Fn(&Channel) -> u32) -> Vec<(Break, Vec<u8>)> {
+        let mut frame: Vec<(Break, Vec<u8>)> = self
+            .footprints()
+            .into_iter()
+            .map(|(b, footprint)| (b, vec![0; footprint.into()]))
+            .collect();
+        for ch in self.all_channels() {
+            let Some((_, data)) = frame.iter_mut().find(|(b, _)| *b == ch.dmx_break) else {
+                continue; // virtual channel
+            };
+            for (offset, byte) in ch.dmx_bytes(value(ch)) {
+                if let Some(d) = usize::from(offset)
+                    .checked_sub(1)
+                    .and_then(|i| data.get_mut(i))
+                {
+                    *d = byte;
+                }
+            }
+        }
+        frame
+    }
+
+    /// All occupied DMX address offsets of the mode, including subfixture
+    /// channels, grouped by break.
+    ///
+    /// Breaks are sorted ascending, and the entries of each break by offset.
+    pub fn channel_layout(&self) -> Vec<(Break, Vec<LayoutEntry<'_>>)> {
+        let mut layout: Vec<(Break, Vec<LayoutEntry>)> = vec![];
+        for channel in self.all_channels() {
+            let entries = match layout.iter_mut().find(|(b, _)| *b == channel.dmx_break) {
+                Some((_, entries)) => entries,
+                None => {
+                    layout.push((channel.dmx_break, vec![]));
+                    match layout.last_mut() {
+                        Some((_, entries)) => entries,
+                        None => continue,
+                    }
+                }
+            };
+            for (byte_index, offset) in channel.offsets.iter().enumerate() {
+                entries.push(LayoutEntry {
+                    offset: *offset,
+                    channel,
+                    byte_index,
+                });
+            }
+        }
+        layout.sort_by_key(|(b, _)| *b.value());
+        for (_, entries) in layout.iter_mut() {
+            entries.sort_by_key(|e| e.offset);
+        }
+        layout
+    }
+
+    /// Iterate over every occupied DMX address of the mode, given the patched
+    /// start address of each break.
+    ///
+    /// Yields the absolute address, the channel and the index of the byte in
+    /// the channel (0 is the most significant byte). Channels in breaks
+    /// without a start address are skipped, as are addresses that would
+    /// overflow.
+    pub fn patched_addresses<'a>(
+        &'a self,
+        start_addresses: &'a HashMap<Break, DmxAddress>,
+    ) -> impl Iterator<Item = (DmxAddress, &'a Channel, usize)> + 'a {
+        self.all_channels().flat_map(move |ch| {
+            let start = start_addresses.get(&ch.dmx_break);
+            ch.offsets
+                .iter()
+                .enumerate()
+                .filter_map(move |(byte_index, offset)| {
+                    let address = start?.add(u32::from(*offset) - 1).ok()?;
+                    Some((address, ch, byte_index))
+                })
+        })
+    }
+
+    /// Insert a main channel at position `index` in `channels`.
+    ///
+    /// The channel functions of the channel must already be part of
+    /// `channel_functions`, see [`DmxMode::add_channel_function`]. The
+    /// `index_in_mode` of the new channel is set to its new position and that
+    /// of all following channels, including subfixture channels, is shifted.
+    /// The channel gets a new [`Channel::id`], the ids of other channels are
+    /// kept.
+    ///
+    /// Does nothing and returns an Err if the channel name is taken or one of
+    /// its offsets is already occupied.
+    pub fn insert_channel(
+        &mut self,
+        index: usize,
+        mut channel: Channel,
+    ) -> Result<(), DmxModeError> {
+        if index > self.channels.len() {
+            return Err(DmxModeError::InvalidChannelIndex(index));
+        }
+        if self.all_channels().any(|ch| ch.name == channel.name) {
+            return Err(DmxModeError::DuplicateChannel(channel.name));
+        }
+        for offset in channel.offsets.iter() {
+            if self
+                .all_channels()
+                .any(|ch| ch.dmx_break == channel.dmx_break && ch.offsets.contains(offset))
+            {
+                return Err(DmxModeError::OccupiedOffset {
+                    dmx_break: channel.dmx_break,
+                    offset: *offset,
+                });
+            }
+        }
+        if let Some(index) = channel
+            .channel_functions
+            .iter()
+            .position(|i| self.channel_functions.node_weight(*i).is_none())
+        {
+            return Err(DmxModeError::UnknownChannelFunction(ChfId {
+                channel: ChannelId {
+                    subfixture: None,
+                    channel: channel.id,
+                },
+                index,
+            }));
+        }
+
+        let index_in_mode = match self.channels.get(index) {
+            Some(ch) => ch.index_in_mode,
+            None => self
+                .all_channels()
+                .map(|ch| ch.index_in_mode + 1)
+                .max()
+                .unwrap_or(0),
+        };
+        self.shift_index_in_mode(index_in_mode, |i| i + 1);
+        channel.index_in_mode = index_in_mode;
+        channel.id = self.new_channel_id();
+        self.channels.insert(index, channel);
+        self.index_channel_functions();
+        Ok(())
+    }
+
+    /// Remove the main channel at position `index` in `channels` and return
+    /// it. The `index_in_mode` of all following channels is shifted back.
+    ///
+    /// The channel functions of the channel stay in `channel_functions`, so
+    /// graph indices remain valid, but their ModeMaster edges are removed.
+    pub fn remove_channel(&mut self, index: usize) -> Result<Channel, DmxModeError> {
+        if index >= self.channels.len() {
+            return Err(DmxModeError::InvalidChannelIndex(index));
+        }
+        let channel = self.channels.remove(index);
+        for chf in channel.channel_functions.iter() {
+            for direction in [Incoming, Outgoing] {
+                while let Some(edge) = self
+                    .channel_functions
+                    .edges_directed(*chf, direction)
+                    .next()
+                    .map(|e| e.id())
+                {
+                    self.channel_functions.remove_edge(edge);
+                }
+            }
+        }
+        self.shift_index_in_mode(channel.index_in_mode + 1, |i| i - 1);
+        self.index_channel_functions();
+        Ok(channel)
+    }
+
+    /// Move the main channel at position `from` in `channels` to position
+    /// `to`, keeping its channel functions and ModeMasters
+    pub fn move_channel(&mut self, from: usize, to: usize) -> Result<(), DmxModeError> {
+        if from >= self.channels.len() {
+            return Err(DmxModeError::InvalidChannelIndex(from));
+        }
+        if to >= self.channels.len() {
+            return Err(DmxModeError::InvalidChannelIndex(to));
+        }
+        let channel = self.channels.remove(from);
+        self.shift_index_in_mode(channel.index_in_mode + 1, |i| i - 1);
+        let index_in_mode = match self.channels.get(to) {
+            Some(ch) => ch.index_in_mode,
+            None => self
+                .all_channels()
+                .map(|ch| ch.index_in_mode + 1)
+                .max()
+                .unwrap_or(0),
+        };
+        self.shift_index_in_mode(index_in_mode, |i| i + 1);
+        self.channels.insert(
+            to,
+            Channel {
+                index_in_mode,
+                ..channel
+            },
+        );
+        Ok(())
+    }
+
+    /// Add a channel function to the end of the main channel at position
+    /// `channel` in `channels` and return its id.
+    ///
+    /// DMXFrom must be bigger than that of the previous channel function and
+    /// fit the channel. The DMXTo of the previous channel function is
+    /// adjusted accordingly, the one of the new channel function set to the
+    /// maximum of the channel.
+    pub fn add_channel_function(
+        &mut self,
+        channel: usize,
+        mut channel_function: ChannelFunction,
+    ) -> Result<ChfId, DmxModeError> {
+        let ch = self
+            .channels
+            .get_mut(channel)
+            .ok_or(DmxModeError::InvalidChannelIndex(channel))?;
+        let max = max_dmx_value(ch.bytes);
+        let dmx_from = channel_function.dmx_from;
+        let previous = match ch.defined_functions().last() {
+            Some(i) => self
+                .channel_functions
+                .node_weight(*i)
+                .map(|chf| chf.dmx_from),
+            None => None,
+        };
+        if dmx_from > max || previous.is_some_and(|previous| dmx_from <= previous) {
+            return Err(DmxModeError::InvalidDmxFrom { dmx_from, max });
+        }
+
+        channel_function.dmx_to = max;
+        let index = self.channel_functions.add_node(channel_function)?;
+        if let Some(previous) = ch
+            .defined_functions()
+            .last()
+            .and_then(|i| self.channel_functions.node_weight_mut(*i))
+        {
+            previous.dmx_to = dmx_from - 1;
+        }
+        ch.channel_functions.push(index);
+        let id = ChfId {
+            channel: ChannelId {
+                subfixture: None,
+                channel: ch.id,
+            },
+            index: ch.channel_functions.len() - 1,
+        };
+        self.chf_ids.insert(index, id);
+        Ok(id)
+    }
+
+    /// Add a ModeMaster edge that makes `dependent` active only while
+    /// `dependency` is between `from` and `to` and return its index.
+    ///
+    /// The range is clipped to the DMX range of `dependency`, like for parsed
+    /// files. Returns an Err if nothing of the range is left.
+    pub fn add_mode_master(
+        &mut self,
+        dependency: ChfId,
+        dependent: ChfId,
+        from: u32,
+        to: u32,
+    ) -> Result<EdgeIndex, DmxModeError> {
+        let index = |id| {
+            let index = self.chf_index(id)?;
+            let chf = self.channel_functions.node_weight(index)?;
+            Some((index, chf))
+        };
+        let (dependency, master) =
+            index(dependency).ok_or(DmxModeError::UnknownChannelFunction(dependency))?;
+        let (dependent, _) =
+            index(dependent).ok_or(DmxModeError::UnknownChannelFunction(dependent))?;
+        let (clipped_from, clipped_to) = (from.max(master.dmx_from), to.min(master.dmx_to));
+        if clipped_to < clipped_from {
+            return Err(DmxModeError::UnreachableModeRange { from, to });
+        }
+        Ok(self.channel_functions.add_edge(
+            dependency,
+            dependent,
+            ModeMaster {
+                from: clipped_from,
+                to: clipped_to,
+                original_from: from,
+                original_to: to,
+            },
+        )?)
+    }
+
+    /// Record how the names of channels and subfixtures were synthesized
+    pub(crate) fn set_naming(
+        &mut self,
+        channel_naming: ChannelNaming,
+        subfixture_naming: SubfixtureNaming,
+    ) {
+        self.channel_naming = channel_naming;
+        self.subfixture_naming = subfixture_naming;
+    }
+
+    /// Update the names GDTF derives from the name of the geometry with given
+    /// graph index after it was renamed from `old` to `new`, see
+    /// [`Gdtf::rename_geometry`].
+    pub(crate) fn rename_geometry(
+        &mut self,
+        geometries: &Geometries,
+        geometry: NodeIndex,
+        old: &Name,
+        new: &Name,
+    ) {
+        // GDTF names of the channels on the geometry, `{Geometry}_{Attribute}`
+        let renamed: Vec<(String, String)> = self
+            .all_channels()
+            .chain(self.uninstantiated_channels())
+            .filter(|ch| ch.template_geometry.unwrap_or(ch.geometry) == geometry)
+            .map(|ch| {
+                (
+                    format!("{old}_{}", ch.attribute),
+                    format!("{new}_{}", ch.attribute),
+                )
+            })
+            .collect();
+        // a channel name or a link starting with one, e.g. to a channel function
+        let rename = |link: &str| {
+            renamed.iter().find_map(|(old, new)| {
+                let rest = link.strip_prefix(old.as_str())?;
+                (rest.is_empty() || rest.starts_with('.')).then(|| format!("{new}{rest}"))
+            })
+        };
+
+        let uses_geometry =
+            |ch: &Channel| ch.geometry == geometry || ch.template_geometry == Some(geometry);
+        let channel_naming = self.channel_naming;
+        let channels = self.channels.iter_mut().chain(
+            self.subfixtures
+                .iter_mut()
+                .chain(self.uninstantiated.iter_mut())
+                .flat_map(|sf| sf.channels.iter_mut()),
+        );
+        for ch in channels.filter(|ch| uses_geometry(ch)) {
+            // names synthesized with `channel_naming` and GDTF names, which
+            // raw DMX channel functions keep, before and after the rename
+            let [named, gdtf_named] =
+                [channel_naming, ChannelNaming::GeometryAttribute].map(|naming| {
+                    let name = |renamed| naming.name_channel(geometries, ch, renamed);
+                    name(Some((geometry, old))).zip(name(None))
+                });
+            if let Some((_, new)) = named.as_ref().filter(|(old, _)| ch.name == *old) {
+                ch.name = new.clone();
+            }
+            if let Some(raw) = ch
+                .raw_function()
+                .and_then(|i| self.channel_functions.node_weight_mut(i))
+            {
+                if let Some((_, new)) = [named, gdtf_named]
+                    .into_iter()
+                    .flatten()
+                    .find(|(old, _)| raw.name == *old)
+                {
+                    raw.name = new;
+                }
+            }
+        }
+        let subfixture_naming = self.subfixture_naming;
+        for sf in self
+            .all_subfixtures_mut()
+            .filter(|sf| sf.geometry == geometry)
+        {
+            let index = sf.id.index;
+            if sf.name == subfixture_naming.subfixture_name(old, index) {
+                sf.name = subfixture_naming.subfixture_name(new, index);
+            }
+        }
+        for relation in self.relations.iter_mut() {
+            if let Some(master) = rename(relation.master.as_str()) {
+                relation.master = master.into_valid();
+            }
+            if let Some(follower) = rename(&relation.follower) {
+                relation.follower = follower;
+            }
+        }
+        for ft_macro in self.macros.iter_mut() {
+            if let Some(chf) = ft_macro.channel_function.as_deref().and_then(rename) {
+                ft_macro.channel_function = Some(chf);
+            }
+            for value in ft_macro
+                .steps
+                .iter_mut()
+                .flat_map(|step| step.values.iter_mut())
+            {
+                if let Some(dmx_channel) = rename(value.dmx_channel.as_str()) {
+                    value.dmx_channel = dmx_channel.into_valid();
+                }
+            }
+        }
+    }
+
+    /// A [`Channel::id`] that no channel of the mode has had so far
+    pub(crate) fn new_channel_id(&mut self) -> usize {
+        let id = self
+            .all_channels()
+            .chain(self.uninstantiated_channels())
+            .map(|ch| ch.id + 1)
+            .max()
+            .unwrap_or(0)
+            .max(self.next_channel_id);
+        self.next_channel_id = id + 1;
+        id
+    }
+
+    /// Apply `shift` to the `index_in_mode` of all channels where it is at
+    /// least `start`
+    fn shift_index_in_mode(&mut self, start: usize, shift: impl Fn(usize) -> usize) {
+        let channels = self.channels.iter_mut().chain(
+            self.subfixtures
+                .iter_mut()
+                .chain(self.uninstantiated.iter_mut())
+                .flat_map(|sf| sf.channels.iter_mut()),
+        );
+        for ch in channels.filter(|ch| ch.index_in_mode >= start) {
+            ch.index_in_mode = shift(ch.index_in_mode);
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DmxModeError {
+    #[error("no main channel at index {0}")]
+    InvalidChannelIndex(usize),
+    #[error("channel name '{0}' already taken")]
+    DuplicateChannel(Name),
+    #[error("offset {offset} in DMX break {dmx_break} is already occupied")]
+    OccupiedOffset { dmx_break: Break, offset: u16 },
+    #[error("unknown channel function {0:?}")]
+    UnknownChannelFunction(ChfId),
+    #[error(
+        "DMXFrom {dmx_from} must be bigger than the previous one and at most {max} for this channel"
+    )]
+    InvalidDmxFrom { dmx_from: u32, max: u32 },
+    #[error("mode range {from} to {to} is outside of the DMX range of the master")]
+    UnreachableModeRange { from: u32, to: u32 },
+    #[error("{0}")]
+    Graph(#[from] CheckedGraphError),
+}
+
 /// ModeMaster Edges go from dependency to dependent channel function
 pub type ChannelFunctions = CheckedGraph<ChannelFunction, ModeMaster, Directed>;
 
@@ -57,30 +833,623 @@ pub fn chfs<'a>(
     })
 }
 
+/// Returned by [`DmxMode::footprint`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Footprint {
+    /// sorted by break
+    pub breaks: Vec<BreakFootprint>,
+    /// number of channels without offsets, which occupy no DMX address
+    pub virtual_channels: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakFootprint {
+    pub dmx_break: Break,
+    /// number of non-virtual channels in this break
+    pub channel_count: usize,
+    /// number of DMX addresses needed to patch this break
+    pub highest_offset: u16,
+}
+
+/// Two channels occupying the same DMX address offset, see
+/// [`DmxMode::overlapping_offsets`]
+#[derive(Debug, Clone, Copy)]
+pub struct Overlap<'a> {
+    pub dmx_break: Break,
+    pub offset: u16,
+    pub first: &'a Channel,
+    pub second: &'a Channel,
+}
+
+/// An occupied DMX address offset in [`DmxMode::channel_layout`]
+#[derive(Debug, Clone, Copy)]
+pub struct LayoutEntry<'a> {
+    /// 1-based offset in the break
+    pub offset: u16,
+    pub channel: &'a Channel,
+    /// index of the byte in the channel, 0 is the most significant byte
+    pub byte_index: usize,
+}
+
 // TODO below should probably be factored into its own file (even what's left at this point?)
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Subfixture {
+    /// display name, see [`SubfixtureNaming`](crate::SubfixtureNaming)
     pub name: Name,
+    /// identifies the subfixture independent of any names
+    pub id: SubfixtureId,
     pub channels: Vec<Channel>,
     pub geometry: NodeIndex,
 }
 
+impl Subfixture {
+    /// Channels of this subfixture whose attribute is `attribute`, see
+    /// [`DmxMode::find_channels_by_attribute`]
+    pub fn channels_by_attribute<'a>(
+        &'a self,
+        attribute: &'a str,
+    ) -> impl Iterator<Item = &'a Channel> + 'a {
+        self.channels
+            .iter()
+            .filter(move |ch| ch.attribute == attribute)
+    }
+}
+
+/// A template channel with its instances, see [`DmxMode::template_channels`]
 #[derive(Debug, Clone)]
+pub struct TemplateChannel<'a> {
+    /// see [`Channel::index_in_mode`]
+    pub index_in_mode: usize,
+    /// geometry in the subtree of the template the channel is defined on
+    pub template_geometry: NodeIndex,
+    pub attribute: &'a Name,
+    /// one instance per GeometryReference, in the order of `subfixtures`
+    pub instances: Vec<TemplateInstance<'a>>,
+}
+
+/// The instance of a template channel for one GeometryReference
+#[derive(Debug, Clone, Copy)]
+pub struct TemplateInstance<'a> {
+    pub subfixture: SubfixtureId,
+    /// graph index of the GeometryReference
+    pub reference: NodeIndex,
+    pub channel: &'a Channel,
+}
+
+/// Identifies a subfixture of a DMX mode without relying on names, which can
+/// change when geometries are renamed. Stays the same when a file is parsed
+/// again, as long as the order of its geometries and channels is unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubfixtureId {
+    /// 0-based position of the subfixture in the DMX mode
+    pub index: usize,
+    /// index of the GeometryReference the subfixture is instantiated by, see
+    /// [`Subfixture::geometry`]
+    pub geometry: usize,
+}
+
+/// Identifies a channel of a DMX mode without relying on names or on its
+/// position in `channels`. Stays the same when the mode is edited, and when a
+/// file is parsed again as long as the order of its geometries and channels is
+/// unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChannelId {
+    /// subfixture of a template channel instance, `None` for main channels
+    subfixture: Option<SubfixtureId>,
+    /// see [`Channel::id`]
+    channel: usize,
+}
+
+/// Identifies a channel function of a DMX mode by its channel and its position
+/// in the channel, unlike the graph index the mode stores it under, which
+/// depends on the order the graph was built in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChfId {
+    channel: ChannelId,
+    /// position in the channel functions of the channel, 0 is the raw DMX
+    /// channel function
+    index: usize,
+}
+
+impl ChfId {
+    /// The channel the channel function belongs to
+    pub fn channel(&self) -> ChannelId {
+        self.channel
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChannelFunction {
     pub name: Name,
+    /// whether this is the implicit raw DMX channel function, which every
+    /// channel has at index 0 and which covers the whole DMX range
+    pub raw_dmx: bool,
+    /// geometry of the channel, see [`Channel::geometry`]
     pub geometry: NodeIndex,
+    /// geometry in the template for functions of template channels, see
+    /// [`Channel::template_geometry`]
+    pub template_geometry: Option<NodeIndex>,
     pub attr: String, // TODO replace by index down the line, I guess
     pub original_attr: String,
     pub dmx_from: u32, // max supported DMX channels per GDTF channel is 4
     pub dmx_to: u32,
-    pub phys_from: f64,
-    pub phys_to: f64,
+    pub phys_from: PhysicalValue,
+    pub phys_to: PhysicalValue,
+    /// physical fade time from minimum to maximum value in seconds
+    pub real_fade: f64,
+    /// time to accelerate from stop to maximum speed in seconds
+    pub real_acceleration: f64,
+    /// minimum physical value that will be used for the DMX output
+    pub min: PhysicalValue,
+    /// maximum physical value that will be used for the DMX output
+    pub max: PhysicalValue,
     pub default: u32,
+    /// name of the linked wheel, `None` if absent
+    pub wheel: Option<Name>,
+    pub channel_sets: Vec<ChannelSet>,
+    /// attributes that are not represented by other fields
+    pub extensions: Extensions,
+    /// where this was parsed from, `None` if not parsed from a file
+    pub source: Option<SourceLocation>,
 }
 
+/// A named DMX range of a channel function
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChannelSet {
+    pub name: Name,
+    pub dmx_from: u32,
+    /// derived from the DMXFrom of the next ChannelSet, like for channel
+    /// functions
+    pub dmx_to: u32,
+    pub phys_from: PhysicalValue,
+    pub phys_to: PhysicalValue,
+    /// 1-based index of the slot in the wheel of the channel function, `None`
+    /// if absent. GDTF Builder writes 0 if no wheel is linked.
+    pub wheel_slot_index: Option<u32>,
+}
+
+/// DMX range of the master in which the dependent channel function is active
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModeMaster {
+    /// ModeFrom, clipped to the DMX range of the master
     pub from: u32,
+    /// ModeTo, clipped to the DMX range of the master
     pub to: u32,
+    /// ModeFrom as given in the file
+    pub original_from: u32,
+    /// ModeTo as given in the file
+    pub original_to: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::GdtfBuilder;
+
+    use super::*;
+
+    #[test]
+    fn default_and_highlight_frame() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Dimmer", 1, vec![2, 3])
+            .unwrap()
+            .channel("Mode", "Body", "Pan", 2, vec![1])
+            .unwrap()
+            .channel("Mode", "Body", "Tilt", 1, vec![])
+            .unwrap()
+            .build();
+        let mode = gdtf.dmx_mode_mut(0).unwrap();
+        for (ch, default) in mode.channels.iter_mut().zip([0x1234, 128, 7]) {
+            ch.default = default;
+        }
+        mode.channels.get_mut(0).unwrap().highlight = Some(0xffff);
+
+        assert_eq!(
+            mode.default_frame(),
+            vec![
+                (1.try_into().unwrap(), vec![0, 0x12, 0x34]),
+                (2.try_into().unwrap(), vec![128]),
+            ]
+        );
+        assert_eq!(
+            mode.highlight_frame(),
+            vec![
+                (1.try_into().unwrap(), vec![0, 0xff, 0xff]),
+                (2.try_into().unwrap(), vec![128]),
+            ]
+        );
+    }
+
+    #[test]
+    fn find_channels_by_attribute() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .geometry("Pixel", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Pan", 1, vec![1])
+            .unwrap()
+            .channel("Mode", "Body", "Dimmer", 1, vec![2])
+            .unwrap()
+            .channel("Mode", "Pixel", "Dimmer", 1, vec![3])
+            .unwrap()
+            .build();
+        let mode = gdtf.dmx_mode_mut(0).unwrap();
+        let pixel_dimmer = mode.channels.pop().unwrap();
+        let geometry = pixel_dimmer.geometry;
+        mode.subfixtures.push(Subfixture {
+            name: "Pixel 1".try_into().unwrap(),
+            id: SubfixtureId {
+                index: 0,
+                geometry: geometry.index(),
+            },
+            channels: vec![pixel_dimmer],
+            geometry,
+        });
+
+        let names = |channels: Vec<&Channel>| -> Vec<String> {
+            channels.iter().map(|ch| ch.name.to_string()).collect()
+        };
+        assert_eq!(
+            names(mode.find_channels_by_attribute("Dimmer").collect()),
+            ["Body_Dimmer", "Pixel_Dimmer"]
+        );
+        assert_eq!(
+            names(mode.find_channels_by_attribute("Pan").collect()),
+            ["Body_Pan"]
+        );
+        assert_eq!(mode.find_channels_by_attribute("Tilt").count(), 0);
+
+        let subfixture = mode.subfixtures.first().unwrap();
+        assert_eq!(
+            names(subfixture.channels_by_attribute("Dimmer").collect()),
+            ["Pixel_Dimmer"]
+        );
+        assert_eq!(subfixture.channels_by_attribute("Pan").count(), 0);
+    }
+
+    #[test]
+    fn stable_ids() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Pan", 1, vec![1])
+            .unwrap()
+            .channel("Mode", "Body", "Dimmer", 1, vec![2])
+            .unwrap()
+            .build();
+        let mode = gdtf.dmx_mode_mut(0).unwrap();
+        let dimmer = mode.channels.pop().unwrap();
+        let dimmer_raw = dimmer.raw_function().unwrap();
+        let subfixture = SubfixtureId {
+            index: 0,
+            geometry: 0,
+        };
+        mode.subfixtures.push(Subfixture {
+            name: "Instance".try_into().unwrap(),
+            id: subfixture,
+            geometry: dimmer.geometry,
+            channels: vec![dimmer],
+        });
+        mode.index_channel_functions();
+
+        let ids: Vec<_> = mode.channels_with_ids().map(|(id, _)| id).collect();
+        let dimmer_id = ChannelId {
+            subfixture: Some(subfixture),
+            channel: 1,
+        };
+        assert_eq!(
+            ids,
+            [
+                ChannelId {
+                    subfixture: None,
+                    channel: 0
+                },
+                dimmer_id
+            ]
+        );
+        assert_eq!(
+            mode.channel_by_id(dimmer_id).map(|ch| ch.name.as_str()),
+            Some("Body_Dimmer")
+        );
+        assert!(mode
+            .channel_by_id(ChannelId {
+                subfixture: None,
+                channel: 1
+            })
+            .is_none());
+
+        let chf = mode.chf_id(dimmer_raw).unwrap();
+        assert_eq!(
+            chf,
+            ChfId {
+                channel: dimmer_id,
+                index: 0
+            }
+        );
+        assert_eq!(
+            mode.channel_function_by_id(chf)
+                .map(|chf| chf.name.as_str()),
+            Some("Body_Dimmer")
+        );
+        assert!(mode
+            .channel_function_by_id(ChfId {
+                channel: dimmer_id,
+                index: 1
+            })
+            .is_none());
+        assert!(mode.chf_id(NodeIndex::new(42)).is_none());
+    }
+
+    #[test]
+    fn ids_are_kept_when_channels_are_edited() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Pan", 1, vec![1])
+            .unwrap()
+            .channel("Mode", "Body", "Dimmer", 1, vec![2])
+            .unwrap()
+            .channel_function("Mode", "Body_Dimmer", "Dimmer", "Dimmer", 0, 0., 1.)
+            .unwrap()
+            .build();
+        let mode = gdtf.dmx_mode_mut(0).unwrap();
+        let dimmer = mode.channels.get(1).unwrap();
+        let dimmer_function = *dimmer.defined_functions().first().unwrap();
+        let chf = mode.chf_id(dimmer_function).unwrap();
+
+        let tilt = GdtfBuilder::new("Manufacturer", "Other")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Tilt", 1, vec![3])
+            .unwrap()
+            .build()
+            .dmx_mode_mut(0)
+            .unwrap()
+            .remove_channel(0)
+            .unwrap();
+        mode.insert_channel(
+            0,
+            Channel {
+                channel_functions: vec![],
+                ..tilt
+            },
+        )
+        .unwrap();
+
+        assert_eq!(mode.channels.get(2).unwrap().index_in_mode, 2);
+        assert_eq!(
+            mode.channel_function_by_id(chf)
+                .map(|chf| chf.name.as_str()),
+            Some("Dimmer")
+        );
+        assert_eq!(mode.chf_id(dimmer_function), Some(chf));
+        let ids: Vec<_> = mode.channels.iter().map(|ch| ch.id).collect();
+        assert_eq!(ids, [2, 0, 1]);
+
+        // the id of a removed channel is not reused
+        let tilt = mode.remove_channel(0).unwrap();
+        mode.insert_channel(0, tilt).unwrap();
+        assert_eq!(mode.channels.first().unwrap().id, 3);
+    }
+
+    #[test]
+    fn edit_channels() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Pan", 1, vec![1])
+            .unwrap()
+            .channel("Mode", "Body", "Dimmer", 1, vec![2])
+            .unwrap()
+            .build();
+        let mode = gdtf.dmx_mode_mut(0).unwrap();
+        let order = |mode: &DmxMode| -> Vec<(String, usize)> {
+            mode.channels
+                .iter()
+                .map(|ch| (ch.name.to_string(), ch.index_in_mode))
+                .collect()
+        };
+        let channel = |name: &str, offset: u16| Channel {
+            name: name.try_into().unwrap(),
+            geometry: NodeIndex::new(0),
+            template_geometry: None,
+            attribute: Default::default(),
+            index_in_mode: 0,
+            id: 0,
+            dmx_break: 1.try_into().unwrap(),
+            bytes: 1,
+            offsets: vec![offset].try_into().unwrap(),
+            channel_functions: vec![],
+            initial_function: None,
+            default: 0,
+            default_source: Default::default(),
+            highlight: None,
+            extensions: Default::default(),
+            source: None,
+        };
+        let channel_function = |name: &str, dmx_from| ChannelFunction {
+            name: name.try_into().unwrap(),
+            raw_dmx: false,
+            geometry: NodeIndex::new(0),
+            template_geometry: None,
+            attr: "Dimmer".into(),
+            original_attr: String::new(),
+            dmx_from,
+            dmx_to: 0,
+            phys_from: PhysicalValue::ZERO,
+            phys_to: PhysicalValue::ONE,
+            real_fade: 0.,
+            real_acceleration: 0.,
+            min: PhysicalValue::ZERO,
+            max: PhysicalValue::ONE,
+            default: 0,
+            wheel: None,
+            channel_sets: vec![],
+            extensions: Default::default(),
+            source: None,
+        };
+
+        // channels
+        let pan = mode.remove_channel(0).unwrap();
+        assert_eq!(order(mode), [("Body_Dimmer".into(), 0)]);
+        mode.insert_channel(0, pan).unwrap();
+        assert_eq!(
+            order(mode),
+            [("Body_Pan".into(), 0), ("Body_Dimmer".into(), 1)]
+        );
+        mode.insert_channel(2, channel("Body_Tilt", 3)).unwrap();
+        assert!(matches!(
+            mode.insert_channel(0, channel("Body_Tilt", 4)),
+            Err(DmxModeError::DuplicateChannel(_))
+        ));
+        assert!(matches!(
+            mode.insert_channel(0, channel("Body_Zoom", 2)),
+            Err(DmxModeError::OccupiedOffset { offset: 2, .. })
+        ));
+        assert!(matches!(
+            mode.insert_channel(5, channel("Body_Zoom", 4)),
+            Err(DmxModeError::InvalidChannelIndex(5))
+        ));
+        mode.move_channel(0, 2).unwrap();
+        assert_eq!(
+            order(mode),
+            [
+                ("Body_Dimmer".into(), 0),
+                ("Body_Tilt".into(), 1),
+                ("Body_Pan".into(), 2)
+            ]
+        );
+
+        // channel functions
+        let closed = mode
+            .add_channel_function(0, channel_function("Closed", 0))
+            .unwrap();
+        let dimmer = mode
+            .add_channel_function(0, channel_function("Dimmer", 10))
+            .unwrap();
+        assert!(matches!(
+            mode.add_channel_function(0, channel_function("Strobe", 5)),
+            Err(DmxModeError::InvalidDmxFrom {
+                dmx_from: 5,
+                max: 255
+            })
+        ));
+        let range = |mode: &DmxMode, id| {
+            let chf = mode.channel_function_by_id(id).unwrap();
+            (chf.dmx_from, chf.dmx_to)
+        };
+        assert_eq!(range(mode, closed), (0, 9));
+        assert_eq!(range(mode, dimmer), (10, 255));
+
+        // ModeMasters
+        let pan_raw = mode.chf_id(mode.channels.get(2).unwrap().raw_function().unwrap());
+        let pan_raw = pan_raw.unwrap();
+        let edge = mode.add_mode_master(pan_raw, dimmer, 128, 300).unwrap();
+        let master = mode.channel_functions.edge_weight(edge).unwrap();
+        assert_eq!((master.from, master.to), (128, 255));
+        assert!(matches!(
+            mode.add_mode_master(pan_raw, closed, 300, 400),
+            Err(DmxModeError::UnreachableModeRange { .. })
+        ));
+        mode.remove_channel(2).unwrap();
+        assert!(mode.channel_function_by_id(pan_raw).is_none());
+        assert!(matches!(
+            mode.add_mode_master(pan_raw, dimmer, 0, 255),
+            Err(DmxModeError::UnknownChannelFunction(id)) if id == pan_raw
+        ));
+        assert_eq!(
+            mode.channel_functions
+                .edges_directed(mode.chf_index(dimmer).unwrap(), Incoming)
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn template_channels() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .geometry("Pixel", None)
+            .unwrap()
+            .geometry("Pixel 1", Some("Body"))
+            .unwrap()
+            .geometry("Pixel 2", Some("Body"))
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Pan", 1, vec![1])
+            .unwrap()
+            .channel("Mode", "Pixel 1", "Dimmer", 1, vec![2])
+            .unwrap()
+            .channel("Mode", "Pixel 2", "Dimmer", 1, vec![3])
+            .unwrap()
+            .build();
+        let pixel = gdtf
+            .geometries
+            .get_index(&"Pixel".try_into().unwrap())
+            .unwrap();
+        let mode = gdtf.dmx_mode_mut(0).unwrap();
+        for index in [1, 0] {
+            let mut channel = mode.channels.pop().unwrap();
+            channel.index_in_mode = 1;
+            channel.template_geometry = Some(pixel);
+            let geometry = channel.geometry;
+            mode.subfixtures.push(Subfixture {
+                name: format!("Subfixture {index}").try_into().unwrap(),
+                id: SubfixtureId {
+                    index,
+                    geometry: geometry.index(),
+                },
+                channels: vec![channel],
+                geometry,
+            });
+        }
+        mode.subfixtures.reverse();
+
+        let templates = mode.template_channels();
+        assert_eq!(templates.len(), 1);
+        let template = templates.first().unwrap();
+        assert_eq!(template.index_in_mode, 1);
+        assert_eq!(template.template_geometry, pixel);
+        assert_eq!(*template.attribute, "Dimmer");
+        let instances: Vec<_> = template
+            .instances
+            .iter()
+            .map(|i| (i.subfixture.index, i.channel.name.to_string()))
+            .collect();
+        assert_eq!(
+            instances,
+            [(0, "Pixel 1_Dimmer".into()), (1, "Pixel 2_Dimmer".into())]
+        );
+    }
 }
@@ -0,0 +1,282 @@
+use std::fmt::{self, Display, Write};
+
+use petgraph::{
+    graph::NodeIndex,
+    visit::EdgeRef,
+    Direction::{Incoming, Outgoing},
+};
+
+use crate::{
+    dmx_modes::{ChfId, DmxMode},
+    geometries::Geometries,
+    geometry::Type,
+};
+
+impl Geometries {
+    /// Graphviz DOT representation of the geometry tree, for debugging.
+    ///
+    /// Nodes are named by graph index and labeled with the geometry name and
+    /// type. Template relationships are dashed edges from the template to
+    /// the GeometryReference.
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph geometries {\n".to_owned();
+        for i in self.graph().node_indices() {
+            if let Some(g) = self.graph().node_weight(i) {
+                let label = format!("{} ({})", g.name, <&str>::from(&g.t));
+                let _ = writeln!(dot, "    {} [label=\"{}\"];", i.index(), escape(&label));
+            }
+        }
+        for (parent, child) in self
+            .graph()
+            .edge_indices()
+            .filter_map(|e| self.graph().edge_endpoints(e))
+        {
+            let _ = writeln!(dot, "    {} -> {};", parent.index(), child.index());
+        }
+        for (template, reference, _) in self.templates().all_edges() {
+            let _ = writeln!(
+                dot,
+                "    {} -> {} [style=dashed, label=\"reference\"];",
+                template.index(),
+                reference.index()
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn write_tree(&self, f: &mut fmt::Formatter<'_>, i: NodeIndex, depth: usize) -> fmt::Result {
+        let Some(g) = self.graph().node_weight(i) else {
+            return Ok(());
+        };
+        write!(f, "{:indent$}{}", "", g.name, indent = 2 * depth)?;
+        if matches!(g.t, Type::Reference { .. }) {
+            let template = self
+                .templates()
+                .neighbors_directed(i, Incoming)
+                .find_map(|t| self.graph().node_weight(t));
+            if let Some(template) = template {
+                write!(f, " -> {}", template.name)?;
+            }
+        }
+        writeln!(f)?;
+        let mut children: Vec<_> = self.graph().neighbors(i).collect();
+        children.sort();
+        for child in children {
+            self.write_tree(f, child, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Indented tree of geometry names, one per line, in the order they were
+/// added. GeometryReferences are followed by `-> {template}`.
+impl Display for Geometries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in self.top_level_indices() {
+            self.write_tree(f, i, 0)?;
+        }
+        Ok(())
+    }
+}
+
+impl DmxMode {
+    /// Graphviz DOT representation of the channel functions and their
+    /// ModeMasters, for debugging.
+    ///
+    /// The channel functions of each channel are grouped in a cluster.
+    /// ModeMaster edges go from the master to the dependent channel function
+    /// and are labeled with the DMX range of the master.
+    pub fn mode_masters_to_dot(&self) -> String {
+        let mut dot = "digraph mode_masters {\n".to_owned();
+        for (c, ch) in self.all_channels().enumerate() {
+            let _ = writeln!(dot, "    subgraph cluster_{c} {{");
+            let _ = writeln!(dot, "        label=\"{}\";", escape(ch.name.as_str()));
+            for i in ch.channel_functions.iter() {
+                if let Some(chf) = self.channel_functions.node_weight(*i) {
+                    let _ = writeln!(
+                        dot,
+                        "        {} [label=\"{}\"];",
+                        i.index(),
+                        escape(chf.name.as_str())
+                    );
+                }
+            }
+            dot.push_str("    }\n");
+        }
+        for i in (0..self.channel_functions.node_count()).map(NodeIndex::new) {
+            for e in self.channel_functions.edges_directed(i, Outgoing) {
+                let _ = writeln!(
+                    dot,
+                    "    {} -> {} [label=\"{}..{}\"];",
+                    e.source().index(),
+                    e.target().index(),
+                    e.weight().from,
+                    e.weight().to
+                );
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Indented text representation of the channels, their channel functions
+    /// with DMX range and the ModeMasters they depend on, for debugging.
+    ///
+    /// ```text
+    /// Beam_Shutter1
+    ///   Shutter 0..=255 if Beam_Dimmer.Strobe 128..=255
+    /// ```
+    pub fn mode_master_tree(&self) -> String {
+        let qualified_name = |id: ChfId| -> String {
+            let chf = self.channel_function_by_id(id);
+            match (self.channel_by_id(id.channel()), chf) {
+                (_, Some(chf)) if chf.raw_dmx => chf.name.to_string(),
+                (Some(ch), Some(chf)) => format!("{}.{}", ch.name, chf.name),
+                (None, Some(chf)) => chf.name.to_string(),
+                (_, None) => format!("{id:?}"),
+            }
+        };
+
+        let mut tree = String::new();
+        for ch in self.all_channels() {
+            let _ = writeln!(tree, "{}", ch.name);
+            for f in ch.functions(self).skip(1) {
+                let _ = write!(
+                    tree,
+                    "  {} {}..={}",
+                    f.chf.name,
+                    f.dmx_range.start(),
+                    f.dmx_range.end()
+                );
+                for (master, mode_master) in f.mode_masters {
+                    let _ = write!(
+                        tree,
+                        " if {} {}..={}",
+                        qualified_name(master),
+                        mode_master.from,
+                        mode_master.to
+                    );
+                }
+                tree.push('\n');
+            }
+        }
+        tree
+    }
+}
+
+/// Escape a string for a quoted DOT ID
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builder::GdtfBuilder, geometry::Geometry};
+
+    use super::*;
+
+    #[test]
+    fn geometries_as_dot_and_tree() {
+        let mut geometries = Geometries::default();
+        let geometry = |name: &str, t| Geometry {
+            name: name.try_into().unwrap(),
+            t,
+            ..Default::default()
+        };
+        let body = geometries
+            .add_top_level(geometry("Body", Type::General))
+            .unwrap();
+        geometries
+            .add(geometry("Beam", Type::Beam(Default::default())), body)
+            .unwrap();
+        let reference = geometries
+            .add(
+                geometry(
+                    "Pixel 1",
+                    Type::Reference {
+                        offsets: Default::default(),
+                    },
+                ),
+                body,
+            )
+            .unwrap();
+        let pixel = geometries
+            .add_top_level(geometry("Pixel", Type::General))
+            .unwrap();
+        geometries
+            .add_template_relationship(pixel, reference)
+            .unwrap();
+
+        assert_eq!(
+            geometries.to_string(),
+            "Body\n  Beam\n  Pixel 1 -> Pixel\nPixel\n"
+        );
+        assert_eq!(
+            geometries.to_dot(),
+            r#"digraph geometries {
+    0 [label="Body (General)"];
+    1 [label="Beam (Beam)"];
+    2 [label="Pixel 1 (Reference)"];
+    3 [label="Pixel (General)"];
+    0 -> 1;
+    0 -> 2;
+    3 -> 2 [style=dashed, label="reference"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn mode_masters_as_dot_and_tree() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Dimmer", 1, vec![1])
+            .unwrap()
+            .channel_function("Mode", "Body_Dimmer", "Dimmer", "Dimmer", 0, 0., 1.)
+            .unwrap()
+            .channel_function("Mode", "Body_Dimmer", "Strobe", "Shutter1", 128, 0., 1.)
+            .unwrap()
+            .channel("Mode", "Body", "Shutter1", 1, vec![2])
+            .unwrap()
+            .channel_function("Mode", "Body_Shutter1", "Shutter", "Shutter1", 0, 0., 1.)
+            .unwrap()
+            .build();
+        let mode = gdtf.dmx_mode_mut(0).unwrap();
+        let ids: Vec<_> = mode
+            .all_channels()
+            .flat_map(|ch| ch.functions(mode).map(|f| f.id))
+            .collect();
+        let strobe = *ids.get(2).unwrap();
+        let shutter = *ids.get(4).unwrap();
+        mode.add_mode_master(strobe, shutter, 128, 255).unwrap();
+
+        assert_eq!(
+            mode.mode_master_tree(),
+            "Body_Dimmer\n  Dimmer 0..=127\n  Strobe 128..=255\n\
+             Body_Shutter1\n  Shutter 0..=255 if Body_Dimmer.Strobe 128..=255\n"
+        );
+        assert_eq!(
+            mode.mode_masters_to_dot(),
+            r#"digraph mode_masters {
+    subgraph cluster_0 {
+        label="Body_Dimmer";
+        0 [label="Body_Dimmer"];
+        1 [label="Dimmer"];
+        2 [label="Strobe"];
+    }
+    subgraph cluster_1 {
+        label="Body_Shutter1";
+        3 [label="Body_Shutter1"];
+        4 [label="Shutter"];
+    }
+    2 -> 4 [label="128..255"];
+}
+"#
+        );
+    }
+}
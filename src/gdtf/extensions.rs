@@ -0,0 +1,33 @@
+/// Content of a GDTF node that is not represented by other fields, like vendor
+/// extensions or additions of newer GDTF versions. It is kept verbatim, so it
+/// survives serialization.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Extensions {
+    /// unknown attributes as pairs of name and value, in document order
+    pub attributes: Vec<(String, String)>,
+    /// unknown child nodes as XML, in document order
+    pub nodes: Vec<String>,
+}
+
+/// Sections of the FixtureType node that are not parsed yet. Each is kept as
+/// XML, so it survives serialization unchanged. `None` if the file does not
+/// have the section.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnparsedSections {
+    pub attribute_definitions: Option<String>,
+    pub physical_descriptions: Option<String>,
+    pub revisions: Option<String>,
+}
+
+impl Extensions {
+    pub fn is_empty(&self) -> bool {
+        self.attributes.is_empty() && self.nodes.is_empty()
+    }
+
+    /// Sort the attributes by name, since their order carries no meaning
+    pub fn sort_attributes(&mut self) {
+        self.attributes.sort();
+    }
+}
@@ -0,0 +1,181 @@
+use uuid::Uuid;
+
+use crate::{dmx_break::Break, name::Name, Gdtf};
+
+/// Summary of a fixture type for display in fixture libraries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureMeta {
+    pub name: Name,
+    pub manufacturer: String,
+    pub fixture_type_id: Uuid,
+    pub modes: Vec<ModeMeta>,
+    pub has_thumbnail: bool,
+}
+
+/// Summary of a DMX mode, see [`FixtureMeta`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModeMeta {
+    pub name: Name,
+    /// name of the mode geometry, `None` if it is missing
+    pub geometry: Option<Name>,
+    /// number of occupied DMX addresses per break, sorted by break
+    pub footprints: Vec<(Break, u16)>,
+}
+
+impl From<&Gdtf> for FixtureMeta {
+    fn from(gdtf: &Gdtf) -> Self {
+        FixtureMeta {
+            name: gdtf.name.clone(),
+            manufacturer: gdtf.manufacturer.clone(),
+            fixture_type_id: gdtf.fixture_type_id,
+            modes: gdtf
+                .dmx_modes()
+                .iter()
+                .map(|mode| ModeMeta {
+                    name: mode.name.clone(),
+                    geometry: mode.geometry_name(&gdtf.geometries).ok().cloned(),
+                    footprints: mode.footprints(),
+                })
+                .collect(),
+            has_thumbnail: gdtf.thumbnail.is_some(),
+        }
+    }
+}
+
+/// Optional FixtureType metadata that fixture libraries use to judge the
+/// quality of a file, see [`Gdtf::missing_metadata`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataField {
+    LongName,
+    Description,
+    Thumbnail,
+    RefFt,
+}
+
+impl MetadataField {
+    /// Name of the attribute on the FixtureType node
+    pub fn attribute(&self) -> &'static str {
+        match self {
+            MetadataField::LongName => "LongName",
+            MetadataField::Description => "Description",
+            MetadataField::Thumbnail => "Thumbnail",
+            MetadataField::RefFt => "RefFT",
+        }
+    }
+}
+
+impl Gdtf {
+    /// Optional FixtureType metadata that is missing or empty, in the order
+    /// of [`MetadataField`]. Whitespace-only strings count as empty.
+    ///
+    /// CanHaveChildren is not reported, since the parser substitutes its
+    /// default `Yes` when the attribute is absent.
+    pub fn missing_metadata(&self) -> Vec<MetadataField> {
+        let blank = |s: &str| s.trim().is_empty();
+        [
+            (MetadataField::LongName, blank(&self.long_name)),
+            (MetadataField::Description, blank(&self.description)),
+            (MetadataField::Thumbnail, self.thumbnail.is_none()),
+            (MetadataField::RefFt, self.ref_ft.is_none()),
+        ]
+        .into_iter()
+        .filter_map(|(field, missing)| missing.then_some(field))
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::graph::NodeIndex;
+
+    use crate::{
+        channel::{Channel, DefaultSource},
+        geometry::Geometry,
+        name::IntoValidName,
+    };
+
+    use super::*;
+
+    fn channel(dmx_break: u16, offsets: Vec<u16>) -> Channel {
+        Channel {
+            name: Default::default(),
+            geometry: NodeIndex::default(),
+            template_geometry: None,
+            attribute: Default::default(),
+            index_in_mode: 0,
+            id: 0,
+            dmx_break: dmx_break.try_into().unwrap(),
+            bytes: offsets.len() as u8,
+            offsets: offsets.try_into().unwrap(),
+            channel_functions: vec![],
+            initial_function: None,
+            default: 0,
+            default_source: DefaultSource::RawChannelFunction,
+            highlight: None,
+            extensions: Default::default(),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn meta_from_gdtf() {
+        let mut gdtf = Gdtf {
+            name: "Spot".into_valid(),
+            manufacturer: "Generic".into(),
+            thumbnail: Some("thumbnail".into()),
+            ..Default::default()
+        };
+        let body = gdtf
+            .geometries
+            .add_top_level(Geometry {
+                name: "Body".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        let mode = gdtf
+            .add_dmx_mode("Mode 1".into_valid(), "".into(), body)
+            .unwrap();
+        let mode = gdtf.dmx_mode_mut(mode).unwrap();
+        mode.channels.push(channel(2, vec![1]));
+        mode.channels.push(channel(1, vec![3, 4]));
+        mode.channels.push(channel(1, vec![2]));
+        mode.channels.push(channel(1, vec![]));
+
+        assert_eq!(
+            FixtureMeta::from(&gdtf),
+            FixtureMeta {
+                name: "Spot".into_valid(),
+                manufacturer: "Generic".into(),
+                fixture_type_id: Uuid::nil(),
+                modes: vec![ModeMeta {
+                    name: "Mode 1".into_valid(),
+                    geometry: Some("Body".into_valid()),
+                    footprints: vec![(1.try_into().unwrap(), 4), (2.try_into().unwrap(), 1)]
+                }],
+                has_thumbnail: true,
+            }
+        );
+    }
+
+    #[test]
+    fn missing_metadata() {
+        let mut gdtf = Gdtf {
+            long_name: "Long Name".into(),
+            description: " ".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            gdtf.missing_metadata(),
+            vec![
+                MetadataField::Description,
+                MetadataField::Thumbnail,
+                MetadataField::RefFt
+            ]
+        );
+
+        gdtf.description = "Description".into();
+        gdtf.thumbnail = Some("thumbnail".into());
+        gdtf.ref_ft = Some(Uuid::nil());
+        assert!(gdtf.missing_metadata().is_empty());
+    }
+}
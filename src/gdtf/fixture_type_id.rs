@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+
+use uuid::Uuid;
+
+use crate::{serialize_gdtf, Gdtf};
+
+/// Several fixture types share a FixtureTypeID but differ in content.
+///
+/// Consoles identify fixture types by this ID, so loading such files together
+/// can silently mix up patches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureTypeIdCollision {
+    pub fixture_type_id: Uuid,
+    /// Indices into the input, grouped by identical content. Contains at least
+    /// two groups.
+    pub groups: Vec<Vec<usize>>,
+}
+
+/// Find FixtureTypeIDs that are used by fixture types with differing content.
+///
+/// Content is compared by the serialization to `description.xml`, so
+/// fixture types that only differ in the archive, like in their resources,
+/// are considered identical. Collisions are sorted by FixtureTypeID.
+pub fn fixture_type_id_collisions<'a>(
+    gdtfs: impl IntoIterator<Item = &'a Gdtf>,
+) -> Vec<FixtureTypeIdCollision> {
+    let mut by_id: BTreeMap<Uuid, Vec<(String, Vec<usize>)>> = BTreeMap::new();
+    for (i, gdtf) in gdtfs.into_iter().enumerate() {
+        let content = serialize_gdtf(gdtf);
+        let groups = by_id.entry(gdtf.fixture_type_id).or_default();
+        match groups.iter_mut().find(|(c, _)| *c == content) {
+            Some((_, indices)) => indices.push(i),
+            None => groups.push((content, vec![i])),
+        }
+    }
+    by_id
+        .into_iter()
+        .filter(|(_, groups)| groups.len() > 1)
+        .map(|(fixture_type_id, groups)| FixtureTypeIdCollision {
+            fixture_type_id,
+            groups: groups.into_iter().map(|(_, indices)| indices).collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::name::IntoValidName;
+
+    use super::*;
+
+    #[test]
+    fn reports_collisions_with_differing_content() {
+        let id = Uuid::from_u128(1);
+        let gdtf = |name: &str, fixture_type_id| Gdtf {
+            name: name.into_valid(),
+            fixture_type_id,
+            ..Default::default()
+        };
+        let gdtfs = [
+            gdtf("A", id),
+            gdtf("A", id),
+            gdtf("B", id),
+            gdtf("C", Uuid::from_u128(2)),
+            gdtf("C", Uuid::from_u128(3)),
+            gdtf("C", Uuid::from_u128(3)),
+        ];
+
+        assert_eq!(
+            fixture_type_id_collisions(&gdtfs),
+            vec![FixtureTypeIdCollision {
+                fixture_type_id: id,
+                groups: vec![vec![0, 1], vec![2]],
+            }]
+        );
+        assert!(fixture_type_id_collisions(&gdtfs[3..]).is_empty());
+    }
+}
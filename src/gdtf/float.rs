@@ -0,0 +1,62 @@
+//! Formatting and parsing of floats in GDTF attributes.
+//!
+//! GDTF always uses '.' as decimal separator, independent of any locale. Some
+//! exporters use the locale of the system instead and write ',' as decimal
+//! separator, which [`parse_comma_decimal`] detects so it can be recovered
+//! from.
+
+use std::{fmt::Display, num::ParseFloatError};
+
+/// Number of decimal places floats are written with
+pub(crate) const PRECISION: usize = 6;
+
+/// Displays a float with '.' as decimal separator and [`PRECISION`] decimal
+/// places, like `0.500000`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Fixed(pub f64);
+
+impl Display for Fixed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.*}", PRECISION, self.0)
+    }
+}
+
+/// Parse a float with '.' as decimal separator, ignoring surrounding
+/// whitespace
+pub(crate) fn parse_float(s: &str) -> Result<f64, ParseFloatError> {
+    s.trim().parse()
+}
+
+/// Parse a float that uses a single ',' as decimal separator, like `0,5`.
+///
+/// Returns None if `s` is not such a number, in particular if it contains a
+/// '.', so that thousands separators are not misread.
+pub(crate) fn parse_comma_decimal(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if s.contains('.') || s.matches(',').count() != 1 {
+        return None;
+    }
+    parse_float(&s.replace(',', ".")).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed() {
+        assert_eq!(Fixed(0.5).to_string(), "0.500000");
+        assert_eq!(Fixed(-1e-7).to_string(), "-0.000000");
+        assert_eq!(Fixed(1234567.0).to_string(), "1234567.000000");
+    }
+
+    #[test]
+    fn comma_decimal() {
+        assert_eq!(parse_comma_decimal("0,5"), Some(0.5));
+        assert_eq!(parse_comma_decimal(" -12,25 "), Some(-12.25));
+        assert_eq!(parse_comma_decimal("0.5"), None);
+        assert_eq!(parse_comma_decimal("1.000,5"), None);
+        assert_eq!(parse_comma_decimal("1,0,0"), None);
+        assert_eq!(parse_comma_decimal("a,b"), None);
+    }
+}
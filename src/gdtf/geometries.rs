@@ -1,35 +1,154 @@
-use std::collections::hash_map::Entry::{Occupied, Vacant};
-use std::collections::HashMap;
+use std::collections::btree_map::Entry::{Occupied, Vacant};
+use std::collections::{BTreeMap, BTreeSet};
 
 use getset::Getters;
 use petgraph::prelude::DiGraphMap;
-use petgraph::visit::Walker;
+use petgraph::stable_graph::StableGraph;
+use petgraph::visit::{Dfs, Walker};
 use petgraph::Direction::{Incoming, Outgoing};
-use petgraph::{graph::NodeIndex, Directed, Graph};
+use petgraph::{graph::NodeIndex, Directed};
 
-use crate::geometry::{Geometry, Type};
+use crate::geometry::{Geometry, Offsets, Type};
 use crate::name::Name;
+use crate::position::Position;
 
 #[derive(Debug, Default, Getters)]
 #[getset(get = "pub")]
 pub struct Geometries {
-    /// Maps geometry name to its graph index. Use for quick name lookup.
-    names: HashMap<Name, NodeIndex>,
+    /// Maps geometry name to its graph index, sorted by name. Use for quick
+    /// name lookup.
+    names: BTreeMap<Name, NodeIndex>,
 
     /// Graph representing the geometry tree. Edges point from parent to child.
     ///
     /// Petgraph is used to avoid having to learn multiple graph/tree-libraries
     /// for this crate. The tree structure is ensured by the modifying methods
     /// and the fact that the field is not mutably accesible from the outside.
+    ///
+    /// A `StableGraph` keeps the graph indices of geometries when others are
+    /// removed, since DMX modes and channels refer to geometries by index.
     graph: GeometryGraph,
 
     // TODO maybe move to CheckedGraph later
     /// Graph with template relationships, i.e. TopLevelGeometry -> GeometryReference.
     templates: DiGraphMap<NodeIndex, ()>,
+
+    /// Instances of template geometries below the top level, see
+    /// [`Geometries::instantiate`], as pairs of the GeometryReference and the
+    /// geometry in the template. Instances are not part of `graph`.
+    instances: BTreeSet<(NodeIndex, NodeIndex)>,
 }
 
 // TODO maybe move to Checked Graph later
-type GeometryGraph = Graph<Geometry, (), Directed>;
+type GeometryGraph = StableGraph<Geometry, (), Directed>;
+
+/// Serialized as a list of the geometries in the order of their graph index,
+/// each with its graph index, the index of its parent, for GeometryReferences
+/// the index of the referenced top-level geometry and for template geometries
+/// the indices of the GeometryReferences that instantiate them. Indices match
+/// the graph indices of the geometries, which DMX modes and channels use.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Geometries {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(serde::Serialize)]
+        struct FlatGeometry<'a> {
+            index: NodeIndex,
+            parent: Option<NodeIndex>,
+            template: Option<NodeIndex>,
+            #[serde(skip_serializing_if = "Vec::is_empty")]
+            instantiated_by: Vec<NodeIndex>,
+            #[serde(flatten)]
+            geometry: &'a Geometry,
+        }
+
+        serializer.collect_seq(self.graph.node_indices().filter_map(|i| {
+            Some(FlatGeometry {
+                index: i,
+                parent: self.parent_index(i),
+                template: self.templates.neighbors_directed(i, Incoming).next(),
+                instantiated_by: self
+                    .instances
+                    .iter()
+                    .filter(|(_, geometry)| *geometry == i)
+                    .map(|(reference, _)| *reference)
+                    .collect(),
+                geometry: self.graph.node_weight(i)?,
+            })
+        }))
+    }
+}
+
+/// Rebuilds the geometry tree and the template relationships from the
+/// serialized form. Geometries must be in ascending order of their graph
+/// index; the indices of removed geometries may be skipped and stay vacant.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Geometries {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(serde::Deserialize)]
+        struct FlatGeometry {
+            index: NodeIndex,
+            parent: Option<NodeIndex>,
+            template: Option<NodeIndex>,
+            #[serde(default)]
+            instantiated_by: Vec<NodeIndex>,
+            #[serde(flatten)]
+            geometry: Geometry,
+        }
+
+        let flat = Vec::<FlatGeometry>::deserialize(deserializer)?;
+        let mut geometries = Geometries::default();
+        let mut vacant = vec![];
+        let mut parents = vec![];
+        let mut references = vec![];
+        let mut instances = vec![];
+        for g in flat {
+            // nothing is removed until all geometries are added, so the node
+            // count is the index of the next geometry
+            while geometries.graph.node_count() < g.index.index() {
+                vacant.push(geometries.graph.add_node(Geometry::default()));
+            }
+            if geometries.graph.node_count() != g.index.index() {
+                return Err(Error::custom(format!(
+                    "geometry index {} is not in ascending order",
+                    g.index.index()
+                )));
+            }
+            let name = g.geometry.name.clone();
+            let index = geometries.graph.add_node(g.geometry);
+            if let Some(taken) = geometries.names.insert(name, index) {
+                return Err(Error::custom(GeometriesError::NameAlreadyTaken(taken)));
+            }
+            instances.extend(g.instantiated_by.into_iter().map(|r| (r, index)));
+            if let Some(parent) = g.parent {
+                parents.push((parent, index));
+            }
+            if let Some(template) = g.template {
+                references.push((template, index));
+            }
+        }
+        for i in vacant {
+            geometries.graph.remove_node(i);
+        }
+        for (parent, child) in parents {
+            geometries
+                .reparent(child, Some(parent))
+                .map_err(Error::custom)?;
+        }
+        for (template, reference) in references {
+            geometries
+                .add_template_relationship(template, reference)
+                .map_err(Error::custom)?;
+        }
+        for (reference, geometry) in instances {
+            geometries
+                .instantiate(geometry, reference)
+                .map_err(Error::custom)?;
+        }
+        Ok(geometries)
+    }
+}
 
 impl Geometries {
     /// Adds a top level geometry and returns its graph index.
@@ -64,6 +183,152 @@ impl Geometries {
         }
     }
 
+    /// Renames the geometry with given graph index.
+    ///
+    /// When the graph index doesn't exist or the new name is taken by another
+    /// geometry, does nothing and returns an Err.
+    ///
+    /// Names derived from the geometry name in DMX modes, e.g. of channels,
+    /// are not updated, use [`Gdtf::rename_geometry`](crate::Gdtf::rename_geometry)
+    /// for that.
+    pub fn rename(
+        &mut self,
+        graph_index: NodeIndex,
+        new_name: Name,
+    ) -> Result<(), GeometriesError> {
+        let graph_index = self.validate_index(graph_index)?;
+        match self.names.get(&new_name) {
+            Some(i) if *i == graph_index => return Ok(()),
+            Some(i) => return Err(GeometriesError::NameAlreadyTaken(*i)),
+            None => {}
+        }
+        if let Some(geometry) = self.graph.node_weight_mut(graph_index) {
+            self.names.remove(&geometry.name);
+            geometry.name = new_name.clone();
+        }
+        self.names.insert(new_name, graph_index);
+        Ok(())
+    }
+
+    /// Moves the geometry with given graph index and its children to a new
+    /// parent, or to the top level if `new_parent` is `None`.
+    ///
+    /// Returns an Err and does nothing if the new parent is the geometry
+    /// itself or one of its descendants, if a template geometry would no
+    /// longer be top-level, if a GeometryReference would end up below its
+    /// own template or if the geometry or one of its descendants has
+    /// instances.
+    pub fn reparent(
+        &mut self,
+        graph_index: NodeIndex,
+        new_parent: Option<NodeIndex>,
+    ) -> Result<(), GeometriesError> {
+        let graph_index = self.validate_index(graph_index)?;
+        let descendants = self.descendants(graph_index);
+        if let Some((_, geometry)) = self
+            .instances
+            .iter()
+            .find(|(_, geometry)| descendants.contains(geometry))
+        {
+            return Err(GeometriesError::Instantiated(*geometry));
+        }
+        if let Some(new_parent) = new_parent {
+            self.check_new_parent(graph_index, new_parent)?;
+        }
+
+        if let Some(old_parent) = self.parent_index(graph_index) {
+            if let Some(edge) = self.graph.find_edge(old_parent, graph_index) {
+                self.graph.remove_edge(edge);
+            }
+        }
+        if let Some(new_parent) = new_parent {
+            self.graph.add_edge(new_parent, graph_index, ());
+        }
+        Ok(())
+    }
+
+    fn check_new_parent(
+        &self,
+        graph_index: NodeIndex,
+        new_parent: NodeIndex,
+    ) -> Result<(), GeometriesError> {
+        let new_parent = self.validate_index(new_parent)?;
+        if new_parent == graph_index || self.ancestors(new_parent).any(|i| i == graph_index) {
+            return Err(GeometriesError::CircularParent {
+                geometry: graph_index,
+                parent: new_parent,
+            });
+        }
+        let geometry = self.get_by_index(graph_index)?;
+        if self.is_template(graph_index) {
+            return Err(GeometriesError::NonTopLevelGeometryReferenced {
+                referenced: geometry.name.clone(),
+                reference: self
+                    .template_references(graph_index)
+                    .find_map(|i| self.graph.node_weight(i))
+                    .map(|g| g.name.clone())
+                    .unwrap_or_default(),
+            });
+        }
+        let new_top_level = self.top_level_geometry_index(new_parent);
+        for i in self.descendants(graph_index) {
+            if self.templates.contains_edge(new_top_level, i) {
+                return Err(GeometriesError::CircularGeometryReference {
+                    reference: self.get_by_index(i)?.name.clone(),
+                    referenced: self.get_by_index(new_top_level)?.name.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the geometry with given graph index together with all its
+    /// descendants and returns the removed geometry. Instances of the removed
+    /// geometries and instances created by removed GeometryReferences are
+    /// removed as well.
+    ///
+    /// Graph indices of the remaining geometries don't change. Graph indices
+    /// of the removed geometries held elsewhere, e.g. in DMX modes, become
+    /// invalid, which is why [`Gdtf::remove_geometry`](crate::Gdtf::remove_geometry)
+    /// checks for them first.
+    ///
+    /// Template geometries that are still referenced by a GeometryReference
+    /// outside of the removed subtree can't be removed.
+    pub(crate) fn remove(&mut self, graph_index: NodeIndex) -> Result<Geometry, GeometriesError> {
+        let graph_index = self.validate_index(graph_index)?;
+        let removed = self.descendants(graph_index);
+        if let Some(template) = removed.iter().find(|i| {
+            self.template_references(**i)
+                .any(|reference| !removed.contains(&reference))
+        }) {
+            return Err(GeometriesError::TemplateInUse(*template));
+        }
+
+        let mut root = None;
+        for i in removed.iter().copied() {
+            self.templates.remove_node(i);
+            let Some(geometry) = self.graph.remove_node(i) else {
+                continue;
+            };
+            self.names.remove(&geometry.name);
+            if i == graph_index {
+                root = Some(geometry);
+            }
+        }
+        self.instances.retain(|(reference, geometry)| {
+            !removed.contains(reference) && !removed.contains(geometry)
+        });
+        root.ok_or(GeometriesError::MissingIndex(graph_index))
+    }
+
+    /// Graph indices of the geometry with given graph index and all its
+    /// descendants
+    pub(crate) fn descendants(&self, graph_index: NodeIndex) -> Vec<NodeIndex> {
+        Dfs::new(&self.graph, graph_index)
+            .iter(&self.graph)
+            .collect()
+    }
+
     /// Get the graph index of a Geometry by its unique `Name`
     pub fn get_index(&self, name: &Name) -> Option<NodeIndex> {
         self.names
@@ -87,6 +352,12 @@ impl Geometries {
             .ok_or(GeometriesError::MissingIndex(graph_index))
     }
 
+    /// Mutable access to all geometries. Names must not be changed, since
+    /// `names` would be out of sync otherwise.
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut Geometry> {
+        self.graph.node_weights_mut()
+    }
+
     /// Returns the graph index of the parent of the geometry with the given
     /// graph_index, or None if the geometry is top level or missing.
     pub fn parent_index(&self, graph_index: NodeIndex) -> Option<NodeIndex> {
@@ -100,6 +371,14 @@ impl Geometries {
         self.parent_index(graph_index).is_none()
     }
 
+    /// Returns an iterator over the graph indices of all top level geometries,
+    /// in the order they were added.
+    pub fn top_level_indices(&self) -> impl Iterator<Item = NodeIndex> + '_ {
+        self.graph
+            .node_indices()
+            .filter(|graph_index| self.is_top_level(*graph_index))
+    }
+
     /// Returns the number of children of the geometry with given graph index.
     ///
     /// If no geometry with the graph index exists, zero is returned.
@@ -149,6 +428,49 @@ impl Geometries {
         self.ancestors(graph_index).last().unwrap_or(graph_index)
     }
 
+    /// Absolute transform of the geometry with given graph index, i.e. its
+    /// position composed with the positions of all its ancestors.
+    ///
+    /// For a geometry in a template, this is the transform of the template
+    /// itself; see [`Geometries::instance_world_matrix`] for its placement by
+    /// a GeometryReference.
+    pub fn world_matrix(&self, graph_index: NodeIndex) -> Result<Position, GeometriesError> {
+        let mut matrix = self.get_by_index(graph_index)?.position;
+        for i in self.ancestors(graph_index) {
+            matrix = self.get_by_index(i)?.position * matrix;
+        }
+        Ok(matrix)
+    }
+
+    /// Absolute transform of the geometry with given graph index inside the
+    /// template instantiated by the GeometryReference `reference`.
+    ///
+    /// The reference takes the place of the top-level template geometry, so
+    /// the position of the template itself is replaced by the world matrix of
+    /// the reference.
+    pub fn instance_world_matrix(
+        &self,
+        graph_index: NodeIndex,
+        reference: NodeIndex,
+    ) -> Result<Position, GeometriesError> {
+        let template = self.top_level_geometry_index(self.validate_index(graph_index)?);
+        if !self.templates.contains_edge(template, reference) {
+            return Err(GeometriesError::NotInReferencedTemplate {
+                geometry: graph_index,
+                reference,
+            });
+        }
+        let mut matrix = if graph_index == template {
+            Position::IDENTITY
+        } else {
+            self.get_by_index(graph_index)?.position
+        };
+        for i in self.ancestors(graph_index).filter(|i| *i != template) {
+            matrix = self.get_by_index(i)?.position * matrix;
+        }
+        Ok(self.world_matrix(reference)? * matrix)
+    }
+
     // TODO why isn't this done while adding the Geometry if it is a reference,
     // this way we can avoid constructing a reference without the corresponding
     // reference relation
@@ -189,6 +511,47 @@ impl Geometries {
         Ok(())
     }
 
+    /// Creates the instance of the template geometry with given graph index
+    /// for the GeometryReference `reference`, together with the instances of
+    /// its ancestors below the top-level template geometry. Does nothing for
+    /// instances that already exist.
+    ///
+    /// An instance is a geometry below the top level of a template as placed
+    /// by one GeometryReference, so channels and channel functions of each
+    /// reference have their own geometry. It is identified by the pair of
+    /// the reference and the template geometry, which is also how channels
+    /// and channel functions refer to it. Instances are not part of the
+    /// geometry tree, the data of an instance is that of its template
+    /// geometry and its placement is given by
+    /// [`Geometries::instance_world_matrix`]. The instance of the top-level
+    /// template geometry is the GeometryReference itself and is not recorded.
+    pub fn instantiate(
+        &mut self,
+        graph_index: NodeIndex,
+        reference: NodeIndex,
+    ) -> Result<(), GeometriesError> {
+        let template = self.top_level_geometry_index(self.validate_index(graph_index)?);
+        if !self.templates.contains_edge(template, reference) {
+            return Err(GeometriesError::NotInReferencedTemplate {
+                geometry: graph_index,
+                reference,
+            });
+        }
+        let mut i = graph_index;
+        while i != template && self.instances.insert((reference, i)) {
+            i = self.parent_index(i).unwrap_or(template);
+        }
+        Ok(())
+    }
+
+    /// Whether the template geometry with given graph index is instantiated
+    /// by the GeometryReference `reference`, see [`Geometries::instantiate`].
+    /// True for the top-level template geometry of the reference.
+    pub fn is_instantiated(&self, graph_index: NodeIndex, reference: NodeIndex) -> bool {
+        self.instances.contains(&(reference, graph_index))
+            || self.templates.contains_edge(graph_index, reference)
+    }
+
     pub fn is_template(&self, a: NodeIndex) -> bool {
         self.templates.contains_node(a)
             && self
@@ -198,9 +561,25 @@ impl Geometries {
                 .is_some()
     }
 
+    /// Graph indices of all GeometryReferences that reference the top-level
+    /// template geometry `a`
     pub fn template_references(&self, a: NodeIndex) -> impl Iterator<Item = NodeIndex> + '_ {
         self.templates.neighbors_directed(a, Outgoing)
     }
+
+    /// Name and offsets of all GeometryReferences that reference the top-level
+    /// template geometry `a`, i.e. all instances of the template
+    pub fn template_instances(&self, a: NodeIndex) -> impl Iterator<Item = (&Name, &Offsets)> {
+        self.template_references(a)
+            .filter_map(|i| match self.graph.node_weight(i) {
+                Some(Geometry {
+                    name,
+                    t: Type::Reference { offsets },
+                    ..
+                }) => Some((name, offsets)),
+                _ => None,
+            })
+    }
 }
 
 pub struct GeometryAncestors {
@@ -232,11 +611,25 @@ pub enum GeometriesError {
     ReferenceForNonReferenceGeometry(NodeIndex),
     #[error("tried to reference a GeometryReference, such chains are superfluous")]
     ReferenceReferenced(NodeIndex),
+    #[error("geometry {geometry:?} can't become a child of {parent:?}, which is itself or one of its descendants")]
+    CircularParent {
+        geometry: NodeIndex,
+        parent: NodeIndex,
+    },
+    #[error("template geometry {0:?} is still referenced by a GeometryReference")]
+    TemplateInUse(NodeIndex),
+    #[error("geometry {geometry:?} is not part of the template referenced by {reference:?}")]
+    NotInReferencedTemplate {
+        geometry: NodeIndex,
+        reference: NodeIndex,
+    },
+    #[error("template geometry {0:?} has instances and can't be moved")]
+    Instantiated(NodeIndex),
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::geometry::Type;
+    use crate::{dmx_address::DmxAddress, geometry::Type};
 
     use super::*;
 
@@ -249,12 +642,14 @@ mod tests {
             .add_top_level(Geometry {
                 name: "a".try_into().unwrap(),
                 t: Type::General,
+                ..Default::default()
             })
             .unwrap();
         let b = g
             .add_top_level(Geometry {
                 name: "b".try_into().unwrap(),
                 t: Type::General,
+                ..Default::default()
             })
             .unwrap();
         let a0 = g
@@ -262,6 +657,7 @@ mod tests {
                 Geometry {
                     name: "a0".try_into().unwrap(),
                     t: Type::General,
+                    ..Default::default()
                 },
                 a,
             )
@@ -271,6 +667,7 @@ mod tests {
                 Geometry {
                     name: "a0a".try_into().unwrap(),
                     t: Type::General,
+                    ..Default::default()
                 },
                 a0,
             )
@@ -281,6 +678,7 @@ mod tests {
             g.add_top_level(Geometry {
                 name: "a".try_into().unwrap(),
                 t: Type::General,
+                ..Default::default()
             }),
             Err(GeometriesError::NameAlreadyTaken(i))
         if i == a));
@@ -289,6 +687,7 @@ mod tests {
                 Geometry {
                     name: "a0a".try_into().unwrap(),
                     t: Type::General,
+                    ..Default::default()
                 },
                 b
             ),
@@ -300,6 +699,7 @@ mod tests {
                 Geometry {
                     name: "c".try_into().unwrap(),
                     t: Type::General,
+                    ..Default::default()
                 },
                 nonexistent_graph_index
             ),
@@ -346,6 +746,202 @@ mod tests {
         assert_eq!(g.qualified_name(a0a), "a.a0.a0a");
         assert_eq!(g.qualified_name(a0), "a.a0");
         assert_eq!(g.qualified_name(a), "a");
+
+        assert_eq!(g.top_level_indices().collect::<Vec<_>>(), vec![a, b]);
+    }
+
+    #[test]
+    fn template_instances() {
+        let mut g = Geometries::default();
+        let template = g
+            .add_top_level(Geometry {
+                name: "template".try_into().unwrap(),
+                ..Default::default()
+            })
+            .unwrap();
+        let main = g
+            .add_top_level(Geometry {
+                name: "main".try_into().unwrap(),
+                ..Default::default()
+            })
+            .unwrap();
+        for (name, offset) in [("instance 1", 1), ("instance 2", 5)] {
+            let offsets = Offsets {
+                normal: BTreeMap::from([(
+                    1.try_into().unwrap(),
+                    DmxAddress::try_from(offset).unwrap(),
+                )]),
+                overwrite: None,
+            };
+            let reference = g
+                .add(
+                    Geometry {
+                        name: name.try_into().unwrap(),
+                        t: Type::Reference { offsets },
+                        ..Default::default()
+                    },
+                    main,
+                )
+                .unwrap();
+            g.add_template_relationship(template, reference).unwrap();
+        }
+
+        let mut instances: Vec<_> = g
+            .template_instances(template)
+            .map(|(name, offsets)| (name.to_string(), offsets.normal.len()))
+            .collect();
+        instances.sort();
+        assert_eq!(
+            instances,
+            vec![("instance 1".into(), 1), ("instance 2".into(), 1)]
+        );
+        assert_eq!(g.template_instances(main).count(), 0);
+    }
+
+    #[test]
+    fn world_matrix_through_reference() {
+        let translation = |x, y, z| {
+            Position([
+                [1., 0., 0., x],
+                [0., 1., 0., y],
+                [0., 0., 1., z],
+                [0., 0., 0., 1.],
+            ])
+        };
+        let geometry = |name: &str, position, t| Geometry {
+            name: name.try_into().unwrap(),
+            position,
+            t,
+            ..Default::default()
+        };
+        let mut g = Geometries::default();
+        let main = g
+            .add_top_level(geometry("Main", translation(1., 0., 0.), Type::General))
+            .unwrap();
+        let pixel = g
+            .add_top_level(geometry("Pixel", translation(100., 0., 0.), Type::General))
+            .unwrap();
+        let lens = g
+            .add(
+                geometry("Lens", translation(0., 0., 1.), Type::General),
+                pixel,
+            )
+            .unwrap();
+        let reference = g
+            .add(
+                geometry(
+                    "Pixel1",
+                    translation(0., 2., 0.),
+                    Type::Reference {
+                        offsets: Offsets {
+                            normal: BTreeMap::new(),
+                            overwrite: None,
+                        },
+                    },
+                ),
+                main,
+            )
+            .unwrap();
+        g.add_template_relationship(pixel, reference).unwrap();
+
+        assert_eq!(g.world_matrix(lens).unwrap().translation(), [100., 0., 1.]);
+        assert_eq!(
+            g.world_matrix(reference).unwrap().translation(),
+            [1., 2., 0.]
+        );
+        assert_eq!(
+            g.instance_world_matrix(lens, reference)
+                .unwrap()
+                .translation(),
+            [1., 2., 1.]
+        );
+        assert_eq!(
+            g.instance_world_matrix(pixel, reference).unwrap(),
+            g.world_matrix(reference).unwrap()
+        );
+        assert!(matches!(
+            g.instance_world_matrix(main, reference),
+            Err(GeometriesError::NotInReferencedTemplate { .. })
+        ));
+        assert!(matches!(
+            g.world_matrix(NodeIndex::new(42)),
+            Err(GeometriesError::MissingIndex(_))
+        ));
+    }
+
+    #[test]
+    fn rename_reparent_remove() {
+        let geometry = |name: &str, t| Geometry {
+            name: name.try_into().unwrap(),
+            t,
+            ..Default::default()
+        };
+        let reference_type = || Type::Reference {
+            offsets: Offsets {
+                normal: BTreeMap::new(),
+                overwrite: None,
+            },
+        };
+        let mut g = Geometries::default();
+        let body = g.add_top_level(geometry("Body", Type::General)).unwrap();
+        let head = g.add(geometry("Head", Type::General), body).unwrap();
+        let beam = g.add(geometry("Beam", Type::General), head).unwrap();
+        let pixel = g.add_top_level(geometry("Pixel", Type::General)).unwrap();
+        let reference = g.add(geometry("Pixel1", reference_type()), head).unwrap();
+        g.add_template_relationship(pixel, reference).unwrap();
+
+        // rename
+        g.rename(beam, "Lens".try_into().unwrap()).unwrap();
+        assert_eq!(g.get_index(&"Lens".try_into().unwrap()), Some(beam));
+        assert_eq!(g.get_index(&"Beam".try_into().unwrap()), None);
+        assert!(matches!(
+            g.rename(beam, "Head".try_into().unwrap()),
+            Err(GeometriesError::NameAlreadyTaken(i)) if i == head
+        ));
+        g.rename(beam, "Lens".try_into().unwrap()).unwrap();
+
+        // reparent
+        assert!(matches!(
+            g.reparent(head, Some(beam)),
+            Err(GeometriesError::CircularParent { .. })
+        ));
+        assert!(matches!(
+            g.reparent(head, Some(head)),
+            Err(GeometriesError::CircularParent { .. })
+        ));
+        assert!(matches!(
+            g.reparent(pixel, Some(body)),
+            Err(GeometriesError::NonTopLevelGeometryReferenced { .. })
+        ));
+        assert!(matches!(
+            g.reparent(head, Some(pixel)),
+            Err(GeometriesError::CircularGeometryReference { .. })
+        ));
+        assert_eq!(g.parent_index(head), Some(body));
+        g.reparent(beam, Some(body)).unwrap();
+        assert_eq!(g.parent_index(beam), Some(body));
+        assert_eq!(g.count_children(head), 1);
+        g.reparent(beam, None).unwrap();
+        assert!(g.is_top_level(beam));
+        g.reparent(beam, Some(head)).unwrap();
+
+        // remove
+        assert!(matches!(
+            g.remove(pixel),
+            Err(GeometriesError::TemplateInUse(i)) if i == pixel
+        ));
+        let removed = g.remove(head).unwrap();
+        assert_eq!(removed.name, "Head");
+        assert_eq!(g.graph().node_count(), 2);
+        assert_eq!(g.names().len(), 2);
+        assert_eq!(g.get_index(&"Body".try_into().unwrap()), Some(body));
+        // graph indices of the remaining geometries are kept
+        assert_eq!(g.get_index(&"Pixel".try_into().unwrap()), Some(pixel));
+        assert_eq!(g.get_by_index(pixel).unwrap().name, "Pixel");
+        assert!(g.validate_index(beam).is_err());
+        assert!(!g.is_template(pixel));
+        g.remove(pixel).unwrap();
+        assert_eq!(g.names().len(), 1);
     }
 
     #[test]
@@ -354,4 +950,72 @@ mod tests {
         assert_eq!(geometries.graph().node_count(), 0);
         assert_eq!(geometries.names().len(), 0);
     }
+
+    #[test]
+    fn instances_of_template_geometries() {
+        let geometry = |name: &str, t| Geometry {
+            name: name.try_into().unwrap(),
+            t,
+            ..Default::default()
+        };
+        let mut g = Geometries::default();
+        let main = g.add_top_level(geometry("Main", Type::General)).unwrap();
+        let pixel = g.add_top_level(geometry("Pixel", Type::General)).unwrap();
+        let lens = g.add(geometry("Lens", Type::General), pixel).unwrap();
+        let reference = g
+            .add(
+                geometry(
+                    "Pixel1",
+                    Type::Reference {
+                        offsets: Offsets::default(),
+                    },
+                ),
+                main,
+            )
+            .unwrap();
+        g.add_template_relationship(pixel, reference).unwrap();
+
+        let reference2 = g
+            .add(
+                geometry(
+                    "Pixel2",
+                    Type::Reference {
+                        offsets: Offsets::default(),
+                    },
+                ),
+                main,
+            )
+            .unwrap();
+        g.add_template_relationship(pixel, reference2).unwrap();
+
+        g.instantiate(lens, reference).unwrap();
+        g.instantiate(lens, reference).unwrap();
+        assert!(g.is_instantiated(lens, reference));
+        assert!(g.is_instantiated(pixel, reference));
+        assert!(!g.is_instantiated(lens, reference2));
+        assert_eq!(
+            g.instances().iter().collect::<Vec<_>>(),
+            [&(reference, lens)]
+        );
+        assert!(matches!(
+            g.instantiate(main, reference),
+            Err(GeometriesError::NotInReferencedTemplate { .. })
+        ));
+
+        // instances are not part of the geometry tree
+        assert_eq!(g.graph().node_count(), 5);
+        assert_eq!(g.count_children(reference), 0);
+
+        // template geometries with instances can be renamed, but not moved
+        g.rename(lens, "Beam".try_into().unwrap()).unwrap();
+        assert!(g.is_instantiated(lens, reference));
+        assert!(matches!(
+            g.reparent(lens, None),
+            Err(GeometriesError::Instantiated(i)) if i == lens
+        ));
+
+        g.remove(reference).unwrap();
+        assert!(g.instances().is_empty());
+        assert_eq!(g.names().len(), 4);
+    }
 }
@@ -1,41 +1,276 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
-use crate::{dmx_break::Break, name::Name};
+use crate::{
+    dmx_address::DmxAddress, dmx_break::Break, extensions::Extensions, name::Name,
+    position::Position, source_location::SourceLocation,
+};
 
 /// A geometry node in the geometry graph
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Geometry {
     pub name: Name,
     pub t: Type,
+    /// relative to the parent geometry
+    pub position: Position,
+    /// Name of the linked model in `Gdtf::models`, `None` if no model is
+    /// linked
+    pub model: Option<Name>,
+    /// attributes that are not represented by other fields
+    pub extensions: Extensions,
+    /// where this was parsed from, `None` if not parsed from a file
+    pub source: Option<SourceLocation>,
 }
 
-/// The Geometry Type as indicated by the XML tag name
-#[derive(Debug, Clone)]
+/// The Geometry Type as indicated by the XML tag name, with the attributes
+/// specific to that type
+///
+/// The name of the variant is available with `<&str>::from(&t)`.
+#[derive(Debug, Clone, Default, strum::IntoStaticStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Type {
+    /// `<Geometry>`
+    #[default]
     General,
-    Reference { offsets: Offsets }, // referenced top level geometry kept in `templates` graph
+    /// Axis has no type-specific attributes in GDTF 1.2, its movement range
+    /// is defined by the physical values of the channel functions operating on
+    /// it
+    Axis,
+    FilterBeam,
+    FilterColor,
+    FilterGobo,
+    FilterShaper,
+    Beam(Beam),
+    MediaServerLayer,
+    MediaServerCamera,
+    MediaServerMaster,
+    Display {
+        /// name of the texture resource without extension, `None` if absent
+        /// or empty
+        texture: Option<String>,
+    },
+    Laser(Laser),
+    WiringObject(WiringObject),
+    Inventory {
+        count: u32,
+    },
+    // TODO parse attributes of Structure and Support
+    Structure,
+    Support,
+    Magnet,
+    // referenced top level geometry kept in `templates` graph
+    Reference {
+        offsets: Offsets,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Beam {
+    pub lamp_type: LampType,
+    /// in W
+    pub power_consumption: f64,
+    /// in lm
+    pub luminous_flux: f64,
+    /// in K
+    pub color_temperature: f64,
+    /// in degrees
+    pub beam_angle: f64,
+    /// in degrees
+    pub field_angle: f64,
+    pub throw_ratio: f64,
+    pub rectangle_ratio: f64,
+    /// in m
+    pub beam_radius: f64,
+    pub beam_type: BeamType,
+    /// 0 to 100
+    pub color_rendering_index: u8,
+    /// name of the emitter describing the spectrum, `None` if absent or empty
+    pub emitter_spectrum: Option<String>,
+}
+
+/// Defaults from GDTF 1.2
+impl Default for Beam {
+    fn default() -> Self {
+        Self {
+            lamp_type: LampType::Discharge,
+            power_consumption: 1000.,
+            luminous_flux: 10000.,
+            color_temperature: 6000.,
+            beam_angle: 25.,
+            field_angle: 25.,
+            throw_ratio: 1.,
+            rectangle_ratio: 1.7777,
+            beam_radius: 0.05,
+            beam_type: BeamType::Wash,
+            color_rendering_index: 100,
+            emitter_spectrum: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LampType {
+    Discharge,
+    Tungsten,
+    Halogen,
+    #[strum(serialize = "LED")]
+    Led,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BeamType {
+    Wash,
+    Spot,
+    None,
+    Rectangle,
+    #[strum(serialize = "PC")]
+    Pc,
+    Fresnel,
+    Glow,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Laser {
+    pub color_type: LaserColorType,
+    /// wavelength in nm, only relevant for `LaserColorType::SingleWave`
+    pub color: f64,
+    /// in W
+    pub output_strength: f64,
+    /// name of the emitter, `None` if absent or empty
+    pub emitter: Option<String>,
+    /// in m
+    pub beam_diameter: f64,
+    /// in mrad
+    pub beam_divergence_min: f64,
+    /// in mrad
+    pub beam_divergence_max: f64,
+    /// in degrees
+    pub scan_angle_pan: f64,
+    /// in degrees
+    pub scan_angle_tilt: f64,
+    /// in m/s
+    pub scan_speed: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, strum::EnumString, strum::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LaserColorType {
+    #[default]
+    #[strum(serialize = "RGB")]
+    Rgb,
+    SingleWave,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WiringObject {
+    pub connector_type: String,
+    pub component_type: ComponentType,
+    pub signal_type: String,
+    pub pin_count: u32,
+    /// in W
+    pub electrical_payload: f64,
+    /// in V
+    pub voltage_range_max: f64,
+    /// in V
+    pub voltage_range_min: f64,
+    /// in Hz
+    pub frequency_range_max: f64,
+    /// in Hz
+    pub frequency_range_min: f64,
+    /// in W
+    pub max_payload: f64,
+    /// in V
+    pub voltage: f64,
+    pub signal_layer: i32,
+    pub cos_phi: f64,
+    /// in A
+    pub fuse_current: f64,
+    pub fuse_rating: Option<FuseRating>,
+    pub orientation: Option<Orientation>,
+    pub wire_group: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, strum::EnumString, strum::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ComponentType {
+    #[default]
+    Input,
+    Output,
+    PowerSource,
+    Consumer,
+    Fuse,
+    NetworkProvider,
+    NetworkInput,
+    NetworkOutput,
+    NetworkInOut,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FuseRating {
+    B,
+    C,
+    D,
+    K,
+    Z,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Orientation {
+    Left,
+    Right,
+    Top,
+    Bottom,
 }
 
-// TODO When Channel parsing is implemented, there needs to be a validation that
-// each `Offsets` in a `GeometryReference` contains precisely the breaks
-// required by channels operating on the referenced geometry. No more or less
-// breaks are allowed to be present (see GDTF 1.2 page 39).
-// This might require a new data structure to hold the offsets for all
-// geometry references for a certain abstract top level geometry.
 #[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Offsets {
-    pub normal: HashMap<Break, i32>, // TODO currently 1-based. 0-based would be easier internally...
+    /// sorted by break
+    #[cfg_attr(feature = "serde", serde(with = "offsets_by_break"))]
+    pub normal: BTreeMap<Break, DmxAddress>,
     pub overwrite: Option<Offset>, // TODO make this mandatory, if it's not there it means there are no offsets at all and we might as well give up the whole GeometryReference...
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Offset {
     pub dmx_break: Break,
-    /// should support "Universe.Address" format according to standard, but that
-    /// is not implemented at the moment
-    pub offset: i32, // TODO is there validation on it?
-                     // TODO GDTF Builder disallows anything less than 1, so maybe we should switch this to validated 1..512 u16
-                     // TODO consider https://docs.rs/bounded-integer/latest/bounded_integer/
+    /// may span universes, e.g. `2.1` is offset 513
+    pub offset: DmxAddress,
+}
+
+/// Serializes offsets as a list of `[break, offset]` pairs. Map keys would
+/// become strings in JSON, which can't be read back as breaks inside the
+/// flattened geometries of [`Geometries`](crate::geometries::Geometries).
+#[cfg(feature = "serde")]
+mod offsets_by_break {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::{dmx_address::DmxAddress, dmx_break::Break};
+
+    pub fn serialize<S: Serializer>(
+        normal: &BTreeMap<Break, DmxAddress>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(normal)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<Break, DmxAddress>, D::Error> {
+        Ok(Vec::<(Break, DmxAddress)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,650 @@
+use std::collections::HashSet;
+
+use crate::{
+    channel::Channel, dmx_address::DmxAddress, dmx_break::Break, dmx_modes::DmxMode,
+    geometry::Type, name::Name, severity::Severity, Gdtf,
+};
+
+/// Result of all integrity checks on a `Gdtf`, grouped in sections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegrityReport {
+    pub sections: Vec<IntegritySection>,
+}
+
+/// The result of one group of checks. Passed if there are no issues.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegritySection {
+    pub name: &'static str,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum IntegrityIssue {
+    #[error("geometry '{geometry}' links unknown model '{model}'")]
+    UnknownModel { geometry: Name, model: Name },
+    #[error("duplicate DMX mode name '{0}'")]
+    DuplicateDmxModeName(Name),
+    #[error("DMX mode '{0}' has no channels")]
+    EmptyDmxMode(Name),
+    #[error(
+        "channels '{first}' and '{second}' in DMX mode '{mode}' both occupy offset {offset} in break {dmx_break}"
+    )]
+    OverlappingChannels {
+        mode: Name,
+        dmx_break: Break,
+        offset: u16,
+        first: Name,
+        second: Name,
+    },
+    #[error(
+        "channel function '{channel_function}' of channel '{channel}' in DMX mode '{mode}' has the \
+        same PhysicalFrom and PhysicalTo"
+    )]
+    DegeneratePhysicalRange {
+        mode: Name,
+        channel: Name,
+        channel_function: Name,
+    },
+    #[error(
+        "channel function '{channel_function}' of channel '{channel}' in DMX mode '{mode}' has a \
+        PhysicalTo below its PhysicalFrom"
+    )]
+    ReversedPhysicalRange {
+        mode: Name,
+        channel: Name,
+        channel_function: Name,
+    },
+    #[error(
+        "GeometryReference '{reference}' declares offset {offset} in break {dmx_break}, beyond the \
+        footprint {footprint} of DMX mode '{mode}'"
+    )]
+    ReferenceOffsetBeyondFootprint {
+        mode: Name,
+        reference: Name,
+        dmx_break: Break,
+        offset: DmxAddress,
+        footprint: u16,
+    },
+    #[error(
+        "channel function '{channel_function}' of channel '{channel}' in DMX mode '{mode}' links \
+        unknown wheel '{wheel}'"
+    )]
+    UnknownWheel {
+        mode: Name,
+        channel: Name,
+        channel_function: Name,
+        wheel: Name,
+    },
+    #[error("relation '{relation}' in DMX mode '{mode}' links unknown channel '{link}'")]
+    DanglingRelation {
+        mode: Name,
+        relation: Name,
+        link: String,
+    },
+    #[error("macro '{ft_macro}' in DMX mode '{mode}' links unknown channel '{link}'")]
+    DanglingMacro {
+        mode: Name,
+        ft_macro: Name,
+        link: String,
+    },
+    #[error("model '{0}' is not used by any geometry")]
+    UnusedModel(Name),
+    #[error("wheel '{0}' is not used by any channel function")]
+    UnusedWheel(Name),
+    #[error(
+        "top-level geometry '{0}' is neither the geometry of a DMX mode nor referenced by a \
+        GeometryReference"
+    )]
+    UnusedGeometry(Name),
+}
+
+impl IntegrityIssue {
+    /// Unused definitions are warnings, all other issues are errors.
+    pub fn severity(&self) -> Severity {
+        match self {
+            IntegrityIssue::UnusedModel(_)
+            | IntegrityIssue::UnusedWheel(_)
+            | IntegrityIssue::UnusedGeometry(_) => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl IntegritySection {
+    pub fn passed(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl IntegrityReport {
+    pub fn passed(&self) -> bool {
+        self.sections.iter().all(IntegritySection::passed)
+    }
+
+    /// All issues of all sections
+    pub fn issues(&self) -> impl Iterator<Item = &IntegrityIssue> {
+        self.sections.iter().flat_map(|s| s.issues.iter())
+    }
+}
+
+impl Gdtf {
+    /// Run all integrity checks.
+    ///
+    /// Parsing already ensures most invariants, so these checks cover
+    /// properties that can be violated by valid building blocks, like
+    /// overlapping channels.
+    pub fn integrity_report(&self) -> IntegrityReport {
+        IntegrityReport {
+            sections: vec![
+                IntegritySection {
+                    name: "models",
+                    issues: self.model_issues(),
+                },
+                IntegritySection {
+                    name: "DMX modes",
+                    issues: self.dmx_mode_issues(),
+                },
+                IntegritySection {
+                    name: "channel function ranges",
+                    issues: self.physical_range_issues(),
+                },
+                IntegritySection {
+                    name: "geometry reference offsets",
+                    issues: self.reference_offset_issues(),
+                },
+                IntegritySection {
+                    name: "references",
+                    issues: self.dangling_reference_issues(),
+                },
+                IntegritySection {
+                    name: "unused definitions",
+                    issues: self.unused_definition_issues(),
+                },
+            ],
+        }
+    }
+
+    fn model_issues(&self) -> Vec<IntegrityIssue> {
+        self.geometries
+            .graph()
+            .node_weights()
+            .filter_map(|g| {
+                let model = g.model.as_ref()?;
+                self.models
+                    .get_by_name(model)
+                    .is_none()
+                    .then(|| IntegrityIssue::UnknownModel {
+                        geometry: g.name.clone(),
+                        model: model.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    fn dmx_mode_issues(&self) -> Vec<IntegrityIssue> {
+        let mut issues = vec![];
+        let mut mode_names = HashSet::new();
+        for mode in self.dmx_modes() {
+            if !mode_names.insert(&mode.name) {
+                issues.push(IntegrityIssue::DuplicateDmxModeName(mode.name.clone()));
+            }
+            if mode.all_channels().next().is_none() {
+                issues.push(IntegrityIssue::EmptyDmxMode(mode.name.clone()));
+            }
+
+            for overlap in mode.overlapping_offsets() {
+                issues.push(IntegrityIssue::OverlappingChannels {
+                    mode: mode.name.clone(),
+                    dmx_break: overlap.dmx_break,
+                    offset: overlap.offset,
+                    first: overlap.first.name.clone(),
+                    second: overlap.second.name.clone(),
+                });
+            }
+        }
+        issues
+    }
+
+    /// Lint for physical ranges that usually indicate authoring mistakes. They
+    /// are valid GDTF, so they are not reported during parsing.
+    fn physical_range_issues(&self) -> Vec<IntegrityIssue> {
+        let mut issues = vec![];
+        for mode in self.dmx_modes() {
+            for ch in mode.all_channels() {
+                for chf in ch
+                    .channel_functions
+                    .iter()
+                    .filter_map(|i| mode.channel_functions.node_weight(*i))
+                {
+                    let names = || (mode.name.clone(), ch.name.clone(), chf.name.clone());
+                    if chf.phys_from == chf.phys_to {
+                        let (mode, channel, channel_function) = names();
+                        issues.push(IntegrityIssue::DegeneratePhysicalRange {
+                            mode,
+                            channel,
+                            channel_function,
+                        });
+                    } else if chf.phys_to < chf.phys_from {
+                        let (mode, channel, channel_function) = names();
+                        issues.push(IntegrityIssue::ReversedPhysicalRange {
+                            mode,
+                            channel,
+                            channel_function,
+                        });
+                    }
+                }
+            }
+        }
+        issues
+    }
+
+    /// Cross-check the Break offsets of GeometryReferences below the geometry
+    /// of each mode against the footprint the mode actually occupies. An
+    /// offset past the footprint means the subfixture of the reference
+    /// would start at addresses no channel of the mode covers.
+    fn reference_offset_issues(&self) -> Vec<IntegrityIssue> {
+        let mut issues = vec![];
+        let graph = self.geometries.graph();
+        for mode in self.dmx_modes() {
+            let footprint = mode.footprint();
+            for reference in graph
+                .node_indices()
+                .filter(|i| self.geometries.top_level_geometry_index(*i) == *mode.geometry())
+            {
+                let Some(geometry) = graph.node_weight(reference) else {
+                    continue;
+                };
+                let Type::Reference { offsets } = &geometry.t else {
+                    continue;
+                };
+                for (dmx_break, offset) in &offsets.normal {
+                    let highest_offset = footprint
+                        .breaks
+                        .iter()
+                        .find(|b| b.dmx_break == *dmx_break)
+                        .map(|b| b.highest_offset)
+                        .unwrap_or(0);
+                    if offset.absolute() > u32::from(highest_offset) {
+                        issues.push(IntegrityIssue::ReferenceOffsetBeyondFootprint {
+                            mode: mode.name.clone(),
+                            reference: geometry.name.clone(),
+                            dmx_break: *dmx_break,
+                            offset: *offset,
+                            footprint: highest_offset,
+                        });
+                    }
+                }
+            }
+        }
+        issues
+    }
+
+    /// Links by name that don't resolve. Model links of geometries are
+    /// checked in the "models" section.
+    fn dangling_reference_issues(&self) -> Vec<IntegrityIssue> {
+        let mut issues = vec![];
+        for mode in self.dmx_modes() {
+            for ch in mode.all_channels() {
+                for chf in ch
+                    .defined_functions()
+                    .iter()
+                    .filter_map(|i| mode.channel_functions.node_weight(*i))
+                {
+                    let Some(wheel) = &chf.wheel else {
+                        continue;
+                    };
+                    if self.wheels.get_by_name(wheel).is_none() {
+                        issues.push(IntegrityIssue::UnknownWheel {
+                            mode: mode.name.clone(),
+                            channel: ch.name.clone(),
+                            channel_function: chf.name.clone(),
+                            wheel: wheel.clone(),
+                        });
+                    }
+                }
+            }
+
+            let channels = self.gdtf_channel_names(mode);
+            let channel_functions = self.gdtf_channel_function_links(mode);
+            for relation in &mode.relations {
+                let master = relation.master.to_string();
+                for link in [master, relation.follower.clone()] {
+                    if !channels.contains(&link) && !channel_functions.contains(&link) {
+                        issues.push(IntegrityIssue::DanglingRelation {
+                            mode: mode.name.clone(),
+                            relation: relation.name.clone(),
+                            link,
+                        });
+                    }
+                }
+            }
+            for ft_macro in &mode.macros {
+                let links = ft_macro
+                    .channel_function
+                    .iter()
+                    .filter(|link| !channel_functions.contains(*link))
+                    .cloned()
+                    .chain(
+                        ft_macro
+                            .steps
+                            .iter()
+                            .flat_map(|step| &step.values)
+                            .map(|value| value.dmx_channel.to_string())
+                            .filter(|link| !channels.contains(link)),
+                    );
+                for link in links {
+                    issues.push(IntegrityIssue::DanglingMacro {
+                        mode: mode.name.clone(),
+                        ft_macro: ft_macro.name.clone(),
+                        link,
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    /// Models, wheels and top-level geometries that nothing links to. They
+    /// are valid GDTF, but often left over from editing.
+    fn unused_definition_issues(&self) -> Vec<IntegrityIssue> {
+        let graph = self.geometries.graph();
+        let used_models: HashSet<&Name> = graph
+            .node_weights()
+            .filter_map(|g| g.model.as_ref())
+            .collect();
+        let used_wheels: HashSet<&Name> = self
+            .dmx_modes()
+            .iter()
+            .flat_map(|mode| mode.channel_functions.node_weights())
+            .filter_map(|chf| chf.wheel.as_ref())
+            .collect();
+
+        let models = self
+            .models
+            .iter()
+            .filter(|m| !used_models.contains(&m.name))
+            .map(|m| IntegrityIssue::UnusedModel(m.name.clone()));
+        let wheels = self
+            .wheels
+            .iter()
+            .filter(|w| !used_wheels.contains(&w.name))
+            .map(|w| IntegrityIssue::UnusedWheel(w.name.clone()));
+        let geometries = self
+            .geometries
+            .top_level_indices()
+            .filter(|i| {
+                !self.geometries.is_template(*i)
+                    && !self.dmx_modes().iter().any(|mode| mode.geometry() == i)
+            })
+            .filter_map(|i| graph.node_weight(i))
+            .map(|g| IntegrityIssue::UnusedGeometry(g.name.clone()));
+        models.chain(wheels).chain(geometries).collect()
+    }
+
+    /// Names GDTF uses to link the channels of `mode`, i.e.
+    /// `{Geometry}_{Attribute}` with the geometry of the template for
+    /// template channels
+    fn gdtf_channel_names(&self, mode: &DmxMode) -> HashSet<String> {
+        mode.all_channels()
+            .map(|ch| self.gdtf_channel_name(ch))
+            .collect()
+    }
+
+    /// Links GDTF uses for the channel functions of `mode`, i.e.
+    /// `{Geometry}_{Attribute}.{Attribute}.{ChannelFunction}`
+    fn gdtf_channel_function_links(&self, mode: &DmxMode) -> HashSet<String> {
+        mode.all_channels()
+            .flat_map(|ch| {
+                let channel = self.gdtf_channel_name(ch);
+                ch.defined_functions()
+                    .iter()
+                    .filter_map(|i| mode.channel_functions.node_weight(*i))
+                    .map(move |chf| format!("{channel}.{}.{}", ch.attribute, chf.name))
+            })
+            .collect()
+    }
+
+    fn gdtf_channel_name(&self, ch: &Channel) -> String {
+        let geometry = self
+            .geometries
+            .get_by_index(ch.template_geometry.unwrap_or(ch.geometry))
+            .map(|g| g.name.to_string())
+            .unwrap_or_default();
+        format!("{geometry}_{}", ch.attribute)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        builder::GdtfBuilder,
+        geometry::{Geometry, Offsets},
+        models::Model,
+        name::IntoValidName,
+        relations::{Relation, RelationType},
+        wheels::Wheel,
+    };
+
+    use super::*;
+
+    #[test]
+    fn integrity_report() {
+        let mut gdtf = Gdtf::default();
+        let body = gdtf
+            .geometries
+            .add_top_level(Geometry {
+                name: "Body".into_valid(),
+                model: Some("Missing".into_valid()),
+                ..Default::default()
+            })
+            .unwrap();
+        gdtf.add_dmx_mode("Mode".into_valid(), "".into(), body)
+            .unwrap();
+
+        let report = gdtf.integrity_report();
+        assert!(!report.passed());
+        assert_eq!(
+            report.issues().collect::<Vec<_>>(),
+            vec![
+                &IntegrityIssue::UnknownModel {
+                    geometry: "Body".into_valid(),
+                    model: "Missing".into_valid()
+                },
+                &IntegrityIssue::EmptyDmxMode("Mode".into_valid()),
+            ]
+        );
+        let failed: Vec<_> = report
+            .sections
+            .iter()
+            .filter(|s| !s.passed())
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(failed, ["models", "DMX modes"]);
+
+        assert!(Gdtf::default().integrity_report().passed());
+    }
+
+    #[test]
+    fn physical_range_lint() {
+        let gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Dimmer", 1, vec![1])
+            .unwrap()
+            .channel_function("Mode", "Body_Dimmer", "Closed", "Dimmer", 0, 0., 0.)
+            .unwrap()
+            .channel_function("Mode", "Body_Dimmer", "Dimmer", "Dimmer", 10, 1., 0.)
+            .unwrap()
+            .channel_function("Mode", "Body_Dimmer", "Open", "Dimmer", 250, 0., 1.)
+            .unwrap()
+            .build();
+
+        let report = gdtf.integrity_report();
+        let issues = |name| {
+            report
+                .sections
+                .iter()
+                .find(|s| s.name == name)
+                .unwrap()
+                .issues
+                .clone()
+        };
+        assert_eq!(
+            issues("channel function ranges"),
+            vec![
+                IntegrityIssue::DegeneratePhysicalRange {
+                    mode: "Mode".into_valid(),
+                    channel: "Body_Dimmer".into_valid(),
+                    channel_function: "Closed".into_valid(),
+                },
+                IntegrityIssue::ReversedPhysicalRange {
+                    mode: "Mode".into_valid(),
+                    channel: "Body_Dimmer".into_valid(),
+                    channel_function: "Dimmer".into_valid(),
+                },
+            ]
+        );
+        assert!(issues("DMX modes").is_empty());
+    }
+
+    #[test]
+    fn reference_offsets_beyond_footprint() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Dimmer", 1, vec![1, 2])
+            .unwrap()
+            .build();
+        let body = gdtf.geometries.get_index(&"Body".into_valid()).unwrap();
+        for (name, offset) in [("Pixel 1", 2), ("Pixel 2", 3)] {
+            gdtf.geometries
+                .add(
+                    Geometry {
+                        name: name.into_valid(),
+                        t: Type::Reference {
+                            offsets: Offsets {
+                                normal: BTreeMap::from([(
+                                    Break::try_from(1).unwrap(),
+                                    DmxAddress::try_from(offset).unwrap(),
+                                )]),
+                                overwrite: None,
+                            },
+                        },
+                        ..Default::default()
+                    },
+                    body,
+                )
+                .unwrap();
+        }
+
+        let report = gdtf.integrity_report();
+        assert_eq!(
+            report
+                .sections
+                .iter()
+                .find(|s| s.name == "geometry reference offsets")
+                .unwrap()
+                .issues,
+            vec![IntegrityIssue::ReferenceOffsetBeyondFootprint {
+                mode: "Mode".into_valid(),
+                reference: "Pixel 2".into_valid(),
+                dmx_break: Break::try_from(1).unwrap(),
+                offset: DmxAddress::try_from(3).unwrap(),
+                footprint: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn dangling_references_and_unused_definitions() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .geometry("Leftover", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Color1", 1, vec![1])
+            .unwrap()
+            .channel_function("Mode", "Body_Color1", "Color", "Color1", 0, 0., 1.)
+            .unwrap()
+            .build();
+        gdtf.models
+            .add(Model {
+                name: "Unused Model".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        gdtf.wheels
+            .add(Wheel {
+                name: "Unused Wheel".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        let mode = gdtf.dmx_mode_mut(0).unwrap();
+        let chf = *mode
+            .channels
+            .first()
+            .unwrap()
+            .defined_functions()
+            .first()
+            .unwrap();
+        mode.channel_functions.node_weight_mut(chf).unwrap().wheel = Some("Missing".into_valid());
+        mode.relations.push(Relation {
+            name: "Virtual Dimmer".into_valid(),
+            master: "Body_Dimmer".into_valid(),
+            follower: "Body_Color1.Color1.Color".into(),
+            relation_type: RelationType::Multiply,
+        });
+
+        let report = gdtf.integrity_report();
+        let issues = |name| {
+            report
+                .sections
+                .iter()
+                .find(|s| s.name == name)
+                .unwrap()
+                .issues
+                .clone()
+        };
+        assert_eq!(
+            issues("references"),
+            vec![
+                IntegrityIssue::UnknownWheel {
+                    mode: "Mode".into_valid(),
+                    channel: "Body_Color1".into_valid(),
+                    channel_function: "Color".into_valid(),
+                    wheel: "Missing".into_valid(),
+                },
+                IntegrityIssue::DanglingRelation {
+                    mode: "Mode".into_valid(),
+                    relation: "Virtual Dimmer".into_valid(),
+                    link: "Body_Dimmer".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            issues("unused definitions"),
+            vec![
+                IntegrityIssue::UnusedModel("Unused Model".into_valid()),
+                IntegrityIssue::UnusedWheel("Unused Wheel".into_valid()),
+                IntegrityIssue::UnusedGeometry("Leftover".into_valid()),
+            ]
+        );
+        assert!(issues("references")
+            .iter()
+            .all(|i| i.severity() == Severity::Error));
+        assert!(issues("unused definitions")
+            .iter()
+            .all(|i| i.severity() == Severity::Warning));
+    }
+}
@@ -0,0 +1,186 @@
+use crate::Gdtf;
+
+impl Gdtf {
+    /// Serialize the complete fixture model to pretty-printed JSON, for tools
+    /// written in other languages.
+    ///
+    /// The geometry graph is flattened into a list of geometries, each with
+    /// its graph index and the index of its parent. Geometries are referred to
+    /// by this graph index, which is not their position in the list: the
+    /// indices of removed geometries are skipped. The channel functions of
+    /// each DMX mode are flattened into a list of nodes and a list of
+    /// ModeMaster edges, and are referred to by their position in the list of
+    /// nodes.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Read a fixture model in the form written by [`Gdtf::to_json`], e.g.
+    /// after editing it, to serialize it to GDTF again.
+    ///
+    /// The geometry tree, template relationships and channel function graphs
+    /// are rebuilt and must be valid. Invalid names, offsets or DMX addresses
+    /// are errors as well. References by index from DMX modes to geometries
+    /// and channel functions are not checked, use
+    /// [`Gdtf::integrity_report`] for that.
+    pub fn from_json(json: &str) -> Result<Gdtf, serde_json::Error> {
+        let mut gdtf: Gdtf = serde_json::from_str(json)?;
+        for mode in gdtf.dmx_modes.iter_mut() {
+            mode.index_channel_functions();
+        }
+        Ok(gdtf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{geometry::Geometry, name::IntoValidName, parser::parse_description};
+
+    use super::*;
+
+    #[test]
+    fn geometries_and_channel_functions_are_flattened() {
+        let gdtf = parse_description(
+            r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Test" FixtureTypeID="00000000-0000-0000-0000-000000000001">
+    <Geometries>
+        <Geometry Name="Body">
+            <Beam Name="Beam"/>
+        </Geometry>
+    </Geometries>
+    <DMXModes>
+        <DMXMode Name="Mode" Geometry="Body">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Beam">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Name="Dimmer" Attribute="Dimmer" DMXFrom="0/1"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#
+                .into(),
+            &Default::default(),
+        )
+        .unwrap()
+        .gdtf;
+
+        let value: Value = serde_json::from_str(&gdtf.to_json().unwrap()).unwrap();
+        let at = |pointer| value.pointer(pointer).unwrap();
+        assert_eq!(at("/data_version"), &json!("1.2"));
+        assert_eq!(
+            at("/fixture_type_id"),
+            &json!("00000000-0000-0000-0000-000000000001")
+        );
+
+        let geometries: Vec<_> = at("/geometries")
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|g| (g.get("name").unwrap(), g.get("parent").unwrap()))
+            .collect();
+        assert_eq!(
+            geometries,
+            [(&json!("Body"), &json!(null)), (&json!("Beam"), &json!(0))]
+        );
+
+        assert_eq!(at("/dmx_modes/0/geometry"), &json!(0));
+        assert_eq!(at("/dmx_modes/0/channels/0/geometry"), &json!(1));
+        assert_eq!(
+            at("/dmx_modes/0/channels/0/channel_functions"),
+            &json!([0, 1])
+        );
+        let channel_functions: Vec<_> = at("/dmx_modes/0/channel_functions/nodes")
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|chf| chf.get("name").unwrap())
+            .collect();
+        assert_eq!(channel_functions, [&json!("Beam_Dimmer"), &json!("Dimmer")]);
+        assert_eq!(at("/dmx_modes/0/channel_functions/edges"), &json!([]));
+    }
+
+    #[test]
+    fn geometries_keep_their_graph_index() {
+        let mut gdtf = Gdtf::default();
+        let mut add = |name: &str| {
+            gdtf.geometries
+                .add_top_level(Geometry {
+                    name: name.into_valid(),
+                    ..Default::default()
+                })
+                .unwrap()
+        };
+        let removed = add("Removed");
+        let kept = add("Kept");
+        gdtf.remove_geometry(removed).unwrap();
+
+        let json = gdtf.to_json().unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value.pointer("/geometries/0/index"), Some(&json!(1)));
+
+        let read = Gdtf::from_json(&json).unwrap();
+        assert_eq!(read.geometries.get_index(&"Kept".into_valid()), Some(kept));
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let gdtf = parse_description(
+            r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Test" FixtureTypeID="00000000-0000-0000-0000-000000000001">
+    <Wheels>
+        <Wheel Name="Gobo">
+            <Slot Name="Open" Color="0.3127,0.3290,100.000000"/>
+        </Wheel>
+    </Wheels>
+    <Geometries>
+        <Geometry Name="Body">
+            <Beam Name="Beam"/>
+            <GeometryReference Name="Pixel1" Geometry="Pixel">
+                <Break DMXBreak="1" DMXOffset="3"/>
+            </GeometryReference>
+        </Geometry>
+        <Geometry Name="Pixel"/>
+    </Geometries>
+    <DMXModes>
+        <DMXMode Name="Mode" Geometry="Body">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Offset="1,2" Geometry="Beam">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Name="Dimmer" Attribute="Dimmer" DMXFrom="0/2" PhysicalTo="1.000000"/>
+                    </LogicalChannel>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Pixel">
+                    <LogicalChannel Attribute="Gobo1">
+                        <ChannelFunction Name="Gobo" Attribute="Gobo1" DMXFrom="0/1" Wheel="Gobo"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#
+                .into(),
+            &Default::default(),
+        )
+        .unwrap()
+        .gdtf;
+
+        let json = gdtf.to_json().unwrap();
+        let roundtripped = Gdtf::from_json(&json).unwrap();
+        assert!(roundtripped.semantically_eq(&gdtf));
+        assert_eq!(roundtripped.to_json().unwrap(), json);
+
+        let mut value: Value = serde_json::from_str(&json).unwrap();
+        *value.pointer_mut("/geometries/1/parent").unwrap() = json!(7);
+        assert!(Gdtf::from_json(&value.to_string()).is_err());
+
+        let mut value: Value = serde_json::from_str(&json).unwrap();
+        *value.pointer_mut("/name").unwrap() = json!("Invalid.Name");
+        assert!(Gdtf::from_json(&value.to_string()).is_err());
+    }
+}
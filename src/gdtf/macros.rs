@@ -0,0 +1,39 @@
+use crate::{extensions::Extensions, name::Name};
+
+/// Fixture type preset, as defined in the `FTPresets` node.
+///
+/// The content of presets is not defined in GDTF 1.2 yet, so all attributes
+/// and child nodes are kept as extensions.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FtPreset {
+    pub extensions: Extensions,
+}
+
+/// Fixture type macro of a DMX mode, as defined in the `FTMacros` node.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FtMacro {
+    pub name: Name,
+    /// link to the channel function as written in GDTF, `None` if absent
+    pub channel_function: Option<String>,
+    /// steps of the `MacroDMX` node, executed in order
+    pub steps: Vec<MacroDmxStep>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MacroDmxStep {
+    /// in seconds
+    pub duration: f64,
+    pub values: Vec<MacroDmxValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MacroDmxValue {
+    /// in the resolution of the channel
+    pub value: u32,
+    /// GDTF name of the DMX channel, i.e. `{Geometry}_{Attribute}`
+    pub dmx_channel: Name,
+}
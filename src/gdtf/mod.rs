@@ -1,22 +1,51 @@
 use getset::Getters;
+use petgraph::graph::NodeIndex;
 use uuid::Uuid;
 
 use self::{
     data_version::DataVersion,
     dmx_modes::DmxMode,
+    extensions::{Extensions, UnparsedSections},
     geometries::{Geometries, GeometriesError},
+    geometry::Geometry,
+    macros::FtPreset,
+    models::Models,
     name::Name,
+    protocols::Protocols,
+    wheels::Wheels,
 };
 
+pub mod beams;
+pub mod builder;
 pub mod channel;
+pub mod channel_label;
 pub mod channel_offsets;
 pub mod checked_graph;
 pub mod data_version;
+pub mod dmx_address;
 pub mod dmx_break;
 pub mod dmx_modes;
+mod dot;
+pub mod extensions;
+pub mod fixture_meta;
+pub mod fixture_type_id;
+pub(crate) mod float;
 pub mod geometries;
 pub mod geometry;
+pub mod integrity;
+#[cfg(feature = "json")]
+mod json;
+pub mod macros;
+pub mod models;
 pub mod name;
+mod normalize;
+pub mod physical_value;
+pub mod position;
+pub mod protocols;
+pub mod relations;
+pub mod severity;
+pub mod source_location;
+pub mod wheels;
 
 /// A mid-level representation of a GDTF fixture.
 ///
@@ -32,7 +61,16 @@ pub mod name;
 /// For example, template channels and geometries are kept as such and not
 /// instantiated. Yet, references between nodes don't have to be kept as strings
 /// but can be encoded with indices or graphs instead.
+///
+/// Iteration order is deterministic, so serialized output and reported
+/// problems are reproducible:
+/// - DMX modes, their channels and channel functions are in the order of the
+///   GDTF file or of construction
+/// - top-level geometries and the children of a geometry are in the order they
+///   were added
+/// - geometry names are sorted by name, reference offsets by DMX break
 #[derive(Debug, Getters)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[getset(get = "pub")]
 pub struct Gdtf {
     pub data_version: DataVersion,
@@ -45,10 +83,22 @@ pub struct Gdtf {
     pub long_name: String,
     pub manufacturer: String,
     pub description: String,
+    /// file name of the thumbnail without extension, `None` if absent or empty
+    pub thumbnail: Option<String>,
+    /// attributes and child nodes of the FixtureType node that are not
+    /// represented by other fields
+    pub extensions: Extensions,
+    /// sections of the FixtureType node that are not parsed yet
+    pub unparsed: UnparsedSections,
 
+    pub wheels: Wheels,
+    pub models: Models,
     pub geometries: Geometries,
 
     dmx_modes: Vec<DmxMode>,
+
+    pub ft_presets: Vec<FtPreset>,
+    pub protocols: Protocols,
 }
 
 impl Default for Gdtf {
@@ -63,8 +113,15 @@ impl Default for Gdtf {
             long_name: Default::default(),
             manufacturer: Default::default(),
             description: Default::default(),
+            thumbnail: Default::default(),
+            extensions: Default::default(),
+            unparsed: Default::default(),
+            wheels: Default::default(),
+            models: Default::default(),
             geometries: Default::default(),
             dmx_modes: Default::default(),
+            ft_presets: Default::default(),
+            protocols: Default::default(),
         }
     }
 }
@@ -81,6 +138,56 @@ impl Gdtf {
             .get_mut(index)
             .ok_or(GdtfError::InvalidDmxModeIndex(index))
     }
+
+    /// Renames the geometry with given graph index, see [`Geometries::rename`].
+    ///
+    /// DMX modes refer to geometries by graph index, so these references stay
+    /// valid. Names that GDTF derives from geometry names are updated in all
+    /// DMX modes: `{Geometry}_{Attribute}` in the names of raw DMX channel
+    /// functions, relations and macros. Names of channels and subfixtures are
+    /// synthesized again with the naming options the mode was parsed with, if
+    /// they still have the name synthesized before the rename.
+    pub fn rename_geometry(
+        &mut self,
+        graph_index: NodeIndex,
+        new_name: Name,
+    ) -> Result<(), GdtfError> {
+        let old_name = self.geometries.get_by_index(graph_index)?.name.clone();
+        self.geometries.rename(graph_index, new_name.clone())?;
+        for mode in self.dmx_modes.iter_mut() {
+            mode.rename_geometry(&self.geometries, graph_index, &old_name, &new_name);
+        }
+        Ok(())
+    }
+
+    /// Removes the geometry with given graph index together with all its
+    /// descendants and returns the removed geometry, see
+    /// [`Geometries::remove`].
+    ///
+    /// Returns an Err and does nothing if a DMX mode, one of its subfixtures,
+    /// channels or channel functions still uses one of the removed geometries.
+    pub fn remove_geometry(&mut self, graph_index: NodeIndex) -> Result<Geometry, GdtfError> {
+        let removed = self
+            .geometries
+            .descendants(self.geometries.validate_index(graph_index)?);
+        let in_use = |i: &NodeIndex| removed.contains(i);
+        if let Some(mode) = self.dmx_modes.iter().find(|mode| {
+            in_use(mode.geometry())
+                || mode.all_subfixtures().any(|sf| in_use(&sf.geometry))
+                || mode
+                    .all_channels()
+                    .chain(mode.uninstantiated_channels())
+                    .any(|ch| {
+                        in_use(&ch.geometry) || ch.template_geometry.as_ref().is_some_and(in_use)
+                    })
+                || mode.channel_functions.node_weights().any(|chf| {
+                    in_use(&chf.geometry) || chf.template_geometry.as_ref().is_some_and(in_use)
+                })
+        }) {
+            return Err(GdtfError::GeometryInUse(mode.name.clone()));
+        }
+        Ok(self.geometries.remove(graph_index)?)
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -91,8 +198,218 @@ pub enum GdtfError {
     GeometriesError(#[from] GeometriesError),
     #[error("DMX mode geometry must be top-level")]
     NonTopLevelGeometry,
+    #[error("geometry is still used by DMX mode '{0}'")]
+    GeometryInUse(Name),
 }
 
 // TODO it's probably worth having a test case here for constructing an example gdtf
 // For example, it seems totally within the standard to have a mode main geometry to be a template geometry,
 // something I don't think I have accounted for
+
+#[cfg(test)]
+mod tests {
+    use super::{builder::GdtfBuilder, *};
+    use crate::{parser::parse_description, ChannelNaming, ParseOptions, SubfixtureNaming};
+
+    #[test]
+    fn remove_geometry_keeps_indices_used_by_modes() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .geometry("Unused", Some("Body"))
+            .unwrap()
+            .geometry("Beam", Some("Body"))
+            .unwrap()
+            .dmx_mode("Mode 1", "Body")
+            .unwrap()
+            .channel("Mode 1", "Beam", "Dimmer", 1, vec![1])
+            .unwrap()
+            .build();
+        let unused = gdtf
+            .geometries
+            .get_index(&"Unused".try_into().unwrap())
+            .unwrap();
+        let beam = gdtf
+            .geometries
+            .get_index(&"Beam".try_into().unwrap())
+            .unwrap();
+
+        assert_eq!(gdtf.remove_geometry(unused).unwrap().name, "Unused");
+        let geometry = gdtf.dmx_mode(0).unwrap().channels.first().unwrap().geometry;
+        assert_eq!(gdtf.geometries.get_by_index(geometry).unwrap().name, "Beam");
+
+        assert!(matches!(
+            gdtf.remove_geometry(beam),
+            Err(GdtfError::GeometryInUse(mode)) if mode == "Mode 1"
+        ));
+        assert_eq!(gdtf.geometries.get_by_index(beam).unwrap().name, "Beam");
+    }
+
+    #[test]
+    fn rename_geometry_updates_derived_names() {
+        let mut gdtf = parse_description(
+            r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Test" FixtureTypeID="00000000-0000-0000-0000-000000000001">
+    <Geometries>
+        <Geometry Name="Body">
+            <Beam Name="Beam"/>
+        </Geometry>
+    </Geometries>
+    <DMXModes>
+        <DMXMode Name="Mode" Geometry="Body">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Beam">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Name="Dimmer" Attribute="Dimmer" DMXFrom="0/1"/>
+                    </LogicalChannel>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Offset="2" Geometry="Body">
+                    <LogicalChannel Attribute="Shutter1">
+                        <ChannelFunction Name="Open" Attribute="Shutter1" DMXFrom="0/1"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+            <Relations>
+                <Relation Name="Virtual" Master="Beam_Dimmer" Follower="Body_Shutter1.Shutter1.Open" Type="Multiply"/>
+            </Relations>
+            <FTMacros>
+                <FTMacro Name="Flash" ChannelFunction="Beam_Dimmer.Dimmer.Dimmer">
+                    <MacroDMX>
+                        <MacroDMXStep Duration="0.5">
+                            <MacroDMXValue Value="255/1" DMXChannel="Beam_Dimmer"/>
+                        </MacroDMXStep>
+                    </MacroDMX>
+                </FTMacro>
+            </FTMacros>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#
+                .into(),
+            &Default::default(),
+        )
+        .unwrap()
+        .gdtf;
+        let beam = gdtf
+            .geometries
+            .get_index(&"Beam".try_into().unwrap())
+            .unwrap();
+
+        gdtf.rename_geometry(beam, "Lens".try_into().unwrap())
+            .unwrap();
+
+        let mode = gdtf.dmx_mode(0).unwrap();
+        let [dimmer, shutter] = mode.channels.as_slice() else {
+            panic!("expected two channels");
+        };
+        assert_eq!(dimmer.geometry, beam);
+        assert_eq!(dimmer.name, "Lens_Dimmer");
+        let raw = dimmer.raw_function().unwrap();
+        assert_eq!(
+            mode.channel_functions.node_weight(raw).unwrap().name,
+            "Lens_Dimmer"
+        );
+        assert_eq!(shutter.name, "Body_Shutter1");
+        let relation = mode.relations.first().unwrap();
+        assert_eq!(relation.master, "Lens_Dimmer");
+        assert_eq!(relation.follower, "Body_Shutter1.Shutter1.Open");
+        let ft_macro = mode.macros.first().unwrap();
+        assert_eq!(
+            ft_macro.channel_function.as_deref(),
+            Some("Lens_Dimmer.Dimmer.Dimmer")
+        );
+        let value = ft_macro.steps.first().unwrap().values.first().unwrap();
+        assert_eq!(value.dmx_channel, "Lens_Dimmer");
+    }
+
+    #[test]
+    fn rename_geometry_updates_names_of_template_instances() {
+        let description = r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Bar" FixtureTypeID="00000000-0000-0000-0000-000000000001">
+    <Geometries>
+        <Geometry Name="Body">
+            <GeometryReference Name="Pixel 1" Geometry="Pixel">
+                <Break DMXBreak="1" DMXOffset="1"/>
+            </GeometryReference>
+            <GeometryReference Name="Pixel 2" Geometry="Pixel">
+                <Break DMXBreak="1" DMXOffset="3"/>
+            </GeometryReference>
+        </Geometry>
+        <Geometry Name="Pixel">
+            <Geometry Name="Beam"/>
+        </Geometry>
+    </Geometries>
+    <DMXModes>
+        <DMXMode Name="Mode" Geometry="Body">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Pixel">
+                    <LogicalChannel Attribute="Dimmer"/>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Offset="2" Geometry="Beam">
+                    <LogicalChannel Attribute="Zoom"/>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#;
+        for (channel_naming, subfixture_naming) in [
+            (
+                ChannelNaming::GeometryAttribute,
+                SubfixtureNaming::GeometryReference,
+            ),
+            (
+                ChannelNaming::IndexGeometryAttribute,
+                SubfixtureNaming::IndexGeometryReference,
+            ),
+            (ChannelNaming::XmlOrder, SubfixtureNaming::Index),
+        ] {
+            let options = ParseOptions {
+                channel_naming,
+                subfixture_naming,
+                ..Default::default()
+            };
+            let names = |gdtf: &Gdtf| {
+                let mode = gdtf.dmx_mode(0).unwrap();
+                let subfixtures: Vec<_> =
+                    mode.subfixtures.iter().map(|sf| sf.name.clone()).collect();
+                let channels: Vec<_> = mode
+                    .all_channels()
+                    .map(|ch| {
+                        let raw = ch.raw_function().unwrap();
+                        let raw = &mode.channel_functions.node_weight(raw).unwrap().name;
+                        (ch.name.to_string(), raw.to_string())
+                    })
+                    .collect();
+                (subfixtures, channels)
+            };
+            let mut gdtf = parse_description(description.into(), &options)
+                .unwrap()
+                .gdtf;
+            let index = |gdtf: &Gdtf, name: &str| {
+                gdtf.geometries
+                    .get_index(&name.try_into().unwrap())
+                    .unwrap()
+            };
+            gdtf.rename_geometry(index(&gdtf, "Pixel 1"), "Cell 1".try_into().unwrap())
+                .unwrap();
+            gdtf.rename_geometry(index(&gdtf, "Beam"), "Lens".try_into().unwrap())
+                .unwrap();
+
+            let expected = parse_description(
+                description
+                    .replace("Pixel 1", "Cell 1")
+                    .replace("Beam", "Lens"),
+                &options,
+            )
+            .unwrap()
+            .gdtf;
+            assert_eq!(
+                names(&gdtf),
+                names(&expected),
+                "{channel_naming:?}, {subfixture_naming:?}"
+            );
+        }
+    }
+}
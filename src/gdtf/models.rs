@@ -0,0 +1,134 @@
+use derive_more::IntoIterator;
+
+use crate::{extensions::Extensions, name::Name};
+
+/// 3D model of a geometry, as defined in the `Models` node of the fixture type.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Model {
+    pub name: Name,
+    /// in meters
+    pub length: f64,
+    /// in meters
+    pub width: f64,
+    /// in meters
+    pub height: f64,
+    pub primitive_type: PrimitiveType,
+    /// file name without extension and without subfolder, `None` if absent
+    /// or empty
+    pub file: Option<String>,
+    /// attributes and child nodes that are not represented by other fields
+    pub extensions: Extensions,
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            length: 0.,
+            width: 0.,
+            height: 0.,
+            primitive_type: PrimitiveType::Undefined,
+            file: None,
+            extensions: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PrimitiveType {
+    Undefined,
+    Cube,
+    Cylinder,
+    Sphere,
+    Base,
+    Yoke,
+    Head,
+    Scanner,
+    Conventional,
+    Pigtail,
+    Base1_1,
+    Scanner1_1,
+    Conventional1_1,
+}
+
+/// All models of a fixture type, in the order they were added. Model names are
+/// unique.
+#[derive(Debug, Default, Clone, IntoIterator, derive_more::Deref)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Vec<Model>"))]
+pub struct Models(Vec<Model>);
+
+#[derive(thiserror::Error, Debug)]
+pub enum ModelsError {
+    #[error("model name '{0}' already taken")]
+    NameAlreadyTaken(Name),
+}
+
+impl Models {
+    /// Adds a model and returns its index.
+    ///
+    /// When the model name is already taken, does nothing and returns an Error.
+    pub fn add(&mut self, model: Model) -> Result<usize, ModelsError> {
+        if self.get_by_name(&model.name).is_some() {
+            return Err(ModelsError::NameAlreadyTaken(model.name));
+        }
+        self.0.push(model);
+        Ok(self.0.len() - 1)
+    }
+
+    /// Get a model by its unique `Name`
+    pub fn get_by_name(&self, name: &Name) -> Option<&Model> {
+        self.0.iter().find(|model| &model.name == name)
+    }
+
+    /// Mutable access to all models. Names must not be changed, so they stay
+    /// unique.
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut Model> {
+        self.0.iter_mut()
+    }
+}
+
+impl TryFrom<Vec<Model>> for Models {
+    type Error = ModelsError;
+
+    fn try_from(vec: Vec<Model>) -> Result<Self, Self::Error> {
+        let mut models = Models::default();
+        for model in vec {
+            models.add(model)?;
+        }
+        Ok(models)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::name::IntoValidName;
+
+    use super::*;
+
+    #[test]
+    fn model_names_are_unique() {
+        let mut models = Models::default();
+        let base = Model {
+            name: "Base".into_valid(),
+            primitive_type: PrimitiveType::Base,
+            ..Default::default()
+        };
+        assert_eq!(models.add(base.clone()).unwrap(), 0);
+        assert!(matches!(
+            models.add(base),
+            Err(ModelsError::NameAlreadyTaken(name)) if name == "Base"
+        ));
+        assert_eq!(models.len(), 1);
+        assert_eq!(
+            models
+                .get_by_name(&"Base".into_valid())
+                .unwrap()
+                .primitive_type,
+            PrimitiveType::Base
+        );
+        assert!(models.get_by_name(&"Yoke".into_valid()).is_none());
+    }
+}
@@ -1,4 +1,10 @@
-use std::str::FromStr;
+use std::{
+    cell::RefCell,
+    collections::{hash_map::RandomState, HashSet},
+    hash::BuildHasher,
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError},
+};
 
 use derive_more::{DebugCustom, Display};
 
@@ -17,47 +23,70 @@ use thiserror::Error;
 /// - U+003F (?)
 /// - U+005B..=U+005E ([\]^)
 /// - U+007B..=U+007F ({|}~<control>)
-#[derive(PartialOrd, PartialEq, Eq, Ord, Clone, Hash, Display, DebugCustom, Default)]
-pub struct Name(String);
+///
+/// The string is shared between clones, so copying names, e.g. when template
+/// channels are instantiated for each subfixture, does not allocate.
+#[derive(PartialOrd, PartialEq, Eq, Ord, Clone, Hash, Display, DebugCustom)]
+pub struct Name(Arc<str>);
+
+impl Default for Name {
+    fn default() -> Self {
+        Self("".into())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Name {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// Names with invalid chars are an error
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Name {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Name::try_from(s).map_err(serde::de::Error::custom)
+    }
+}
 
 impl TryFrom<&str> for Name {
     type Error = NameError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let mut invalid_chars = String::new();
+        // most names are valid, they are shared without an intermediate String
+        if !value.contains(is_invalid_char) {
+            return Ok(Self(value.into_arc()));
+        }
 
-        let name = Self(
-            value
-                .chars()
-                .map(|c| match c {
-                    '!'
-                    | '$'
-                    | '&'
-                    | ','
-                    | '.'
-                    | '?'
-                    | '\x00'..='\x1f'
-                    | '['..='^'
-                    | '{'..='\x7f' => {
-                        invalid_chars.push(c);
-                        '□'
-                    }
-                    _ => c,
-                })
-                .collect::<String>(),
-        );
-
-        if invalid_chars.is_empty() {
-            Ok(name)
-        } else {
-            Err(NameError {
-                fixed: name,
-                invalid_chars,
+        let mut invalid_chars = String::new();
+        let fixed: String = value
+            .chars()
+            .map(|c| {
+                if is_invalid_char(c) {
+                    invalid_chars.push(c);
+                    '□'
+                } else {
+                    c
+                }
             })
-        }
+            .collect();
+        Err(NameError {
+            fixed: Self(fixed.as_str().into_arc()),
+            invalid_chars,
+        })
     }
 }
 
+/// Chars that are not allowed in a Name, see [`Name`]
+fn is_invalid_char(c: char) -> bool {
+    matches!(
+        c,
+        '!' | '$' | '&' | ',' | '.' | '?' | '\x00'..='\x1f' | '['..='^' | '{'..='\x7f'
+    )
+}
+
 impl TryFrom<String> for Name {
     type Error = NameError;
 
@@ -76,13 +105,13 @@ impl FromStr for Name {
 
 impl PartialEq<str> for Name {
     fn eq(&self, other: &str) -> bool {
-        self.0 == other
+        &*self.0 == other
     }
 }
 
 impl PartialEq<&str> for Name {
     fn eq(&self, other: &&str) -> bool {
-        self.0 == *other
+        &*self.0 == *other
     }
 }
 
@@ -97,6 +126,70 @@ impl IntoValidName for &str {
     }
 }
 
+/// Number of independently locked parts of a [`NameInterner`], so threads
+/// rarely wait for each other
+const INTERNER_SHARDS: usize = 16;
+
+/// Strings of names shared between threads, so names that occur in many files,
+/// like attributes and geometry names, are allocated once. Used by
+/// [`parse_many`](crate::parse_many).
+pub(crate) struct NameInterner {
+    hasher: RandomState,
+    shards: [Mutex<HashSet<Arc<str>>>; INTERNER_SHARDS],
+}
+
+thread_local! {
+    /// Interner used for new names on this thread, see [`NameInterner::scope`]
+    static INTERNER: RefCell<Option<Arc<NameInterner>>> = const { RefCell::new(None) };
+}
+
+impl NameInterner {
+    pub(crate) fn new() -> Self {
+        Self {
+            hasher: RandomState::new(),
+            shards: Default::default(),
+        }
+    }
+
+    fn intern(&self, s: &str) -> Arc<str> {
+        let shard = self.hasher.hash_one(s) as usize % INTERNER_SHARDS;
+        let Some(shard) = self.shards.get(shard) else {
+            return s.into();
+        };
+        let mut strings = shard.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(interned) = strings.get(s) {
+            return interned.clone();
+        }
+        let interned: Arc<str> = s.into();
+        strings.insert(interned.clone());
+        interned
+    }
+
+    /// Run `f` with all names created on the current thread taken from `self`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn scope<T>(self: &Arc<Self>, f: impl FnOnce() -> T) -> T {
+        let previous = INTERNER.with(|i| i.replace(Some(self.clone())));
+        let result = f();
+        INTERNER.with(|i| i.replace(previous));
+        result
+    }
+}
+
+trait IntoArc {
+    /// The shared string of a name, from the interner of the current thread
+    /// if there is one
+    fn into_arc(self) -> Arc<str>;
+}
+
+impl IntoArc for &str {
+    fn into_arc(self) -> Arc<str> {
+        INTERNER.with(|i| match &*i.borrow() {
+            Some(interner) => interner.intern(self),
+            None => self.into(),
+        })
+    }
+}
+
 impl Name {
     /// Construct the default name based on the XML tag name and the 0-based XML
     /// node index in its parent.
@@ -161,4 +254,31 @@ mod tests {
         assert_eq!("\"yay\"", format!("{:?}", Name::try_from("yay").unwrap()));
         assert_eq!("\"yay\"", format!("{:#?}", Name::try_from("yay").unwrap()));
     }
+
+    #[test]
+    fn clones_share_the_string() {
+        let name = Name::try_from("Beam").unwrap();
+        let clone = name.clone();
+        assert!(Arc::ptr_eq(&name.0, &clone.0));
+        assert_eq!(<Name as Default>::default(), "");
+    }
+
+    #[test]
+    fn interner_shares_strings_between_threads() {
+        let interner = Arc::new(NameInterner::new());
+        let names: Vec<Name> = (0..2)
+            .map(|_| {
+                let interner = interner.clone();
+                std::thread::spawn(move || interner.scope(|| "Beam".into_valid()))
+            })
+            .map(|thread| thread.join().unwrap())
+            .collect();
+        let [a, b] = names.as_slice() else {
+            panic!("expected two names");
+        };
+        assert!(Arc::ptr_eq(&a.0, &b.0));
+
+        let outside = Name::try_from("Beam").unwrap();
+        assert!(!Arc::ptr_eq(&a.0, &outside.0));
+    }
 }
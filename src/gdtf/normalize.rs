@@ -0,0 +1,123 @@
+use petgraph::graph::NodeIndex;
+
+use crate::{serializer::serialize_normalized, Gdtf};
+
+impl Gdtf {
+    /// Remove differences that carry no meaning, so fixture types that
+    /// describe the same fixture compare equal field by field:
+    /// - unknown attributes are sorted by name
+    /// - source locations are removed
+    ///
+    /// Float formatting and attributes left at their default are already
+    /// normalized by parsing.
+    pub fn normalize(&mut self) {
+        self.extensions.sort_attributes();
+        for model in self.models.iter_mut() {
+            model.extensions.sort_attributes();
+        }
+        for geometry in self.geometries.iter_mut() {
+            geometry.extensions.sort_attributes();
+            geometry.source = None;
+        }
+        for mode in &mut self.dmx_modes {
+            mode.extensions.sort_attributes();
+            mode.source = None;
+            for ch in mode.channels.iter_mut() {
+                ch.extensions.sort_attributes();
+                ch.source = None;
+            }
+            for ch in mode
+                .all_subfixtures_mut()
+                .flat_map(|sf| sf.channels.iter_mut())
+            {
+                ch.extensions.sort_attributes();
+                ch.source = None;
+            }
+            for i in 0..mode.channel_functions.node_count() {
+                if let Some(chf) = mode.channel_functions.node_weight_mut(NodeIndex::new(i)) {
+                    chf.extensions.sort_attributes();
+                    chf.source = None;
+                }
+            }
+        }
+    }
+
+    /// Whether both fixture types describe the same fixture, even if their
+    /// files differ in attribute order, float formatting or attributes that
+    /// are left at their default. The FixtureTypeID is compared as well.
+    pub fn semantically_eq(&self, other: &Gdtf) -> bool {
+        serialize_normalized(self) == serialize_normalized(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parser::parse_description, serialize_gdtf};
+
+    use super::*;
+
+    fn parse(description: &str) -> Gdtf {
+        parse_description(description.into(), &Default::default())
+            .unwrap()
+            .gdtf
+    }
+
+    #[test]
+    fn textual_differences_are_semantically_equal() {
+        let a = parse(
+            r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Test" FixtureTypeID="00000000-0000-0000-0000-000000000001" VendorA="1" VendorB="2">
+    <Geometries>
+        <Geometry Name="Body" Position="{1,0,0,0}{0,1,0,0}{0,0,1,0}{0,0,0,1}"/>
+    </Geometries>
+    <DMXModes>
+        <DMXMode Name="Mode" Geometry="Body">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Body">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Name="Dimmer" Attribute="Dimmer" DMXFrom="0/1" PhysicalTo="1.000000"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#,
+        );
+        let mut b = parse(
+            r#"<GDTF DataVersion="1.2">
+<FixtureType
+    VendorB="2" VendorA="1" FixtureTypeID="00000000-0000-0000-0000-000000000001" Name="Test">
+    <Geometries><Geometry Name="Body"/></Geometries>
+    <DMXModes>
+        <DMXMode Geometry="Body" Name="Mode">
+            <DMXChannels>
+                <DMXChannel Geometry="Body" Offset="1" DMXBreak="1">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Attribute="Dimmer" Name="Dimmer" DMXFrom="0/1" PhysicalTo="1"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#,
+        );
+        assert!(a.semantically_eq(&b));
+        assert_ne!(serialize_gdtf(&a), serialize_gdtf(&b));
+
+        b.normalize();
+        assert_eq!(
+            b.extensions.attributes,
+            vec![
+                ("VendorA".to_owned(), "1".to_owned()),
+                ("VendorB".to_owned(), "2".to_owned())
+            ]
+        );
+        assert!(b.dmx_modes().first().unwrap().source.is_none());
+        assert!(a.semantically_eq(&b));
+
+        b.description = "changed".into();
+        assert!(!a.semantically_eq(&b));
+    }
+}
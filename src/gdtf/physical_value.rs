@@ -0,0 +1,103 @@
+use std::{fmt::Display, num::ParseFloatError, str::FromStr};
+
+use crate::float::{parse_float, Fixed};
+
+/// A physical value like PhysicalFrom/PhysicalTo of a channel function.
+///
+/// Always finite. Displays with six decimals, like GDTF Builder writes them.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "f64"))]
+pub struct PhysicalValue(f64);
+
+#[derive(Debug, thiserror::Error)]
+pub enum PhysicalValueError {
+    #[error("physical value must be finite, got {0}")]
+    NonFinite(f64),
+    #[error("invalid float; {0}")]
+    InvalidFloat(#[from] ParseFloatError),
+}
+
+impl PhysicalValue {
+    pub const ZERO: PhysicalValue = PhysicalValue(0.);
+    pub const ONE: PhysicalValue = PhysicalValue(1.);
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+
+    /// Like `try_from`, but clamps infinite values to the biggest finite
+    /// value of the same sign. NaN still returns an error.
+    pub fn saturating(v: f64) -> Result<Self, PhysicalValueError> {
+        if v.is_nan() {
+            return Err(PhysicalValueError::NonFinite(v));
+        }
+        Ok(PhysicalValue(v.clamp(f64::MIN, f64::MAX)))
+    }
+}
+
+impl TryFrom<f64> for PhysicalValue {
+    type Error = PhysicalValueError;
+
+    fn try_from(v: f64) -> Result<Self, Self::Error> {
+        if !v.is_finite() {
+            return Err(PhysicalValueError::NonFinite(v));
+        }
+        Ok(PhysicalValue(v))
+    }
+}
+
+impl From<PhysicalValue> for f64 {
+    fn from(v: PhysicalValue) -> Self {
+        v.0
+    }
+}
+
+impl FromStr for PhysicalValue {
+    type Err = PhysicalValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_float(s)?.try_into()
+    }
+}
+
+impl Display for PhysicalValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Fixed(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        let v: PhysicalValue = "1.5".parse().unwrap();
+        assert_eq!(v.value(), 1.5);
+        assert_eq!(v.to_string(), "1.500000");
+        assert_eq!(PhysicalValue::ZERO.to_string(), "0.000000");
+        assert_eq!(" -0.25 ".parse::<PhysicalValue>().unwrap().value(), -0.25);
+
+        assert!(matches!(
+            "inf".parse::<PhysicalValue>(),
+            Err(PhysicalValueError::NonFinite(_))
+        ));
+        assert!(matches!(
+            "NaN".parse::<PhysicalValue>(),
+            Err(PhysicalValueError::NonFinite(_))
+        ));
+        assert!(matches!(
+            "None".parse::<PhysicalValue>(),
+            Err(PhysicalValueError::InvalidFloat(_))
+        ));
+
+        assert_eq!(
+            PhysicalValue::saturating(f64::NEG_INFINITY)
+                .unwrap()
+                .value(),
+            f64::MIN
+        );
+        assert!(PhysicalValue::saturating(f64::NAN).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use std::{fmt::Display, num::ParseFloatError, ops::Mul, str::FromStr};
+
+use crate::float::{parse_float, Fixed};
+
+/// Transformation of a geometry relative to its parent, as a 4x4 matrix in
+/// row-major order.
+///
+/// The upper left 3x3 block is the rotation, the last column the translation
+/// in meters. For a valid position, the last row is `{0,0,0,1}` and the
+/// rotation is orthonormal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position(pub [[f64; 4]; 4]);
+
+#[derive(Debug, thiserror::Error)]
+pub enum PositionError {
+    #[error("expected 4 rows of 4 comma separated numbers in curly braces")]
+    InvalidFormat,
+    #[error("invalid number; {0}")]
+    InvalidNumber(#[from] ParseFloatError),
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Position {
+    pub const IDENTITY: Position = Position([
+        [1., 0., 0., 0.],
+        [0., 1., 0., 0.],
+        [0., 0., 1., 0.],
+        [0., 0., 0., 1.],
+    ]);
+
+    /// in meters
+    pub fn translation(&self) -> [f64; 3] {
+        let [[_, _, _, x], [_, _, _, y], [_, _, _, z], _] = self.0;
+        [x, y, z]
+    }
+
+    /// Whether the last row is `{0,0,0,1}` within `tolerance`
+    pub fn is_affine(&self, tolerance: f64) -> bool {
+        let [_, _, _, last_row] = self.0;
+        last_row
+            .iter()
+            .zip([0., 0., 0., 1.])
+            .all(|(v, expected)| (v - expected).abs() <= tolerance)
+    }
+
+    /// Whether the columns of the rotation are orthogonal unit vectors within
+    /// `tolerance`
+    pub fn is_orthonormal(&self, tolerance: f64) -> bool {
+        let columns = self.rotation_columns();
+        columns.iter().enumerate().all(|(i, a)| {
+            columns.iter().enumerate().all(|(j, b)| {
+                let expected = if i == j { 1. } else { 0. };
+                (dot(a, b) - expected).abs() <= tolerance
+            })
+        })
+    }
+
+    /// Return an affine position with orthonormal rotation and the same
+    /// translation.
+    ///
+    /// The rotation columns are orthonormalized with Gram-Schmidt, starting
+    /// from the first column. If the rotation is degenerate, it is replaced by
+    /// the identity.
+    pub fn orthonormalized(&self) -> Position {
+        let [c0, c1, c2] = self.rotation_columns();
+        let orthonormal = normalize(c0).and_then(|u0| {
+            let u1 = normalize(sub(c1, scale(u0, dot(&c1, &u0))))?;
+            let u2 = normalize(sub(
+                sub(c2, scale(u0, dot(&c2, &u0))),
+                scale(u1, dot(&c2, &u1)),
+            ))?;
+            Some([u0, u1, u2])
+        });
+        let [u0, u1, u2] = orthonormal.unwrap_or([[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]]);
+        let [x, y, z] = self.translation();
+        Position([
+            [u0[0], u1[0], u2[0], x],
+            [u0[1], u1[1], u2[1], y],
+            [u0[2], u1[2], u2[2], z],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    fn rotation_columns(&self) -> [[f64; 3]; 3] {
+        let [[a, b, c, _], [d, e, f, _], [g, h, i, _], _] = self.0;
+        [[a, d, g], [b, e, h], [c, f, i]]
+    }
+}
+
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn scale(a: [f64; 3], factor: f64) -> [f64; 3] {
+    a.map(|v| v * factor)
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    let [a0, a1, a2] = a;
+    let [b0, b1, b2] = b;
+    [a0 - b0, a1 - b1, a2 - b2]
+}
+
+fn normalize(a: [f64; 3]) -> Option<[f64; 3]> {
+    let length = dot(&a, &a).sqrt();
+    (length > f64::EPSILON).then(|| scale(a, 1. / length))
+}
+
+/// Composition of transforms: `parent * child` maps from the coordinates of
+/// the child to those of the parent's parent.
+impl Mul for Position {
+    type Output = Position;
+
+    fn mul(self, rhs: Position) -> Position {
+        let mut product = [[0.; 4]; 4];
+        for (product_row, row) in product.iter_mut().zip(self.0) {
+            for (column, value) in product_row.iter_mut().enumerate() {
+                *value = row
+                    .iter()
+                    .zip(rhs.0)
+                    .map(|(a, rhs_row)| a * rhs_row.get(column).copied().unwrap_or_default())
+                    .sum();
+            }
+        }
+        Position(product)
+    }
+}
+
+impl FromStr for Position {
+    type Err = PositionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let inner = s
+            .trim()
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .ok_or(PositionError::InvalidFormat)?;
+        let rows = inner
+            .split("}{")
+            .map(|row| {
+                let values = row
+                    .split(',')
+                    .map(parse_float)
+                    .collect::<Result<Vec<_>, _>>()?;
+                <[f64; 4]>::try_from(values).map_err(|_| PositionError::InvalidFormat)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Position(
+            rows.try_into().map_err(|_| PositionError::InvalidFormat)?,
+        ))
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for [a, b, c, d] in self.0 {
+            write!(f, "{{{},{},{},{}}}", Fixed(a), Fixed(b), Fixed(c), Fixed(d))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_validate() {
+        let p: Position = "{1.000000,0.000000,0.000000,0.500000}{0.000000,1.000000,0.000000,0.000000}{0.000000,0.000000,1.000000,-0.200000}{0,0,0,1}"
+            .parse()
+            .unwrap();
+        assert_eq!(p.translation(), [0.5, 0., -0.2]);
+        assert!(p.is_affine(1e-6));
+        assert!(p.is_orthonormal(1e-6));
+        assert_eq!(
+            p.to_string(),
+            "{1.000000,0.000000,0.000000,0.500000}{0.000000,1.000000,0.000000,0.000000}{0.000000,0.000000,1.000000,-0.200000}{0.000000,0.000000,0.000000,1.000000}"
+        );
+
+        assert!("{1,0,0,0}{0,1,0,0}{0,0,1,0}".parse::<Position>().is_err());
+        assert!("{1,0,0}{0,1,0}{0,0,1}{0,0,0}".parse::<Position>().is_err());
+        assert!("{a,0,0,0}{0,1,0,0}{0,0,1,0}{0,0,0,1}"
+            .parse::<Position>()
+            .is_err());
+    }
+
+    #[test]
+    fn orthonormalize() {
+        let scaled: Position = "{2,0,0,1}{0,1,1,2}{0,0,1,3}{0,0,1,1}".parse().unwrap();
+        assert!(!scaled.is_affine(1e-6));
+        assert!(!scaled.is_orthonormal(1e-6));
+
+        let fixed = scaled.orthonormalized();
+        assert!(fixed.is_affine(1e-9));
+        assert!(fixed.is_orthonormal(1e-9));
+        assert_eq!(fixed.translation(), [1., 2., 3.]);
+
+        let degenerate = Position([[0.; 4]; 4]).orthonormalized();
+        assert_eq!(degenerate, Position::IDENTITY);
+    }
+
+    #[test]
+    fn compose() {
+        let parent: Position = "{0,-1,0,1}{1,0,0,0}{0,0,1,0}{0,0,0,1}".parse().unwrap();
+        let child: Position = "{1,0,0,2}{0,1,0,0}{0,0,1,3}{0,0,0,1}".parse().unwrap();
+        assert_eq!(Position::IDENTITY * child, child);
+        assert_eq!(child * Position::IDENTITY, child);
+
+        // child translation is rotated by the parent, then offset
+        let world = parent * child;
+        assert_eq!(world.translation(), [1., 2., 3.]);
+        assert!(world.is_affine(1e-9));
+        assert!(world.is_orthonormal(1e-9));
+    }
+}
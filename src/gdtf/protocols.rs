@@ -0,0 +1,50 @@
+/// Protocol mappings of the fixture type, as defined in the `Protocols` node.
+///
+/// A protocol is `None` if the fixture type does not define it.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Protocols {
+    pub art_net: Option<ArtNet>,
+    pub sacn: Option<Sacn>,
+    pub posi_stage_net: Option<PosiStageNet>,
+    pub open_sound_control: Option<OpenSoundControl>,
+    pub citp: Option<Citp>,
+    /// FTRDM node as XML, kept verbatim until RDM information is parsed
+    pub ftrdm: Option<String>,
+}
+
+/// Art-Net specific mapping of DMX values.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArtNet {
+    pub maps: Vec<DmxMap>,
+}
+
+/// sACN specific mapping of DMX values.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sacn {
+    pub maps: Vec<DmxMap>,
+}
+
+/// Maps a value of the protocol (`key`) to a DMX value (`value`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DmxMap {
+    pub key: u32,
+    pub value: u32,
+}
+
+// The following protocols have no content defined in GDTF 1.2 yet
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PosiStageNet;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpenSoundControl;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Citp;
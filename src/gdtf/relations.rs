@@ -0,0 +1,25 @@
+use crate::name::Name;
+
+/// Relation between a master channel and a follower channel function of a
+/// DMX mode, as defined in the `Relations` node.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Relation {
+    pub name: Name,
+    /// GDTF name of the master DMX channel, i.e. `{Geometry}_{Attribute}`
+    pub master: Name,
+    /// link to the follower channel function as written in GDTF, i.e.
+    /// `{Geometry}_{Attribute}.{Attribute}.{ChannelFunction}`
+    pub follower: String,
+    pub relation_type: RelationType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RelationType {
+    /// the output of the follower is multiplied by the master, e.g. a virtual
+    /// dimmer for the color channels of an LED fixture
+    Multiply,
+    /// the master overrides the follower
+    Override,
+}
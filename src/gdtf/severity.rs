@@ -0,0 +1,10 @@
+/// How serious a problem in a GDTF file is, for reports that group or filter
+/// problems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, strum::Display)]
+pub enum Severity {
+    /// Part of the file is missing from the result or the result is not
+    /// usable as the file intends
+    Error,
+    /// The file is not conformant, but the result still represents it
+    Warning,
+}
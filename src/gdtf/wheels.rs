@@ -0,0 +1,407 @@
+use std::{fmt::Display, str::FromStr};
+
+use derive_more::IntoIterator;
+
+use crate::{
+    channel::Channel,
+    dmx_modes::{ChannelSet, DmxMode},
+    float::{parse_float, Fixed},
+    name::Name,
+    Gdtf,
+};
+
+/// A wheel of color filters, gobos, prisms, etc., as defined in the `Wheels`
+/// node of the fixture type.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wheel {
+    pub name: Name,
+    /// in XML order. Channel sets refer to them by 1-based index.
+    pub slots: Vec<Slot>,
+}
+
+/// A slot of a [`Wheel`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Slot {
+    pub name: Name,
+    pub color: ColorCie,
+    /// file name of the gobo image in the folder `wheels` of the archive,
+    /// without extension, `None` if absent or empty
+    pub media_file_name: Option<String>,
+}
+
+impl Default for Slot {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            color: ColorCie::WHITE,
+            media_file_name: None,
+        }
+    }
+}
+
+/// A color in the CIE 1931 xyY color space
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorCie {
+    pub x: f64,
+    pub y: f64,
+    /// luminance
+    pub Y: f64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ColorCieError {
+    #[error("expected 3 comma-separated values")]
+    WrongValueCount,
+    #[error("invalid value: {0}")]
+    InvalidValue(#[from] std::num::ParseFloatError),
+}
+
+impl ColorCie {
+    /// D65 white point at full luminance, the default color of a slot
+    pub const WHITE: ColorCie = ColorCie {
+        x: 0.3127,
+        y: 0.329,
+        Y: 100.,
+    };
+}
+
+/// Parses the GDTF ColorCIE format `x,y,Y`
+impl FromStr for ColorCie {
+    type Err = ColorCieError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut values = s.split(',').map(parse_float);
+        match (values.next(), values.next(), values.next(), values.next()) {
+            (Some(x), Some(y), Some(luminance), None) => Ok(ColorCie {
+                x: x?,
+                y: y?,
+                Y: luminance?,
+            }),
+            _ => Err(ColorCieError::WrongValueCount),
+        }
+    }
+}
+
+impl Display for ColorCie {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{},{}", Fixed(self.x), Fixed(self.y), Fixed(self.Y))
+    }
+}
+
+/// All wheels of a fixture type, in the order they were added. Wheel names are
+/// unique.
+#[derive(Debug, Default, Clone, IntoIterator, derive_more::Deref)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Vec<Wheel>"))]
+pub struct Wheels(Vec<Wheel>);
+
+#[derive(thiserror::Error, Debug)]
+pub enum WheelsError {
+    #[error("wheel name '{0}' already taken")]
+    NameAlreadyTaken(Name),
+}
+
+impl Wheels {
+    /// Adds a wheel and returns its index.
+    ///
+    /// When the wheel name is already taken, does nothing and returns an Error.
+    pub fn add(&mut self, wheel: Wheel) -> Result<usize, WheelsError> {
+        if self.get_by_name(&wheel.name).is_some() {
+            return Err(WheelsError::NameAlreadyTaken(wheel.name));
+        }
+        self.0.push(wheel);
+        Ok(self.0.len() - 1)
+    }
+
+    /// Get a wheel by its unique `Name`
+    pub fn get_by_name(&self, name: &Name) -> Option<&Wheel> {
+        self.0.iter().find(|w| &w.name == name)
+    }
+}
+
+impl TryFrom<Vec<Wheel>> for Wheels {
+    type Error = WheelsError;
+
+    fn try_from(vec: Vec<Wheel>) -> Result<Self, Self::Error> {
+        let mut wheels = Wheels::default();
+        for wheel in vec {
+            wheels.add(wheel)?;
+        }
+        Ok(wheels)
+    }
+}
+
+/// A DMX range of a wheel channel that selects a slot, see
+/// [`Gdtf::color_wheel_summary`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorSlotRange {
+    pub dmx_from: u32,
+    pub dmx_to: u32,
+    pub slot_name: Name,
+    pub color: ColorCie,
+}
+
+/// A DMX range of a gobo channel that selects a slot, see
+/// [`Gdtf::gobo_index_map`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GoboSlotRange {
+    pub dmx_from: u32,
+    pub dmx_to: u32,
+    pub slot_name: Name,
+    /// file name of the gobo image in the folder `wheels` of the archive,
+    /// without extension, `None` if the slot has none
+    pub media_file_name: Option<String>,
+}
+
+/// The gobo slots selected by one channel, see [`Gdtf::gobo_index_map`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GoboChannelMap {
+    pub channel: Name,
+    pub slots: Vec<GoboSlotRange>,
+}
+
+impl Gdtf {
+    /// The slots a wheel channel of `mode` selects, with their DMX range and
+    /// color, ordered by DMX value. This is what a color picker needs.
+    ///
+    /// Derived from the channel sets with a WheelSlotIndex in channel functions
+    /// linked to a wheel. Channel sets without a slot, like continuous
+    /// rotation, are left out.
+    pub fn color_wheel_summary(&self, mode: &DmxMode, channel: &Channel) -> Vec<ColorSlotRange> {
+        self.slot_ranges(mode, channel)
+            .into_iter()
+            .map(|(set, slot)| ColorSlotRange {
+                dmx_from: set.dmx_from,
+                dmx_to: set.dmx_to,
+                slot_name: slot.name.clone(),
+                color: slot.color,
+            })
+            .collect()
+    }
+
+    /// For every channel of `mode` that selects a slot with a gobo image, the
+    /// selected slots with their DMX range and media file, ordered by DMX
+    /// value. Useful to prepare gobo thumbnails ahead of time.
+    ///
+    /// Slots are derived like in [`Gdtf::color_wheel_summary`].
+    pub fn gobo_index_map(&self, mode: &DmxMode) -> Vec<GoboChannelMap> {
+        mode.all_channels()
+            .filter_map(|channel| {
+                let ranges = self.slot_ranges(mode, channel);
+                if ranges
+                    .iter()
+                    .all(|(_, slot)| slot.media_file_name.is_none())
+                {
+                    return None;
+                }
+                let slots = ranges
+                    .into_iter()
+                    .map(|(set, slot)| GoboSlotRange {
+                        dmx_from: set.dmx_from,
+                        dmx_to: set.dmx_to,
+                        slot_name: slot.name.clone(),
+                        media_file_name: slot.media_file_name.clone(),
+                    })
+                    .collect();
+                Some(GoboChannelMap {
+                    channel: channel.name.clone(),
+                    slots,
+                })
+            })
+            .collect()
+    }
+
+    /// Channel sets of `channel` that select a wheel slot, with the slot,
+    /// ordered by DMX value
+    fn slot_ranges<'a>(
+        &'a self,
+        mode: &'a DmxMode,
+        channel: &Channel,
+    ) -> Vec<(&'a ChannelSet, &'a Slot)> {
+        let mut ranges: Vec<(&ChannelSet, &Slot)> = channel
+            .defined_functions()
+            .iter()
+            .filter_map(|i| mode.channel_functions.node_weight(*i))
+            .filter_map(|chf| {
+                let wheel = self.wheels.get_by_name(chf.wheel.as_ref()?)?;
+                Some(chf.channel_sets.iter().filter_map(move |set| {
+                    let index = usize::try_from(set.wheel_slot_index?).ok()?;
+                    let slot = wheel.slots.get(index.checked_sub(1)?)?;
+                    Some((set, slot))
+                }))
+            })
+            .flatten()
+            .collect();
+        ranges.sort_by_key(|(set, _)| set.dmx_from);
+        ranges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builder::GdtfBuilder, name::IntoValidName};
+
+    use super::*;
+
+    #[test]
+    fn color_wheel_summary() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Color1", 1, vec![1])
+            .unwrap()
+            .channel_function("Mode", "Body_Color1", "Color", "Color1", 0, 0., 1.)
+            .unwrap()
+            .build();
+        let red = ColorCie {
+            x: 0.64,
+            y: 0.33,
+            Y: 21.26,
+        };
+        gdtf.wheels
+            .add(Wheel {
+                name: "Colors".into_valid(),
+                slots: vec![
+                    Slot {
+                        name: "Open".into_valid(),
+                        ..Default::default()
+                    },
+                    Slot {
+                        name: "Red".into_valid(),
+                        color: red,
+                        media_file_name: None,
+                    },
+                ],
+            })
+            .unwrap();
+
+        let mode = gdtf.dmx_mode_mut(0).unwrap();
+        let function_index = *mode
+            .channels
+            .first()
+            .unwrap()
+            .defined_functions()
+            .first()
+            .unwrap();
+        let function = mode
+            .channel_functions
+            .node_weight_mut(function_index)
+            .unwrap();
+        function.wheel = Some("Colors".into_valid());
+        let set = |name: &str, dmx_from, dmx_to, wheel_slot_index| ChannelSet {
+            name: name.into_valid(),
+            dmx_from,
+            dmx_to,
+            phys_from: Default::default(),
+            phys_to: Default::default(),
+            wheel_slot_index,
+        };
+        function.channel_sets = vec![
+            set("Red", 128, 255, Some(2)),
+            set("Open", 0, 63, Some(1)),
+            set("Spin", 64, 127, None),
+        ];
+
+        let mode = gdtf.dmx_modes().first().unwrap();
+        let channel = mode.channels.first().unwrap();
+        assert_eq!(
+            gdtf.color_wheel_summary(mode, channel),
+            [
+                ColorSlotRange {
+                    dmx_from: 0,
+                    dmx_to: 63,
+                    slot_name: "Open".into_valid(),
+                    color: ColorCie::WHITE,
+                },
+                ColorSlotRange {
+                    dmx_from: 128,
+                    dmx_to: 255,
+                    slot_name: "Red".into_valid(),
+                    color: red,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn gobo_index_map() {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Dimmer", 1, vec![1])
+            .unwrap()
+            .channel("Mode", "Body", "Gobo1", 1, vec![2])
+            .unwrap()
+            .channel_function("Mode", "Body_Gobo1", "Gobo", "Gobo1", 0, 0., 1.)
+            .unwrap()
+            .build();
+        gdtf.wheels
+            .add(Wheel {
+                name: "Gobos".into_valid(),
+                slots: vec![
+                    Slot {
+                        name: "Open".into_valid(),
+                        ..Default::default()
+                    },
+                    Slot {
+                        name: "Dots".into_valid(),
+                        media_file_name: Some("dots".into()),
+                        ..Default::default()
+                    },
+                ],
+            })
+            .unwrap();
+
+        let mode = gdtf.dmx_mode_mut(0).unwrap();
+        let gobo_channel = mode.channels.get(1).unwrap();
+        let function_index = *gobo_channel.defined_functions().first().unwrap();
+        let function = mode
+            .channel_functions
+            .node_weight_mut(function_index)
+            .unwrap();
+        function.wheel = Some("Gobos".into_valid());
+        let set = |name: &str, dmx_from, dmx_to, wheel_slot_index| ChannelSet {
+            name: name.into_valid(),
+            dmx_from,
+            dmx_to,
+            phys_from: Default::default(),
+            phys_to: Default::default(),
+            wheel_slot_index: Some(wheel_slot_index),
+        };
+        function.channel_sets = vec![set("Open", 0, 127, 1), set("Dots", 128, 255, 2)];
+
+        let mode = gdtf.dmx_modes().first().unwrap();
+        assert_eq!(
+            gdtf.gobo_index_map(mode),
+            [GoboChannelMap {
+                channel: "Body_Gobo1".into_valid(),
+                slots: vec![
+                    GoboSlotRange {
+                        dmx_from: 0,
+                        dmx_to: 127,
+                        slot_name: "Open".into_valid(),
+                        media_file_name: None,
+                    },
+                    GoboSlotRange {
+                        dmx_from: 128,
+                        dmx_to: 255,
+                        slot_name: "Dots".into_valid(),
+                        media_file_name: Some("dots".into()),
+                    },
+                ],
+            }]
+        );
+    }
+}
@@ -2,10 +2,9 @@ use xxhash_rust::xxh3::xxh3_128;
 
 use zip::{result::ZipError, ZipArchive};
 
-use std::{
-    fs::File,
-    io::{Read, Seek},
-};
+use crate::{serialize_gdtf, serializer::serialize_normalized, Gdtf};
+
+use std::io::{Read, Seek};
 
 /// Hash a GDTF file based on the filenames inside the archive and the files'
 /// CRC32 checksums.
@@ -24,15 +23,47 @@ pub fn hash_gdtf<T: Read + Seek>(gdtf_file: T) -> Result<u128, ZipError> {
 
 /// Hash a GDTF file based on the filenames inside the archive and the files'
 /// CRC32 checksums. Returns a hex string representation of the hash.
-pub fn hash_gdtf_to_string(file: File) -> Result<String, ZipError> {
+pub fn hash_gdtf_to_string<T: Read + Seek>(file: T) -> Result<String, ZipError> {
     let hash = hash_gdtf(file)?;
     Ok(format!("{hash:x}"))
 }
 
+/// Hash an in-memory fixture based on its semantic content, independent of any
+/// archive it was read from.
+///
+/// The hash is taken over the `description.xml` from [`serialize_gdtf`], so
+/// two models hash equal exactly when they serialize to the same description.
+/// The FixtureTypeID is part of the content, as are the sections that are kept
+/// verbatim. Not part of the hash are:
+/// - what the parser drops and so never reaches the model, as listed on
+///   [`serialize_gdtf`]
+/// - state of the model that is not written to GDTF: source locations of
+///   nodes and the naming options a DMX mode was parsed with
+pub fn hash_gdtf_model(gdtf: &Gdtf) -> u128 {
+    xxh3_128(serialize_gdtf(gdtf).as_bytes())
+}
+
+/// Hash an in-memory fixture so that fixtures which are
+/// [semantically equal](Gdtf::semantically_eq) hash equal, e.g. the same
+/// fixture repackaged into another archive or saved by another editor.
+///
+/// Unlike [`hash_gdtf_model`], the order of unknown attributes does not
+/// matter. Otherwise it covers the same content and leaves out the same
+/// things. Child nodes kept verbatim, i.e. those of
+/// [`Extensions`](crate::extensions::Extensions) and the sections that are not
+/// parsed yet, are hashed as written, so their attribute order and
+/// whitespace still matter. Files in the archive besides `description.xml`
+/// are not part of the hash.
+pub fn hash_gdtf_semantic(gdtf: &Gdtf) -> u128 {
+    xxh3_128(serialize_normalized(gdtf).as_bytes())
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs::File;
 
+    use crate::{builder::GdtfBuilder, parser::parse_description};
+
     use super::*;
 
     #[test]
@@ -95,4 +126,64 @@ mod tests {
             hash_gdtf(archive_change_inner_creation_date).unwrap()
         )
     }
+
+    fn built_fixture() -> Gdtf {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Dimmer", 1, vec![1])
+            .unwrap()
+            .build();
+        gdtf.fixture_type_id = uuid::Uuid::from_u128(1);
+        gdtf
+    }
+
+    #[test]
+    fn model_hash_depends_on_content_only() {
+        let gdtf = built_fixture();
+        assert_eq!(hash_gdtf_model(&gdtf), hash_gdtf_model(&built_fixture()));
+
+        let reparsed = parse_description(serialize_gdtf(&gdtf), &Default::default())
+            .unwrap()
+            .gdtf;
+        assert_eq!(hash_gdtf_model(&gdtf), hash_gdtf_model(&reparsed));
+
+        let mut changed = built_fixture();
+        changed.description = "changed".into();
+        assert_ne!(hash_gdtf_model(&gdtf), hash_gdtf_model(&changed));
+
+        let mut changed = built_fixture();
+        changed.unparsed.revisions =
+            Some(r#"<Revisions><Revision Text="new"/></Revisions>"#.into());
+        assert_ne!(hash_gdtf_model(&gdtf), hash_gdtf_model(&changed));
+    }
+
+    #[test]
+    fn semantic_hash_ignores_attribute_order() {
+        let mut gdtf = built_fixture();
+        gdtf.extensions.attributes = vec![
+            ("VendorA".into(), "1".into()),
+            ("VendorB".into(), "2".into()),
+        ];
+        let mut reordered = built_fixture();
+        reordered.extensions.attributes = vec![
+            ("VendorB".into(), "2".into()),
+            ("VendorA".into(), "1".into()),
+        ];
+        assert_ne!(hash_gdtf_model(&gdtf), hash_gdtf_model(&reordered));
+        assert_eq!(hash_gdtf_semantic(&gdtf), hash_gdtf_semantic(&reordered));
+
+        reordered.long_name = "changed".into();
+        assert_ne!(hash_gdtf_semantic(&gdtf), hash_gdtf_semantic(&reordered));
+
+        let mut changed = built_fixture();
+        changed.protocols.ftrdm = Some(r#"<FTRDM ManufacturerID="0x0001"/>"#.into());
+        assert_ne!(
+            hash_gdtf_semantic(&built_fixture()),
+            hash_gdtf_semantic(&changed)
+        );
+    }
 }
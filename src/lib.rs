@@ -1,8 +1,23 @@
 #![warn(clippy::unwrap_used, clippy::expect_used, clippy::indexing_slicing)]
 
+pub mod archive;
+pub mod diff;
+pub mod estimate;
 mod gdtf;
 pub mod hash;
+pub mod low_level;
 mod parser;
+pub mod resolve;
+pub mod resources;
+mod serializer;
+#[cfg(feature = "share-api")]
+pub mod share;
+#[cfg(feature = "test_support")]
+pub mod test_support;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+pub use diff::diff;
 pub use gdtf::*;
 pub use parser::*;
+pub use serializer::*;
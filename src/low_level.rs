@@ -0,0 +1,43 @@
+//! Entry points and types for consumers that need the parse result as it
+//! closely follows the GDTF file, e.g. editors or migration tools.
+//!
+//! Everything here is re-exported from elsewhere in the crate and gathered in
+//! one place for convenience.
+//!
+//! # Stability
+//!
+//! The items in this module follow the structure of GDTF and the parser more
+//! closely than the rest of the API. They will change together with the
+//! parser and are not yet covered by any stability guarantee.
+
+pub use crate::parser::{
+    parse, parse_with_options, Action, Error, HandledProblem, ParseOptions, ParsedGdtf, Problem,
+    ProblemAt, Problems,
+};
+
+pub use crate::gdtf::{
+    channel::{Channel, FunctionEntry},
+    channel_offsets::{ChannelOffsets, OffsetError},
+    dmx_address::{DmxAddress, DmxAddressError},
+    dmx_break::Break,
+    dmx_modes::{
+        BreakFootprint, ChannelFunction, ChannelFunctions, ChannelId, ChfId, DmxMode, DmxModeError,
+        Footprint, LayoutEntry, ModeMaster, Overlap, Subfixture, SubfixtureId, TemplateChannel,
+        TemplateInstance,
+    },
+    extensions::Extensions,
+    geometries::{Geometries, GeometriesError},
+    geometry::{
+        Beam, BeamType, ComponentType, FuseRating, Geometry, LampType, Laser, LaserColorType,
+        Offset, Offsets, Orientation, Type as GeometryType, WiringObject,
+    },
+    models::{Model, Models, PrimitiveType},
+    name::Name,
+    physical_value::{PhysicalValue, PhysicalValueError},
+    position::{Position, PositionError},
+    relations::{Relation, RelationType},
+    wheels::{
+        ColorCie, ColorCieError, ColorSlotRange, GoboChannelMap, GoboSlotRange, Slot, Wheel,
+        Wheels, WheelsError,
+    },
+};
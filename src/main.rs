@@ -0,0 +1,446 @@
+//! The `gdtf` command line tool for working with GDTF files.
+//!
+//! Usage:
+//! - `gdtf inspect [--json] <file>`
+//! - `gdtf validate [--warnings-as-errors] <files…>`
+//! - `gdtf diff <old file> <new file>`
+//! - `gdtf extract <file> <folder>`
+//! - `gdtf repack <folder> <file>`
+//! - `gdtf to-json <file>`
+
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, Read},
+    path::Path,
+    process::ExitCode,
+};
+
+use opengdtf::{
+    archive::GdtfArchive,
+    diff,
+    diff::{Change, FieldChange, ModeChange},
+    dmx_modes::DmxMode,
+    geometries::Geometries,
+    parse, pretty_print_xml,
+    severity::Severity,
+    write_description_archive, ParsedGdtf,
+};
+use petgraph::graph::NodeIndex;
+use serde_json::{json, Value};
+
+const USAGE: &str = "usage:
+  gdtf inspect [--json] <file>
+  gdtf validate [--warnings-as-errors] <files…>
+  gdtf diff <old file> <new file>
+  gdtf extract <file> <folder>
+  gdtf repack <folder> <file>
+  gdtf to-json <file>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.split_first() {
+        Some((command, rest)) if command == "inspect" => inspect(rest),
+        Some((command, rest)) if command == "validate" => validate(rest),
+        Some((command, rest)) if command == "diff" => diff_files(rest),
+        Some((command, rest)) if command == "extract" => extract(rest),
+        Some((command, rest)) if command == "repack" => repack(rest),
+        Some((command, rest)) if command == "to-json" => to_json(rest),
+        _ => Err(USAGE.into()),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Print metadata, geometry tree, modes with their channel layout and the
+/// problems of a GDTF file
+fn inspect(args: &[String]) -> Result<ExitCode, String> {
+    let (flags, files) = split_args(args, &["--json"])?;
+    let json = flags.contains(&"--json");
+    let [path] = files[..] else {
+        return Err(USAGE.into());
+    };
+    let parsed = parse_file(path)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&inspect_json(&parsed)).map_err(|e| e.to_string())?
+        );
+    } else {
+        print_inspect(&parsed);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Parse and check all files and print their problems and integrity issues
+/// grouped by severity. Fails if any file has errors.
+fn validate(args: &[String]) -> Result<ExitCode, String> {
+    let (flags, files) = split_args(args, &["--warnings-as-errors"])?;
+    let warnings_as_errors = flags.contains(&"--warnings-as-errors");
+    if files.is_empty() {
+        return Err(USAGE.into());
+    }
+
+    let mut failed = false;
+    for path in files {
+        let findings = match parse_file(path) {
+            Ok(parsed) => findings(&parsed),
+            Err(e) => vec![(Severity::Error, e)],
+        };
+        let count = |severity| findings.iter().filter(|(s, _)| *s == severity).count();
+        let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+        println!("{path}: {errors} errors, {warnings} warnings");
+        for severity in [Severity::Error, Severity::Warning] {
+            for (_, message) in findings.iter().filter(|(s, _)| *s == severity) {
+                println!("  {severity}: {message}");
+            }
+        }
+        failed |= errors > 0 || (warnings_as_errors && warnings > 0);
+    }
+
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Print the changes of metadata, geometries, DMX modes and channels from
+/// the old to the new file. Like the `diff` tool, fails if there are any.
+fn diff_files(args: &[String]) -> Result<ExitCode, String> {
+    let (_, files) = split_args(args, &[])?;
+    let [old, new] = files[..] else {
+        return Err(USAGE.into());
+    };
+    let changes = diff(&parse_file(old)?.gdtf, &parse_file(new)?.gdtf);
+    if changes.is_empty() {
+        println!("no differences");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if !changes.metadata.is_empty() {
+        println!("Metadata:");
+        print_fields(&changes.metadata, 1);
+    }
+    if !changes.geometries.is_empty() {
+        println!("Geometries:");
+        for change in &changes.geometries {
+            print_change(change, 1);
+        }
+    }
+    if !changes.dmx_modes.is_empty() {
+        println!("DMX Modes:");
+        for change in &changes.dmx_modes {
+            match change {
+                ModeChange::Added(name) => println!("  + {name}"),
+                ModeChange::Removed(name) => println!("  - {name}"),
+                ModeChange::Changed {
+                    name,
+                    fields,
+                    channels,
+                } => {
+                    println!("  ~ {name}");
+                    print_fields(fields, 2);
+                    for change in channels {
+                        print_change(change, 2);
+                    }
+                }
+            }
+        }
+    }
+    Ok(ExitCode::FAILURE)
+}
+
+fn print_change(change: &Change, depth: usize) {
+    let indent = "  ".repeat(depth);
+    match change {
+        Change::Added(name) => println!("{indent}+ {name}"),
+        Change::Removed(name) => println!("{indent}- {name}"),
+        Change::Changed { name, fields } => {
+            println!("{indent}~ {name}");
+            print_fields(fields, depth + 1);
+        }
+    }
+}
+
+fn print_fields(fields: &[FieldChange], depth: usize) {
+    let indent = "  ".repeat(depth);
+    for FieldChange { field, old, new } in fields {
+        println!("{indent}{field}: '{old}' -> '{new}'");
+    }
+}
+
+/// Problems of parsing and issues of the integrity checks, with their
+/// severity
+fn findings(parsed: &ParsedGdtf) -> Vec<(Severity, String)> {
+    let report = parsed.gdtf.integrity_report();
+    parsed
+        .problems
+        .iter()
+        .map(|p| (p.severity(), format!("[{}] {p}", p.problem().code())))
+        .chain(report.issues().map(|i| (i.severity(), i.to_string())))
+        .collect()
+}
+
+/// Split arguments into the given flags and the remaining positional
+/// arguments. Other flags are an error.
+fn split_args<'a>(
+    args: &'a [String],
+    known_flags: &[&str],
+) -> Result<(Vec<&'a str>, Vec<&'a str>), String> {
+    let mut flags = vec![];
+    let mut positional = vec![];
+    for arg in args.iter().map(String::as_str) {
+        if known_flags.contains(&arg) {
+            flags.push(arg);
+        } else if arg.starts_with("--") {
+            return Err(format!("unknown flag '{arg}'\n{USAGE}"));
+        } else {
+            positional.push(arg);
+        }
+    }
+    Ok((flags, positional))
+}
+
+/// Extract all files of a GDTF archive to a folder, with `description.xml`
+/// pretty-printed for editing by hand
+fn extract(args: &[String]) -> Result<ExitCode, String> {
+    let (_, files) = split_args(args, &[])?;
+    let [path, dir] = files[..] else {
+        return Err(USAGE.into());
+    };
+    let file = File::open(path).map_err(|e| format!("could not open '{path}': {e}"))?;
+    let mut archive =
+        GdtfArchive::new(file).map_err(|e| format!("could not open '{path}': {e}"))?;
+    let names: Vec<String> = archive.file_names().map(str::to_owned).collect();
+
+    for name in names {
+        let read_err = |e: String| format!("could not read '{name}' in '{path}': {e}");
+        let mut zip_file = archive.file(&name).map_err(|e| read_err(e.to_string()))?;
+        if zip_file.is_dir() {
+            continue;
+        }
+        let Some(relative) = zip_file.enclosed_name().map(Path::to_owned) else {
+            return Err(read_err("path leaves the archive".into()));
+        };
+        let mut content = vec![];
+        zip_file
+            .read_to_end(&mut content)
+            .map_err(|e| read_err(e.to_string()))?;
+        if name == "description.xml" {
+            let description = String::from_utf8(content).map_err(|e| read_err(e.to_string()))?;
+            content = pretty_print_xml(&description)
+                .map_err(|e| read_err(e.to_string()))?
+                .into_bytes();
+        }
+
+        let target = Path::new(dir).join(relative);
+        let write_err = |e: io::Error| format!("could not write '{}': {e}", target.display());
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(write_err)?;
+        }
+        fs::write(&target, content).map_err(write_err)?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Pack a folder created by `extract` into a GDTF archive and check that the
+/// result can be parsed
+fn repack(args: &[String]) -> Result<ExitCode, String> {
+    let (_, files) = split_args(args, &[])?;
+    let [dir, path] = files[..] else {
+        return Err(USAGE.into());
+    };
+    let dir = Path::new(dir);
+    let description_path = dir.join("description.xml");
+    let description = fs::read_to_string(&description_path)
+        .map_err(|e| format!("could not read '{}': {e}", description_path.display()))?;
+    let mut resources = vec![];
+    resource_files(dir, dir, &mut resources)?;
+
+    let file = File::create(path).map_err(|e| format!("could not create '{path}': {e}"))?;
+    write_description_archive(&description, resources, file)
+        .map_err(|e| format!("could not write '{path}': {e}"))?;
+
+    let parsed = parse_file(path)?;
+    println!("{path}: {} problems", parsed.problems.len());
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Files below `dir` as pairs of path in the archive, relative to `root`,
+/// and content. `description.xml` in `root` is left out.
+fn resource_files(
+    root: &Path,
+    dir: &Path,
+    files: &mut Vec<(String, Vec<u8>)>,
+) -> Result<(), String> {
+    let read_err = |e: io::Error| format!("could not read '{}': {e}", dir.display());
+    let mut entries = fs::read_dir(dir)
+        .map_err(read_err)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(read_err)?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            resource_files(root, &path, files)?;
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let archive_path = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if archive_path == "description.xml" {
+            continue;
+        }
+        files.push((archive_path, fs::read(&path).map_err(read_err)?));
+    }
+    Ok(())
+}
+
+/// Print the complete fixture model as JSON, see [`opengdtf::Gdtf::to_json`]
+fn to_json(args: &[String]) -> Result<ExitCode, String> {
+    let (_, files) = split_args(args, &[])?;
+    let [path] = files[..] else {
+        return Err(USAGE.into());
+    };
+    let json = parse_file(path)?
+        .gdtf
+        .to_json()
+        .map_err(|e| e.to_string())?;
+    println!("{json}");
+    Ok(ExitCode::SUCCESS)
+}
+
+fn parse_file(path: &str) -> Result<ParsedGdtf, String> {
+    let file = File::open(path).map_err(|e| format!("could not open '{path}': {e}"))?;
+    parse(file).map_err(|e| format!("could not parse '{path}': {e}"))
+}
+
+/// Children of a geometry in the order they were parsed
+fn children(geometries: &Geometries, i: NodeIndex) -> Vec<NodeIndex> {
+    let mut children: Vec<_> = geometries.graph().neighbors(i).collect();
+    children.sort();
+    children
+}
+
+fn print_inspect(parsed: &ParsedGdtf) {
+    let gdtf = &parsed.gdtf;
+    println!("Name: {}", gdtf.name);
+    println!("Manufacturer: {}", gdtf.manufacturer);
+    println!("FixtureTypeID: {}", gdtf.fixture_type_id);
+    println!("DataVersion: {}", gdtf.data_version);
+
+    println!("\nGeometries:");
+    for i in gdtf.geometries.top_level_indices() {
+        print_geometry(&gdtf.geometries, i, 1);
+    }
+
+    println!("\nDMX Modes:");
+    for mode in gdtf.dmx_modes() {
+        print_mode(mode, &gdtf.geometries);
+    }
+
+    println!("\nProblems: {}", parsed.problems.len());
+    for p in &parsed.problems {
+        println!("  [{}] {p}", p.problem().code());
+    }
+}
+
+fn print_geometry(geometries: &Geometries, i: NodeIndex, depth: usize) {
+    let Ok(geometry) = geometries.get_by_index(i) else {
+        return;
+    };
+    let indent = "  ".repeat(depth);
+    println!("{indent}{} ({})", geometry.name, <&str>::from(&geometry.t));
+    for child in children(geometries, i) {
+        print_geometry(geometries, child, depth + 1);
+    }
+}
+
+fn print_mode(mode: &DmxMode, geometries: &Geometries) {
+    let geometry = mode
+        .geometry_name(geometries)
+        .map(|n| n.to_string())
+        .unwrap_or_default();
+    println!("  {} (Geometry: {geometry})", mode.name);
+    for (dmx_break, entries) in mode.channel_layout() {
+        println!("    Break {dmx_break}");
+        for entry in entries {
+            let byte = if entry.channel.bytes > 1 {
+                format!(" (byte {}/{})", entry.byte_index + 1, entry.channel.bytes)
+            } else {
+                "".into()
+            };
+            println!("      {:>3} {}{byte}", entry.offset, entry.channel.name);
+        }
+    }
+}
+
+fn inspect_json(parsed: &ParsedGdtf) -> Value {
+    let gdtf = &parsed.gdtf;
+    json!({
+        "name": gdtf.name.to_string(),
+        "manufacturer": gdtf.manufacturer,
+        "fixture_type_id": gdtf.fixture_type_id.to_string(),
+        "data_version": gdtf.data_version.to_string(),
+        "geometries": gdtf
+            .geometries
+            .top_level_indices()
+            .map(|i| geometry_json(&gdtf.geometries, i))
+            .collect::<Vec<_>>(),
+        "dmx_modes": gdtf
+            .dmx_modes()
+            .iter()
+            .map(|mode| mode_json(mode, &gdtf.geometries))
+            .collect::<Vec<_>>(),
+        "problems": parsed.problems,
+    })
+}
+
+fn geometry_json(geometries: &Geometries, i: NodeIndex) -> Value {
+    let Ok(geometry) = geometries.get_by_index(i) else {
+        return Value::Null;
+    };
+    json!({
+        "name": geometry.name.to_string(),
+        "type": <&str>::from(&geometry.t),
+        "children": children(geometries, i)
+            .into_iter()
+            .map(|child| geometry_json(geometries, child))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn mode_json(mode: &DmxMode, geometries: &Geometries) -> Value {
+    json!({
+        "name": mode.name.to_string(),
+        "geometry": mode.geometry_name(geometries).ok().map(|n| n.to_string()),
+        "breaks": mode
+            .channel_layout()
+            .into_iter()
+            .map(|(dmx_break, entries)| json!({
+                "break": dmx_break.value(),
+                "offsets": entries
+                    .iter()
+                    .map(|entry| json!({
+                        "offset": entry.offset,
+                        "channel": entry.channel.name.to_string(),
+                        "byte_index": entry.byte_index,
+                    }))
+                    .collect::<Vec<_>>(),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
@@ -0,0 +1,133 @@
+//! Parsing rules for constructs of older GDTF versions.
+//!
+//! Parsing assumes GDTF 1.2 semantics. Constructs that were removed in later
+//! versions are listed here with the last version that defines them. Files
+//! that declare such a version are adapted to 1.2 semantics, newer files must
+//! not use the construct, so it is ignored. A problem is raised either way.
+//!
+//! Only the constructs listed here are adapted. Other differences between
+//! versions, like renamed attributes, are parsed with 1.2 semantics.
+
+use roxmltree::Node;
+
+use crate::{data_version::DataVersion, Action, Problem};
+
+use super::problems::ProblemsMut;
+
+/// An XML attribute that was removed from GDTF after `last_version`
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LegacyAttribute {
+    pub tag: &'static str,
+    pub attr: &'static str,
+    pub last_version: DataVersion,
+    /// how the attribute is adapted, for files up to `last_version`
+    pub adaptation: &'static str,
+}
+
+/// The default DMX value of a channel, replaced by `InitialFunction` and the
+/// `Default` of channel functions in GDTF 1.1
+pub(crate) const DMX_CHANNEL_DEFAULT: LegacyAttribute = LegacyAttribute {
+    tag: "DMXChannel",
+    attr: "Default",
+    last_version: DataVersion::V1_0,
+    adaptation: "it as default value of the channel if InitialFunction is absent",
+};
+
+impl LegacyAttribute {
+    /// Returns the content of the attribute if it is present and should be
+    /// adapted for `data_version`. A problem is raised if it is present.
+    pub(crate) fn get<'a>(
+        &self,
+        n: &Node<'a, '_>,
+        data_version: DataVersion,
+        problems: &mut impl ProblemsMut,
+    ) -> Option<&'a str> {
+        let content = n.attribute(self.attr)?;
+        let adapt = data_version <= self.last_version;
+        let action = if adapt {
+            Action::used(self.adaptation)
+        } else {
+            Action::ignored("attribute")
+        };
+        Problem::LegacyAttribute {
+            attr: self.attr.to_owned(),
+            tag: self.tag.to_owned(),
+            last_version: self.last_version,
+            data_version,
+        }
+        .at(n)
+        .handled_by(action, problems);
+        adapt.then_some(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{channel::DefaultSource, parser::parse_description};
+
+    use super::*;
+
+    fn description(data_version: &str) -> String {
+        format!(
+            r#"<GDTF DataVersion="{data_version}">
+<FixtureType Name="Test" ShortName="" LongName="" Manufacturer="" Description="" FixtureTypeID="00000000-0000-0000-0000-000000000001">
+    <Wheels/>
+    <Models/>
+    <Geometries>
+        <Geometry Name="Body"/>
+    </Geometries>
+    <DMXModes>
+        <DMXMode Name="Mode" Geometry="Body">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Body" Default="128/1">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Name="Dimmer" Attribute="Dimmer" DMXFrom="0/1"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#
+        )
+    }
+
+    #[test]
+    fn dmx_channel_default() {
+        let defaults = |data_version| {
+            let parsed = parse_description(description(data_version), &Default::default()).unwrap();
+            let handled = parsed.problems.first().unwrap();
+            assert!(matches!(
+                handled.problem(),
+                Problem::LegacyAttribute { attr, .. } if attr == "Default"
+            ));
+            assert_eq!(parsed.problems.len(), 1, "{:?}", parsed.problems);
+            let ch = parsed
+                .gdtf
+                .dmx_modes()
+                .first()
+                .unwrap()
+                .channels
+                .first()
+                .unwrap();
+            (ch.default, ch.default_source, handled.action.clone())
+        };
+
+        assert_eq!(
+            defaults("1.0"),
+            (
+                128,
+                DefaultSource::DmxChannelDefault,
+                Action::used(DMX_CHANNEL_DEFAULT.adaptation)
+            )
+        );
+        assert_eq!(
+            defaults("1.2"),
+            (
+                0,
+                DefaultSource::FirstChannelFunction,
+                Action::ignored("attribute")
+            )
+        );
+    }
+}
@@ -7,9 +7,9 @@ pub enum DmxValueError {
     #[error("empty DMXValue")]
     Empty,
     #[error("value is not a valid u32; {0}")]
-    InvalidValue(Box<dyn std::error::Error>),
+    InvalidValue(Box<dyn std::error::Error + Send + Sync>),
     #[error("byte count is not a valid u8; {0}")]
-    InvalidBytes(Box<dyn std::error::Error>),
+    InvalidBytes(Box<dyn std::error::Error + Send + Sync>),
     #[error("DMXValue bigger than maximum value {0} of given bytes")]
     ValueTooBig(u32),
     #[error("DMXValues are only supported with up to 4 bytes")]
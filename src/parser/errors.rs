@@ -3,17 +3,53 @@ use std::io;
 use thiserror::Error;
 use zip::result::ZipError;
 
+use super::HandledProblem;
+
 /// An unrecoverable GDTF Parsing Error.
+///
+/// The underlying cause is available as [`std::error::Error::source`].
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
-    #[error("invalid XML: {0}")]
+    #[error(transparent)]
+    Archive(#[from] ArchiveError),
+    #[error(transparent)]
+    Description(#[from] DescriptionError),
+    /// The [`RecoveryPolicy`](crate::RecoveryPolicy) aborted on a problem
+    #[error("parsing aborted on problem: {0}")]
+    Aborted(Box<HandledProblem>),
+}
+
+/// The GDTF file or its zip archive could not be read.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ArchiveError {
+    #[error("file could not be opened")]
+    FileOpen(#[source] io::Error),
+    #[error("zip error")]
+    InvalidZip(#[from] ZipError),
+    #[error("'description.xml' not found in GDTF zip archive")]
+    DescriptionXmlMissing(#[source] ZipError),
+    #[error("'description.xml' could not be read")]
+    InvalidDescriptionXml(#[source] io::Error),
+    #[error("nested GDTF file '{path}' could not be read")]
+    InvalidNestedGdtf {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// The content of `description.xml` is not a GDTF description.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum DescriptionError {
+    #[error("invalid XML")]
     InvalidXml(#[from] roxmltree::Error),
+    /// The XML could not be tokenized, when reading only the metadata with
+    /// [`parse_gdtf_metadata`](crate::parse_gdtf_metadata)
+    #[error("invalid XML")]
+    InvalidXmlSyntax(#[from] xmlparser::Error),
     #[error("root node 'GDTF' not found")]
     NoRootNode,
-    #[error("zip error: {0}")]
-    InvalidZip(#[from] ZipError),
-    #[error("'description.xml' not found in GDTF zip archive: {0}")]
-    DescriptionXmlMissing(ZipError),
-    #[error("'description.xml' could not be read: {0}")]
-    InvalidDescriptionXml(io::Error),
 }
@@ -0,0 +1,217 @@
+use roxmltree::Node;
+
+use crate::{
+    extensions::{Extensions, UnparsedSections},
+    Action, Problem,
+};
+
+use super::{
+    parse_xml::{GetXmlAttribute, GetXmlNode},
+    problems::ProblemsMut,
+};
+
+/// Collect the attributes and child elements of `n` that are not known, so
+/// they can be preserved. Unknown child elements raise a problem, since they
+/// may change the meaning of the node.
+pub(crate) fn parse_extensions(
+    n: Node,
+    known_attributes: &[&str],
+    known_children: &[&str],
+    problems: &mut impl ProblemsMut,
+) -> Extensions {
+    let mut nodes = vec![];
+    for child in n.children().filter(|c| c.is_element()) {
+        let tag = child.tag_name().name();
+        if known_children.contains(&tag) {
+            continue;
+        }
+        Problem::UnexpectedXmlNode(tag.into())
+            .at(&child)
+            .handled_by(Action::KeptAsExtension, problems);
+        nodes.push(child.verbatim().to_owned());
+    }
+    Extensions {
+        attributes: n.unknown_attributes(known_attributes),
+        nodes,
+    }
+}
+
+/// Collect the attributes of `n` that are not known. For nodes whose children
+/// are checked by their own parser, so no child nodes are kept.
+pub(crate) fn parse_unknown_attributes(n: Node, known_attributes: &[&str]) -> Extensions {
+    Extensions {
+        attributes: n.unknown_attributes(known_attributes),
+        nodes: vec![],
+    }
+}
+
+/// Keep the sections of the FixtureType node that are not parsed yet as XML
+pub(crate) fn parse_unparsed_sections(fixture_type: Node) -> UnparsedSections {
+    let verbatim = |tag| {
+        fixture_type
+            .children()
+            .find(|n| n.has_tag_name(tag))
+            .map(|n| n.verbatim().to_owned())
+    };
+    UnparsedSections {
+        attribute_definitions: verbatim("AttributeDefinitions"),
+        physical_descriptions: verbatim("PhysicalDescriptions"),
+        revisions: verbatim("Revisions"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{name::IntoValidName, parser::parse_description, serialize_gdtf, Gdtf};
+
+    #[test]
+    fn extensions_survive_serialization() {
+        let description = r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Test" FixtureTypeID="00000000-0000-0000-0000-000000000001" FutureAttribute="1">
+    <Models>
+        <Model Name="Body" FutureModelAttribute="2">
+            <FutureModelNode Value="3"/>
+        </Model>
+    </Models>
+    <FutureNode>
+        <Child Text="a &amp; b"/>
+    </FutureNode>
+</FixtureType>
+</GDTF>"#;
+        let parsed = parse_description(description.into(), &Default::default()).unwrap();
+        let reparsed = parse_description(serialize_gdtf(&parsed.gdtf), &Default::default())
+            .unwrap()
+            .gdtf;
+
+        for gdtf in [&parsed.gdtf, &reparsed] {
+            assert_eq!(
+                gdtf.extensions.attributes,
+                vec![("FutureAttribute".to_owned(), "1".to_owned())]
+            );
+            assert_eq!(
+                gdtf.extensions.nodes,
+                ["<FutureNode>\n        <Child Text=\"a &amp; b\"/>\n    </FutureNode>"]
+            );
+            let model = gdtf.models.first().unwrap();
+            assert_eq!(
+                model.extensions.attributes,
+                vec![("FutureModelAttribute".to_owned(), "2".to_owned())]
+            );
+            assert_eq!(model.extensions.nodes, [r#"<FutureModelNode Value="3"/>"#]);
+        }
+        assert!(parsed.problems.iter().any(|p| matches!(
+            p.problem(),
+            crate::Problem::UnexpectedXmlNode(tag) if tag == "FutureNode"
+        )));
+    }
+
+    #[test]
+    fn unparsed_sections_survive_serialization() {
+        let description = r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Test" FixtureTypeID="00000000-0000-0000-0000-000000000001">
+    <AttributeDefinitions>
+        <Attributes>
+            <Attribute Name="Dimmer" Pretty="Dim" PhysicalUnit="LuminousIntensity"/>
+        </Attributes>
+    </AttributeDefinitions>
+    <Revisions>
+        <Revision Date="2024-01-01T00:00:00" Text="first"/>
+    </Revisions>
+    <Protocols>
+        <FTRDM ManufacturerID="0x0001" DeviceModelID="0x0002"/>
+    </Protocols>
+</FixtureType>
+</GDTF>"#;
+        let parsed = parse_description(description.into(), &Default::default()).unwrap();
+        let reparsed = parse_description(serialize_gdtf(&parsed.gdtf), &Default::default())
+            .unwrap()
+            .gdtf;
+
+        assert_eq!(parsed.gdtf.unparsed.physical_descriptions, None);
+        for gdtf in [&parsed.gdtf, &reparsed] {
+            let unparsed = &gdtf.unparsed;
+            assert!(unparsed
+                .attribute_definitions
+                .as_deref()
+                .unwrap()
+                .contains(r#"<Attribute Name="Dimmer" Pretty="Dim""#));
+            assert_eq!(
+                unparsed.revisions.as_deref().unwrap(),
+                "<Revisions>\n        <Revision Date=\"2024-01-01T00:00:00\" Text=\"first\"/>\n    </Revisions>"
+            );
+            assert_eq!(
+                gdtf.protocols.ftrdm.as_deref(),
+                Some(r#"<FTRDM ManufacturerID="0x0001" DeviceModelID="0x0002"/>"#)
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_attributes_keep_their_order() {
+        let description = r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Test" FixtureTypeID="00000000-0000-0000-0000-000000000001">
+    <Geometries>
+        <Geometry Name="Body" VendorZ="1" VendorA="2">
+            <Beam Name="Beam" LuminousFlux="1000" VendorLens="Wide"/>
+        </Geometry>
+    </Geometries>
+    <DMXModes>
+        <DMXMode Name="Mode" Geometry="Body" VendorMode="3">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Beam" VendorChannel="4">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Name="Dimmer" Attribute="Dimmer" DMXFrom="0/1" VendorCurve="Square"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#;
+        let parsed = parse_description(description.into(), &Default::default()).unwrap();
+        let reparsed = parse_description(serialize_gdtf(&parsed.gdtf), &Default::default())
+            .unwrap()
+            .gdtf;
+
+        let attributes = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let geometry = |gdtf: &Gdtf, name: &str| {
+            let geometries = &gdtf.geometries;
+            let i = geometries.get_index(&name.into_valid()).unwrap();
+            geometries.get_by_index(i).unwrap().extensions.clone()
+        };
+        for gdtf in [&parsed.gdtf, &reparsed] {
+            assert_eq!(
+                geometry(gdtf, "Body").attributes,
+                attributes(&[("VendorZ", "1"), ("VendorA", "2")])
+            );
+            assert_eq!(
+                geometry(gdtf, "Beam").attributes,
+                attributes(&[("VendorLens", "Wide")])
+            );
+            let mode = gdtf.dmx_modes().first().unwrap();
+            assert_eq!(
+                mode.extensions.attributes,
+                attributes(&[("VendorMode", "3")])
+            );
+            let ch = mode.channels.first().unwrap();
+            assert_eq!(
+                ch.extensions.attributes,
+                attributes(&[("VendorChannel", "4")])
+            );
+            let chf = ch
+                .defined_functions()
+                .first()
+                .and_then(|i| mode.channel_functions.node_weight(*i))
+                .unwrap();
+            assert_eq!(
+                chf.extensions.attributes,
+                attributes(&[("VendorCurve", "Square")])
+            );
+        }
+    }
+}
@@ -2,7 +2,7 @@ use roxmltree::Node;
 
 use crate::{
     name::{IntoValidName, Name},
-    Problem,
+    Action, DuplicateNameRecovery, Problem,
 };
 use petgraph::graph::NodeIndex;
 
@@ -22,6 +22,13 @@ pub(super) struct Duplicate<'a> {
 impl<'a> GeometriesParser<'a> {
     pub(super) fn parse_duplicates(&mut self) {
         while let Some(dup) = self.duplicates.pop_front() {
+            if self.options.policy().duplicate_geometry_name() == DuplicateNameRecovery::Skip {
+                Problem::DuplicateGeometryName(dup.name.clone())
+                    .at(&dup.n)
+                    .handled_by(Action::IgnoredNode, self.problems);
+                continue;
+            }
+
             let name_to_increment = match self.try_renaming_with_top_level_name(&dup) {
                 Ok(()) => continue,
                 Err(name_to_increment) => name_to_increment,
@@ -79,7 +86,7 @@ impl<'a> GeometriesParser<'a> {
         }
         Problem::DuplicateGeometryName(dup.name.clone())
             .at(&dup.n)
-            .handled_by("deduplication failed, ignoring node", self.problems);
+            .handled_by(Action::DeduplicationFailed, self.problems);
     }
 
     fn handle_renamed_geometry(&mut self, dup: &Duplicate<'a>, suggested_name: &Name) {
@@ -92,7 +99,12 @@ impl<'a> GeometriesParser<'a> {
                 self.renamed_top_level_geometries.insert(graph_ind);
             }
 
-            problem.handled_by(format!("renamed to '{suggested_name}'"), self.problems);
+            problem.handled_by(
+                Action::RenamedTo {
+                    name: suggested_name.clone(),
+                },
+                self.problems,
+            );
 
             if let ContinueParsing::Children = continue_parsing {
                 self.add_children(
@@ -103,9 +115,9 @@ impl<'a> GeometriesParser<'a> {
             }
         } else {
             problem.handled_by(
-                format!(
-                    "renamed to '{suggested_name}' but still ignoring node due to some other error"
-                ),
+                Action::RenamedToButIgnoredNode {
+                    name: suggested_name.clone(),
+                },
                 self.problems,
             )
         }
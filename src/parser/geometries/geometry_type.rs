@@ -0,0 +1,211 @@
+use std::{fmt::Display, str::FromStr};
+
+use roxmltree::Node;
+
+use crate::{
+    extensions::Extensions,
+    geometry::{Beam, Laser, Type, WiringObject},
+    parser::{
+        extensions::parse_unknown_attributes, parse_xml::GetXmlAttribute, problems::HandleProblem,
+    },
+    Action,
+};
+
+use super::GeometriesParser;
+
+impl<'a> GeometriesParser<'a> {
+    /// Get the type of a geometry node that is not a GeometryReference,
+    /// parsing the attributes specific to the type.
+    ///
+    /// Returns None if the tag name is not a known geometry type.
+    pub(super) fn geometry_type(&mut self, n: Node) -> Option<Type> {
+        Some(match n.tag_name().name() {
+            "Geometry" => Type::General,
+            "Axis" => Type::Axis,
+            "FilterBeam" => Type::FilterBeam,
+            "FilterColor" => Type::FilterColor,
+            "FilterGobo" => Type::FilterGobo,
+            "FilterShaper" => Type::FilterShaper,
+            "Beam" => Type::Beam(self.beam(n)),
+            "MediaServerLayer" => Type::MediaServerLayer,
+            "MediaServerCamera" => Type::MediaServerCamera,
+            "MediaServerMaster" => Type::MediaServerMaster,
+            "Display" => Type::Display {
+                texture: non_empty_attribute(n, "Texture"),
+            },
+            "Laser" => Type::Laser(self.laser(n)),
+            "WiringObject" => Type::WiringObject(self.wiring_object(n)),
+            "Inventory" => Type::Inventory {
+                count: self.attribute_or(n, "Count", 1),
+            },
+            "Structure" => Type::Structure,
+            "Support" => Type::Support,
+            "Magnet" => Type::Magnet,
+            _ => return None,
+        })
+    }
+
+    fn beam(&mut self, n: Node) -> Beam {
+        let d = Beam::default();
+        Beam {
+            lamp_type: self.attribute_or(n, "LampType", d.lamp_type),
+            power_consumption: self.float_or(n, "PowerConsumption", d.power_consumption),
+            luminous_flux: self.float_or(n, "LuminousFlux", d.luminous_flux),
+            color_temperature: self.float_or(n, "ColorTemperature", d.color_temperature),
+            beam_angle: self.float_or(n, "BeamAngle", d.beam_angle),
+            field_angle: self.float_or(n, "FieldAngle", d.field_angle),
+            throw_ratio: self.float_or(n, "ThrowRatio", d.throw_ratio),
+            rectangle_ratio: self.float_or(n, "RectangleRatio", d.rectangle_ratio),
+            beam_radius: self.float_or(n, "BeamRadius", d.beam_radius),
+            beam_type: self.attribute_or(n, "BeamType", d.beam_type),
+            color_rendering_index: self.attribute_or(
+                n,
+                "ColorRenderingIndex",
+                d.color_rendering_index,
+            ),
+            emitter_spectrum: non_empty_attribute(n, "EmitterSpectrum"),
+        }
+    }
+
+    fn laser(&mut self, n: Node) -> Laser {
+        let d = Laser::default();
+        Laser {
+            color_type: self.attribute_or(n, "ColorType", d.color_type),
+            color: self.float_or(n, "Color", d.color),
+            output_strength: self.float_or(n, "OutputStrength", d.output_strength),
+            emitter: non_empty_attribute(n, "Emitter"),
+            beam_diameter: self.float_or(n, "BeamDiameter", d.beam_diameter),
+            beam_divergence_min: self.float_or(n, "BeamDivergenceMin", d.beam_divergence_min),
+            beam_divergence_max: self.float_or(n, "BeamDivergenceMax", d.beam_divergence_max),
+            scan_angle_pan: self.float_or(n, "ScanAnglePan", d.scan_angle_pan),
+            scan_angle_tilt: self.float_or(n, "ScanAngleTilt", d.scan_angle_tilt),
+            scan_speed: self.float_or(n, "ScanSpeed", d.scan_speed),
+        }
+    }
+
+    fn wiring_object(&mut self, n: Node) -> WiringObject {
+        let d = WiringObject::default();
+        WiringObject {
+            connector_type: n.attribute("ConnectorType").unwrap_or("").to_owned(),
+            component_type: self.attribute_or(n, "ComponentType", d.component_type),
+            signal_type: n.attribute("SignalType").unwrap_or("").to_owned(),
+            pin_count: self.attribute_or(n, "PinCount", d.pin_count),
+            electrical_payload: self.float_or(n, "ElectricalPayLoad", d.electrical_payload),
+            voltage_range_max: self.float_or(n, "VoltageRangeMax", d.voltage_range_max),
+            voltage_range_min: self.float_or(n, "VoltageRangeMin", d.voltage_range_min),
+            frequency_range_max: self.float_or(n, "FrequencyRangeMax", d.frequency_range_max),
+            frequency_range_min: self.float_or(n, "FrequencyRangeMin", d.frequency_range_min),
+            max_payload: self.float_or(n, "MaxPayLoad", d.max_payload),
+            voltage: self.float_or(n, "Voltage", d.voltage),
+            signal_layer: self.attribute_or(n, "SignalLayer", d.signal_layer),
+            cos_phi: self.float_or(n, "CosPhi", d.cos_phi),
+            fuse_current: self.float_or(n, "FuseCurrent", d.fuse_current),
+            fuse_rating: self.optional_attribute(n, "FuseRating"),
+            orientation: self.optional_attribute(n, "Orientation"),
+            wire_group: n.attribute("WireGroup").unwrap_or("").to_owned(),
+        }
+    }
+
+    /// Parse an optional attribute, using `default` if it is missing or
+    /// invalid
+    fn attribute_or<T: FromStr + Display>(&mut self, n: Node, attr: &str, default: T) -> T
+    where
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+    {
+        n.parse_attribute(attr)
+            .transpose()
+            .ok_or_handled_by(Action::used_default(&default), self.problems)
+            .flatten()
+            .unwrap_or(default)
+    }
+
+    /// Parse an optional float attribute, using `default` if it is missing or
+    /// invalid
+    fn float_or(&mut self, n: Node, attr: &str, default: f64) -> f64 {
+        n.parse_float_attribute(attr, self.problems)
+            .transpose()
+            .ok_or_handled_by(Action::used_default(default), self.problems)
+            .flatten()
+            .unwrap_or(default)
+    }
+
+    /// Parse an optional attribute, which is None if missing, empty or invalid
+    fn optional_attribute<T: FromStr>(&mut self, n: Node, attr: &str) -> Option<T>
+    where
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
+    {
+        n.map_parse_attribute(attr, |opt| opt.filter(|s| !s.is_empty()))?
+            .ok_or_handled_by(
+                Action::SetToNone {
+                    field: Some(attr.to_owned()),
+                },
+                self.problems,
+            )
+    }
+}
+
+/// Attributes of a geometry node that are not parsed, which depend on its type
+pub(super) fn geometry_extensions(n: Node) -> Extensions {
+    let specific: &[&str] = match n.tag_name().name() {
+        "Beam" => &[
+            "LampType",
+            "PowerConsumption",
+            "LuminousFlux",
+            "ColorTemperature",
+            "BeamAngle",
+            "FieldAngle",
+            "ThrowRatio",
+            "RectangleRatio",
+            "BeamRadius",
+            "BeamType",
+            "ColorRenderingIndex",
+            "EmitterSpectrum",
+        ],
+        "Display" => &["Texture"],
+        "Laser" => &[
+            "ColorType",
+            "Color",
+            "OutputStrength",
+            "Emitter",
+            "BeamDiameter",
+            "BeamDivergenceMin",
+            "BeamDivergenceMax",
+            "ScanAnglePan",
+            "ScanAngleTilt",
+            "ScanSpeed",
+        ],
+        "WiringObject" => &[
+            "ConnectorType",
+            "ComponentType",
+            "SignalType",
+            "PinCount",
+            "ElectricalPayLoad",
+            "VoltageRangeMax",
+            "VoltageRangeMin",
+            "FrequencyRangeMax",
+            "FrequencyRangeMin",
+            "MaxPayLoad",
+            "Voltage",
+            "SignalLayer",
+            "CosPhi",
+            "FuseCurrent",
+            "FuseRating",
+            "Orientation",
+            "WireGroup",
+        ],
+        "Inventory" => &["Count"],
+        "GeometryReference" => &["Geometry"],
+        _ => &[],
+    };
+    let known: Vec<&str> = ["Name", "Position", "Model"]
+        .into_iter()
+        .chain(specific.iter().copied())
+        .collect();
+    parse_unknown_attributes(n, &known)
+}
+
+fn non_empty_attribute(n: Node, attr: &str) -> Option<String> {
+    n.attribute(attr)
+        .filter(|s| !s.is_empty())
+        .map(str::to_owned)
+}
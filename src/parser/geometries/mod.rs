@@ -3,22 +3,32 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use petgraph::graph::NodeIndex;
 use roxmltree::Node;
 
-use self::{deduplication::Duplicate, reference::DeferredReference};
+use self::{
+    deduplication::Duplicate, geometry_type::geometry_extensions, reference::DeferredReference,
+};
 
-use super::parse_xml::{GetXmlAttribute, GetXmlNode};
+use super::{
+    parse_xml::{GetXmlAttribute, GetXmlNode},
+    problems::HandleProblem,
+};
 
 use crate::{
     geometries::Geometries,
     geometry::{Geometry, Type},
+    models::Models,
     name::Name,
-    Problem, Problems,
+    Action, ParseOptions, Problem, Problems,
 };
 
 mod deduplication;
+mod geometry_type;
+mod position;
 mod reference;
 
 pub(crate) struct GeometriesParser<'a> {
     geometries: &'a mut Geometries,
+    models: &'a Models,
+    options: &'a ParseOptions,
     problems: &'a mut Problems,
     references: VecDeque<DeferredReference<'a>>,
     duplicates: VecDeque<Duplicate<'a>>,
@@ -31,9 +41,16 @@ pub(crate) struct GeometriesParser<'a> {
 pub(crate) struct GeometryLookup(HashMap<(Name, Name), Name>);
 
 impl<'a> GeometriesParser<'a> {
-    pub(crate) fn new(geometries: &'a mut Geometries, problems: &'a mut Problems) -> Self {
+    pub(crate) fn new(
+        geometries: &'a mut Geometries,
+        models: &'a Models,
+        options: &'a ParseOptions,
+        problems: &'a mut Problems,
+    ) -> Self {
         GeometriesParser {
             geometries,
+            models,
+            options,
             problems,
             references: Default::default(),
             duplicates: Default::default(),
@@ -50,7 +67,7 @@ impl<'a> GeometriesParser<'a> {
         let geometries = match fixture_type.find_required_child("Geometries") {
             Ok(geometries) => geometries,
             Err(p) => {
-                p.handled_by("leaving geometries empty", self.problems);
+                p.handled_by(Action::left_empty("geometries"), self.problems);
                 return self.rename_lookup;
             }
         };
@@ -61,12 +78,12 @@ impl<'a> GeometriesParser<'a> {
                 if let Some(Geometry {
                     name,
                     t: Type::Reference { .. },
+                    ..
                 }) = &self.geometries.graph().node_weight(graph_ind)
                 {
-                    Problem::UnexpectedTopLevelGeometryReference(name.to_owned()).at(&n).handled_by("keeping GeometryReference, \
-                    but it is useless because a top-level GeometryReference can only be used for a DMX mode \
-                    that is offset from another one, which is useless because one can just change the start address on \
-                    the lighting console", self.problems);
+                    Problem::UnexpectedTopLevelGeometryReference(name.to_owned())
+                        .at(&n)
+                        .handled_by(Action::KeptTopLevelGeometryReference, self.problems);
                 }
                 if let ContinueParsing::Children = continue_parsing {
                     self.add_children(n, graph_ind, graph_ind);
@@ -154,31 +171,51 @@ impl<'a> GeometriesParser<'a> {
     ) -> Option<(NodeIndex, ContinueParsing)> {
         let (geometry, continue_parsing) = {
             match n.tag_name().name() {
-                "Geometry" | "Axis" | "FilterBeam" | "FilterColor" | "FilterGobo"
-                | "FilterShaper" | "Beam" | "MediaServerLayer" | "MediaServerCamera"
-                | "MediaServerMaster" | "Display" | "Laser" | "WiringObject" | "Inventory"
-                | "Structure" | "Support" | "Magnet" => Some((
-                    Geometry {
-                        name,
-                        t: Type::General,
-                    },
-                    ContinueParsing::Children,
-                )),
                 "GeometryReference" => {
                     Some((self.named_geometry_reference(n, name)?, ContinueParsing::No))
                 }
-                tag => {
-                    Problem::UnexpectedXmlNode(tag.into())
-                        .at(&n)
-                        .handled_by("ignoring node", self.problems);
-                    None
-                }
+                tag => match self.geometry_type(n) {
+                    Some(t) => Some((
+                        Geometry {
+                            position: self.position(n, &name),
+                            name,
+                            t,
+                            model: self.linked_model(n),
+                            extensions: geometry_extensions(n),
+                            source: Some(n.source_location()),
+                        },
+                        ContinueParsing::Children,
+                    )),
+                    None => {
+                        Problem::UnexpectedXmlNode(tag.into())
+                            .at(&n)
+                            .handled_by(Action::IgnoredNode, self.problems);
+                        None
+                    }
+                },
             }
         }?;
         let graph_ind = self.add_to_geometries(geometry, parent_graph_ind, n)?;
         Some((graph_ind, continue_parsing))
     }
 
+    /// Parse the optional Model attribute and ensure the model exists.
+    ///
+    /// An empty attribute is treated like a missing one. Unknown models cause a
+    /// problem and return None.
+    fn linked_model(&mut self, n: Node) -> Option<Name> {
+        let model = n
+            .map_parse_attribute::<Name, _>("Model", |opt| opt.filter(|s| !s.is_empty()))?
+            .ok_or_handled_by(Action::NotLinkedModel, self.problems)?;
+        if self.models.get_by_name(&model).is_none() {
+            Problem::UnknownModel(model)
+                .at(&n)
+                .handled_by(Action::NotLinkedModel, self.problems);
+            return None;
+        }
+        Some(model)
+    }
+
     fn add_to_geometries(
         &mut self,
         geometry: Geometry,
@@ -192,7 +229,7 @@ impl<'a> GeometriesParser<'a> {
         .map_err(|err| {
             Problem::Unexpected(err.into())
                 .at(&n)
-                .handled_by("ignoring node", self.problems)
+                .handled_by(Action::IgnoredNode, self.problems)
         })
         .ok()
     }
@@ -203,7 +240,6 @@ enum ContinueParsing {
     No,
 }
 
-#[allow(dead_code)] // TODO remove once dependent code is written
 impl GeometryLookup {
     pub(crate) fn deduplicated_name(&self, top_level_geometry: Name, geometry: Name) -> Name {
         match self.0.get(&(top_level_geometry, geometry.clone())) {
@@ -229,16 +265,31 @@ impl GeometryLookup {
 mod tests {
     use petgraph::Direction::Incoming;
 
-    use crate::{geometry::Offset, name::IntoValidName};
+    use crate::{
+        geometry::{BeamType, ComponentType, LampType, LaserColorType, Offset},
+        models::Model,
+        name::IntoValidName,
+        position::Position,
+        source_location::SourceLocation,
+    };
 
     use super::*;
 
     fn parse_geometries(ft_str: &str) -> (Geometries, GeometryLookup, Problems) {
+        parse_geometries_with(ft_str, &Models::default(), &ParseOptions::default())
+    }
+
+    fn parse_geometries_with(
+        ft_str: &str,
+        models: &Models,
+        options: &ParseOptions,
+    ) -> (Geometries, GeometryLookup, Problems) {
         let doc = roxmltree::Document::parse(ft_str).unwrap();
         let ft = doc.root_element();
         let mut problems: Problems = vec![];
         let mut geometries = Geometries::default();
-        let rename_lookup = GeometriesParser::new(&mut geometries, &mut problems).parse_from(&ft);
+        let rename_lookup =
+            GeometriesParser::new(&mut geometries, models, options, &mut problems).parse_from(&ft);
         (geometries, rename_lookup, problems)
     }
 
@@ -274,6 +325,13 @@ mod tests {
             .get_index(&"AbstractElement".into_valid())
             .unwrap();
         assert!(geometries.is_top_level(abstract_element));
+        assert_eq!(
+            geometries.get_by_index(abstract_element).unwrap().source,
+            Some(SourceLocation {
+                line: 4,
+                column: 13
+            })
+        );
 
         let main = geometries.get_index(&"Main".into_valid()).unwrap();
         assert!(geometries.is_top_level(main));
@@ -290,9 +348,9 @@ mod tests {
             Geometry {
                 t: Type::Reference { offsets },
                 ..
-            } if matches!(offsets.overwrite, Some(Offset{dmx_break, offset}) if dmx_break.value() == &1 && offset == 1)
-            && offsets.normal.get(&1.try_into().unwrap()).unwrap() == &1
-            && offsets.normal.get(&2.try_into().unwrap()).unwrap() == &1
+            } if matches!(offsets.overwrite, Some(Offset{dmx_break, offset}) if dmx_break.value() == &1 && offset.absolute() == 1)
+            && offsets.normal.get(&1.try_into().unwrap()).unwrap().absolute() == 1
+            && offsets.normal.get(&2.try_into().unwrap()).unwrap().absolute() == 1
         ));
         assert!(matches!(
             geometries
@@ -307,9 +365,9 @@ mod tests {
             Geometry {
                 t: Type::Reference { offsets },
                 ..
-            } if matches!(offsets.overwrite, Some(Offset{dmx_break, offset}) if dmx_break.value() == &1 && offset == 2)
-            && offsets.normal.get(&1.try_into().unwrap()).unwrap() == &3
-            && offsets.normal.get(&2.try_into().unwrap()).unwrap() == &4
+            } if matches!(offsets.overwrite, Some(Offset{dmx_break, offset}) if dmx_break.value() == &1 && offset.absolute() == 2)
+            && offsets.normal.get(&1.try_into().unwrap()).unwrap().absolute() == 3
+            && offsets.normal.get(&2.try_into().unwrap()).unwrap().absolute() == 4
         ));
         assert!(matches!(
             geometries
@@ -629,4 +687,222 @@ mod tests {
         assert_eq!(geometries.graph().node_count(), 4);
         assert_eq!(geometries.templates().edge_count(), 1); // Main 1 -> Element 1 is kept but useless
     }
+
+    #[test]
+    fn geometry_reference_chains_can_be_flattened() {
+        let ft_str = r#"
+    <FixtureType>
+        <Geometries>
+            <Geometry Name="Main 1" />
+            <Geometry Name="Main 2">
+                <GeometryReference Geometry="Element 1" Name="Element 2" />
+                <GeometryReference Geometry="Element 3" Name="Element 4" />
+            </Geometry>
+            <Geometry Name="Main 3">
+                <GeometryReference Geometry="Main 1" Name="Element 1" />
+                <GeometryReference Geometry="Element 4" Name="Element 3" />
+            </Geometry>
+        </Geometries>
+    </FixtureType>
+            "#;
+        let options = ParseOptions {
+            flatten_geometry_reference_chains: true,
+            ..Default::default()
+        };
+
+        let (geometries, _, problems) = parse_geometries_with(ft_str, &Models::default(), &options);
+
+        let mut problems_iter = problems.iter();
+        let flattened = problems_iter.next().unwrap();
+        assert!(matches!(
+            flattened.problem(),
+            Problem::GeometryReferenceChain { reference, referenced }
+            if reference == "Element 2" && referenced == "Element 1"
+        ));
+        assert_eq!(
+            flattened.action,
+            Action::FlattenedReferenceChain {
+                to: "Main 1".into_valid()
+            }
+        );
+        // circular chains can't be flattened
+        for _ in 0..2 {
+            assert!(matches!(
+                problems_iter.next().unwrap().problem(),
+                Problem::InvalidGeometryReference(..)
+            ));
+        }
+        assert!(problems_iter.next().is_none());
+
+        let main_1 = geometries.get_index(&"Main 1".into_valid()).unwrap();
+        let element_2 = geometries.get_index(&"Element 2".into_valid()).unwrap();
+        assert_eq!(geometries.templates().edge_count(), 2);
+        assert!(geometries
+            .templates()
+            .neighbors_directed(element_2, Incoming)
+            .eq([main_1]));
+    }
+
+    #[test]
+    fn geometries_are_linked_to_models() {
+        let ft_str = r#"
+    <FixtureType>
+        <Geometries>
+            <Geometry Name="Base" Model="Base">
+                <Beam Name="Beam" Model="Missing Model"/>
+                <Geometry Name="No Model" Model=""/>
+                <GeometryReference Geometry="Element" Name="Reference" Model="Base"/>
+            </Geometry>
+            <Geometry Name="Element"/>
+        </Geometries>
+    </FixtureType>
+            "#;
+        let mut models = Models::default();
+        models
+            .add(Model {
+                name: "Base".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let (geometries, _, problems) =
+            parse_geometries_with(ft_str, &models, &ParseOptions::default());
+
+        let model_of = |name: &str| {
+            geometries
+                .get_by_index(geometries.get_index(&name.into_valid()).unwrap())
+                .unwrap()
+                .model
+                .clone()
+        };
+        assert_eq!(model_of("Base"), Some("Base".into_valid()));
+        assert_eq!(model_of("Beam"), None);
+        assert_eq!(model_of("No Model"), None);
+        assert_eq!(model_of("Reference"), Some("Base".into_valid()));
+
+        let mut problems = problems.iter().map(|p| p.problem());
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::UnknownModel(name) if name == "Missing Model"
+        ));
+        assert!(problems.next().is_none());
+    }
+
+    #[test]
+    fn geometry_types_keep_their_attributes() {
+        let ft_str = r#"
+    <FixtureType>
+        <Geometries>
+            <Geometry Name="Base">
+                <Axis Name="Yoke">
+                    <Beam Name="Beam" BeamAngle="12.5" BeamType="PC" LampType="LED" LuminousFlux="not a number"/>
+                </Axis>
+                <Laser Name="Laser" ColorType="SingleWave" Color="532" ScanSpeed="2.5"/>
+                <WiringObject Name="Power In" ComponentType="PowerSource" ConnectorType="powerCON" PinCount="3" FuseRating="X"/>
+                <Display Name="Display" Texture=""/>
+                <Inventory Name="Spare Lamps" Count="2"/>
+            </Geometry>
+        </Geometries>
+    </FixtureType>
+            "#;
+        let (geometries, _, problems) = parse_geometries(ft_str);
+        let type_of = |name: &str| {
+            geometries
+                .get_by_index(geometries.get_index(&name.into_valid()).unwrap())
+                .unwrap()
+                .t
+                .clone()
+        };
+
+        assert!(matches!(type_of("Base"), Type::General));
+        assert!(matches!(type_of("Yoke"), Type::Axis));
+        let Type::Beam(beam) = type_of("Beam") else {
+            panic!("expected beam")
+        };
+        assert_eq!(beam.beam_angle, 12.5);
+        assert_eq!(beam.beam_type, BeamType::Pc);
+        assert_eq!(beam.lamp_type, LampType::Led);
+        assert_eq!(beam.luminous_flux, 10000., "invalid value uses default");
+        assert_eq!(beam.color_rendering_index, 100);
+        let Type::Laser(laser) = type_of("Laser") else {
+            panic!("expected laser")
+        };
+        assert_eq!(laser.color_type, LaserColorType::SingleWave);
+        assert_eq!(laser.color, 532.);
+        assert_eq!(laser.scan_speed, 2.5);
+        let Type::WiringObject(wiring) = type_of("Power In") else {
+            panic!("expected wiring object")
+        };
+        assert_eq!(wiring.component_type, ComponentType::PowerSource);
+        assert_eq!(wiring.connector_type, "powerCON");
+        assert_eq!(wiring.pin_count, 3);
+        assert_eq!(wiring.fuse_rating, None);
+        assert!(matches!(
+            type_of("Display"),
+            Type::Display { texture: None }
+        ));
+        assert!(matches!(
+            type_of("Spare Lamps"),
+            Type::Inventory { count: 2 }
+        ));
+
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().all(|p| matches!(
+            p.problem(),
+            Problem::InvalidAttribute { attr, .. } if attr == "LuminousFlux" || attr == "FuseRating"
+        )));
+    }
+
+    #[test]
+    fn invalid_positions_raise_problems() {
+        let ft_str = r#"
+    <FixtureType>
+        <Geometries>
+            <Geometry Name="Valid" Position="{1.000000,0.000000,0.000000,0.100000}{0.000000,0.000000,-1.000000,0.200000}{0.000000,1.000000,0.000000,0.300000}{0,0,0,1}"/>
+            <Geometry Name="Scaled" Position="{2,0,0,1}{0,2,0,2}{0,0,2,3}{0,0,0,1}"/>
+            <Geometry Name="Projective" Position="{1,0,0,0}{0,1,0,0}{0,0,1,0}{0,0,1,1}"/>
+            <Geometry Name="Invalid" Position="not a matrix"/>
+        </Geometries>
+    </FixtureType>
+            "#;
+        let position_of = |geometries: &Geometries, name: &str| {
+            geometries
+                .get_by_index(geometries.get_index(&name.into_valid()).unwrap())
+                .unwrap()
+                .position
+        };
+
+        let (geometries, _, problems) = parse_geometries(ft_str);
+        assert_eq!(
+            position_of(&geometries, "Valid").translation(),
+            [0.1, 0.2, 0.3]
+        );
+        assert!(!position_of(&geometries, "Scaled").is_orthonormal(1e-4));
+        assert_eq!(position_of(&geometries, "Invalid"), Position::IDENTITY);
+        let mut problems = problems.iter().map(|p| p.problem());
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::NonOrthonormalPosition(name) if name == "Scaled"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::NonAffinePosition(name) if name == "Projective"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::InvalidAttribute { attr, .. } if attr == "Position"
+        ));
+        assert!(problems.next().is_none());
+
+        let options = ParseOptions {
+            orthonormalize_geometry_positions: true,
+            ..Default::default()
+        };
+        let (geometries, _, problems) = parse_geometries_with(ft_str, &Models::default(), &options);
+        assert_eq!(problems.len(), 3);
+        let scaled = position_of(&geometries, "Scaled");
+        assert!(scaled.is_orthonormal(1e-9));
+        assert_eq!(scaled.translation(), [1., 2., 3.]);
+        assert!(position_of(&geometries, "Projective").is_affine(1e-9));
+    }
 }
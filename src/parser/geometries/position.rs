@@ -0,0 +1,47 @@
+use roxmltree::Node;
+
+use crate::{
+    name::Name,
+    parser::{parse_xml::GetXmlAttribute, problems::HandleProblem},
+    position::Position,
+    Action, Problem,
+};
+
+use super::GeometriesParser;
+
+/// Tolerance for validating positions, GDTF Builder writes 6 decimals
+const POSITION_TOLERANCE: f64 = 1e-4;
+
+impl<'a> GeometriesParser<'a> {
+    /// Parse the Position attribute and validate it.
+    ///
+    /// A missing attribute results in the identity. Positions that are not
+    /// affine or not orthonormal raise a problem and are orthonormalized if
+    /// requested in the options.
+    pub(super) fn position(&mut self, n: Node, name: &Name) -> Position {
+        let position: Position = n
+            .parse_attribute("Position")
+            .transpose()
+            .ok_or_handled_by(Action::used_default("identity"), self.problems)
+            .flatten()
+            .unwrap_or_default();
+
+        let problem = if !position.is_affine(POSITION_TOLERANCE) {
+            Problem::NonAffinePosition(name.clone())
+        } else if !position.is_orthonormal(POSITION_TOLERANCE) {
+            Problem::NonOrthonormalPosition(name.clone())
+        } else {
+            return position;
+        };
+
+        if self.options.orthonormalize_geometry_positions {
+            problem
+                .at(&n)
+                .handled_by(Action::used("orthonormalized position"), self.problems);
+            position.orthonormalized()
+        } else {
+            problem.at(&n).handled_by(Action::KeptAsIs, self.problems);
+            position
+        }
+    }
+}
@@ -1,16 +1,20 @@
 use crate::{
+    dmx_address::DmxAddress,
     dmx_break::Break,
     geometry::{Geometry, Offset, Offsets, Type},
     name::Name,
-    parser::{parse_xml::GetXmlAttribute, problems::HandleProblem},
-    Problem, ProblemAt, Problems,
+    parser::{
+        parse_xml::{GetXmlAttribute, GetXmlNode},
+        problems::HandleProblem,
+    },
+    Action, Problem, ProblemAt, Problems,
 };
 
-use super::GeometriesParser;
+use super::{geometry_type::geometry_extensions, GeometriesParser};
 use petgraph::graph::NodeIndex;
 use roxmltree::Node;
 
-use std::collections::hash_map::Entry::Vacant;
+use std::collections::{btree_map::Entry::Vacant, HashMap, HashSet};
 
 #[derive(Debug)]
 pub(super) struct DeferredReference<'a> {
@@ -28,13 +32,17 @@ impl<'a> GeometriesParser<'a> {
         let offsets = parse_reference_offsets(n, &name, self.problems);
 
         let geometry = Geometry {
+            position: self.position(n, &name),
             name: name.clone(),
             t: Type::Reference { offsets },
+            model: self.linked_model(n),
+            extensions: geometry_extensions(n),
+            source: Some(n.source_location()),
         };
 
         let ref_string = n
             .parse_required_attribute::<Name>("Geometry")
-            .ok_or_handled_by("not parsing node", self.problems)?;
+            .ok_or_handled_by(Action::IgnoredNode, self.problems)?;
         self.references.push_back(DeferredReference {
             referencing_node: n,
             name,
@@ -45,12 +53,34 @@ impl<'a> GeometriesParser<'a> {
     }
 
     pub(super) fn parse_references(&mut self) {
-        while let Some(d) = self.references.pop_front() {
+        let chain_targets: HashMap<Name, Name> = if self.options.flatten_geometry_reference_chains {
+            self.references
+                .iter()
+                .map(|d| (d.name.clone(), d.referenced.clone()))
+                .collect()
+        } else {
+            Default::default()
+        };
+
+        while let Some(mut d) = self.references.pop_front() {
+            if let Some(end) = self.end_of_reference_chain(&d.referenced, &chain_targets) {
+                Problem::GeometryReferenceChain {
+                    reference: d.name.clone(),
+                    referenced: d.referenced,
+                }
+                .at(&d.referencing_node)
+                .handled_by(
+                    Action::FlattenedReferenceChain { to: end.clone() },
+                    self.problems,
+                );
+                d.referenced = end;
+            }
+
             let referenced =
                 match self.get_index_of_referenced_geometry(d.referencing_node, d.referenced) {
                     Ok(v) => v,
                     Err(p) => {
-                        p.handled_by("not adding reference", self.problems);
+                        p.handled_by(Action::NotAddedReference, self.problems);
                         continue;
                     }
                 };
@@ -59,7 +89,7 @@ impl<'a> GeometriesParser<'a> {
                 None => {
                     Problem::Unexpected("geometry reference node never added".into())
                         .at(&d.referencing_node)
-                        .handled_by("not adding reference", self.problems);
+                        .handled_by(Action::NotAddedReference, self.problems);
                     continue;
                 }
             };
@@ -69,12 +99,39 @@ impl<'a> GeometriesParser<'a> {
             {
                 Problem::InvalidGeometryReference(err)
                     .at(&d.referencing_node)
-                    .handled_by("not adding reference", self.problems);
+                    .handled_by(Action::NotAddedReference, self.problems);
                 continue;
             };
         }
     }
 
+    /// If `referenced` is a GeometryReference, follow the chain of references
+    /// in `chain_targets` to the first geometry that is not a reference and
+    /// return its name.
+    ///
+    /// Returns None if `referenced` is not a reference or if the chain is
+    /// broken or circular.
+    fn end_of_reference_chain(
+        &self,
+        referenced: &Name,
+        chain_targets: &HashMap<Name, Name>,
+    ) -> Option<Name> {
+        let mut visited = HashSet::new();
+        let mut current = referenced;
+        while let Type::Reference { .. } = self
+            .geometries
+            .get_by_index(self.geometries.get_index(current)?)
+            .ok()?
+            .t
+        {
+            if !visited.insert(current) {
+                return None;
+            }
+            current = chain_targets.get(current)?;
+        }
+        (current != referenced).then(|| current.clone())
+    }
+
     fn get_index_of_referenced_geometry(
         &mut self,
         n: Node,
@@ -97,13 +154,19 @@ fn parse_reference_offsets(n: Node, name: &Name, problems: &mut Problems) -> Off
         .rev(); // start at last element, which provides the Overwrite offset if present
 
     if let Some(last_break) = nodes.next() {
-        offsets.overwrite = parse_break(last_break)
-            .ok_or_handled_by("ignoring node and setting overwrite to None", problems);
+        offsets.overwrite = parse_break(last_break).ok_or_handled_by(
+            Action::SetToNone {
+                field: Some("overwrite".into()),
+            },
+            problems,
+        );
     };
 
     for n in nodes {
-        let Some(Offset { dmx_break, offset }) = parse_break(n).ok_or_handled_by("ignoring node", problems) else {
-            continue
+        let Some(Offset { dmx_break, offset }) =
+            parse_break(n).ok_or_handled_by(Action::IgnoredNode, problems)
+        else {
+            continue;
         };
 
         if offsets.normal.contains_key(&dmx_break) {
@@ -112,7 +175,7 @@ fn parse_reference_offsets(n: Node, name: &Name, problems: &mut Problems) -> Off
                 geometry_reference: name.to_owned(),
             }
             .at(&n)
-            .handled_by("overwriting previous value", problems)
+            .handled_by(Action::OverwrotePreviousValue, problems)
         }
         offsets.normal.insert(dmx_break, offset);
     }
@@ -132,7 +195,9 @@ fn parse_break(n: Node) -> Result<Offset, ProblemAt> {
         dmx_break: n
             .parse_attribute("DMXBreak")
             .unwrap_or_else(|| Ok(Break::default()))?,
-        offset: n.parse_attribute("DMXOffset").unwrap_or(Ok(1))?,
+        offset: n
+            .parse_attribute("DMXOffset")
+            .unwrap_or_else(|| Ok(DmxAddress::default()))?,
     })
 }
 
@@ -149,7 +214,12 @@ mod tests {
     fn test_parse_break_node() {
         assert!(matches!(
             parse_break_node(r#"<Break DMXBreak="1" DMXOffset="1" />"#),
-            Ok(Offset{dmx_break, offset}) if dmx_break.value() == &1u16 && offset == 1
+            Ok(Offset{dmx_break, offset}) if dmx_break.value() == &1u16 && offset.absolute() == 1
+        ));
+
+        assert!(matches!(
+            parse_break_node(r#"<Break DMXBreak="1" DMXOffset="2.1" />"#),
+            Ok(Offset{offset, ..}) if offset.absolute() == 513
         ));
 
         assert!(matches!(
@@ -158,6 +228,14 @@ mod tests {
         ));
     }
 
+    fn normal_offset(offsets: &Offsets, dmx_break: u16) -> u32 {
+        offsets
+            .normal
+            .get(&dmx_break.try_into().unwrap())
+            .unwrap()
+            .absolute()
+    }
+
     fn run_parse_reference_offsets(xml: &str) -> (Problems, Offsets) {
         let doc = roxmltree::Document::parse(xml).unwrap();
         let n = doc.root_element();
@@ -184,13 +262,13 @@ mod tests {
             offsets.overwrite,
             Some(Offset {
                 dmx_break: 3.try_into().unwrap(),
-                offset: 4
+                offset: DmxAddress::try_from(4).unwrap()
             })
         );
         assert_eq!(offsets.normal.len(), 3);
-        assert_eq!(offsets.normal.get(&1.try_into().unwrap()).unwrap(), &6);
-        assert_eq!(offsets.normal.get(&2.try_into().unwrap()).unwrap(), &5);
-        assert_eq!(offsets.normal.get(&3.try_into().unwrap()).unwrap(), &4);
+        assert_eq!(normal_offset(&offsets, 1), 6);
+        assert_eq!(normal_offset(&offsets, 2), 5);
+        assert_eq!(normal_offset(&offsets, 3), 4);
     }
 
     #[test]
@@ -207,12 +285,12 @@ mod tests {
             offsets.overwrite,
             Some(Offset {
                 dmx_break: 3.try_into().unwrap(),
-                offset: 4
+                offset: DmxAddress::try_from(4).unwrap()
             })
         );
         assert_eq!(offsets.normal.len(), 2);
-        assert_eq!(offsets.normal.get(&1.try_into().unwrap()).unwrap(), &6);
-        assert_eq!(offsets.normal.get(&3.try_into().unwrap()).unwrap(), &4);
+        assert_eq!(normal_offset(&offsets, 1), 6);
+        assert_eq!(normal_offset(&offsets, 3), 4);
     }
 
     #[test]
@@ -229,12 +307,12 @@ mod tests {
             offsets.overwrite,
             Some(Offset {
                 dmx_break: 1.try_into().unwrap(),
-                offset: 4
+                offset: DmxAddress::try_from(4).unwrap()
             })
         );
         assert_eq!(offsets.normal.len(), 2);
-        assert_eq!(offsets.normal.get(&1.try_into().unwrap()).unwrap(), &6);
-        assert_eq!(offsets.normal.get(&2.try_into().unwrap()).unwrap(), &5);
+        assert_eq!(normal_offset(&offsets, 1), 6);
+        assert_eq!(normal_offset(&offsets, 2), 5);
     }
 
     #[test]
@@ -249,8 +327,8 @@ mod tests {
         assert_eq!(problems.len(), 1);
         assert_eq!(offsets.overwrite, None);
         assert_eq!(offsets.normal.len(), 2);
-        assert_eq!(offsets.normal.get(&1.try_into().unwrap()).unwrap(), &6);
-        assert_eq!(offsets.normal.get(&2.try_into().unwrap()).unwrap(), &5);
+        assert_eq!(normal_offset(&offsets, 1), 6);
+        assert_eq!(normal_offset(&offsets, 2), 5);
     }
 
     #[test]
@@ -271,7 +349,7 @@ mod tests {
                 ..
             }
         if duplicate_break == &Break::try_from(2).unwrap()));
-        assert_eq!(offsets.normal.get(&2.try_into().unwrap()).unwrap(), &2); // higher element takes precedence
+        assert_eq!(normal_offset(&offsets, 2), 2); // higher element takes precedence
     }
 
     #[test]
@@ -0,0 +1,279 @@
+use roxmltree::Node;
+
+use crate::{
+    macros::{FtMacro, FtPreset, MacroDmxStep, MacroDmxValue},
+    name::Name,
+    Action, ParsedGdtf, Problem, ProblemAt,
+};
+
+use super::{
+    dmx_value::parse_dmx,
+    extensions::parse_extensions,
+    parse_xml::GetXmlAttribute,
+    problems::{HandleProblem, ProblemsMut},
+};
+
+impl ParsedGdtf {
+    /// Parse the optional FTPresets node.
+    pub(crate) fn parse_ft_presets(&mut self, fixture_type: Node) {
+        let Some(presets) = fixture_type
+            .children()
+            .find(|n| n.has_tag_name("FTPresets"))
+        else {
+            return;
+        };
+
+        for n in presets.children().filter(|n| n.is_element()) {
+            if n.has_tag_name("FTPreset") {
+                let extensions = parse_extensions(n, &[], &[], self);
+                self.gdtf.ft_presets.push(FtPreset { extensions });
+            } else {
+                unexpected_node(n, self);
+            }
+        }
+    }
+
+    /// Parse the optional FTMacros node of a DMX mode.
+    ///
+    /// Must be called after the channels of the mode were added, because DMX
+    /// values of macros are scaled to the resolution of their channel, and
+    /// before channels are renamed, because macros refer to the GDTF names.
+    pub(crate) fn parse_ft_macros(
+        &mut self,
+        mode_node: Node,
+        mode_ind: usize,
+    ) -> Result<(), ProblemAt> {
+        let Some(macros) = mode_node.children().find(|n| n.has_tag_name("FTMacros")) else {
+            return Ok(());
+        };
+
+        for (i, n) in macros.children().filter(|n| n.is_element()).enumerate() {
+            if !n.has_tag_name("FTMacro") {
+                unexpected_node(n, self);
+                continue;
+            }
+            let ft_macro = self.parse_ft_macro(n, i, mode_ind);
+            self.gdtf
+                .dmx_mode_mut(mode_ind)
+                .map_err(|e| Problem::from(e).at(&n))?
+                .macros
+                .push(ft_macro);
+        }
+        Ok(())
+    }
+
+    fn parse_ft_macro(
+        &mut self,
+        n: Node,
+        node_index_in_xml_parent: usize,
+        mode_ind: usize,
+    ) -> FtMacro {
+        let name = n.name(node_index_in_xml_parent, self);
+        let channel_function = n
+            .attribute("ChannelFunction")
+            .filter(|s| !s.is_empty())
+            .map(str::to_owned);
+
+        let mut steps = vec![];
+        for macro_dmx in n.children().filter(|n| n.is_element()) {
+            if !macro_dmx.has_tag_name("MacroDMX") {
+                unexpected_node(macro_dmx, self);
+                continue;
+            }
+            for step in macro_dmx.children().filter(|n| n.is_element()) {
+                if !step.has_tag_name("MacroDMXStep") {
+                    unexpected_node(step, self);
+                    continue;
+                }
+                steps.push(self.parse_macro_dmx_step(step, mode_ind));
+            }
+        }
+
+        FtMacro {
+            name,
+            channel_function,
+            steps,
+        }
+    }
+
+    fn parse_macro_dmx_step(&mut self, step: Node, mode_ind: usize) -> MacroDmxStep {
+        let duration = step
+            .parse_float_attribute("Duration", self)
+            .transpose()
+            .ok_or_handled_by(Action::used_default(1), self)
+            .flatten()
+            .unwrap_or(1.);
+
+        let mut values = vec![];
+        for n in step.children().filter(|n| n.is_element()) {
+            if !n.has_tag_name("MacroDMXValue") {
+                unexpected_node(n, self);
+                continue;
+            }
+            if let Some(value) = self
+                .parse_macro_dmx_value(n, mode_ind)
+                .ok_or_handled_by(Action::IgnoredNode, self)
+            {
+                values.push(value);
+            }
+        }
+
+        MacroDmxStep { duration, values }
+    }
+
+    fn parse_macro_dmx_value(&self, n: Node, mode_ind: usize) -> Result<MacroDmxValue, ProblemAt> {
+        let mode = self
+            .gdtf
+            .dmx_mode(mode_ind)
+            .map_err(|e| Problem::from(e).at(&n))?;
+
+        let dmx_channel: Name = n.parse_required_attribute("DMXChannel")?;
+        let bytes = mode
+            .channels
+            .iter()
+            .chain(mode.subfixtures.iter().flat_map(|sf| sf.channels.iter()))
+            .find(|ch| ch.name == dmx_channel)
+            .ok_or_else(|| Problem::UnknownChannel(dmx_channel.clone(), mode.name.clone()).at(&n))?
+            .bytes;
+
+        let content = n.required_attribute("Value")?;
+        let value = parse_dmx(content, bytes).map_err(|e| {
+            Problem::InvalidAttribute {
+                attr: "Value".to_owned(),
+                tag: "MacroDMXValue".to_owned(),
+                content: content.to_owned(),
+                source: Box::new(e),
+                expected_type: "DMXValue".to_owned(),
+            }
+            .at(&n)
+        })?;
+
+        Ok(MacroDmxValue { value, dmx_channel })
+    }
+}
+
+fn unexpected_node(n: Node, problems: &mut impl ProblemsMut) {
+    Problem::UnexpectedXmlNode(n.tag_name().name().into())
+        .at(&n)
+        .handled_by(Action::IgnoredNode, problems);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{extensions::Extensions, geometry::Geometry, name::IntoValidName};
+
+    use super::*;
+
+    #[test]
+    fn parse_ft_presets() {
+        let input = r#"
+<FixtureType>
+    <FTPresets>
+        <FTPreset/>
+        <NotAPreset/>
+        <FTPreset Name="Warm">
+            <Value Dimmer="1"/>
+        </FTPreset>
+    </FTPresets>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let mut parsed = ParsedGdtf::default();
+        parsed.parse_ft_presets(doc.root_element());
+
+        assert_eq!(
+            parsed.gdtf.ft_presets,
+            vec![
+                FtPreset::default(),
+                FtPreset {
+                    extensions: Extensions {
+                        attributes: vec![("Name".into(), "Warm".into())],
+                        nodes: vec![r#"<Value Dimmer="1"/>"#.into()],
+                    }
+                }
+            ]
+        );
+        let mut problems = parsed.problems.iter().map(|p| p.problem());
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::UnexpectedXmlNode(tag) if tag == "NotAPreset"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::UnexpectedXmlNode(tag) if tag == "Value"
+        ));
+        assert!(problems.next().is_none());
+    }
+
+    #[test]
+    fn parse_ft_macros() {
+        let input = r#"
+<FixtureType>
+    <DMXModes>
+        <DMXMode Geometry="Body" Name="Mode 1">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Geometry="Body" Offset="1,2">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Attribute="Dimmer" DMXFrom="0/1" Default="0/1" Name="Dimmer"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+            <FTMacros>
+                <FTMacro Name="Flash" ChannelFunction="Body_Dimmer.Dimmer.Dimmer">
+                    <MacroDMX>
+                        <MacroDMXStep Duration="0.5">
+                            <MacroDMXValue Value="255/1" DMXChannel="Body_Dimmer"/>
+                        </MacroDMXStep>
+                        <MacroDMXStep>
+                            <MacroDMXValue Value="0/1" DMXChannel="Body_Dimmer"/>
+                            <MacroDMXValue Value="0/1" DMXChannel="Body_Shutter1"/>
+                        </MacroDMXStep>
+                    </MacroDMX>
+                </FTMacro>
+            </FTMacros>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let mut parsed = ParsedGdtf::default();
+        parsed
+            .gdtf
+            .geometries
+            .add_top_level(Geometry {
+                name: "Body".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        parsed.parse_dmx_modes(doc.root_element(), &Default::default(), &Default::default());
+
+        assert_eq!(parsed.problems.len(), 1);
+        assert!(matches!(
+            parsed.problems.first().unwrap().problem(),
+            Problem::UnknownChannel(ch, _) if ch == "Body_Shutter1"
+        ));
+
+        let mode = parsed.gdtf.dmx_modes().first().unwrap();
+        assert_eq!(
+            mode.macros,
+            vec![FtMacro {
+                name: "Flash".into_valid(),
+                channel_function: Some("Body_Dimmer.Dimmer.Dimmer".into()),
+                steps: vec![
+                    MacroDmxStep {
+                        duration: 0.5,
+                        values: vec![MacroDmxValue {
+                            value: 65535,
+                            dmx_channel: "Body_Dimmer".into_valid()
+                        }]
+                    },
+                    MacroDmxStep {
+                        duration: 1.,
+                        values: vec![MacroDmxValue {
+                            value: 0,
+                            dmx_channel: "Body_Dimmer".into_valid()
+                        }]
+                    },
+                ]
+            }]
+        );
+    }
+}
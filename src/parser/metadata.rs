@@ -0,0 +1,254 @@
+use std::{
+    borrow::Cow,
+    io::{Read, Seek},
+};
+
+use xmlparser::{ElementEnd, Token, Tokenizer};
+
+use crate::{
+    archive::GdtfArchive,
+    fixture_meta::{FixtureMeta, ModeMeta},
+    name::{IntoValidName, Name},
+};
+
+use super::errors::{DescriptionError, Error};
+
+/// Read the summary of a GDTF file without building the full model, for
+/// indexing fixture libraries.
+///
+/// Only the attributes of the FixtureType and of the DMX modes are read,
+/// geometries and channels are skipped. Therefore, [`ModeMeta::footprints`]
+/// are empty and mode geometries are not checked to exist. Invalid or missing
+/// values are replaced by defaults without reporting problems, use
+/// [`parse`](crate::parse) for a full validation.
+pub fn parse_gdtf_metadata<T: Read + Seek>(reader: T) -> Result<FixtureMeta, Error> {
+    let description = GdtfArchive::new(reader)?.description()?;
+    parse_description_metadata(&description)
+}
+
+/// Attributes of a start tag, with their values unescaped
+type Attributes<'a> = Vec<(&'a str, Cow<'a, str>)>;
+
+/// Tokenizes the description without building a tree and stops at the end of
+/// the DMXModes node, so the XML after it is neither read nor checked.
+pub(crate) fn parse_description_metadata(description: &str) -> Result<FixtureMeta, Error> {
+    // open elements below the root, which must be GDTF
+    let mut path: Vec<&str> = vec![];
+    let mut has_root = false;
+    // the start tag that is being read
+    let mut element = "";
+    let mut attributes: Attributes = vec![];
+    let mut fixture_type: Option<Attributes> = None;
+    let mut modes: Vec<ModeMeta> = vec![];
+
+    for token in Tokenizer::from(description) {
+        match token.map_err(DescriptionError::InvalidXmlSyntax)? {
+            Token::ElementStart { local, .. } => {
+                element = local.as_str();
+                attributes.clear();
+            }
+            Token::Attribute { local, value, .. } => {
+                attributes.push((local.as_str(), attribute_value(value.as_str())));
+            }
+            Token::ElementEnd {
+                end: end @ (ElementEnd::Open | ElementEnd::Empty),
+                ..
+            } => {
+                let is_root = !has_root;
+                match (is_root, path.as_slice(), element) {
+                    (true, _, "GDTF") => has_root = true,
+                    (true, _, _) => break,
+                    (false, [], "FixtureType") if fixture_type.is_none() => {
+                        fixture_type = Some(std::mem::take(&mut attributes));
+                    }
+                    (false, ["FixtureType", "DMXModes"], "DMXMode") => {
+                        let attribute = |attr| find_attribute(&attributes, attr);
+                        modes.push(ModeMeta {
+                            name: attribute("Name").map_or_else(
+                                || Name::valid_default("DMXMode", modes.len()),
+                                IntoValidName::into_valid,
+                            ),
+                            geometry: attribute("Geometry").map(IntoValidName::into_valid),
+                            footprints: vec![],
+                        });
+                    }
+                    _ => {}
+                }
+                if !is_root && matches!(end, ElementEnd::Open) {
+                    path.push(element);
+                }
+            }
+            Token::ElementEnd {
+                end: ElementEnd::Close(_, local),
+                ..
+            } => {
+                path.pop();
+                // everything of interest is in the first FixtureType
+                match (path.as_slice(), local.as_str()) {
+                    (["FixtureType"], "DMXModes") | ([], "FixtureType") => break,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    if !has_root {
+        return Err(DescriptionError::NoRootNode.into());
+    }
+
+    let fixture_type = fixture_type.unwrap_or_default();
+    let attribute = |attr| find_attribute(&fixture_type, attr).unwrap_or_default();
+    Ok(FixtureMeta {
+        name: attribute("Name").into_valid(),
+        manufacturer: attribute("Manufacturer").to_owned(),
+        fixture_type_id: attribute("FixtureTypeID").parse().unwrap_or_default(),
+        modes,
+        has_thumbnail: !attribute("Thumbnail").is_empty(),
+    })
+}
+
+fn find_attribute<'a>(attributes: &'a Attributes, attr: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(name, _)| *name == attr)
+        .map(|(_, value)| value.as_ref())
+}
+
+/// The value of an attribute as roxmltree reads it: entity and character
+/// references are resolved and whitespace characters become spaces
+fn attribute_value(raw: &str) -> Cow<'_, str> {
+    const SPECIAL: [char; 4] = ['&', '\t', '\n', '\r'];
+    if !raw.contains(SPECIAL) {
+        return Cow::Borrowed(raw);
+    }
+    let mut value = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(i) = rest.find(SPECIAL) {
+        let (before, special) = rest.split_at(i);
+        value.push_str(before);
+        if let Some(reference) = special.strip_prefix('&') {
+            let resolved = reference
+                .split_once(';')
+                .and_then(|(name, after)| Some((resolve_reference(name)?, after)));
+            match resolved {
+                Some((c, after)) => {
+                    value.push(c);
+                    rest = after;
+                }
+                None => {
+                    value.push('&');
+                    rest = reference;
+                }
+            }
+        } else {
+            // a line break is normalized to a single space
+            value.push(' ');
+            rest = special
+                .strip_prefix("\r\n")
+                .unwrap_or_else(|| special.get(1..).unwrap_or_default());
+        }
+    }
+    value.push_str(rest);
+    Cow::Owned(value)
+}
+
+/// The character of a reference like `amp` or `#x26` between `&` and `;`
+fn resolve_reference(name: &str) -> Option<char> {
+    let code = match name {
+        "amp" => return Some('&'),
+        "lt" => return Some('<'),
+        "gt" => return Some('>'),
+        "quot" => return Some('"'),
+        "apos" => return Some('\''),
+        _ => match name.strip_prefix("#x") {
+            Some(hex) => u32::from_str_radix(hex, 16),
+            None => name.strip_prefix('#')?.parse(),
+        },
+    };
+    code.ok().and_then(char::from_u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn metadata_without_full_parse() {
+        let meta = parse_description_metadata(
+            r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Spot" Manufacturer="Vendor" FixtureTypeID="00000000-0000-0000-0000-000000000001" Thumbnail="spot">
+    <Geometries>
+        <Geometry Name="Body"/>
+    </Geometries>
+    <DMXModes>
+        <DMXMode Name="Standard" Geometry="Body"/>
+        <DMXMode Geometry="Missing"/>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#,
+        )
+        .unwrap();
+
+        assert_eq!(meta.name, "Spot");
+        assert_eq!(meta.manufacturer, "Vendor");
+        assert_eq!(meta.fixture_type_id, Uuid::from_u128(1));
+        assert!(meta.has_thumbnail);
+        let modes: Vec<_> = meta
+            .modes
+            .iter()
+            .map(|m| {
+                (
+                    m.name.to_string(),
+                    m.geometry.as_ref().map(|g| g.to_string()),
+                )
+            })
+            .collect();
+        assert_eq!(
+            modes,
+            [
+                ("Standard".into(), Some("Body".into())),
+                ("DMXMode 2".into(), Some("Missing".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn metadata_reading_stops_after_dmx_modes() {
+        // the rest of the description is neither needed nor checked
+        let meta = parse_description_metadata(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<GDTF DataVersion="1.2">
+<FixtureType Name="Spot &amp; Wash" Manufacturer="A &#x26; B&#10;C" Thumbnail="">
+    <!-- <DMXMode Name="Comment"/> -->
+    <DMXModes>
+        <DMXMode Name="Mode &lt;1&gt;" Geometry="Body">
+            <DMXChannels>
+                <DMXMode Name="Nested"/>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+    <Protocols><this is not XML"#,
+        )
+        .unwrap();
+
+        assert_eq!(meta.name, "Spot □ Wash");
+        assert_eq!(meta.manufacturer, "A & B\nC");
+        assert!(!meta.has_thumbnail);
+        let modes: Vec<_> = meta.modes.iter().map(|m| m.name.to_string()).collect();
+        assert_eq!(modes, ["Mode <1>"]);
+    }
+
+    #[test]
+    fn metadata_of_invalid_description() {
+        assert!(matches!(
+            parse_description_metadata("<NotGDTF/>"),
+            Err(Error::Description(DescriptionError::NoRootNode))
+        ));
+        assert!(matches!(
+            parse_description_metadata("<GDTF><FixtureType Name=Spot/></GDTF>"),
+            Err(Error::Description(DescriptionError::InvalidXmlSyntax(_)))
+        ));
+    }
+}
@@ -1,27 +1,55 @@
 #![allow(clippy::result_large_err)]
 // TODO fix warning later, it is only a memory usage problem, due to an enum
 // variant in `ProblemType` with many fields
+mod compatibility;
 mod dmx_value;
 mod errors;
+mod extensions;
 mod geometries;
+mod macros;
+mod metadata;
+mod models;
 mod modes;
+mod options;
 mod parse_xml;
+mod physical_value;
 mod problems;
+mod protocols;
+mod recovery;
+mod relations;
+mod wheels;
 mod yes_no;
 
 use std::io::{Read, Seek};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    fs::File,
+    path::PathBuf,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
 
 use roxmltree::Node;
 use uuid::Uuid;
 
-use crate::Gdtf;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::name::NameInterner;
+use crate::{
+    archive::{ArchiveEntry, GdtfArchive},
+    severity::Severity,
+    Gdtf,
+};
 
 pub use self::{
-    errors::Error,
-    problems::{HandledProblem, Problem, ProblemAt, Problems},
+    errors::{ArchiveError, DescriptionError, Error},
+    metadata::parse_gdtf_metadata,
+    options::{ChannelNaming, DefaultFallback, ParseOptions, SubfixtureNaming},
+    problems::{Action, HandledProblem, Problem, ProblemAt, Problems},
+    recovery::{DefaultRecovery, DuplicateNameRecovery, Recovery, RecoveryPolicy},
 };
 
 use self::{
+    extensions::{parse_extensions, parse_unparsed_sections},
     geometries::GeometriesParser,
     parse_xml::{get_xml_attribute::GetXmlAttribute, AssignOrHandle, GetXmlNode},
     problems::ProblemsMut,
@@ -32,50 +60,130 @@ use self::{
 pub struct ParsedGdtf {
     pub gdtf: Gdtf,
     pub problems: Problems,
+    /// Files in the GDTF archive, empty if not parsed from an archive
+    pub archive_entries: Vec<ArchiveEntry>,
 }
 
 pub fn parse<T: Read + Seek>(reader: T) -> Result<ParsedGdtf, Error> {
-    let mut zip = zip::ZipArchive::new(reader)?;
-    let mut description_file = zip
-        .by_name("description.xml")
-        .map_err(Error::DescriptionXmlMissing)?;
-
-    let size: usize = description_file.size().try_into().unwrap_or(0);
-    let mut description = String::with_capacity(size);
+    parse_with_options(reader, &ParseOptions::default())
+}
 
-    description_file
-        .read_to_string(&mut description)
-        .map_err(Error::InvalidDescriptionXml)?;
+pub fn parse_with_options<T: Read + Seek>(
+    reader: T,
+    options: &ParseOptions,
+) -> Result<ParsedGdtf, Error> {
+    GdtfArchive::new(reader)?.parse(options)
+}
 
-    parse_description(description)
+/// Parse many GDTF files in parallel, on at most as many threads as the
+/// system has available parallelism.
+///
+/// Results are yielded as soon as they are available, so not necessarily in
+/// the order of `paths`. Each result comes with the path it belongs to. If the
+/// iterator is dropped early, the remaining files are not parsed.
+///
+/// Names are interned across all files, so e.g. attribute and geometry names
+/// that many of them have in common share one allocation.
+///
+/// Not available on WebAssembly, which has neither a file system nor threads.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn parse_many(
+    paths: impl IntoIterator<Item = impl Into<PathBuf>>,
+    options: &ParseOptions,
+) -> impl Iterator<Item = (PathBuf, Result<ParsedGdtf, Error>)> {
+    let paths: Vec<PathBuf> = paths.into_iter().map(Into::into).collect();
+    let threads = thread::available_parallelism()
+        .map(usize::from)
+        .unwrap_or(1)
+        .min(paths.len());
+    let queue = Arc::new(Mutex::new(paths.into_iter()));
+    let interner = Arc::new(NameInterner::new());
+    let (sender, receiver) = mpsc::channel();
+    for _ in 0..threads {
+        let (queue, sender, options) = (queue.clone(), sender.clone(), options.clone());
+        let interner = interner.clone();
+        thread::spawn(move || {
+            interner.scope(|| loop {
+                let next = match queue.lock() {
+                    Ok(mut queue) => queue.next(),
+                    Err(_) => None, // another worker panicked
+                };
+                let Some(path) = next else {
+                    break;
+                };
+                let parsed = File::open(&path)
+                    .map_err(|e| Error::from(ArchiveError::FileOpen(e)))
+                    .and_then(|file| parse_with_options(file, &options));
+                if sender.send((path, parsed)).is_err() {
+                    break; // receiver dropped
+                }
+            })
+        });
+    }
+    receiver.into_iter()
 }
 
-fn parse_description(description: String) -> Result<ParsedGdtf, Error> {
-    let doc = roxmltree::Document::parse(&description)?;
+pub(crate) fn parse_description(
+    description: String,
+    options: &ParseOptions,
+) -> Result<ParsedGdtf, Error> {
+    let doc = roxmltree::Document::parse(&description).map_err(DescriptionError::InvalidXml)?;
     let gdtf = doc
         .descendants()
         .find(|n| n.has_tag_name("GDTF"))
-        .ok_or(Error::NoRootNode)?;
+        .ok_or(DescriptionError::NoRootNode)?;
 
     let mut parsed = ParsedGdtf::default();
-    parsed.parse(gdtf);
+    parsed.parse(gdtf, options);
+
+    let policy = options.policy();
+    if let Some(i) = parsed
+        .problems
+        .iter()
+        .position(|p| policy.recover(p) == Recovery::Abort)
+    {
+        return Err(Error::Aborted(Box::new(parsed.problems.swap_remove(i))));
+    }
 
     Ok(parsed)
 }
 
 impl ParsedGdtf {
-    fn parse(&mut self, gdtf: Node) {
+    /// Number of problems with [`Severity::Error`], see [`Action::severity`]
+    pub fn error_count(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    /// Number of problems with [`Severity::Warning`], see [`Action::severity`]
+    pub fn warning_count(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    /// Whether the file was parsed without any problems, neither errors nor
+    /// warnings
+    pub fn is_clean(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.problems
+            .iter()
+            .filter(|p| p.severity() == severity)
+            .count()
+    }
+
+    fn parse(&mut self, gdtf: Node, options: &ParseOptions) {
         gdtf.parse_required_attribute("DataVersion")
             .assign_or_handle(&mut self.gdtf.data_version, &mut self.problems);
 
-        self.parse_fixture_type(gdtf);
+        self.parse_fixture_type(gdtf, options);
     }
 
-    fn parse_fixture_type(&mut self, gdtf: Node) {
+    fn parse_fixture_type(&mut self, gdtf: Node, options: &ParseOptions) {
         let fixture_type = match gdtf.find_required_child("FixtureType") {
             Ok(g) => g,
             Err(p) => {
-                p.handled_by("returning empty fixture type", self);
+                p.handled_by(Action::left_empty("fixture type"), self);
                 return;
             }
         };
@@ -99,16 +207,59 @@ impl ParsedGdtf {
             .parse_required_attribute("Manufacturer")
             .assign_or_handle(&mut self.gdtf.manufacturer, &mut self.problems);
 
+        self.gdtf.thumbnail = fixture_type
+            .attribute("Thumbnail")
+            .filter(|s| !s.is_empty())
+            .map(str::to_owned);
+        self.gdtf.extensions = parse_extensions(
+            fixture_type,
+            &[
+                "Name",
+                "ShortName",
+                "LongName",
+                "Manufacturer",
+                "Description",
+                "FixtureTypeID",
+                "Thumbnail",
+                "RefFT",
+                "CanHaveChildren",
+            ],
+            &[
+                "AttributeDefinitions",
+                "Wheels",
+                "PhysicalDescriptions",
+                "Models",
+                "Geometries",
+                "DMXModes",
+                "Revisions",
+                "FTPresets",
+                "Protocols",
+            ],
+            self,
+        );
+        self.gdtf.unparsed = parse_unparsed_sections(fixture_type);
+
         self.parse_ref_ft(fixture_type);
         self.parse_can_have_children(fixture_type);
 
-        GeometriesParser::new(&mut self.gdtf.geometries, &mut self.problems)
-            .parse_from(&fixture_type);
+        self.parse_wheels(fixture_type);
+        self.parse_models(fixture_type);
+
+        let geometry_lookup = GeometriesParser::new(
+            &mut self.gdtf.geometries,
+            &self.gdtf.models,
+            options,
+            &mut self.problems,
+        )
+        .parse_from(&fixture_type);
 
         // TODO parse Attributes (needed for nice display of values in DMXChannel)
         // TODO then test Attribute linking in DMXChannel's
 
-        self.parse_dmx_modes(fixture_type);
+        self.parse_dmx_modes(fixture_type, &geometry_lookup, options);
+
+        self.parse_ft_presets(fixture_type);
+        self.parse_protocols(fixture_type);
     }
 
     /// Parse RefFT attribute
@@ -124,7 +275,12 @@ impl ParsedGdtf {
         {
             Some(Ok(v)) => Some(v),
             Some(Err(p)) => {
-                p.handled_by("setting ref_ft to None", self);
+                p.handled_by(
+                    Action::SetToNone {
+                        field: Some("ref_ft".into()),
+                    },
+                    self,
+                );
                 None
             }
             None => None,
@@ -152,18 +308,88 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn problems_are_counted_by_severity() {
+        let mut parsed = ParsedGdtf::default();
+        assert!(parsed.is_clean());
+
+        let doc = Document::parse("<DMXMode/>").unwrap();
+        let node = doc.root_element();
+        Problem::UnexpectedXmlNode("DMXMode".into())
+            .at(&node)
+            .handled_by(Action::used_default("DMXMode 1"), &mut parsed.problems);
+        assert_eq!((parsed.error_count(), parsed.warning_count()), (0, 1));
+        assert!(!parsed.is_clean());
+
+        for _ in 0..2 {
+            Problem::UnexpectedXmlNode("DMXMode".into())
+                .at(&node)
+                .handled_by(Action::IgnoredNode, &mut parsed.problems);
+        }
+        assert_eq!((parsed.error_count(), parsed.warning_count()), (2, 1));
+    }
+
+    #[test]
+    fn parse_many_files() {
+        let dir = "tests/example_files/examples/channel_layout_test";
+        let paths = [
+            "Test@Channel_Layout_Test@v1_first_try.gdtf",
+            "Test@Channel_Layout_Test@v1_first_try.empty.gdtf",
+            "missing.gdtf",
+        ]
+        .map(|file| format!("{dir}/{file}"));
+
+        let mut results: Vec<_> = parse_many(paths, &Default::default()).collect();
+        results.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut results = results.into_iter().map(|(_, parsed)| parsed);
+        assert!(matches!(
+            results.next().unwrap(),
+            Err(Error::Archive(ArchiveError::DescriptionXmlMissing(_)))
+        ));
+        assert_eq!(
+            results.next().unwrap().unwrap().gdtf.name,
+            "Channel Layout Test"
+        );
+        assert!(matches!(
+            results.next().unwrap(),
+            Err(Error::Archive(ArchiveError::FileOpen(_)))
+        ));
+        assert!(results.next().is_none());
+    }
+
+    #[test]
+    fn parse_many_shares_names_between_files() {
+        let path =
+            "tests/example_files/examples/channel_layout_test/Test@Channel_Layout_Test@v1_first_try.gdtf";
+        let names: Vec<_> = parse_many([path, path], &Default::default())
+            .map(|(_, parsed)| parsed.unwrap().gdtf.name)
+            .collect();
+        let [a, b] = names.as_slice() else {
+            panic!("expected two results");
+        };
+        assert_eq!(a, b);
+        assert_eq!(a.as_str().as_ptr(), b.as_str().as_ptr());
+    }
+
     #[test]
     fn xml_error() {
         let invalid_xml = "<this></that>".to_string();
-        let res = parse_description(invalid_xml);
-        assert!(matches!(res, Err(Error::InvalidXml(..))));
+        let res = parse_description(invalid_xml, &Default::default());
+        assert!(matches!(
+            res,
+            Err(Error::Description(DescriptionError::InvalidXml(..)))
+        ));
     }
 
     #[test]
     fn no_root_node_error() {
         let invalid_xml = "<this></this>".to_string();
-        let res = parse_description(invalid_xml);
-        assert!(matches!(res, Err(Error::NoRootNode)));
+        let res = parse_description(invalid_xml, &Default::default());
+        assert!(matches!(
+            res,
+            Err(Error::Description(DescriptionError::NoRootNode))
+        ));
     }
 
     #[test]
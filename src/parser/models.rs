@@ -0,0 +1,150 @@
+use roxmltree::Node;
+
+use crate::{
+    models::{Model, ModelsError, PrimitiveType},
+    Action, ParsedGdtf, Problem,
+};
+
+use super::{
+    extensions::parse_extensions,
+    parse_xml::{GetXmlAttribute, GetXmlNode},
+    problems::HandleProblem,
+};
+
+impl ParsedGdtf {
+    pub(crate) fn parse_models(&mut self, fixture_type: Node) {
+        let models = match fixture_type.find_required_child("Models") {
+            Ok(v) => v,
+            Err(p) => {
+                p.handled_by(Action::left_empty("models"), self);
+                return;
+            }
+        };
+
+        for (i, n) in models.children().filter(|n| n.is_element()).enumerate() {
+            if !n.has_tag_name("Model") {
+                Problem::UnexpectedXmlNode(n.tag_name().name().into())
+                    .at(&n)
+                    .handled_by(Action::IgnoredNode, self);
+                continue;
+            }
+
+            let model = self.parse_model(n, i);
+            if let Err(ModelsError::NameAlreadyTaken(name)) = self.gdtf.models.add(model) {
+                Problem::DuplicateModelName(name)
+                    .at(&n)
+                    .handled_by(Action::IgnoredNode, self);
+            }
+        }
+    }
+
+    fn parse_model(&mut self, n: Node, node_index_in_xml_parent: usize) -> Model {
+        let name = n.name(node_index_in_xml_parent, self);
+        let [length, width, height] = ["Length", "Width", "Height"].map(|attr| {
+            n.parse_float_attribute(attr, self)
+                .transpose()
+                .ok_or_handled_by(Action::used_default(0), self)
+                .flatten()
+                .unwrap_or(0.)
+        });
+        let primitive_type = n
+            .parse_attribute("PrimitiveType")
+            .transpose()
+            .ok_or_handled_by(Action::used_default(PrimitiveType::Undefined), self)
+            .flatten()
+            .unwrap_or(PrimitiveType::Undefined);
+        let file = n
+            .attribute("File")
+            .filter(|s| !s.is_empty())
+            .map(str::to_owned);
+        let extensions = parse_extensions(
+            n,
+            &["Name", "Length", "Width", "Height", "PrimitiveType", "File"],
+            &[],
+            self,
+        );
+
+        Model {
+            name,
+            length,
+            width,
+            height,
+            primitive_type,
+            file,
+            extensions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{extensions::Extensions, name::IntoValidName};
+
+    use super::*;
+
+    #[test]
+    fn parse_models() {
+        let input = r#"
+<FixtureType>
+    <Models>
+        <Model File="base" Height="0.200000" Length="0.400000" Name="Base" PrimitiveType="Base" Width="0.300000" VendorExtension="42"/>
+        <Model File="" Height="0.100000" Length="0.100000" Name="Head" PrimitiveType="Head" Width="0.100000"/>
+        <Model Name="Base" PrimitiveType="Cube"/>
+        <Model Height="invalid" PrimitiveType="NotAPrimitive"/>
+        <NotAModel Name="Yoke"/>
+    </Models>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let mut parsed = ParsedGdtf::default();
+        parsed.parse_models(doc.root_element());
+
+        let mut problems = parsed.problems.iter().map(|p| p.problem());
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::DuplicateModelName(name) if name == "Base"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::XmlAttributeMissing { attr, .. } if attr == "Name"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::InvalidAttribute { attr, .. } if attr == "Height"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::InvalidAttribute { attr, .. } if attr == "PrimitiveType"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::UnexpectedXmlNode(tag) if tag == "NotAModel"
+        ));
+        assert!(problems.next().is_none());
+
+        let models = &parsed.gdtf.models;
+        assert_eq!(models.len(), 3);
+        assert_eq!(
+            models.get_by_name(&"Base".into_valid()).unwrap(),
+            &Model {
+                name: "Base".into_valid(),
+                length: 0.4,
+                width: 0.3,
+                height: 0.2,
+                primitive_type: PrimitiveType::Base,
+                file: Some("base".into()),
+                extensions: Extensions {
+                    attributes: [("VendorExtension".into(), "42".into())].into(),
+                    nodes: vec![],
+                },
+            }
+        );
+        assert_eq!(models.get_by_name(&"Head".into_valid()).unwrap().file, None);
+        assert_eq!(
+            models.get_by_name(&"Model 4".into_valid()).unwrap(),
+            &Model {
+                name: "Model 4".into_valid(),
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     cmp::{max, min},
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
 };
 
 use itertools::Itertools;
@@ -8,20 +8,29 @@ use petgraph::graph::NodeIndex;
 use roxmltree::Node;
 use thiserror::Error;
 
-use crate::channel::Channel;
+use crate::channel::{Channel, DefaultSource};
 use crate::{
     channel_offsets::ChannelOffsets,
     dmx_break::Break,
-    dmx_modes::{ChannelFunction, DmxMode, ModeMaster, Subfixture},
+    dmx_modes::{ChannelFunction, ChannelSet, DmxMode, ModeMaster, Subfixture, SubfixtureId},
     geometries::Geometries,
     geometry::{Geometry, Type},
     name::{IntoValidName, Name},
-    ParsedGdtf, Problem, ProblemAt, Problems,
+    physical_value::PhysicalValue,
+    Action, ChannelNaming, DefaultFallback, ParseOptions, ParsedGdtf, Problem, ProblemAt, Problems,
+    SubfixtureNaming,
 };
 
 use super::{
+    compatibility::DMX_CHANNEL_DEFAULT,
     dmx_value::{bytes_max_value, parse_dmx},
-    parse_xml::{get_xml_attribute::parse_attribute_content, GetXmlAttribute, GetXmlNode},
+    extensions::parse_unknown_attributes,
+    geometries::GeometryLookup,
+    parse_xml::{
+        get_xml_attribute::{parse_attribute_content, parse_name_or_fix},
+        GetXmlAttribute, GetXmlNode,
+    },
+    physical_value::parse_physical_value,
     problems::{HandleOption, HandleProblem, ProblemsMut, TransformUnexpected},
 };
 
@@ -32,11 +41,16 @@ use super::{
 // - split into maybe 2-3 files?
 
 impl ParsedGdtf {
-    pub(crate) fn parse_dmx_modes(&mut self, fixture_type: Node) {
+    pub(crate) fn parse_dmx_modes(
+        &mut self,
+        fixture_type: Node,
+        geometry_lookup: &GeometryLookup,
+        options: &ParseOptions,
+    ) {
         let modes = match fixture_type.find_required_child("DMXModes") {
             Ok(v) => v,
             Err(p) => {
-                p.handled_by("leaving DMX modes empty", self);
+                p.handled_by(Action::left_empty("DMX modes"), self);
                 return;
             }
         };
@@ -46,18 +60,26 @@ impl ParsedGdtf {
             .filter(|n| n.is_element() && n.tag_name().name() == "DMXMode")
             .enumerate()
         {
-            DmxModeParser::parse(mode, i, self).ok_or_handled_by("ignoring DMX Mode", self);
+            DmxModeParser::parse(mode, i, self, geometry_lookup, options)
+                .ok_or_handled_by(Action::ignored("DMX Mode"), self);
         }
     }
 }
 
 struct DmxModeParser<'a> {
     parsed: &'a mut ParsedGdtf,
+    geometry_lookup: &'a GeometryLookup,
     mode_master_queue: Vec<DeferredModeMaster<'a>>,
     template_channels: TemplateChannels,
+    /// breaks of each GeometryReference that template channels were
+    /// instantiated with
+    used_reference_breaks: BTreeMap<NodeIndex, HashSet<ChannelBreak>>,
     mode_ind: usize,
     mode_node: Node<'a, 'a>,
     mode_name: Name,
+    channel_naming: ChannelNaming,
+    subfixture_naming: SubfixtureNaming,
+    default_fallback: DefaultFallback,
 }
 
 impl<'a> ProblemsMut for DmxModeParser<'a> {
@@ -85,73 +107,273 @@ impl<'a> DmxModeParser<'a> {
             .map_err(|e| Problem::from(e).at(&self.mode_node))
     }
 
-    fn parse(mode_node: Node, i: usize, parsed: &'a mut ParsedGdtf) -> Result<(), ProblemAt> {
+    fn parse(
+        mode_node: Node,
+        i: usize,
+        parsed: &'a mut ParsedGdtf,
+        geometry_lookup: &'a GeometryLookup,
+        options: &ParseOptions,
+    ) -> Result<(), ProblemAt> {
         let name = mode_node.name(i, parsed);
         let description = mode_node.attribute("Description").unwrap_or("").to_owned();
 
-        let mode_geometry_name = mode_node.parse_required_attribute("Geometry")?;
-        let geometry = parsed
-            .gdtf
-            .geometries
-            .get_index(&mode_geometry_name)
-            .ok_or_else(|| {
-                Problem::UnknownGeometry(mode_geometry_name.to_owned()).at(&mode_node)
-            })?;
+        let geometry = match mode_node
+            .parse_required_attribute::<Name>("Geometry")
+            .and_then(|mode_geometry_name| {
+                parsed
+                    .gdtf
+                    .geometries
+                    .get_index(&mode_geometry_name)
+                    .ok_or_else(|| Problem::UnknownGeometry(mode_geometry_name).at(&mode_node))
+            }) {
+            Ok(geometry) => geometry,
+            Err(p) => {
+                // Several files on GDTF Share have modes with missing or
+                // misspelled geometries. Falling back keeps the channels.
+                let fallback = match parsed.gdtf.geometries.top_level_indices().next() {
+                    Some(fallback) => fallback,
+                    None => return Err(p),
+                };
+                let fallback_name = parsed
+                    .gdtf
+                    .geometries
+                    .get_by_index(fallback)
+                    .unexpected_err_at(&mode_node)?
+                    .name
+                    .clone();
+                p.handled_by(
+                    Action::UsedFirstTopLevelGeometry {
+                        name: fallback_name,
+                    },
+                    parsed,
+                );
+                fallback
+            }
+        };
 
         let mode_ind = parsed
             .gdtf
             .add_dmx_mode(name.clone(), description, geometry)
             .map_err(|e| Problem::from(e).at(&mode_node))?;
+        let mode = parsed
+            .gdtf
+            .dmx_mode_mut(mode_ind)
+            .map_err(|e| Problem::from(e).at(&mode_node))?;
+        mode.source = Some(mode_node.source_location());
+        mode.extensions = parse_unknown_attributes(mode_node, &["Name", "Description", "Geometry"]);
+        mode.set_naming(options.channel_naming, options.subfixture_naming);
 
         let mut parser = DmxModeParser {
             parsed,
+            geometry_lookup,
             mode_master_queue: Default::default(),
             template_channels: Default::default(),
+            used_reference_breaks: Default::default(),
             mode_ind,
             mode_node,
             mode_name: name,
+            channel_naming: options.channel_naming,
+            subfixture_naming: options.subfixture_naming,
+            default_fallback: options.default_fallback,
         };
 
         mode_node
             .find_required_child("DMXChannels")
             .map(|n| parser.parse_dmx_channels(n))
-            .ok_or_handled_by("leaving DMX mode empty", &mut parser);
+            .ok_or_handled_by(Action::left_empty("DMX mode"), &mut parser);
+
+        parser
+            .parsed
+            .parse_relations(mode_node, mode_ind)
+            .ok_or_handled_by(Action::ignored("remaining relations"), &mut parser);
+
+        parser
+            .parsed
+            .parse_ft_macros(mode_node, mode_ind)
+            .ok_or_handled_by(Action::ignored("remaining macros"), &mut parser);
+
+        parser
+            .apply_channel_naming()
+            .ok_or_handled_by(Action::used("GDTF channel names"), &mut parser);
+        parser
+            .apply_subfixture_naming()
+            .ok_or_handled_by(Action::used("GeometryReference names"), &mut parser);
+
+        parser
+            .check_overlapping_offsets()
+            .ok_or_handled_by(Action::ignored("overlap check"), &mut parser);
+        parser.check_unused_reference_breaks();
+
+        let mode = parser.mode_mut()?;
+        if options.keep_template_channels {
+            mode.keep_templates_uninstantiated();
+        }
+        mode.index_channel_functions();
+        Ok(())
+    }
+
+    /// Report DMX address offsets used by multiple channels. These are kept,
+    /// because dropping either channel would lose information.
+    fn check_overlapping_offsets(&mut self) -> Result<(), ProblemAt> {
+        let mode = self.mode()?;
+        let problems: Vec<ProblemAt> = mode
+            .overlapping_offsets()
+            .into_iter()
+            .map(|overlap| {
+                Problem::OverlappingChannelOffsets {
+                    mode: mode.name.clone(),
+                    dmx_break: overlap.dmx_break,
+                    offset: overlap.offset,
+                    first: overlap.first.name.clone(),
+                    second: overlap.second.name.clone(),
+                }
+                .at(&self.mode_node)
+            })
+            .collect();
+        for p in problems {
+            p.handled_by(Action::KeptAsIs, self);
+        }
         Ok(())
     }
 
+    /// Report Break entries of GeometryReferences that no template channel of
+    /// the mode uses. Missing entries are reported during instantiation.
+    /// References without any instantiated channel are not used by this
+    /// mode and are not checked.
+    fn check_unused_reference_breaks(&mut self) {
+        let mut problems = vec![];
+        for (reference, used) in &self.used_reference_breaks {
+            let Ok(Geometry {
+                name,
+                t: Type::Reference { offsets },
+                ..
+            }) = self.geometries().get_by_index(*reference)
+            else {
+                continue;
+            };
+            // the Overwrite offset is the last Break node, which also counts
+            // for its own break, so it is not reported on its own
+            let unused = offsets
+                .normal
+                .keys()
+                .filter(|b| !used.contains(&ChannelBreak::Break(**b)));
+            for br in unused {
+                problems.push(
+                    Problem::UnusedBreakInReference {
+                        br: br.to_string(),
+                        reference: name.clone(),
+                        mode: self.mode_name.clone(),
+                    }
+                    .at(&self.mode_node),
+                );
+            }
+        }
+        for p in problems {
+            p.handled_by(Action::KeptAsIs, self);
+        }
+    }
+
     fn parse_dmx_channels<'b: 'a>(&mut self, dmx_channels: Node<'b, 'b>) {
-        for channel in dmx_channels
+        for (i, channel) in dmx_channels
             .children()
             .filter(|n| n.is_element() && n.tag_name().name() == "DMXChannel")
+            .enumerate()
         {
-            self.parse_dmx_channel(channel)
-                .ok_or_handled_by("ignoring channel", self);
+            self.parse_dmx_channel(channel, i)
+                .ok_or_handled_by(Action::ignored("channel"), self);
         }
 
         while let Some(deferred_mode_master) = self.mode_master_queue.pop() {
             self.handle_mode_master(deferred_mode_master)
-                .ok_or_handled_by("ignoring mode master", self);
+                .ok_or_handled_by(Action::ignored("mode master"), self);
+        }
+    }
+
+    /// Rename all channels of the mode according to `channel_naming`.
+    ///
+    /// During parsing, channels must be named like GDTF does, because
+    /// InitialFunction and ModeMaster refer to them by these names. Therefore,
+    /// other names can only be applied afterwards.
+    fn apply_channel_naming(&mut self) -> Result<(), ProblemAt> {
+        if self.channel_naming == ChannelNaming::GeometryAttribute {
+            return Ok(());
         }
+
+        let mode = self.mode()?;
+        let new_name = |ch: &Channel| -> Result<Name, ProblemAt> {
+            self.channel_naming
+                .name_channel(self.geometries(), ch, None)
+                .ok_or_unexpected_at("missing channel geometry", &self.mode_node)
+        };
+        let channel_names: Vec<Name> = mode.channels.iter().map(new_name).try_collect()?;
+        let subfixture_channel_names: Vec<Vec<Name>> = mode
+            .subfixtures
+            .iter()
+            .map(|sf| {
+                sf.channels
+                    .iter()
+                    .map(new_name)
+                    .try_collect::<_, Vec<_>, _>()
+            })
+            .try_collect()?;
+
+        let mode = self.mode_mut()?;
+        for (ch, name) in mode.channels.iter_mut().zip(channel_names) {
+            ch.name = name;
+        }
+        for (sf, names) in mode.subfixtures.iter_mut().zip(subfixture_channel_names) {
+            for (ch, name) in sf.channels.iter_mut().zip(names) {
+                ch.name = name;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rename all subfixtures of the mode according to `subfixture_naming`.
+    ///
+    /// Must be called after all channels were parsed, since ModeMasters are
+    /// resolved by the name of the GeometryReference.
+    fn apply_subfixture_naming(&mut self) -> Result<(), ProblemAt> {
+        let naming = self.subfixture_naming;
+        for sf in self.mode_mut()?.subfixtures.iter_mut() {
+            sf.name = naming.subfixture_name(&sf.name, sf.id.index);
+        }
+        Ok(())
     }
 
-    fn parse_dmx_channel<'b: 'a>(&mut self, channel: Node<'b, 'b>) -> Result<(), ProblemAt> {
-        // TODO look up geometry in geometry rename lookup instead of geometries!
+    fn parse_dmx_channel<'b: 'a>(
+        &mut self,
+        channel: Node<'b, 'b>,
+        index_in_mode: usize,
+    ) -> Result<(), ProblemAt> {
+        let mode_geometry = *self.mode()?.geometry();
+        let mode_geometry_name = self
+            .geometries()
+            .get_by_index(mode_geometry)
+            .unexpected_err_at(&channel)?
+            .name
+            .clone();
+        // Geometries that were renamed during deduplication are still
+        // referenced by their original name, so look them up per mode
         let geometry_index = channel
             .parse_required_attribute("Geometry")
             .and_then(|geometry| {
+                let geometry = self
+                    .geometry_lookup
+                    .deduplicated_name(mode_geometry_name, geometry);
                 self.geometries()
                     .get_index(&geometry)
                     .ok_or_else(|| Problem::UnknownGeometry(geometry).at(&channel))
             })
-            .ok_or_handled_by("using mode geometry", self)
-            .unwrap_or(*self.mode_mut()?.geometry());
+            .ok_or_handled_by(Action::used("mode geometry"), self)
+            .unwrap_or(mode_geometry);
 
         // GDTF 1.2 says this field should be a "Node" (we call it NamePath)
         // But Attributes aren't nested, so there should only ever be one Name here, with no dot
         let first_logic_attribute: Name = channel
             .find_required_child("LogicalChannel")
             .and_then(|n| n.parse_required_attribute("Attribute"))
-            .ok_or_handled_by("using empty", self)
+            .ok_or_handled_by(Action::used("empty"), self)
             .unwrap_or_default();
 
         let name = {
@@ -172,14 +394,14 @@ impl<'a> DmxModeParser<'a> {
                     "Overwrite" => Ok(ChannelBreak::Overwrite),
                     s => parse_attribute_content(&channel, s, "DMXBreak").map(ChannelBreak::Break),
                 }
-                .ok_or_handled_by("using default", self)
+                .ok_or_handled_by(Action::UsedDefault { value: None }, self)
             })
             .unwrap_or_default();
 
         let offsets: ChannelOffsets = channel
             .parse_attribute("Offset")
             .transpose()
-            .ok_or_handled_by("using None", self)
+            .ok_or_handled_by(Action::used("None"), self)
             .flatten()
             .unwrap_or_default();
 
@@ -191,18 +413,40 @@ impl<'a> DmxModeParser<'a> {
 
         let max_dmx_value = bytes_max_value(channel_bytes);
 
+        let extensions = parse_unknown_attributes(
+            channel,
+            &[
+                "DMXBreak",
+                "Offset",
+                "InitialFunction",
+                "Highlight",
+                "Geometry",
+                DMX_CHANNEL_DEFAULT.attr,
+            ],
+        );
+
         let mut channel_functions = Vec::<(ChannelFunction, Node)>::default();
         // let mut channel_function_ids: Vec<NodeIndex> = Default::default();
         let raw_channel_function = ChannelFunction {
             name: name.to_owned(),
+            raw_dmx: true,
             geometry: geometry_index,
+            template_geometry: None,
             attr: "NoFeature".into(),
             original_attr: "RawDMX".into(),
             dmx_from: 0,
             dmx_to: max_dmx_value,
-            phys_from: 0.,
-            phys_to: 1.,
+            phys_from: PhysicalValue::ZERO,
+            phys_to: PhysicalValue::ONE,
+            real_fade: 0.,
+            real_acceleration: 0.,
+            min: PhysicalValue::ZERO,
+            max: PhysicalValue::ONE,
             default: 0,
+            wheel: None,
+            channel_sets: vec![],
+            extensions: Default::default(),
+            source: Some(channel.source_location()),
         };
         channel_functions.push((raw_channel_function, channel));
 
@@ -231,46 +475,98 @@ impl<'a> DmxModeParser<'a> {
             }
         }
 
-        let default = match channel
-            .attribute("InitialFunction")
+        let data_version = self.parsed.gdtf.data_version;
+        let legacy_default = DMX_CHANNEL_DEFAULT
+            .get(&channel, data_version, self)
             .and_then(|s| {
-                s.split('.')
-                    .next_tuple()
-                    .filter(|(ch, _lch, _chf)| (&name == ch))
-                    .map(|(_ch, _lch, chf)| chf)
-                    .ok_or_else(|| {
-                        Problem::InvalidInitialFunction {
-                            s: s.to_owned(),
-                            channel: name.to_owned(),
-                            mode: self.mode_name.to_owned(),
+                parse_dmx(s, channel_bytes)
+                    .map_err(|e| {
+                        Problem::InvalidAttribute {
+                            attr: "Default".to_owned(),
+                            tag: "DMXChannel".to_owned(),
+                            content: s.to_owned(),
+                            source: Box::new(e),
+                            expected_type: "DMXValue".to_owned(),
                         }
                         .at(&channel)
                     })
-                    .ok_or_handled_by("using default", self)
-            })
-            .and_then(|chf_name| {
-                channel_functions
-                    .iter()
-                    .find(|(chf, _)| chf.name == chf_name)
-            })
-            .map(|(chf, _)| chf.default)
-        {
-            Some(d) => d,
-            None => {
+                    .ok_or_handled_by(Action::ignored("attribute"), self)
+            });
+
+        // InitialFunction is `{Geometry}_{Attribute}.{LogicalChannel}.{ChannelFunction}`
+        // with the geometry name as written in the file, before deduplication
+        let gdtf_channel_name = format!(
+            "{}_{first_logic_attribute}",
+            channel.attribute("Geometry").unwrap_or_default()
+        );
+        // position in `channel_functions`, the raw DMX channel function can't
+        // be referenced
+        let initial_function = channel.attribute("InitialFunction").and_then(|s| {
+            s.split('.')
+                .next_tuple()
+                .filter(|(ch, _lch, _chf)| *ch == gdtf_channel_name)
+                .and_then(|(_ch, lch, chf_name)| {
+                    channel_functions
+                        .iter()
+                        .skip(1)
+                        .position(|(chf, n)| {
+                            chf.name == chf_name
+                                && n.parent().and_then(|l| l.attribute("Attribute")) == Some(lch)
+                        })
+                        .map(|i| i + 1)
+                })
+                .ok_or_else(|| {
+                    Problem::InvalidInitialFunction {
+                        s: s.to_owned(),
+                        channel: name.to_owned(),
+                        mode: self.mode_name.to_owned(),
+                    }
+                    .at(&channel)
+                })
+                .ok_or_handled_by(Action::UsedDefault { value: None }, self)
+        });
+        let initial_function_default = initial_function
+            .and_then(|i| channel_functions.get(i))
+            .map(|(chf, _)| chf.default);
+        let (default, default_source) = match (initial_function_default, legacy_default) {
+            (Some(d), _) => (d, DefaultSource::InitialFunction),
+            (None, Some(d)) => (d, DefaultSource::DmxChannelDefault),
+            (None, None) => {
                 let mut it = channel_functions.iter();
-                let raw = it.next();
-                match it.next() {
-                    Some(first) => first.0.default,
-                    None => {
-                        raw.ok_or_unexpected_at("no raw channel function", &channel)?
-                            .0
-                            .default
+                let raw = it
+                    .next()
+                    .ok_or_unexpected_at("no raw channel function", &channel)?;
+                match (self.default_fallback, it.next()) {
+                    (DefaultFallback::FirstChannelFunction, Some(first)) => {
+                        (first.0.default, DefaultSource::FirstChannelFunction)
                     }
+                    _ => (raw.0.default, DefaultSource::RawChannelFunction),
                 }
             }
         };
 
-        if !self.geometries().is_template(geometry_index) {
+        let highlight = channel
+            .attribute("Highlight")
+            .filter(|s| *s != "None")
+            .and_then(|s| {
+                parse_dmx(s, channel_bytes)
+                    .map_err(|e| {
+                        Problem::InvalidAttribute {
+                            attr: "Highlight".to_owned(),
+                            tag: "DMXChannel".to_owned(),
+                            content: s.to_owned(),
+                            source: Box::new(e),
+                            expected_type: "DMXValue".to_owned(),
+                        }
+                        .at(&channel)
+                    })
+                    .ok_or_handled_by(Action::SetToNone { field: None }, self)
+            });
+
+        // a channel on any geometry in the subtree of a template is instantiated
+        // for every reference to the template
+        let template = self.geometries().top_level_geometry_index(geometry_index);
+        if !self.geometries().is_template(template) {
             let actual_dmx_break = match dmx_break {
                 ChannelBreak::Break(b) => b,
                 ChannelBreak::Overwrite => Err(Problem::InvalidBreakOverwrite {
@@ -278,7 +574,7 @@ impl<'a> DmxModeParser<'a> {
                     mode: self.mode_name.to_owned(),
                 }
                 .at(&channel))
-                .ok_or_handled_by("using break 1", self)
+                .ok_or_handled_by(Action::used("break 1"), self)
                 .unwrap_or_default(),
             };
 
@@ -287,20 +583,30 @@ impl<'a> DmxModeParser<'a> {
 
             let channel = Channel {
                 name,
+                geometry: geometry_index,
+                template_geometry: None,
+                attribute: first_logic_attribute,
+                index_in_mode,
+                id: index_in_mode,
                 dmx_break: actual_dmx_break,
                 offsets,
+                initial_function: initial_function
+                    .and_then(|i| channel_function_ids.get(i))
+                    .copied(),
                 channel_functions: channel_function_ids,
                 bytes: channel_bytes,
                 default,
+                default_source,
+                highlight,
+                extensions,
+                source: Some(channel.source_location()),
             };
             self.mode_mut()?.channels.push(channel);
         } else {
             // template channel
             let mut instances = HashMap::<Name, Name>::new(); // Subfixture Name -> Instantiated Channel Name
-            let template_references: Vec<_> = self
-                .geometries()
-                .template_references(geometry_index)
-                .collect();
+            let template_references: Vec<_> =
+                self.geometries().template_references(template).collect();
             for ref_ind in template_references {
                 let (reference_name, reference_offsets) = {
                     let reference = self
@@ -308,8 +614,8 @@ impl<'a> DmxModeParser<'a> {
                         .get_by_index(ref_ind)
                         .unexpected_err_at(&channel)?;
                     let reference_offsets = if let Geometry {
-                        name: _,
                         t: Type::Reference { offsets },
+                        ..
                     } = reference
                     {
                         offsets
@@ -318,7 +624,7 @@ impl<'a> DmxModeParser<'a> {
                             "template pointed to geometry that was not a reference".into(),
                         )
                         .at(&channel)
-                        .handled_by("skipping", self);
+                        .handled_by(Action::Skipped, self);
                         continue;
                     };
 
@@ -335,7 +641,7 @@ impl<'a> DmxModeParser<'a> {
                                 mode: self.mode_name.to_owned(),
                             }
                             .at(&channel)
-                            .handled_by("skipping", self);
+                            .handled_by(Action::Skipped, self);
                             continue;
                         }
                     },
@@ -350,14 +656,33 @@ impl<'a> DmxModeParser<'a> {
                                     mode: self.mode_name.to_owned(),
                                 }
                                 .at(&channel)
-                                .handled_by("skipping", self);
+                                .handled_by(Action::Skipped, self);
                                 continue;
                             }
                         },
                     ),
                 };
-
-                let channel_name = format!("{reference_name}_{first_logic_attribute}").into_valid();
+                self.used_reference_breaks
+                    .entry(ref_ind)
+                    .or_default()
+                    .insert(dmx_break);
+
+                // every reference gets its own instance of a geometry below the
+                // top-level template geometry
+                self.parsed
+                    .gdtf
+                    .geometries
+                    .instantiate(geometry_index, ref_ind)
+                    .unexpected_err_at(&channel)?;
+
+                // channels on geometries below the top-level template also carry
+                // the geometry name, so they don't collide
+                let channel_name = if geometry_index == template {
+                    format!("{reference_name}_{first_logic_attribute}")
+                } else {
+                    format!("{reference_name}_{name}")
+                }
+                .into_valid();
 
                 let channel_function_ids = self.add_channel_functions(
                     channel_functions.iter().enumerate().map(|(i, (chf, n))| {
@@ -368,8 +693,8 @@ impl<'a> DmxModeParser<'a> {
                         };
                         (
                             ChannelFunction {
-                                geometry: ref_ind, // TODO doesn't work with multi-level geometry reference,
-                                // then the corresponding lower level instantiated geometry would be needed, which doesn't exist
+                                geometry: ref_ind,
+                                template_geometry: Some(geometry_index),
                                 name: chf_name,
                                 ..chf.clone()
                             },
@@ -382,20 +707,28 @@ impl<'a> DmxModeParser<'a> {
 
                 let dmx_channel = Channel {
                     name: channel_name,
+                    geometry: ref_ind,
+                    template_geometry: Some(geometry_index),
+                    attribute: first_logic_attribute.clone(),
+                    index_in_mode,
+                    id: index_in_mode,
                     dmx_break: actual_dmx_break,
                     offsets: offsets
                         .clone()
-                        .add_all(
-                            (offsets_offset - 1)
-                                .try_into()
-                                .unexpected_err_at(&channel)?,
-                        )
+                        .add_all(offsets_offset.absolute() - 1)
                         .map_err(|e| Problem::ChannelOffsetError(e).at(&channel))
-                        .ok_or_handled_by("using empty", self)
+                        .ok_or_handled_by(Action::used("empty"), self)
                         .unwrap_or_default(),
+                    initial_function: initial_function
+                        .and_then(|i| channel_function_ids.get(i))
+                        .copied(),
                     channel_functions: channel_function_ids,
                     bytes: channel_bytes,
                     default,
+                    default_source,
+                    highlight,
+                    extensions: extensions.clone(),
+                    source: Some(channel.source_location()),
                 };
                 let sf: &mut Subfixture = if let Some(sf) = self
                     .mode_mut()?
@@ -405,8 +738,13 @@ impl<'a> DmxModeParser<'a> {
                 {
                     sf
                 } else {
-                    self.mode_mut()?.subfixtures.push(Subfixture {
+                    let subfixtures = &mut self.mode_mut()?.subfixtures;
+                    subfixtures.push(Subfixture {
                         name: reference_name.to_owned(),
+                        id: SubfixtureId {
+                            index: subfixtures.len(),
+                            geometry: ref_ind.index(),
+                        },
                         channels: vec![],
                         geometry: ref_ind,
                     });
@@ -481,7 +819,7 @@ impl<'a> DmxModeParser<'a> {
         let original_attribute = chf.attribute("OriginalAttribute").unwrap_or("");
         let chf_name: Name = chf
             .parse_attribute("Name")
-            .and_then(|r| r.ok_or_handled_by("using default", self))
+            .and_then(|r| r.ok_or_handled_by(Action::UsedDefault { value: None }, self))
             .unwrap_or_else(|| format!("{chf_attr} {}", index_in_parent + 1).into_valid());
         let dmx_from = chf
             .attribute("DMXFrom")
@@ -497,7 +835,7 @@ impl<'a> DmxModeParser<'a> {
                         }
                         .at(&chf)
                     })
-                    .ok_or_handled_by("using default 0", self)
+                    .ok_or_handled_by(Action::used_default(0), self)
             })
             .unwrap_or(0);
         // The convention to use the next ChannelFunction in XML order for DMXTo is not official
@@ -518,7 +856,9 @@ impl<'a> DmxModeParser<'a> {
                         .at(&chf)
                     })
                     .ok_or_handled_by(
-                        "using maximum channel value for DMXTo of previous channel function",
+                        Action::used(
+                            "maximum channel value for DMXTo of previous channel function",
+                        ),
                         self,
                     )
             })
@@ -539,33 +879,157 @@ impl<'a> DmxModeParser<'a> {
                         }
                         .at(&chf)
                     })
-                    .ok_or_handled_by("using default 0", self)
+                    .ok_or_handled_by(Action::used_default(0), self)
             })
             .unwrap_or(0);
-        let phys_from = chf
-            .parse_attribute("PhysicalFrom")
+        let phys_from = parse_physical_value(&chf, "PhysicalFrom", PhysicalValue::ZERO, self);
+        let phys_to = parse_physical_value(&chf, "PhysicalTo", PhysicalValue::ONE, self);
+        let real_fade = chf
+            .parse_float_attribute("RealFade", self)
             .transpose()
-            .ok_or_handled_by("using default 0", self)
+            .ok_or_handled_by(Action::used_default(0), self)
             .flatten()
             .unwrap_or(0.);
-        let phys_to = chf
-            .parse_attribute("PhysicalTo")
+        let real_acceleration = chf
+            .parse_float_attribute("RealAcceleration", self)
             .transpose()
-            .ok_or_handled_by("using default 1", self)
+            .ok_or_handled_by(Action::used_default(0), self)
             .flatten()
-            .unwrap_or(1.);
+            .unwrap_or(0.);
+        let min = parse_physical_value(&chf, "Min", phys_from, self);
+        let max = parse_physical_value(&chf, "Max", phys_to, self);
+        let wheel = self.linked_wheel(chf);
 
-        Ok(ChannelFunction {
+        let mut channel_function = ChannelFunction {
             name: chf_name,
+            raw_dmx: false,
             geometry: geometry_index,
+            template_geometry: None,
             attr: chf_attr.to_owned(),
             original_attr: original_attribute.to_owned(),
             dmx_from,
             dmx_to,
             phys_from,
             phys_to,
+            real_fade,
+            real_acceleration,
+            min,
+            max,
             default,
-        })
+            wheel,
+            channel_sets: vec![],
+            extensions: parse_unknown_attributes(
+                chf,
+                &[
+                    "Name",
+                    "Attribute",
+                    "OriginalAttribute",
+                    "DMXFrom",
+                    "Default",
+                    "PhysicalFrom",
+                    "PhysicalTo",
+                    "RealFade",
+                    "RealAcceleration",
+                    "Min",
+                    "Max",
+                    "Wheel",
+                    "ModeMaster",
+                    "ModeFrom",
+                    "ModeTo",
+                ],
+            ),
+            source: Some(chf.source_location()),
+        };
+        channel_function.channel_sets =
+            self.parse_channel_sets(chf, channel_bytes, &channel_function);
+        Ok(channel_function)
+    }
+
+    /// Parse the optional Wheel attribute of a channel function and ensure the
+    /// wheel exists.
+    ///
+    /// An empty attribute is treated like a missing one. Unknown wheels cause a
+    /// problem and return None.
+    fn linked_wheel(&mut self, chf: Node) -> Option<Name> {
+        let set_to_none = || Action::SetToNone {
+            field: Some("wheel".to_owned()),
+        };
+        let wheel = chf
+            .map_parse_attribute::<Name, _>("Wheel", |opt| opt.filter(|s| !s.is_empty()))?
+            .ok_or_handled_by(set_to_none(), self)?;
+        if self.parsed.gdtf.wheels.get_by_name(&wheel).is_none() {
+            Problem::UnknownWheel(wheel)
+                .at(&chf)
+                .handled_by(set_to_none(), self);
+            return None;
+        }
+        Some(wheel)
+    }
+
+    /// Parse the ChannelSets of a channel function.
+    ///
+    /// Like for channel functions, DMXTo is derived from the DMXFrom of the
+    /// next ChannelSet in XML order. Missing values are taken from the channel
+    /// function.
+    fn parse_channel_sets(
+        &mut self,
+        chf_node: Node,
+        channel_bytes: u8,
+        chf: &ChannelFunction,
+    ) -> Vec<ChannelSet> {
+        let set_nodes: Vec<(Node, Option<u32>)> = chf_node
+            .children()
+            .filter(|n| n.has_tag_name("ChannelSet"))
+            .map(|n| {
+                let dmx_from = n.attribute("DMXFrom").and_then(|s| {
+                    parse_dmx(s, channel_bytes)
+                        .map_err(|e| {
+                            Problem::InvalidAttribute {
+                                attr: "DMXFrom".to_owned(),
+                                tag: "ChannelSet".to_owned(),
+                                content: s.to_owned(),
+                                source: Box::new(e),
+                                expected_type: "DMXValue".to_owned(),
+                            }
+                            .at(&n)
+                        })
+                        .ok_or_handled_by(Action::used("DMXFrom of channel function"), self)
+                });
+                (n, dmx_from)
+            })
+            .collect();
+
+        let mut channel_sets = Vec::with_capacity(set_nodes.len());
+        for (i, (n, dmx_from)) in set_nodes.iter().enumerate() {
+            let dmx_from = dmx_from.unwrap_or(chf.dmx_from);
+            let dmx_to = set_nodes
+                .get(i + 1)
+                .and_then(|(_, next_dmx_from)| *next_dmx_from)
+                .filter(|next_dmx_from| dmx_from < *next_dmx_from)
+                .map(|next_dmx_from| next_dmx_from - 1)
+                .unwrap_or(chf.dmx_to);
+            let name = n
+                .attribute("Name")
+                .map(|s| parse_name_or_fix(n, s, self))
+                .unwrap_or_default();
+            let phys_from = parse_physical_value(n, "PhysicalFrom", chf.phys_from, self);
+            let phys_to = parse_physical_value(n, "PhysicalTo", chf.phys_to, self);
+            let wheel_slot_index = n
+                .parse_attribute("WheelSlotIndex")
+                .transpose()
+                .ok_or_handled_by(Action::SetToNone { field: None }, self)
+                .flatten();
+
+            channel_sets.push(ChannelSet {
+                name,
+                dmx_from,
+                dmx_to,
+                phys_from,
+                phys_to,
+                wheel_slot_index,
+            });
+        }
+        channel_sets
     }
 
     fn handle_mode_master(&mut self, d: DeferredModeMaster) -> Result<(), ProblemAt> {
@@ -707,7 +1171,7 @@ impl<'a> DmxModeParser<'a> {
                 }
                 .at(&d.chf_node)
             })
-            .ok_or_handled_by("using default 0", self)
+            .ok_or_handled_by(Action::used_default(0), self)
             .unwrap_or(0);
         let mode_to = parse_dmx(mode_to, dependency_bytes)
             .map_err(|e| {
@@ -720,7 +1184,7 @@ impl<'a> DmxModeParser<'a> {
                 }
                 .at(&d.chf_node)
             })
-            .ok_or_handled_by("using default 0", self)
+            .ok_or_handled_by(Action::used_default(0), self)
             .unwrap_or(0);
 
         let clipped_mode_from = max(mode_from, master_from);
@@ -735,6 +1199,21 @@ impl<'a> DmxModeParser<'a> {
             }
             .at(&d.chf_node));
         }
+        if (clipped_mode_from, clipped_mode_to) != (mode_from, mode_to) {
+            Problem::ClippedModeMasterRange {
+                name: d.chf_name,
+                dmx_mode: self.mode_name.to_owned(),
+                mode_from,
+                mode_to,
+                master_from,
+                master_to,
+            }
+            .at(&d.chf_node)
+            .handled_by(
+                Action::used(format!("{clipped_mode_from} to {clipped_mode_to}")),
+                self,
+            );
+        }
 
         self.mode_mut()?
             .channel_functions
@@ -744,6 +1223,8 @@ impl<'a> DmxModeParser<'a> {
                 ModeMaster {
                     from: clipped_mode_from,
                     to: clipped_mode_to,
+                    original_from: mode_from,
+                    original_to: mode_to,
                 },
             )
             .unexpected_err_at(&d.chf_node)?;
@@ -755,7 +1236,7 @@ impl<'a> DmxModeParser<'a> {
 #[error("mode master attribute must contain either zero or two period separators")]
 pub struct ModeMasterParseError();
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ChannelBreak {
     Overwrite,
     Break(Break),
@@ -781,11 +1262,15 @@ struct DeferredModeMaster<'a> {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
     use crate::{
+        channel_offsets::OffsetError,
+        dmx_address::DmxAddress,
         dmx_break::Break,
+        dmx_modes::{BreakFootprint, Footprint},
         geometry::{Geometry, Offsets, Type},
+        serialize_gdtf,
+        source_location::SourceLocation,
+        Gdtf,
     };
 
     use super::*;
@@ -810,8 +1295,8 @@ mod tests {
                 </DMXChannel>
                 <DMXChannel DMXBreak="1" Geometry="Beam" Highlight="0/1" InitialFunction="Beam_StrobeFrequency.StrobeFrequency.StrobeFrequency" Offset="2,3">
                     <LogicalChannel Attribute="StrobeFrequency" DMXChangeTimeLimit="0.000000" Master="Grand" MibFade="0.000000" Snap="No">
-                        <ChannelFunction Attribute="StrobeFrequency" CustomName="" DMXFrom="0/1" Default="0/1" Max="1.000000" Min="0.000000" 
-                                Name="StrobeFrequency" OriginalAttribute="" PhysicalFrom="0.000000" PhysicalTo="1.000000" RealAcceleration="0.000000" RealFade="0.000000"
+                        <ChannelFunction Attribute="StrobeFrequency" CustomName="" DMXFrom="0/1" Default="0/1" Min="0.200000" 
+                                Name="StrobeFrequency" OriginalAttribute="" PhysicalFrom="0.000000" PhysicalTo="1.000000" RealAcceleration="0.050000" RealFade="0.100000"
                                 ModeMaster="Beam_Dimmer" ModeFrom="128/1" ModeTo="65535/2">
                             <ChannelSet DMXFrom="0/1" Name="slowest" WheelSlotIndex="0"/>
                             <ChannelSet DMXFrom="1/1" Name="" WheelSlotIndex="0"/>
@@ -837,6 +1322,7 @@ mod tests {
             .add_top_level(Geometry {
                 name: "Body".into_valid(),
                 t: Type::General,
+                ..Default::default()
             })
             .unwrap();
         let beam_index = parsed
@@ -846,11 +1332,12 @@ mod tests {
                 Geometry {
                     name: "Beam".into_valid(),
                     t: Type::General,
+                    ..Default::default()
                 },
                 body_index,
             )
             .unwrap();
-        parsed.parse_dmx_modes(ft);
+        parsed.parse_dmx_modes(ft, &Default::default(), &Default::default());
 
         assert_eq!(parsed.problems.len(), 0);
 
@@ -864,26 +1351,78 @@ mod tests {
         assert_eq!(mode.name, "Mode 1");
         assert_eq!(mode.description, "not a Name.");
         assert_eq!(mode.geometry(), &body_index);
+        assert_eq!(mode.source, Some(SourceLocation { line: 4, column: 9 }));
 
         assert_eq!(mode.subfixtures.len(), 0);
 
         let mut channels = mode.channels.iter();
         let dimmer = channels.next().expect("first channel");
         assert_eq!(dimmer.name, "Beam_Dimmer");
+        assert_eq!(
+            dimmer.source,
+            Some(SourceLocation {
+                line: 6,
+                column: 17
+            })
+        );
         assert_eq!(dimmer.offsets.first().expect("one offset"), &1);
         assert_eq!(dimmer.bytes, 1);
         assert_eq!(dimmer.bytes as usize, dimmer.offsets.len());
         assert_eq!(dimmer.default, 200); // 200/1 = 51200/2
+        assert_eq!(dimmer.highlight, Some(127));
         assert_eq!(dimmer.channel_functions.len(), 3); // 1 raw + 2 normal
+        assert_eq!(dimmer.defined_functions().len(), 2);
+        assert!(
+            mode.channel_functions
+                .node_weight(dimmer.raw_function().unwrap())
+                .unwrap()
+                .raw_dmx
+        );
 
         let dimmer_chf = mode
             .channel_functions
             .node_weight(*dimmer.channel_functions.get(1).unwrap())
             .unwrap();
         assert_eq!(dimmer_chf.name, "Dimmer");
+        assert_eq!(
+            dimmer_chf.source,
+            Some(SourceLocation {
+                line: 8,
+                column: 25
+            })
+        );
         assert_eq!(dimmer_chf.dmx_from, 0);
         assert_eq!(dimmer_chf.dmx_to, 127);
         assert_eq!(dimmer_chf.geometry, beam_index);
+        assert_eq!(
+            dimmer_chf.channel_sets,
+            vec![
+                ChannelSet {
+                    name: "closed".into_valid(),
+                    dmx_from: 0,
+                    dmx_to: 0,
+                    phys_from: PhysicalValue::ZERO,
+                    phys_to: PhysicalValue::ONE,
+                    wheel_slot_index: Some(0),
+                },
+                ChannelSet {
+                    name: "".into_valid(),
+                    dmx_from: 1,
+                    dmx_to: 126,
+                    phys_from: PhysicalValue::ZERO,
+                    phys_to: PhysicalValue::ONE,
+                    wheel_slot_index: Some(0),
+                },
+                ChannelSet {
+                    name: "open".into_valid(),
+                    dmx_from: 127,
+                    dmx_to: 127,
+                    phys_from: PhysicalValue::ZERO,
+                    phys_to: PhysicalValue::ONE,
+                    wheel_slot_index: Some(0),
+                },
+            ]
+        );
         let strobe_chf = mode
             .channel_functions
             .node_weight(*dimmer.channel_functions.get(2).unwrap())
@@ -901,6 +1440,14 @@ mod tests {
         assert_eq!(freq_chf.name, "StrobeFrequency");
         assert_eq!(freq_chf.dmx_from, 0);
         assert_eq!(freq_chf.dmx_to, 65535);
+        assert_eq!(freq_chf.real_fade, 0.1);
+        assert_eq!(freq_chf.real_acceleration, 0.05);
+        assert_eq!(freq_chf.min.value(), 0.2);
+        assert_eq!(
+            freq_chf.max,
+            PhysicalValue::ONE,
+            "Max defaults to PhysicalTo"
+        );
         let nof_chf = mode
             .channel_functions
             .node_weight(*freq.channel_functions.get(2).unwrap())
@@ -961,6 +1508,7 @@ mod tests {
             .add_top_level(Geometry {
                 name: "Body".into_valid(),
                 t: Type::General,
+                ..Default::default()
             })
             .unwrap();
         let abstract_index = parsed
@@ -969,6 +1517,7 @@ mod tests {
             .add_top_level(Geometry {
                 name: "AbstractGeometry".into_valid(),
                 t: Type::General,
+                ..Default::default()
             })
             .unwrap();
         let ref1_index = parsed
@@ -979,10 +1528,14 @@ mod tests {
                     name: "Pixel1".into_valid(),
                     t: Type::Reference {
                         offsets: Offsets {
-                            normal: HashMap::from([(Break::try_from(1).unwrap(), 1)]),
+                            normal: BTreeMap::from([(
+                                Break::try_from(1).unwrap(),
+                                DmxAddress::try_from(1).unwrap(),
+                            )]),
                             overwrite: None,
                         },
                     },
+                    ..Default::default()
                 },
                 body_index,
             )
@@ -995,10 +1548,14 @@ mod tests {
                     name: "Pixel2".into_valid(),
                     t: Type::Reference {
                         offsets: Offsets {
-                            normal: HashMap::from([(Break::try_from(1).unwrap(), 2)]),
+                            normal: BTreeMap::from([(
+                                Break::try_from(1).unwrap(),
+                                DmxAddress::try_from(2).unwrap(),
+                            )]),
                             overwrite: None,
                         },
                     },
+                    ..Default::default()
                 },
                 body_index,
             )
@@ -1014,7 +1571,7 @@ mod tests {
             .add_template_relationship(abstract_index, ref2_index)
             .unwrap();
 
-        parsed.parse_dmx_modes(ft);
+        parsed.parse_dmx_modes(ft, &Default::default(), &Default::default());
 
         assert!(parsed.problems.is_empty());
 
@@ -1068,29 +1625,19 @@ mod tests {
         // TODO what happens if the modeMaster-referenced Channel or ChannelFunction is a template?
         // Then it can only work out if they are in the same subfixture and they reference in the instantiated form with 1:1 mapping
 
-        // TODO what happens if a Channel references a Geometry that is a child of a template top-level geometry, do we pick
-        // that up and also treat it as a template channel? -> We should probably instantiate GeometryReference nodes as
-        // the Geometry (including its subtree) they reference
-
         // TODO test geometry renaming and lookup with DMXChannels
     }
 
     #[test]
-    fn default_channel_function_name() {
+    fn unused_breaks_in_reference() {
         let input = r#"
 <FixtureType>
     <DMXModes>
-        <DMXMode Description="not a Name." Geometry="Body" Name="Mode 1">
+        <DMXMode Geometry="Body" Name="Mode 1">
             <DMXChannels>
-                <DMXChannel DMXBreak="1" Geometry="Body" Highlight="127/1" InitialFunction="Body_Dimmer.Dimmer.Dimmer 1" Offset="1">
-                    <LogicalChannel Attribute="Dimmer" DMXChangeTimeLimit="0.000000" Master="Grand" MibFade="0.000000" Snap="No">
-                        <ChannelFunction Attribute="Dimmer" CustomName="" DMXFrom="0/1" Default="0/1" Max="1.000000" Min="0.000000" OriginalAttribute="" PhysicalFrom="0.000000" PhysicalTo="1.000000" RealAcceleration="0.000000" RealFade="0.000000">
-                            <ChannelSet DMXFrom="0/1" Name="closed" WheelSlotIndex="0"/>
-                            <ChannelSet DMXFrom="1/1" Name="" WheelSlotIndex="0"/>
-                            <ChannelSet DMXFrom="127/1" Name="open" WheelSlotIndex="0"/>
-                        </ChannelFunction>
-                        <ChannelFunction Attribute="StrobeModeShutter" CustomName="" DMXFrom="128/1" Default="51200/2" Max="1.000000" Min="1.000000" Name="Strobe" OriginalAttribute="" PhysicalFrom="1.000000" PhysicalTo="1.000000" RealAcceleration="0.000000" RealFade="0.000000">
-                        </ChannelFunction>
+                <DMXChannel DMXBreak="1" Geometry="AbstractGeometry" Offset="1">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Attribute="Dimmer" DMXFrom="0/1" Name="Dimmer"/>
                     </LogicalChannel>
                 </DMXChannel>
             </DMXChannels>
@@ -1100,28 +1647,1056 @@ mod tests {
         let doc = roxmltree::Document::parse(input).unwrap();
         let ft = doc.root_element();
         let mut parsed = ParsedGdtf::default();
-        let _body_index = parsed
+        let body_index = parsed
             .gdtf
             .geometries
             .add_top_level(Geometry {
                 name: "Body".into_valid(),
                 t: Type::General,
+                ..Default::default()
+            })
+            .unwrap();
+        let abstract_index = parsed
+            .gdtf
+            .geometries
+            .add_top_level(Geometry {
+                name: "AbstractGeometry".into_valid(),
+                t: Type::General,
+                ..Default::default()
             })
             .unwrap();
-        parsed.parse_dmx_modes(ft);
+        let ref_index = parsed
+            .gdtf
+            .geometries
+            .add(
+                Geometry {
+                    name: "Pixel".into_valid(),
+                    t: Type::Reference {
+                        offsets: Offsets {
+                            normal: BTreeMap::from([
+                                (
+                                    Break::try_from(1).unwrap(),
+                                    DmxAddress::try_from(1).unwrap(),
+                                ),
+                                (
+                                    Break::try_from(2).unwrap(),
+                                    DmxAddress::try_from(1).unwrap(),
+                                ),
+                            ]),
+                            overwrite: None,
+                        },
+                    },
+                    ..Default::default()
+                },
+                body_index,
+            )
+            .unwrap();
+        parsed
+            .gdtf
+            .geometries
+            .add_template_relationship(abstract_index, ref_index)
+            .unwrap();
 
-        assert_eq!(parsed.problems.len(), 0);
+        parsed.parse_dmx_modes(ft, &Default::default(), &Default::default());
 
-        let chf_names: Vec<Name> = parsed
-            .gdtf
-            .dmx_modes()
-            .first()
-            .unwrap()
-            .channel_functions
-            .node_weights()
-            .map(|chf| chf.name.clone())
+        assert_eq!(parsed.problems.len(), 1);
+        assert!(matches!(
+            parsed.problems.first().unwrap().problem(),
+            Problem::UnusedBreakInReference { br, reference, .. }
+                if br == "2" && reference == "Pixel"
+        ));
+        let mode = parsed.gdtf.dmx_modes().first().unwrap();
+        assert_eq!(mode.subfixtures.len(), 1);
+    }
+
+    #[test]
+    fn channels_below_template_geometry_are_instantiated() {
+        let input = r#"
+<FixtureType>
+    <DMXModes>
+        <DMXMode Geometry="Body" Name="Mode 1">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Geometry="AbstractGeometry" Offset="1">
+                    <LogicalChannel Attribute="Dimmer"/>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Geometry="Beam" Offset="2">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Attribute="Dimmer" DMXFrom="0/1" Default="0/1" Name="Dimmer"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let ft = doc.root_element();
+        let mut parsed = ParsedGdtf::default();
+        let geometries = &mut parsed.gdtf.geometries;
+        let body_index = geometries
+            .add_top_level(Geometry {
+                name: "Body".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        let abstract_index = geometries
+            .add_top_level(Geometry {
+                name: "AbstractGeometry".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        let beam_index = geometries
+            .add(
+                Geometry {
+                    name: "Beam".into_valid(),
+                    ..Default::default()
+                },
+                abstract_index,
+            )
+            .unwrap();
+        let mut references = vec![];
+        for (name, offset) in [("Pixel1", 1), ("Pixel2", 3)] {
+            let reference = geometries
+                .add(
+                    Geometry {
+                        name: name.into_valid(),
+                        t: Type::Reference {
+                            offsets: Offsets {
+                                normal: BTreeMap::from([(
+                                    Break::try_from(1).unwrap(),
+                                    DmxAddress::try_from(offset).unwrap(),
+                                )]),
+                                overwrite: None,
+                            },
+                        },
+                        ..Default::default()
+                    },
+                    body_index,
+                )
+                .unwrap();
+            geometries
+                .add_template_relationship(abstract_index, reference)
+                .unwrap();
+            references.push(reference);
+        }
+
+        parsed.parse_dmx_modes(ft, &Default::default(), &Default::default());
+
+        assert!(parsed.problems.is_empty());
+        let mode = parsed.gdtf.dmx_modes().first().unwrap();
+        assert_eq!(mode.channels.len(), 0);
+        assert_eq!(mode.subfixtures.len(), 2);
+        let geometries = &parsed.gdtf.geometries;
+        for (sf, reference) in mode.subfixtures.iter().zip(references.iter().copied()) {
+            assert_eq!(sf.geometry, reference);
+            assert!(geometries.is_instantiated(beam_index, reference));
+
+            let summary: Vec<_> = sf
+                .channels
+                .iter()
+                .map(|ch| {
+                    assert_eq!(ch.reference(), Some(reference));
+                    for chf in ch.channel_functions.iter() {
+                        let chf = mode.channel_functions.node_weight(*chf).unwrap();
+                        assert_eq!(
+                            (chf.geometry, chf.template_geometry),
+                            (ch.geometry, ch.template_geometry)
+                        );
+                    }
+                    (ch.name.to_string(), ch.geometry, ch.template_geometry)
+                })
+                .collect();
+            assert_eq!(
+                summary,
+                [
+                    (
+                        format!("{}_Dimmer", sf.name),
+                        reference,
+                        Some(abstract_index)
+                    ),
+                    (
+                        format!("{}_Beam_Dimmer", sf.name),
+                        reference,
+                        Some(beam_index)
+                    ),
+                ]
+            );
+        }
+        let instances: Vec<_> = references.iter().map(|r| (*r, beam_index)).collect();
+        assert!(geometries.instances().iter().eq(instances.iter()));
+        // instances are not part of the geometry tree
+        assert_eq!(geometries.get_index(&"Beam".into_valid()), Some(beam_index));
+        assert!(references
+            .iter()
+            .all(|reference| geometries.count_children(*reference) == 0));
+    }
+
+    #[test]
+    fn subfixture_channels_are_checked_for_overlaps_and_offset_range() {
+        let input = r#"
+<FixtureType>
+    <DMXModes>
+        <DMXMode Geometry="Body" Name="Mode 1">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Geometry="Body" Offset="4">
+                    <LogicalChannel Attribute="Dimmer"/>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Geometry="AbstractGeometry" Offset="1,2">
+                    <LogicalChannel Attribute="Dimmer"/>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let ft = doc.root_element();
+        let mut parsed = ParsedGdtf::default();
+        let geometries = &mut parsed.gdtf.geometries;
+        let body_index = geometries
+            .add_top_level(Geometry {
+                name: "Body".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        let abstract_index = geometries
+            .add_top_level(Geometry {
+                name: "AbstractGeometry".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        for (name, offset) in [("Pixel1", 3), ("Pixel2", 512)] {
+            let reference = geometries
+                .add(
+                    Geometry {
+                        name: name.into_valid(),
+                        t: Type::Reference {
+                            offsets: Offsets {
+                                normal: BTreeMap::from([(
+                                    Break::try_from(1).unwrap(),
+                                    DmxAddress::try_from(offset).unwrap(),
+                                )]),
+                                overwrite: None,
+                            },
+                        },
+                        ..Default::default()
+                    },
+                    body_index,
+                )
+                .unwrap();
+            geometries
+                .add_template_relationship(abstract_index, reference)
+                .unwrap();
+        }
+
+        parsed.parse_dmx_modes(ft, &Default::default(), &Default::default());
+
+        assert_eq!(parsed.problems.len(), 2, "{:?}", parsed.problems);
+        assert!(parsed.problems.iter().any(|p| matches!(
+            p.problem(),
+            Problem::ChannelOffsetError(OffsetError::OutsideRange)
+        )));
+        assert!(parsed.problems.iter().any(|p| matches!(
+            p.problem(),
+            Problem::OverlappingChannelOffsets { offset: 4, first, second, .. }
+            if *first == "Body_Dimmer" && *second == "Pixel1_Dimmer"
+        )));
+
+        let mode = parsed.gdtf.dmx_modes().first().unwrap();
+        let offsets: Vec<_> = mode
+            .all_channels()
+            .map(|ch| (ch.name.to_string(), ch.offsets.to_vec()))
             .collect();
-        assert!(chf_names.contains(&"Body_Dimmer".into_valid()));
-        assert!(chf_names.contains(&"Dimmer 1".into_valid()));
+        assert_eq!(
+            offsets,
+            [
+                ("Body_Dimmer".into(), vec![4]),
+                ("Pixel1_Dimmer".into(), vec![3, 4]),
+                ("Pixel2_Dimmer".into(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_mode_geometry_falls_back_to_first_top_level_geometry() {
+        let input = r#"
+<FixtureType>
+    <DMXModes>
+        <DMXMode Geometry="Misspelled Body" Name="Mode 1">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Geometry="Body" Offset="1">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Attribute="Dimmer" DMXFrom="0/1" Name="Dimmer"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+        <DMXMode Name="Mode 2">
+            <DMXChannels/>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let ft = doc.root_element();
+        let mut parsed = ParsedGdtf::default();
+        let body_index = parsed
+            .gdtf
+            .geometries
+            .add_top_level(Geometry {
+                name: "Body".into_valid(),
+                t: Type::General,
+                ..Default::default()
+            })
+            .unwrap();
+        parsed
+            .gdtf
+            .geometries
+            .add_top_level(Geometry {
+                name: "Other".into_valid(),
+                t: Type::General,
+                ..Default::default()
+            })
+            .unwrap();
+        parsed.parse_dmx_modes(ft, &Default::default(), &Default::default());
+
+        assert_eq!(parsed.problems.len(), 2);
+        assert!(matches!(
+            parsed.problems.first().unwrap().problem(),
+            Problem::UnknownGeometry(name) if name == "Misspelled Body"
+        ));
+        assert!(matches!(
+            parsed.problems.get(1).unwrap().problem(),
+            Problem::XmlAttributeMissing { attr, .. } if attr == "Geometry"
+        ));
+
+        let modes = parsed.gdtf.dmx_modes();
+        assert_eq!(modes.len(), 2);
+        for mode in modes {
+            assert_eq!(mode.geometry(), &body_index);
+            assert_eq!(mode.geometry_name(&parsed.gdtf.geometries).unwrap(), "Body");
+        }
+        assert_eq!(modes.first().unwrap().channels.len(), 1);
+    }
+
+    #[test]
+    fn unknown_mode_geometry_without_any_geometries_ignores_mode() {
+        let input = r#"
+<FixtureType>
+    <DMXModes>
+        <DMXMode Geometry="Body" Name="Mode 1">
+            <DMXChannels/>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let ft = doc.root_element();
+        let mut parsed = ParsedGdtf::default();
+        parsed.parse_dmx_modes(ft, &Default::default(), &Default::default());
+
+        assert_eq!(parsed.problems.len(), 1);
+        assert!(parsed.gdtf.dmx_modes().is_empty());
+    }
+
+    #[test]
+    fn default_channel_function_name() {
+        let input = r#"
+<FixtureType>
+    <DMXModes>
+        <DMXMode Description="not a Name." Geometry="Body" Name="Mode 1">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Geometry="Body" Highlight="127/1" InitialFunction="Body_Dimmer.Dimmer.Dimmer 1" Offset="1">
+                    <LogicalChannel Attribute="Dimmer" DMXChangeTimeLimit="0.000000" Master="Grand" MibFade="0.000000" Snap="No">
+                        <ChannelFunction Attribute="Dimmer" CustomName="" DMXFrom="0/1" Default="0/1" Max="1.000000" Min="0.000000" OriginalAttribute="" PhysicalFrom="0.000000" PhysicalTo="1.000000" RealAcceleration="0.000000" RealFade="0.000000">
+                            <ChannelSet DMXFrom="0/1" Name="closed" WheelSlotIndex="0"/>
+                            <ChannelSet DMXFrom="1/1" Name="" WheelSlotIndex="0"/>
+                            <ChannelSet DMXFrom="127/1" Name="open" WheelSlotIndex="0"/>
+                        </ChannelFunction>
+                        <ChannelFunction Attribute="StrobeModeShutter" CustomName="" DMXFrom="128/1" Default="51200/2" Max="1.000000" Min="1.000000" Name="Strobe" OriginalAttribute="" PhysicalFrom="1.000000" PhysicalTo="1.000000" RealAcceleration="0.000000" RealFade="0.000000">
+                        </ChannelFunction>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let ft = doc.root_element();
+        let mut parsed = ParsedGdtf::default();
+        let _body_index = parsed
+            .gdtf
+            .geometries
+            .add_top_level(Geometry {
+                name: "Body".into_valid(),
+                t: Type::General,
+                ..Default::default()
+            })
+            .unwrap();
+        parsed.parse_dmx_modes(ft, &Default::default(), &Default::default());
+
+        assert_eq!(parsed.problems.len(), 0);
+
+        let chf_names: Vec<Name> = parsed
+            .gdtf
+            .dmx_modes()
+            .first()
+            .unwrap()
+            .channel_functions
+            .node_weights()
+            .map(|chf| chf.name.clone())
+            .collect();
+        assert!(chf_names.contains(&"Body_Dimmer".into_valid()));
+        assert!(chf_names.contains(&"Dimmer 1".into_valid()));
+    }
+
+    #[test]
+    fn channel_naming_is_configurable() {
+        let input = r#"
+<FixtureType>
+    <DMXModes>
+        <DMXMode Geometry="Body" Name="Mode 1">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Geometry="Body" InitialFunction="Body_Dimmer.Dimmer.Dimmer" Offset="1">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Attribute="Dimmer" DMXFrom="0/1" Default="0/1" Name="Dimmer"/>
+                    </LogicalChannel>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Geometry="Body" Offset="2">
+                    <LogicalChannel Attribute="Shutter1">
+                        <ChannelFunction Attribute="Shutter1" DMXFrom="0/1" Default="0/1" Name="Shutter" ModeMaster="Body_Dimmer" ModeFrom="1/1" ModeTo="255/1"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let ft = doc.root_element();
+
+        let names = |channel_naming| {
+            let mut parsed = ParsedGdtf::default();
+            let body_index = parsed
+                .gdtf
+                .geometries
+                .add_top_level(Geometry {
+                    name: "Body".into_valid(),
+                    ..Default::default()
+                })
+                .unwrap();
+            let options = ParseOptions {
+                channel_naming,
+                ..Default::default()
+            };
+            parsed.parse_dmx_modes(ft, &Default::default(), &options);
+            assert_eq!(parsed.problems.len(), 0);
+
+            let mode = parsed.gdtf.dmx_modes().first().unwrap();
+            let shutter = mode.channels.get(1).unwrap();
+            assert_eq!(shutter.geometry, body_index);
+            assert_eq!(shutter.attribute, "Shutter1");
+            assert_eq!(shutter.index_in_mode, 1);
+
+            mode.channels
+                .iter()
+                .map(|ch| ch.name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(ChannelNaming::GeometryAttribute),
+            ["Body_Dimmer", "Body_Shutter1"]
+        );
+        assert_eq!(
+            names(ChannelNaming::IndexGeometryAttribute),
+            ["1_Body_Dimmer", "2_Body_Shutter1"]
+        );
+        assert_eq!(names(ChannelNaming::XmlOrder), ["Channel 1", "Channel 2"]);
+    }
+
+    #[test]
+    fn channel_naming_keeps_template_instances_apart() {
+        let description = r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Bar" FixtureTypeID="00000000-0000-0000-0000-000000000001">
+    <Geometries>
+        <Geometry Name="Body">
+            <GeometryReference Name="Pixel 1" Geometry="Pixel">
+                <Break DMXBreak="1" DMXOffset="2"/>
+            </GeometryReference>
+            <GeometryReference Name="Pixel 2" Geometry="Pixel">
+                <Break DMXBreak="1" DMXOffset="4"/>
+            </GeometryReference>
+        </Geometry>
+        <Geometry Name="Pixel">
+            <Geometry Name="Beam"/>
+        </Geometry>
+    </Geometries>
+    <DMXModes>
+        <DMXMode Name="Mode" Geometry="Body">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Body">
+                    <LogicalChannel Attribute="Pan"/>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Pixel">
+                    <LogicalChannel Attribute="Dimmer"/>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Offset="2" Geometry="Beam">
+                    <LogicalChannel Attribute="Dimmer"/>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#;
+        let names = |channel_naming| {
+            let options = ParseOptions {
+                channel_naming,
+                ..Default::default()
+            };
+            let gdtf = crate::parser::parse_description(description.into(), &options)
+                .unwrap()
+                .gdtf;
+            let names: Vec<_> = gdtf
+                .dmx_modes()
+                .first()
+                .unwrap()
+                .all_channels()
+                .map(|ch| ch.name.to_string())
+                .collect();
+            let unique: HashSet<_> = names.iter().collect();
+            assert_eq!(unique.len(), names.len(), "{names:?}");
+            names
+        };
+
+        assert_eq!(
+            names(ChannelNaming::GeometryAttribute),
+            [
+                "Body_Pan",
+                "Pixel 1_Dimmer",
+                "Pixel 1_Beam_Dimmer",
+                "Pixel 2_Dimmer",
+                "Pixel 2_Beam_Dimmer"
+            ]
+        );
+        assert_eq!(
+            names(ChannelNaming::IndexGeometryAttribute),
+            [
+                "1_Body_Pan",
+                "2_Pixel 1_Dimmer",
+                "3_Pixel 1_Beam_Dimmer",
+                "2_Pixel 2_Dimmer",
+                "3_Pixel 2_Beam_Dimmer"
+            ]
+        );
+        assert_eq!(
+            names(ChannelNaming::XmlOrder),
+            [
+                "Channel 1",
+                "Pixel 1_Channel 2",
+                "Pixel 1_Channel 3",
+                "Pixel 2_Channel 2",
+                "Pixel 2_Channel 3"
+            ]
+        );
+    }
+
+    #[test]
+    fn subfixture_naming_is_configurable() {
+        let description = r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Bar" FixtureTypeID="00000000-0000-0000-0000-000000000001">
+    <Geometries>
+        <Geometry Name="Body">
+            <GeometryReference Name="Pixel 1" Geometry="Pixel">
+                <Break DMXBreak="1" DMXOffset="1"/>
+            </GeometryReference>
+            <GeometryReference Name="Pixel 2" Geometry="Pixel">
+                <Break DMXBreak="1" DMXOffset="2"/>
+            </GeometryReference>
+        </Geometry>
+        <Geometry Name="Pixel"/>
+    </Geometries>
+    <DMXModes>
+        <DMXMode Name="Mode" Geometry="Body">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Pixel">
+                    <LogicalChannel Attribute="Dimmer"/>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#;
+        let subfixtures = |subfixture_naming| {
+            let options = ParseOptions {
+                subfixture_naming,
+                ..Default::default()
+            };
+            let gdtf = crate::parser::parse_description(description.into(), &options)
+                .unwrap()
+                .gdtf;
+            gdtf.dmx_modes()
+                .first()
+                .unwrap()
+                .subfixtures
+                .iter()
+                .map(|sf| (sf.name.to_string(), sf.id))
+                .collect::<Vec<_>>()
+        };
+
+        // ids don't depend on the naming, names are derived from them
+        let by_reference = subfixtures(SubfixtureNaming::GeometryReference);
+        for (naming, expected) in [
+            (
+                SubfixtureNaming::IndexGeometryReference,
+                "{index}_{reference}",
+            ),
+            (SubfixtureNaming::Index, "Subfixture {index}"),
+        ] {
+            let renamed = subfixtures(naming);
+            assert_eq!(renamed.len(), by_reference.len());
+            for ((name, id), (reference, reference_id)) in renamed.iter().zip(&by_reference) {
+                assert_eq!(id, reference_id);
+                let index = (id.index + 1).to_string();
+                let expected = expected
+                    .replace("{index}", &index)
+                    .replace("{reference}", reference);
+                assert_eq!(name, &expected);
+            }
+        }
+
+        let mut by_geometry: Vec<_> = by_reference
+            .iter()
+            .map(|(name, id)| (id.geometry, name.as_str()))
+            .collect();
+        by_geometry.sort();
+        assert_eq!(by_geometry, [(1, "Pixel 1"), (2, "Pixel 2")]);
+        let mut indices: Vec<_> = by_reference.iter().map(|(_, id)| id.index).collect();
+        indices.sort();
+        assert_eq!(indices, [0, 1]);
+    }
+
+    #[test]
+    fn template_channels_can_be_kept_uninstantiated() {
+        let description = r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Bar" FixtureTypeID="00000000-0000-0000-0000-000000000001">
+    <Geometries>
+        <Geometry Name="Body">
+            <GeometryReference Name="Pixel 1" Geometry="Pixel">
+                <Break DMXBreak="1" DMXOffset="2"/>
+            </GeometryReference>
+            <GeometryReference Name="Pixel 2" Geometry="Pixel">
+                <Break DMXBreak="1" DMXOffset="3"/>
+            </GeometryReference>
+        </Geometry>
+        <Geometry Name="Pixel"/>
+    </Geometries>
+    <DMXModes>
+        <DMXMode Name="Mode" Geometry="Body">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Body">
+                    <LogicalChannel Attribute="Dimmer"/>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Offset="1" Geometry="Pixel">
+                    <LogicalChannel Attribute="ColorAdd_R"/>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>
+</GDTF>"#;
+        let parse = |keep_template_channels| {
+            let options = ParseOptions {
+                keep_template_channels,
+                ..Default::default()
+            };
+            crate::parser::parse_description(description.into(), &options)
+                .unwrap()
+                .gdtf
+        };
+        let instantiated = parse(false);
+        let mut kept = parse(true);
+
+        let mode = kept.dmx_modes().first().unwrap();
+        assert!(!mode.templates_instantiated());
+        assert!(mode.subfixtures.is_empty());
+        let names: Vec<_> = mode.all_channels().map(|ch| ch.name.to_string()).collect();
+        assert_eq!(names, ["Body_Dimmer"]);
+        let templates = mode.template_channels();
+        assert_eq!(templates.len(), 1);
+        let template = templates.first().unwrap();
+        assert_eq!(template.attribute, &"ColorAdd_R".into_valid());
+        let references: Vec<_> = template
+            .instances
+            .iter()
+            .map(|i| {
+                kept.geometries
+                    .get_by_index(i.reference)
+                    .unwrap()
+                    .name
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(references, ["Pixel 1", "Pixel 2"]);
+        assert_eq!(serialize_gdtf(&kept), serialize_gdtf(&instantiated));
+
+        let summary = |gdtf: &Gdtf| {
+            gdtf.dmx_modes()
+                .first()
+                .unwrap()
+                .subfixtures
+                .iter()
+                .map(|sf| {
+                    let ch = sf.channels.first().unwrap();
+                    (sf.id, ch.name.to_string(), ch.offsets.to_vec())
+                })
+                .collect::<Vec<_>>()
+        };
+        let mode = kept.dmx_mode_mut(0).unwrap();
+        mode.instantiate_templates();
+        assert!(mode.templates_instantiated());
+        assert_eq!(summary(&kept), summary(&instantiated));
+        assert_eq!(
+            summary(&kept)
+                .into_iter()
+                .map(|(_, _, offsets)| offsets)
+                .collect::<Vec<_>>(),
+            [vec![2], vec![3]]
+        );
+    }
+
+    #[test]
+    fn default_fallback_is_configurable() {
+        let input = r#"
+<FixtureType>
+    <DMXModes>
+        <DMXMode Geometry="Body" Name="Mode 1">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Geometry="Body" InitialFunction="Body_Dimmer.Dimmer.Open" Offset="1">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Attribute="Dimmer" DMXFrom="0/1" Default="10/1" Name="Dimmer"/>
+                        <ChannelFunction Attribute="Dimmer" DMXFrom="200/1" Default="255/1" Name="Open"/>
+                    </LogicalChannel>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Geometry="Body" Offset="2">
+                    <LogicalChannel Attribute="Shutter1">
+                        <ChannelFunction Attribute="Shutter1" DMXFrom="0/1" Default="20/1" Name="Shutter"/>
+                    </LogicalChannel>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Geometry="Body" Offset="3">
+                    <LogicalChannel Attribute="Pan"/>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let ft = doc.root_element();
+
+        let defaults = |default_fallback| {
+            let mut parsed = ParsedGdtf::default();
+            parsed
+                .gdtf
+                .geometries
+                .add_top_level(Geometry {
+                    name: "Body".into_valid(),
+                    ..Default::default()
+                })
+                .unwrap();
+            let options = ParseOptions {
+                default_fallback,
+                ..Default::default()
+            };
+            parsed.parse_dmx_modes(ft, &Default::default(), &options);
+            assert_eq!(parsed.problems.len(), 0);
+
+            parsed
+                .gdtf
+                .dmx_modes()
+                .first()
+                .unwrap()
+                .channels
+                .iter()
+                .map(|ch| (ch.default, ch.default_source))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            defaults(DefaultFallback::FirstChannelFunction),
+            [
+                (255, DefaultSource::InitialFunction),
+                (20, DefaultSource::FirstChannelFunction),
+                (0, DefaultSource::RawChannelFunction),
+            ]
+        );
+        assert_eq!(
+            defaults(DefaultFallback::RawChannelFunction),
+            [
+                (255, DefaultSource::InitialFunction),
+                (0, DefaultSource::RawChannelFunction),
+                (0, DefaultSource::RawChannelFunction),
+            ]
+        );
+    }
+
+    #[test]
+    fn initial_function_is_validated() {
+        let input = r#"
+<FixtureType>
+    <DMXModes>
+        <DMXMode Geometry="Body" Name="Mode 1">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Geometry="Body" InitialFunction="Body_Dimmer.Dimmer.Open" Offset="1">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Attribute="Dimmer" DMXFrom="0/1" Default="10/1" Name="Dimmer"/>
+                        <ChannelFunction Attribute="Dimmer" DMXFrom="200/1" Default="255/1" Name="Open"/>
+                    </LogicalChannel>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Geometry="Body" InitialFunction="Body_Shutter1.Shutter2.Shutter" Offset="2">
+                    <LogicalChannel Attribute="Shutter1">
+                        <ChannelFunction Attribute="Shutter1" DMXFrom="0/1" Default="20/1" Name="Shutter"/>
+                    </LogicalChannel>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Geometry="Body" InitialFunction="Body_Zoom.Zoom.Wide" Offset="3">
+                    <LogicalChannel Attribute="Zoom">
+                        <ChannelFunction Attribute="Zoom" DMXFrom="0/1" Default="30/1" Name="Zoom"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let ft = doc.root_element();
+        let mut parsed = ParsedGdtf::default();
+        parsed
+            .gdtf
+            .geometries
+            .add_top_level(Geometry {
+                name: "Body".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        let options = ParseOptions {
+            channel_naming: ChannelNaming::XmlOrder,
+            ..Default::default()
+        };
+        parsed.parse_dmx_modes(ft, &Default::default(), &options);
+
+        // wrong logical channel and unknown channel function
+        assert_eq!(parsed.problems.len(), 2);
+        assert!(parsed
+            .problems
+            .iter()
+            .all(|p| matches!(p.problem(), Problem::InvalidInitialFunction { .. })));
+
+        let mode = parsed.gdtf.dmx_modes().first().unwrap();
+        let initial_functions: Vec<_> = mode
+            .channels
+            .iter()
+            .map(|ch| {
+                (
+                    ch.initial_function(mode).map(|chf| chf.name.to_string()),
+                    ch.default,
+                    ch.default_source,
+                )
+            })
+            .collect();
+        assert_eq!(
+            initial_functions,
+            [
+                (Some("Open".into()), 255, DefaultSource::InitialFunction),
+                (None, 20, DefaultSource::FirstChannelFunction),
+                (None, 30, DefaultSource::FirstChannelFunction),
+            ]
+        );
+    }
+
+    #[test]
+    fn overlapping_offsets_are_kept_with_problem() {
+        let input = r#"
+<FixtureType>
+    <DMXModes>
+        <DMXMode Geometry="Body" Name="Mode 1">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Geometry="Body" Offset="1,2">
+                    <LogicalChannel Attribute="Dimmer"/>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Geometry="Body" Offset="2">
+                    <LogicalChannel Attribute="Shutter1"/>
+                </DMXChannel>
+                <DMXChannel DMXBreak="2" Geometry="Body" Offset="5">
+                    <LogicalChannel Attribute="Pan"/>
+                </DMXChannel>
+                <DMXChannel Geometry="Body" Offset="None">
+                    <LogicalChannel Attribute="Tilt"/>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let ft = doc.root_element();
+        let mut parsed = ParsedGdtf::default();
+        parsed
+            .gdtf
+            .geometries
+            .add_top_level(Geometry {
+                name: "Body".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        parsed.parse_dmx_modes(ft, &Default::default(), &Default::default());
+
+        assert_eq!(parsed.problems.len(), 1);
+        assert!(matches!(
+            parsed.problems.first().unwrap().problem(),
+            Problem::OverlappingChannelOffsets { offset: 2, first, second, .. }
+            if *first == "Body_Dimmer" && *second == "Body_Shutter1"
+        ));
+
+        let mode = parsed.gdtf.dmx_modes().first().unwrap();
+        assert_eq!(mode.channels.len(), 4);
+        assert_eq!(
+            mode.footprint(),
+            Footprint {
+                breaks: vec![
+                    BreakFootprint {
+                        dmx_break: 1.try_into().unwrap(),
+                        channel_count: 2,
+                        highest_offset: 2,
+                    },
+                    BreakFootprint {
+                        dmx_break: 2.try_into().unwrap(),
+                        channel_count: 1,
+                        highest_offset: 5,
+                    },
+                ],
+                virtual_channels: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn channel_geometry_is_looked_up_after_deduplication() {
+        let input = r#"
+<GDTF DataVersion="1.2">
+    <FixtureType Name="Test">
+        <Geometries>
+            <Geometry Name="Body A">
+                <Beam Name="Beam"/>
+            </Geometry>
+            <Geometry Name="Body B">
+                <Beam Name="Beam"/>
+            </Geometry>
+        </Geometries>
+        <DMXModes>
+            <DMXMode Geometry="Body A" Name="Mode A">
+                <DMXChannels>
+                    <DMXChannel DMXBreak="1" Geometry="Beam" Offset="1">
+                        <LogicalChannel Attribute="Dimmer"/>
+                    </DMXChannel>
+                </DMXChannels>
+            </DMXMode>
+            <DMXMode Geometry="Body B" Name="Mode B">
+                <DMXChannels>
+                    <DMXChannel DMXBreak="1" Geometry="Beam" Offset="1">
+                        <LogicalChannel Attribute="Dimmer"/>
+                    </DMXChannel>
+                </DMXChannels>
+            </DMXMode>
+        </DMXModes>
+    </FixtureType>
+</GDTF>"#;
+        let parsed = crate::parser::parse_description(input.into(), &Default::default()).unwrap();
+
+        let geometries = &parsed.gdtf.geometries;
+        let channel_geometries: Vec<_> = parsed
+            .gdtf
+            .dmx_modes()
+            .iter()
+            .map(|mode| {
+                let channel = mode.channels.first().unwrap();
+                let geometry = geometries.get_by_index(channel.geometry).unwrap();
+                (geometry.name.to_string(), channel.name.to_string())
+            })
+            .collect();
+        assert_eq!(
+            channel_geometries,
+            [
+                ("Beam".into(), "Beam_Dimmer".into()),
+                ("Beam (in Body B)".into(), "Beam (in Body B)_Dimmer".into()),
+            ]
+        );
+        assert!(parsed
+            .problems
+            .iter()
+            .all(|p| !matches!(p.problem(), Problem::UnknownGeometry(_))));
+    }
+
+    #[test]
+    fn clipped_mode_master_range_keeps_original_values() {
+        let input = r#"
+<FixtureType>
+    <DMXModes>
+        <DMXMode Geometry="Body" Name="Mode 1">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Geometry="Body" Offset="1">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Attribute="Dimmer" DMXFrom="0/1" Name="Dimmer"/>
+                        <ChannelFunction Attribute="StrobeModeShutter" DMXFrom="128/1" Name="Strobe"/>
+                    </LogicalChannel>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Geometry="Body" Offset="2">
+                    <LogicalChannel Attribute="Zoom">
+                        <ChannelFunction Attribute="Zoom" DMXFrom="0/1" Name="Zoom"
+                            ModeMaster="Body_Dimmer.Dimmer.Dimmer" ModeFrom="100/1" ModeTo="200/1"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let ft = doc.root_element();
+        let mut parsed = ParsedGdtf::default();
+        parsed
+            .gdtf
+            .geometries
+            .add_top_level(Geometry {
+                name: "Body".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        parsed.parse_dmx_modes(ft, &Default::default(), &Default::default());
+
+        assert_eq!(parsed.problems.len(), 1);
+        assert!(matches!(
+            parsed.problems.first().unwrap().problem(),
+            Problem::ClippedModeMasterRange {
+                mode_from: 100,
+                mode_to: 200,
+                master_from: 0,
+                master_to: 127,
+                ..
+            }
+        ));
+
+        let mode = parsed.gdtf.dmx_modes().first().unwrap();
+        let dimmer = mode
+            .channels
+            .first()
+            .unwrap()
+            .defined_functions()
+            .first()
+            .copied()
+            .unwrap();
+        let zoom = mode
+            .channels
+            .get(1)
+            .unwrap()
+            .defined_functions()
+            .first()
+            .copied()
+            .unwrap();
+        let edge_ind = mode.channel_functions.find_edge(dimmer, zoom).unwrap();
+        let edge = mode.channel_functions.edge_weight(edge_ind).unwrap();
+        assert_eq!((edge.from, edge.to), (100, 127));
+        assert_eq!((edge.original_from, edge.original_to), (100, 200));
     }
 }
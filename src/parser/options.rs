@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use petgraph::graph::NodeIndex;
+
+use crate::{
+    channel::Channel,
+    geometries::Geometries,
+    name::{IntoValidName, Name},
+};
+
+use super::recovery::{DefaultRecovery, RecoveryPolicy};
+
+/// Options to customize how a GDTF file is parsed.
+///
+/// The `Default` options are used by [`parse`](crate::parse).
+#[derive(Debug, Default, Clone)]
+pub struct ParseOptions {
+    /// GeometryReferences that point to another GeometryReference are invalid.
+    /// By default, a problem is raised and the link is dropped. If this is set,
+    /// the chain is followed to the first geometry that is not a reference and
+    /// the link is made to it instead. A problem is still raised.
+    ///
+    /// Some legacy files from GDTF Share rely on this.
+    pub flatten_geometry_reference_chains: bool,
+    /// How names of DMX channels are synthesized.
+    pub channel_naming: ChannelNaming,
+    /// How subfixtures are named. Use [`Subfixture::id`] to identify them
+    /// across parses.
+    ///
+    /// [`Subfixture::id`]: crate::dmx_modes::Subfixture::id
+    pub subfixture_naming: SubfixtureNaming,
+    /// Geometry positions that are not affine or whose rotation is not
+    /// orthonormal always raise a problem. By default, they are kept as they
+    /// are. If this is set, they are replaced by the closest orthonormal
+    /// affine position with the same translation.
+    pub orthonormalize_geometry_positions: bool,
+    /// Where the default value of a DMX channel comes from if its
+    /// InitialFunction is absent or invalid.
+    pub default_fallback: DefaultFallback,
+    /// Template channels are instantiated for every GeometryReference to their
+    /// template geometry, with one subfixture per reference. If this is set,
+    /// a parsed mode only has its main channels in
+    /// [`DmxMode::subfixtures`] and [`DmxMode::all_channels`], and
+    /// [`DmxMode::template_channels`] lists every template channel once with
+    /// its references. [`DmxMode::instantiate_templates`] adds the
+    /// subfixtures.
+    ///
+    /// The instances are still resolved while parsing, so problems with
+    /// ModeMasters and Break offsets are reported either way.
+    ///
+    /// [`DmxMode::subfixtures`]: crate::dmx_modes::DmxMode::subfixtures
+    /// [`DmxMode::all_channels`]: crate::dmx_modes::DmxMode::all_channels
+    /// [`DmxMode::template_channels`]: crate::dmx_modes::DmxMode::template_channels
+    /// [`DmxMode::instantiate_templates`]: crate::dmx_modes::DmxMode::instantiate_templates
+    pub keep_template_channels: bool,
+    /// How the parser recovers from problems. `None` uses the built-in
+    /// behavior, see [`RecoveryPolicy`].
+    pub recovery_policy: Option<Arc<dyn RecoveryPolicy>>,
+}
+
+impl ParseOptions {
+    pub(crate) fn policy(&self) -> &dyn RecoveryPolicy {
+        self.recovery_policy.as_deref().unwrap_or(&DefaultRecovery)
+    }
+}
+
+/// Strategy for synthesizing channel names.
+///
+/// GDTF does not give DMX channels a name. Instead, they are referenced by
+/// `{Geometry}_{Attribute}`, where `Attribute` is the attribute of the first
+/// logical channel. The original components are available on
+/// [`Channel`](crate::channel::Channel) regardless of the strategy.
+///
+/// All instances of a template channel share their position in the DMX mode.
+/// For them, `Geometry` is the GeometryReference, followed by the geometry in
+/// the template if the channel is not on the top-level template geometry,
+/// e.g. `Pixel 1_Beam`. `XmlOrder` prefixes the name of the
+/// GeometryReference.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelNaming {
+    /// `{Geometry}_{Attribute}`, e.g. `Beam_Dimmer`
+    #[default]
+    GeometryAttribute,
+    /// `{Index}_{Geometry}_{Attribute}`, e.g. `3_Beam_Dimmer`, where `Index`
+    /// is the 1-based position of the DMXChannel in the DMX mode
+    IndexGeometryAttribute,
+    /// `Channel {Index}`, e.g. `Channel 3`, where `Index` is the 1-based
+    /// position of the DMXChannel in the DMX mode. Instances of template
+    /// channels are named `{GeometryReference}_Channel {Index}`, e.g.
+    /// `Pixel 1_Channel 3`.
+    XmlOrder,
+}
+
+/// Strategy for naming subfixtures, the instances of template channels.
+///
+/// By default, subfixtures are named after their GeometryReference. That name
+/// changes if the GeometryReference is renamed, e.g. during deduplication of
+/// geometry names.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SubfixtureNaming {
+    /// `{GeometryReference}`, e.g. `Pixel 1`
+    #[default]
+    GeometryReference,
+    /// `{Index}_{GeometryReference}`, e.g. `1_Pixel 1`, where `Index` is the
+    /// 1-based position of the subfixture in the DMX mode
+    IndexGeometryReference,
+    /// `Subfixture {Index}`, e.g. `Subfixture 1`, where `Index` is the 1-based
+    /// position of the subfixture in the DMX mode
+    Index,
+}
+
+/// Fallback for the default value of a DMX channel without a usable
+/// InitialFunction. The chosen source is recorded in
+/// [`Channel::default_source`](crate::channel::Channel::default_source).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultFallback {
+    /// Use the first channel function after the raw DMX channel function, or
+    /// the raw DMX channel function if there is none. This is what GDTF
+    /// Builder displays.
+    #[default]
+    FirstChannelFunction,
+    /// Always use the raw DMX channel function, i.e. a default of 0
+    RawChannelFunction,
+}
+
+impl ChannelNaming {
+    /// Name of `ch` with this strategy. `renamed` replaces the name of one
+    /// geometry, e.g. to get the name from before that geometry was renamed.
+    /// `None` if a geometry of the channel is missing.
+    pub(crate) fn name_channel(
+        &self,
+        geometries: &Geometries,
+        ch: &Channel,
+        renamed: Option<(NodeIndex, &Name)>,
+    ) -> Option<Name> {
+        let name_of = |i: NodeIndex| match renamed {
+            Some((geometry, name)) if geometry == i => Some(name),
+            _ => geometries.get_by_index(i).ok().map(|g| &g.name),
+        };
+        let reference = match ch.reference() {
+            Some(reference) => Some(name_of(reference)?),
+            None => None,
+        };
+        // instances of template channels are named after their
+        // GeometryReference, like in GDTF, so they don't collide
+        let geometry = match (reference, ch.template_geometry) {
+            (Some(reference), Some(template_geometry))
+                if !geometries.is_top_level(template_geometry) =>
+            {
+                format!("{reference}_{}", name_of(template_geometry)?)
+            }
+            (Some(reference), _) => reference.to_string(),
+            (None, _) => name_of(ch.geometry)?.to_string(),
+        };
+        Some(self.channel_name(&geometry, &ch.attribute, ch.index_in_mode, reference))
+    }
+
+    /// `reference` is the GeometryReference of an instance of a template
+    /// channel, which `geometry` already includes
+    fn channel_name(
+        &self,
+        geometry: &str,
+        attribute: &Name,
+        index: usize,
+        reference: Option<&Name>,
+    ) -> Name {
+        let index = index + 1;
+        match (self, reference) {
+            (ChannelNaming::GeometryAttribute, _) => format!("{geometry}_{attribute}"),
+            (ChannelNaming::IndexGeometryAttribute, _) => {
+                format!("{index}_{geometry}_{attribute}")
+            }
+            (ChannelNaming::XmlOrder, None) => format!("Channel {index}"),
+            (ChannelNaming::XmlOrder, Some(reference)) => format!("{reference}_Channel {index}"),
+        }
+        .into_valid()
+    }
+}
+
+impl SubfixtureNaming {
+    pub(crate) fn subfixture_name(&self, reference: &Name, index: usize) -> Name {
+        let index = index + 1;
+        match self {
+            SubfixtureNaming::GeometryReference => return reference.clone(),
+            SubfixtureNaming::IndexGeometryReference => format!("{index}_{reference}"),
+            SubfixtureNaming::Index => format!("Subfixture {index}"),
+        }
+        .into_valid()
+    }
+}
@@ -5,9 +5,10 @@ use std::str::FromStr;
 use roxmltree::Node;
 
 use crate::{
+    float::{parse_comma_decimal, Fixed},
     name::Name,
     parser::problems::{HandleProblem, ProblemsMut},
-    Problem, ProblemAt, Problems,
+    Action, Problem, ProblemAt, Problems,
 };
 
 pub(crate) trait GetXmlAttribute<'a> {
@@ -15,7 +16,7 @@ pub(crate) trait GetXmlAttribute<'a> {
 
     fn parse_required_attribute<T: FromStr>(&self, attr: &str) -> Result<T, ProblemAt>
     where
-        <T as FromStr>::Err: std::error::Error + 'static;
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static;
 
     fn parse_required_attribute_or<T: FromStr>(
         &self,
@@ -24,7 +25,7 @@ pub(crate) trait GetXmlAttribute<'a> {
         problems: &mut Problems,
     ) -> T
     where
-        <T as FromStr>::Err: std::error::Error + 'static;
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static;
 
     fn parse_required_attribute_or_default<T: FromStr + Default>(
         &self,
@@ -32,18 +33,26 @@ pub(crate) trait GetXmlAttribute<'a> {
         problems: &mut Problems,
     ) -> T
     where
-        <T as FromStr>::Err: std::error::Error + 'static;
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static;
 
     fn parse_attribute<T: FromStr>(&self, attr: &str) -> Option<Result<T, ProblemAt>>
     where
-        <T as FromStr>::Err: std::error::Error + 'static;
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static;
 
     fn map_parse_attribute<T: FromStr, F>(&self, attr: &str, f: F) -> Option<Result<T, ProblemAt>>
     where
-        <T as FromStr>::Err: std::error::Error + 'static,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
         F: FnOnce(Option<&str>) -> Option<&str>;
 
+    fn parse_float_attribute(
+        &self,
+        attr: &str,
+        problems: &mut impl ProblemsMut,
+    ) -> Option<Result<f64, ProblemAt>>;
+
     fn name(&self, node_index_in_xml_parent: usize, problems: &mut impl ProblemsMut) -> Name;
+
+    fn unknown_attributes(&self, known: &[&str]) -> Vec<(String, String)>;
 }
 
 impl<'a> GetXmlAttribute<'a> for Node<'_, '_> {
@@ -64,7 +73,7 @@ impl<'a> GetXmlAttribute<'a> for Node<'_, '_> {
     /// returned.
     fn parse_required_attribute<T: FromStr>(&self, attr: &str) -> Result<T, ProblemAt>
     where
-        <T as FromStr>::Err: std::error::Error + 'static,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
     {
         let content = self.required_attribute(attr)?;
         parse_attribute_content(self, content, attr)
@@ -77,10 +86,10 @@ impl<'a> GetXmlAttribute<'a> for Node<'_, '_> {
         problems: &mut Problems,
     ) -> T
     where
-        <T as FromStr>::Err: std::error::Error + 'static,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
     {
         self.parse_required_attribute(attr)
-            .ok_or_handled_by("using default", problems)
+            .ok_or_handled_by(Action::UsedDefault { value: None }, problems)
             .unwrap_or(opt)
     }
 
@@ -90,10 +99,10 @@ impl<'a> GetXmlAttribute<'a> for Node<'_, '_> {
         problems: &mut Problems,
     ) -> T
     where
-        <T as FromStr>::Err: std::error::Error + 'static,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
     {
         self.parse_required_attribute(attr)
-            .ok_or_handled_by("using default", problems)
+            .ok_or_handled_by(Action::UsedDefault { value: None }, problems)
             .unwrap_or_default()
     }
 
@@ -101,7 +110,7 @@ impl<'a> GetXmlAttribute<'a> for Node<'_, '_> {
     /// returns None.
     fn parse_attribute<T: FromStr>(&self, attr: &str) -> Option<Result<T, ProblemAt>>
     where
-        <T as FromStr>::Err: std::error::Error + 'static,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
     {
         let content = self.attribute(attr)?;
         Some(parse_attribute_content(self, content, attr))
@@ -114,13 +123,26 @@ impl<'a> GetXmlAttribute<'a> for Node<'_, '_> {
     /// conditions.
     fn map_parse_attribute<T: FromStr, F>(&self, attr: &str, f: F) -> Option<Result<T, ProblemAt>>
     where
-        <T as FromStr>::Err: std::error::Error + 'static,
+        <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
         F: FnOnce(Option<&str>) -> Option<&str>,
     {
         let content = f(self.attribute(attr))?;
         Some(parse_attribute_content(self, content, attr))
     }
 
+    /// Parse an optional XML attribute to a float. If it is missing, returns
+    /// None.
+    ///
+    /// A ',' as decimal separator is accepted, raising a problem.
+    fn parse_float_attribute(
+        &self,
+        attr: &str,
+        problems: &mut impl ProblemsMut,
+    ) -> Option<Result<f64, ProblemAt>> {
+        let content = self.attribute(attr)?;
+        Some(parse_float_content(self, content, attr, problems))
+    }
+
     /// Get attribute "Name" and parse to GDTF type Name.
     ///
     /// node_index_in_xml_parent is a 0-based index.
@@ -138,12 +160,23 @@ impl<'a> GetXmlAttribute<'a> for Node<'_, '_> {
                 let default_name =
                     Name::valid_default(self.tag_name().name(), node_index_in_xml_parent);
                 p.handled_by(
-                    format!("using default name '{default_name}'"),
+                    Action::UsedDefaultName {
+                        name: default_name.clone(),
+                    },
                     problems_provider,
                 );
                 default_name
             })
     }
+
+    /// Collect all attributes whose name is not in `known` in document order,
+    /// so they can be preserved.
+    fn unknown_attributes(&self, known: &[&str]) -> Vec<(String, String)> {
+        self.attributes()
+            .filter(|a| !known.contains(&a.name()))
+            .map(|a| (a.name().to_owned(), a.value().to_owned()))
+            .collect()
+    }
 }
 
 pub(crate) fn parse_attribute_content<T: FromStr>(
@@ -152,7 +185,7 @@ pub(crate) fn parse_attribute_content<T: FromStr>(
     attr: &str,
 ) -> Result<T, ProblemAt>
 where
-    <T as FromStr>::Err: std::error::Error + 'static,
+    <T as FromStr>::Err: std::error::Error + Send + Sync + 'static,
 {
     content.parse::<T>().map_err(|err| {
         Problem::InvalidAttribute {
@@ -166,7 +199,28 @@ where
     })
 }
 
-fn parse_name_or_fix(node: &Node, name: &str, problems: &mut impl ProblemsMut) -> Name {
+/// Parse attribute content to a float. If it uses ',' as decimal separator, a
+/// problem is raised and the value is used anyway.
+pub(crate) fn parse_float_content(
+    node: &Node,
+    content: &str,
+    attr: &str,
+    problems: &mut impl ProblemsMut,
+) -> Result<f64, ProblemAt> {
+    parse_attribute_content(node, content, attr).or_else(|p| {
+        let v = parse_comma_decimal(content).ok_or(p)?;
+        Problem::CommaDecimalSeparator {
+            attr: attr.to_owned(),
+            tag: node.tag_name().name().to_owned(),
+            content: content.to_owned(),
+        }
+        .at(node)
+        .handled_by(Action::used(Fixed(v).to_string()), problems);
+        Ok(v)
+    })
+}
+
+pub(crate) fn parse_name_or_fix(node: &Node, name: &str, problems: &mut impl ProblemsMut) -> Name {
     Name::try_from(name).unwrap_or_else(|e| {
         let fixed = e.fixed.clone();
         Problem::InvalidAttribute {
@@ -177,7 +231,7 @@ fn parse_name_or_fix(node: &Node, name: &str, problems: &mut impl ProblemsMut) -
             expected_type: "Name".to_owned(),
         }
         .at(node)
-        .handled_by("replacing invalid chars with '□'", problems);
+        .handled_by(Action::ReplacedInvalidChars, problems);
         fixed
     })
 }
@@ -196,22 +250,22 @@ mod tests {
         let mut problems: Problems = vec![];
         assert_eq!(
             n.parse_required_attribute::<u32>("attr")
-                .ok_or_handled_by("setting None", &mut problems),
+                .ok_or_handled_by(Action::SetToNone { field: None }, &mut problems),
             Some(300)
         );
         assert_eq!(
             n.parse_required_attribute::<String>("attr")
-                .ok_or_handled_by("setting None", &mut problems),
+                .ok_or_handled_by(Action::SetToNone { field: None }, &mut problems),
             Some("300".to_string())
         );
         assert_eq!(
             n.parse_required_attribute::<u8>("attr")
-                .ok_or_handled_by("setting None", &mut problems),
+                .ok_or_handled_by(Action::SetToNone { field: None }, &mut problems),
             None
         );
         assert_eq!(
             n.parse_required_attribute::<String>("missing")
-                .ok_or_handled_by("setting None", &mut problems),
+                .ok_or_handled_by(Action::SetToNone { field: None }, &mut problems),
             None
         );
         assert_eq!(problems.len(), 2);
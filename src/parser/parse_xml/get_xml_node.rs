@@ -1,9 +1,11 @@
 use roxmltree::Node;
 
-use crate::{Problem, ProblemAt};
+use crate::{source_location::SourceLocation, Problem, ProblemAt};
 
 pub(crate) trait GetXmlNode {
     fn find_required_child(&self, tag: &str) -> Result<Node, ProblemAt>;
+    fn source_location(&self) -> SourceLocation;
+    fn verbatim(&self) -> &str;
 }
 
 impl GetXmlNode for Node<'_, '_> {
@@ -18,4 +20,21 @@ impl GetXmlNode for Node<'_, '_> {
             .at(self)),
         }
     }
+
+    /// Get the position of the node in the document.
+    fn source_location(&self) -> SourceLocation {
+        let pos = self.document().text_pos_at(self.position());
+        SourceLocation {
+            line: pos.row,
+            column: pos.col,
+        }
+    }
+
+    /// The XML text of the node as it is in the document.
+    fn verbatim(&self) -> &str {
+        self.document()
+            .input_text()
+            .get(self.range())
+            .unwrap_or_default()
+    }
 }
@@ -0,0 +1,103 @@
+use roxmltree::Node;
+
+use crate::{
+    parser::{parse_xml::get_xml_attribute::parse_float_content, problems::ProblemsMut},
+    physical_value::PhysicalValue,
+    Action, Problem,
+};
+
+/// Parse an optional attribute holding a physical value.
+///
+/// A missing attribute or the sentinel "None" result in `default` without a
+/// problem. Infinite values are clamped to the biggest finite value of the
+/// same sign and NaN is replaced by `default`, both raising a problem.
+pub(crate) fn parse_physical_value(
+    node: &Node,
+    attr: &str,
+    default: PhysicalValue,
+    problems: &mut impl ProblemsMut,
+) -> PhysicalValue {
+    let Some(content) = node.attribute(attr).map(str::trim).filter(|s| *s != "None") else {
+        return default;
+    };
+    let v = match parse_float_content(node, content, attr, problems) {
+        Ok(v) => v,
+        Err(p) => {
+            p.handled_by(Action::used_default(default), problems);
+            return default;
+        }
+    };
+    if let Ok(v) = PhysicalValue::try_from(v) {
+        return v;
+    }
+    let problem = Problem::NonFinitePhysicalValue {
+        attr: attr.to_owned(),
+        tag: node.tag_name().name().to_owned(),
+        value: v,
+    }
+    .at(node);
+    match PhysicalValue::saturating(v) {
+        Ok(clamped) => {
+            problem.handled_by(Action::used(clamped.to_string()), problems);
+            clamped
+        }
+        Err(_) => {
+            problem.handled_by(Action::used_default(default), problems);
+            default
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Problems;
+
+    use super::*;
+
+    fn run(xml: &str) -> (PhysicalValue, Problems) {
+        let doc = roxmltree::Document::parse(xml).unwrap();
+        let mut problems: Problems = vec![];
+        let v = parse_physical_value(
+            &doc.root_element(),
+            "PhysicalTo",
+            PhysicalValue::ONE,
+            &mut problems,
+        );
+        (v, problems)
+    }
+
+    #[test]
+    fn physical_values() {
+        let (v, problems) = run(r#"<ChannelFunction PhysicalTo="2.500000"/>"#);
+        assert_eq!(v.value(), 2.5);
+        assert!(problems.is_empty());
+
+        let (v, problems) = run(r#"<ChannelFunction/>"#);
+        assert_eq!(v, PhysicalValue::ONE);
+        assert!(problems.is_empty());
+
+        let (v, problems) = run(r#"<ChannelFunction PhysicalTo="None"/>"#);
+        assert_eq!(v, PhysicalValue::ONE);
+        assert!(problems.is_empty());
+
+        let (v, problems) = run(r#"<ChannelFunction PhysicalTo="-inf"/>"#);
+        assert_eq!(v.value(), f64::MIN);
+        assert_eq!(problems.len(), 1);
+
+        let (v, problems) = run(r#"<ChannelFunction PhysicalTo="NaN"/>"#);
+        assert_eq!(v, PhysicalValue::ONE);
+        assert_eq!(problems.len(), 1);
+
+        let (v, problems) = run(r#"<ChannelFunction PhysicalTo="one"/>"#);
+        assert_eq!(v, PhysicalValue::ONE);
+        assert_eq!(problems.len(), 1);
+
+        let (v, problems) = run(r#"<ChannelFunction PhysicalTo="2,500000"/>"#);
+        assert_eq!(v.value(), 2.5);
+        assert_eq!(problems.len(), 1);
+        assert!(matches!(
+            problems.first().unwrap().problem(),
+            Problem::CommaDecimalSeparator { attr, .. } if attr == "PhysicalTo"
+        ));
+    }
+}
@@ -1,10 +1,13 @@
 //! The problems system is the core error handling mechanism in the GDTF parser.
 //! See the unit tests of this module for an example of how to do it.
 
+use std::ops::Range;
+
 use roxmltree::{Node, TextPos};
 
 use crate::{
-    channel_offsets::OffsetError, dmx_break::Break, geometries::GeometriesError, name::Name,
+    channel_offsets::OffsetError, data_version::DataVersion, dmx_break::Break,
+    geometries::GeometriesError, name::Name, severity::Severity, source_location::SourceLocation,
     GdtfError,
 };
 
@@ -22,7 +25,151 @@ impl ProblemsMut for Problems {
 #[error("{p}; {action}")]
 pub struct HandledProblem {
     p: ProblemAt,
-    pub action: String,
+    pub action: Action,
+}
+
+/// The action the parser took to recover from a problem.
+///
+/// The `Display` implementation provides a short human readable description.
+/// The name of the variant is available with `<&str>::from(&action)`.
+#[derive(Debug, Clone, PartialEq, strum::IntoStaticStr)]
+pub enum Action {
+    /// A default value was used. `value` contains its display representation
+    /// if it is known.
+    UsedDefault { value: Option<String> },
+    /// A default name was generated and used.
+    UsedDefaultName { name: Name },
+    /// A specific substitute was used, described by `value`.
+    Used { value: String },
+    /// The first top-level geometry was used in place of an unusable one.
+    UsedFirstTopLevelGeometry { name: Name },
+    /// A value was set to None. `field` names the value if it is not obvious.
+    SetToNone { field: Option<String> },
+    /// A node was renamed to avoid a conflict.
+    RenamedTo { name: Name },
+    /// A node was renamed but still had to be ignored due to another problem.
+    RenamedToButIgnoredNode { name: Name },
+    /// Invalid characters in a name were replaced with '□'.
+    ReplacedInvalidChars,
+    /// A previously parsed value was overwritten.
+    OverwrotePreviousValue,
+    /// The XML node was ignored.
+    IgnoredNode,
+    /// Renaming a duplicate node failed and it was ignored.
+    DeduplicationFailed,
+    /// Something other than a node was ignored, described by `what`.
+    Ignored { what: String },
+    /// The current item was skipped.
+    Skipped,
+    /// The reference was not added to the geometry templates.
+    NotAddedReference,
+    /// A chain of GeometryReferences was resolved to the geometry at its end.
+    FlattenedReferenceChain { to: Name },
+    /// The model was not linked to the geometry.
+    NotLinkedModel,
+    /// A collection was left empty, described by `what`.
+    LeftEmpty { what: String },
+    /// A top-level GeometryReference was kept even though it is not useful.
+    KeptTopLevelGeometryReference,
+    /// The value was kept even though it is invalid.
+    KeptAsIs,
+    /// The node was kept verbatim in the extensions of its parent.
+    KeptAsExtension,
+}
+
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::UsedDefault { value: None } => write!(f, "using default"),
+            Action::UsedDefault { value: Some(v) } => write!(f, "using default {v}"),
+            Action::UsedDefaultName { name } => write!(f, "using default name '{name}'"),
+            Action::Used { value } => write!(f, "using {value}"),
+            Action::UsedFirstTopLevelGeometry { name } => {
+                write!(f, "using first top-level geometry '{name}'")
+            }
+            Action::SetToNone { field: None } => write!(f, "setting None"),
+            Action::SetToNone { field: Some(field) } => write!(f, "setting {field} to None"),
+            Action::RenamedTo { name } => write!(f, "renamed to '{name}'"),
+            Action::RenamedToButIgnoredNode { name } => write!(
+                f,
+                "renamed to '{name}' but still ignoring node due to some other error"
+            ),
+            Action::ReplacedInvalidChars => write!(f, "replacing invalid chars with '□'"),
+            Action::OverwrotePreviousValue => write!(f, "overwriting previous value"),
+            Action::IgnoredNode => write!(f, "ignoring node"),
+            Action::DeduplicationFailed => write!(f, "deduplication failed, ignoring node"),
+            Action::Ignored { what } => write!(f, "ignoring {what}"),
+            Action::Skipped => write!(f, "skipping"),
+            Action::NotAddedReference => write!(f, "not adding reference"),
+            Action::FlattenedReferenceChain { to } => {
+                write!(f, "flattening reference chain to '{to}'")
+            }
+            Action::NotLinkedModel => write!(f, "not linking model"),
+            Action::LeftEmpty { what } => write!(f, "leaving {what} empty"),
+            Action::KeptTopLevelGeometryReference => write!(
+                f,
+                "keeping GeometryReference, but it is useless because a top-level \
+                GeometryReference can only be used for a DMX mode that is offset from another \
+                one, which is useless because one can just change the start address on the \
+                lighting console"
+            ),
+            Action::KeptAsIs => write!(f, "keeping it as is"),
+            Action::KeptAsExtension => write!(f, "keeping it as extension"),
+        }
+    }
+}
+
+impl Action {
+    /// Shorthand for `Action::UsedDefault` with a known value.
+    pub(crate) fn used_default(value: impl ToString) -> Self {
+        Action::UsedDefault {
+            value: Some(value.to_string()),
+        }
+    }
+
+    /// Shorthand for `Action::Used`.
+    pub(crate) fn used(value: impl Into<String>) -> Self {
+        Action::Used {
+            value: value.into(),
+        }
+    }
+
+    /// Shorthand for `Action::Ignored`.
+    pub(crate) fn ignored(what: impl Into<String>) -> Self {
+        Action::Ignored { what: what.into() }
+    }
+
+    /// Shorthand for `Action::LeftEmpty`.
+    pub(crate) fn left_empty(what: impl Into<String>) -> Self {
+        Action::LeftEmpty { what: what.into() }
+    }
+
+    /// Actions that drop part of the file are errors, actions that keep it
+    /// with a substitute or as is are warnings.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Action::RenamedToButIgnoredNode { .. }
+            | Action::IgnoredNode
+            | Action::DeduplicationFailed
+            | Action::Ignored { .. }
+            | Action::Skipped
+            | Action::NotAddedReference
+            | Action::NotLinkedModel
+            | Action::LeftEmpty { .. } => Severity::Error,
+            Action::UsedDefault { .. }
+            | Action::UsedDefaultName { .. }
+            | Action::Used { .. }
+            | Action::UsedFirstTopLevelGeometry { .. }
+            | Action::SetToNone { .. }
+            | Action::RenamedTo { .. }
+            | Action::ReplacedInvalidChars
+            | Action::OverwrotePreviousValue
+            | Action::FlattenedReferenceChain { .. }
+            | Action::KeptTopLevelGeometryReference
+            | Action::KeptAsIs
+            | Action::KeptAsExtension => Severity::Warning,
+        }
+    }
 }
 
 /// A recoverable problem in a GDTF file, with position information.
@@ -31,6 +178,9 @@ pub struct HandledProblem {
 pub struct ProblemAt {
     p: Problem,
     at: TextPos,
+    range: Range<usize>,
+    attribute_range: Option<Range<usize>>,
+    xml_path: String,
 }
 
 /// A recoverable kind of problem in a GDTF file.
@@ -47,11 +197,23 @@ pub enum Problem {
         attr: String,
         tag: String,
         content: String,
-        source: Box<dyn std::error::Error>,
+        source: Box<dyn std::error::Error + Send + Sync>,
         expected_type: String,
     },
     #[error("unexpected node <{0}>")]
     UnexpectedXmlNode(String),
+    #[error("duplicate node <{0}>")]
+    DuplicateXmlNode(String),
+    #[error("non-finite physical value {value} in attribute {attr} on <{tag}>")]
+    NonFinitePhysicalValue {
+        attr: String,
+        tag: String,
+        value: f64,
+    },
+    #[error("Position of geometry '{0}' is not affine, the last row must be {{0,0,0,1}}")]
+    NonAffinePosition(Name),
+    #[error("rotation in Position of geometry '{0}' is not orthonormal")]
+    NonOrthonormalPosition(Name),
     #[error("duplicate Geometry name '{0}'")]
     DuplicateGeometryName(Name),
     #[error(
@@ -67,6 +229,34 @@ pub enum Problem {
     UnknownGeometry(Name),
     #[error("invalid GeometryReference: {0}")]
     InvalidGeometryReference(GeometriesError),
+    #[error("GeometryReference '{reference}' references another GeometryReference '{referenced}'")]
+    GeometryReferenceChain { reference: Name, referenced: Name },
+    #[error("duplicate Model name '{0}'")]
+    DuplicateModelName(Name),
+    #[error("unknown Model '{0}' referenced")]
+    UnknownModel(Name),
+    #[error("duplicate Wheel name '{0}'")]
+    DuplicateWheelName(Name),
+    #[error("unknown Wheel '{0}' referenced")]
+    UnknownWheel(Name),
+    #[error(
+        "attribute '{attr}' of {tag} is only defined up to GDTF {last_version}, \
+        the file declares DataVersion {data_version}"
+    )]
+    LegacyAttribute {
+        attr: String,
+        tag: String,
+        last_version: DataVersion,
+        data_version: DataVersion,
+    },
+    #[error(
+        "attribute {attr}=\"{content}\" on <{tag}> uses ',' as decimal separator instead of '.'"
+    )]
+    CommaDecimalSeparator {
+        attr: String,
+        tag: String,
+        content: String,
+    },
     #[error("geometry '{geometry}' of DMX mode '{mode}' is not top level")]
     NonTopLevelDmxModeGeometry { geometry: Name, mode: Name },
     #[error("got {0} bytes for channel but only up to 4 are supported")]
@@ -83,6 +273,18 @@ pub enum Problem {
         mode_from: u32,
         mode_to: u32,
     },
+    #[error(
+        "ModeFrom/ModeTo {mode_from} to {mode_to} of channel function '{name}' in DMX mode \
+        '{dmx_mode}' exceed the DMX range {master_from} to {master_to} of the ModeMaster"
+    )]
+    ClippedModeMasterRange {
+        name: Name,
+        dmx_mode: Name,
+        mode_from: u32,
+        mode_to: u32,
+        master_from: u32,
+        master_to: u32,
+    },
     #[error("channel with name {0} not found in mode {1}")]
     UnknownChannel(Name, Name),
     #[error("channel function with name {name} not found in mode {mode}")]
@@ -95,6 +297,14 @@ pub enum Problem {
     },
     #[error("GeometryReference is missing the break {br} for channel {ch} in mode {mode}")]
     MissingBreakInReference { br: String, ch: Name, mode: Name },
+    #[error(
+        "GeometryReference '{reference}' has break {br}, which no channel in mode {mode} uses"
+    )]
+    UnusedBreakInReference {
+        br: String,
+        reference: Name,
+        mode: Name,
+    },
     #[error("break of channel {ch} in mode {mode} was Overwrite but did not reference template geometry")]
     InvalidBreakOverwrite { ch: Name, mode: Name },
     #[error("ModeMaster '{mode_master}' in channel '{ch}' of mode '{mode}' is ambiguous because the linked \
@@ -104,6 +314,16 @@ pub enum Problem {
         ch: Name,
         mode: Name,
     },
+    #[error(
+        "channels '{first}' and '{second}' in DMX mode '{mode}' both occupy offset {offset} in break {dmx_break}"
+    )]
+    OverlappingChannelOffsets {
+        mode: Name,
+        dmx_break: Break,
+        offset: u16,
+        first: Name,
+        second: Name,
+    },
     #[error("Gdtf domain error: {0}")]
     GdtfError(#[from] GdtfError),
     #[error("invalid channel offsets: {0}")]
@@ -112,36 +332,157 @@ pub enum Problem {
         "unexpected condition occured. This is a fault in opengdtf. \
         Please open an issue at https://github.com/Firionus/opengdtf/issues/new. Caused by: {0}"
     )]
-    Unexpected(Box<dyn std::error::Error>),
+    Unexpected(Box<dyn std::error::Error + Send + Sync>),
 }
 
 impl Problem {
     /// Add position information to problem based on Node where it occured.
     pub(crate) fn at(self, node: &Node) -> ProblemAt {
+        let attribute_range = self
+            .attribute()
+            .and_then(|attr| node.attribute_node(attr))
+            .map(|attr| attr.range());
         ProblemAt {
-            p: self,
             at: node.document().text_pos_at(node.position()),
+            range: node.range(),
+            attribute_range,
+            xml_path: xml_path(node),
+            p: self,
+        }
+    }
+
+    /// Name of the XML attribute the problem is about, if any
+    pub fn attribute(&self) -> Option<&str> {
+        match self {
+            Problem::XmlAttributeMissing { attr, .. }
+            | Problem::InvalidAttribute { attr, .. }
+            | Problem::NonFinitePhysicalValue { attr, .. }
+            | Problem::LegacyAttribute { attr, .. }
+            | Problem::CommaDecimalSeparator { attr, .. } => Some(attr),
+            _ => None,
         }
     }
+
+    /// Stable, machine-readable code of the kind of problem, like `OG1023`.
+    ///
+    /// Codes are never changed or reused, so they can be matched on in reports
+    /// across versions of opengdtf.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Problem::XmlNodeMissing { .. } => "OG1001",
+            Problem::XmlAttributeMissing { .. } => "OG1002",
+            Problem::InvalidAttribute { .. } => "OG1003",
+            Problem::UnexpectedXmlNode(_) => "OG1004",
+            Problem::DuplicateXmlNode(_) => "OG1005",
+            Problem::NonFinitePhysicalValue { .. } => "OG1006",
+            Problem::NonAffinePosition(_) => "OG1007",
+            Problem::NonOrthonormalPosition(_) => "OG1008",
+            Problem::DuplicateGeometryName(_) => "OG1009",
+            Problem::DuplicateDmxBreak { .. } => "OG1010",
+            Problem::UnexpectedTopLevelGeometryReference(_) => "OG1011",
+            Problem::UnknownGeometry(_) => "OG1012",
+            Problem::InvalidGeometryReference(_) => "OG1013",
+            Problem::GeometryReferenceChain { .. } => "OG1014",
+            Problem::DuplicateModelName(_) => "OG1015",
+            Problem::UnknownModel(_) => "OG1016",
+            Problem::NonTopLevelDmxModeGeometry { .. } => "OG1017",
+            Problem::UnsupportedByteCount(_) => "OG1018",
+            Problem::MissingModeFromOrTo(_) => "OG1019",
+            Problem::UnreachableChannelFunction { .. } => "OG1020",
+            Problem::UnknownChannel(..) => "OG1021",
+            Problem::UnknownChannelFunction { .. } => "OG1022",
+            Problem::InvalidInitialFunction { .. } => "OG1023",
+            Problem::MissingBreakInReference { .. } => "OG1024",
+            Problem::InvalidBreakOverwrite { .. } => "OG1025",
+            Problem::AmbiguousModeMaster { .. } => "OG1026",
+            Problem::OverlappingChannelOffsets { .. } => "OG1027",
+            Problem::GdtfError(_) => "OG1028",
+            Problem::ChannelOffsetError(_) => "OG1029",
+            Problem::Unexpected(_) => "OG1030",
+            Problem::DuplicateWheelName(_) => "OG1031",
+            Problem::UnknownWheel(_) => "OG1032",
+            Problem::LegacyAttribute { .. } => "OG1033",
+            Problem::CommaDecimalSeparator { .. } => "OG1034",
+            Problem::ClippedModeMasterRange { .. } => "OG1035",
+            Problem::UnusedBreakInReference { .. } => "OG1036",
+        }
+    }
+}
+
+/// See [`ProblemAt::xml_path`]
+fn xml_path(node: &Node) -> String {
+    let mut segments: Vec<String> = node
+        .ancestors()
+        .filter(|n| n.is_element() && n.parent_element().is_some())
+        .map(|n| {
+            let tag = n.tag_name().name();
+            let same_tag = |s: &Node| s.is_element() && s.tag_name().name() == tag;
+            let siblings = n
+                .parent()
+                .map_or(0, |p| p.children().filter(same_tag).count());
+            if siblings > 1 {
+                // prev_siblings includes the node itself, so this is 1-based
+                let index = n.prev_siblings().filter(same_tag).count();
+                format!("{tag}[{index}]")
+            } else {
+                tag.to_owned()
+            }
+        })
+        .collect();
+    if segments.is_empty() {
+        // the root element itself
+        return node.tag_name().name().to_owned();
+    }
+    segments.reverse();
+    segments.join("/")
 }
 
 impl ProblemAt {
     /// Specify what action was taken to resolve the problem and then push it
     /// onto the problems.
-    pub(crate) fn handled_by<T: Into<String>>(
-        self,
-        action: T,
-        problems_provider: &mut impl ProblemsMut,
-    ) {
-        problems_provider.problems_mut().push(HandledProblem {
-            p: self,
-            action: action.into(),
-        });
+    pub(crate) fn handled_by(self, action: Action, problems_provider: &mut impl ProblemsMut) {
+        problems_provider
+            .problems_mut()
+            .push(HandledProblem { p: self, action });
     }
 
     pub fn problem(&self) -> &Problem {
         &self.p
     }
+
+    /// Position of the start of the XML node in `description.xml`
+    pub fn location(&self) -> SourceLocation {
+        SourceLocation {
+            line: self.at.row,
+            column: self.at.col,
+        }
+    }
+
+    /// Byte range of the XML node in `description.xml`, including its children
+    pub fn byte_range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Byte range of the attribute the problem is about in `description.xml`,
+    /// `None` if the problem is not about an attribute or the attribute is
+    /// missing on the node
+    pub fn attribute_byte_range(&self) -> Option<Range<usize>> {
+        self.attribute_range.clone()
+    }
+
+    /// Name of the XML attribute the problem is about, see
+    /// [`Problem::attribute`]
+    pub fn attribute(&self) -> Option<&str> {
+        self.p.attribute()
+    }
+
+    /// Path of element names from below the root element to the XML node,
+    /// like `FixtureType/DMXModes/DMXMode[2]/DMXChannels/DMXChannel[5]`. The
+    /// 1-based index is only added if the parent has several elements of
+    /// that name.
+    pub fn xml_path(&self) -> &str {
+        &self.xml_path
+    }
 }
 
 /// Implementors can provide a mutable reference to a Problems vector.
@@ -159,15 +500,19 @@ pub(crate) trait ProblemsMut {
     // This might require more implementations for ProblemsMut.
 }
 
-pub(crate) trait HandleProblem<T, S: Into<String>> {
-    fn ok_or_handled_by(self, action: S, problems: &mut impl ProblemsMut) -> Option<T>;
+pub(crate) trait HandleProblem<T> {
+    fn ok_or_handled_by(self, action: Action, problems: &mut impl ProblemsMut) -> Option<T>;
 }
 
-impl<T, S: Into<String>> HandleProblem<T, S> for Result<T, ProblemAt> {
+impl<T> HandleProblem<T> for Result<T, ProblemAt> {
     /// Specify what action will be taken to resolve a possible Err(Problem),
     /// push it onto problems and return None. If the result is Ok(v), Some(v)
     /// is returned instead.
-    fn ok_or_handled_by(self, action: S, problems_provider: &mut impl ProblemsMut) -> Option<T> {
+    fn ok_or_handled_by(
+        self,
+        action: Action,
+        problems_provider: &mut impl ProblemsMut,
+    ) -> Option<T> {
         match self {
             Ok(t) => Some(t),
             Err(p) => {
@@ -180,12 +525,12 @@ impl<T, S: Into<String>> HandleProblem<T, S> for Result<T, ProblemAt> {
 
 // TODO maybe add Result<_, Problem>.err_at(&Node) -> Result<_, ProblemAt>
 
-pub(crate) trait HandleOption<T, S: Into<Box<dyn std::error::Error>>> {
+pub(crate) trait HandleOption<T, S: Into<Box<dyn std::error::Error + Send + Sync>>> {
     fn ok_or_unexpected(self, why: S) -> Result<T, Problem>;
     fn ok_or_unexpected_at(self, why: S, at: &Node) -> Result<T, ProblemAt>;
 }
 
-impl<T, S: Into<Box<dyn std::error::Error>>> HandleOption<T, S> for Option<T> {
+impl<T, S: Into<Box<dyn std::error::Error + Send + Sync>>> HandleOption<T, S> for Option<T> {
     fn ok_or_unexpected(self, description: S) -> Result<T, Problem> {
         self.ok_or_else(|| Problem::Unexpected(description.into()))
     }
@@ -195,11 +540,13 @@ impl<T, S: Into<Box<dyn std::error::Error>>> HandleOption<T, S> for Option<T> {
     }
 }
 
-pub(crate) trait TransformUnexpected<T, E: Into<Box<dyn std::error::Error>>> {
+pub(crate) trait TransformUnexpected<T, E: Into<Box<dyn std::error::Error + Send + Sync>>> {
     fn unexpected_err_at(self, at: &Node) -> Result<T, ProblemAt>;
 }
 
-impl<T, E: Into<Box<dyn std::error::Error>>> TransformUnexpected<T, E> for Result<T, E> {
+impl<T, E: Into<Box<dyn std::error::Error + Send + Sync>>> TransformUnexpected<T, E>
+    for Result<T, E>
+{
     fn unexpected_err_at(self, at: &Node) -> Result<T, ProblemAt> {
         self.map_err(|e| Problem::Unexpected(e.into()).at(at))
     }
@@ -209,10 +556,44 @@ impl HandledProblem {
     pub fn problem(&self) -> &Problem {
         &self.p.p
     }
+
+    /// Severity of the problem, see [`Action::severity`]
+    pub fn severity(&self) -> Severity {
+        self.action.severity()
+    }
+
+    /// The problem with its position information
+    pub fn problem_at(&self) -> &ProblemAt {
+        &self.p
+    }
+}
+
+/// Serialized as a flat struct with the problem code and message, the position
+/// in `description.xml` and the action taken, for machine-readable reports.
+#[cfg(feature = "serde")]
+impl serde::Serialize for HandledProblem {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let location = self.p.location();
+        let mut s = serializer.serialize_struct("HandledProblem", 9)?;
+        s.serialize_field("code", self.problem().code())?;
+        s.serialize_field("message", &self.problem().to_string())?;
+        s.serialize_field("line", &location.line)?;
+        s.serialize_field("column", &location.column)?;
+        s.serialize_field("byte_range", &self.p.byte_range())?;
+        s.serialize_field("xml_path", self.p.xml_path())?;
+        s.serialize_field("attribute", &self.p.attribute())?;
+        s.serialize_field("action", <&str>::from(&self.action))?;
+        s.serialize_field("action_message", &self.action.to_string())?;
+        s.end()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::name::IntoValidName;
+
     use super::*;
 
     #[test]
@@ -225,17 +606,137 @@ mod tests {
         // encounter a problem
         Problem::UnexpectedXmlNode("whatsThis".into())
             .at(&node)
-            .handled_by("ignoring node", &mut problems);
+            .handled_by(Action::IgnoredNode, &mut problems);
 
         assert!(matches!(
             problems.first().unwrap(),
             HandledProblem {
-                action,
+                action: Action::IgnoredNode,
                 p: ProblemAt {
                     at,
-                    p: Problem::UnexpectedXmlNode(..)
+                    p: Problem::UnexpectedXmlNode(..),
+                    ..
                 }
-            } if action == "ignoring node" && at == &TextPos{row: 1, col: 1}
-        ))
+            } if at == &TextPos{row: 1, col: 1}
+        ));
+        assert_eq!(
+            problems.first().unwrap().to_string(),
+            "unexpected node <whatsThis> (line 1:1); ignoring node"
+        );
+    }
+
+    #[test]
+    fn severity_follows_action() {
+        let mut problems = Problems::new();
+        let binding = roxmltree::Document::parse(r#"<DMXMode />"#).unwrap();
+        let node = binding.root_element();
+
+        Problem::UnexpectedXmlNode("DMXMode".into())
+            .at(&node)
+            .handled_by(Action::IgnoredNode, &mut problems);
+        Problem::UnexpectedXmlNode("DMXMode".into())
+            .at(&node)
+            .handled_by(Action::used_default("DMXMode 1"), &mut problems);
+
+        let severities: Vec<_> = problems.iter().map(HandledProblem::severity).collect();
+        assert_eq!(severities, [Severity::Error, Severity::Warning]);
+    }
+
+    #[test]
+    fn problem_position_information() {
+        let xml = r#"<GDTF>
+    <FixtureType>
+        <DMXModes>
+            <DMXMode/>
+            <DMXMode>
+                <DMXChannels>
+                    <DMXChannel Offset="1"/>
+                    <DMXChannel Offset="x"/>
+                </DMXChannels>
+            </DMXMode>
+        </DMXModes>
+    </FixtureType>
+</GDTF>"#;
+        let doc = roxmltree::Document::parse(xml).unwrap();
+        let node = doc
+            .descendants()
+            .find(|n| n.has_tag_name("DMXChannel") && n.attribute("Offset") == Some("x"))
+            .unwrap();
+
+        let p = Problem::XmlAttributeMissing {
+            attr: "Offset".into(),
+            tag: "DMXChannel".into(),
+        }
+        .at(&node);
+        assert_eq!(
+            p.xml_path(),
+            "FixtureType/DMXModes/DMXMode[2]/DMXChannels/DMXChannel[2]"
+        );
+        assert_eq!(
+            p.location(),
+            SourceLocation {
+                line: 8,
+                column: 21
+            }
+        );
+        assert_eq!(xml.get(p.byte_range()), Some(r#"<DMXChannel Offset="x"/>"#));
+        assert_eq!(p.attribute(), Some("Offset"));
+        assert_eq!(
+            p.attribute_byte_range().and_then(|r| xml.get(r)),
+            Some(r#"Offset="x""#)
+        );
+
+        let p = Problem::UnexpectedXmlNode("GDTF".into()).at(&doc.root_element());
+        assert_eq!(p.xml_path(), "GDTF");
+        assert_eq!(p.attribute_byte_range(), None);
+    }
+
+    #[test]
+    fn action_display() {
+        assert_eq!(
+            Action::UsedDefault { value: None }.to_string(),
+            "using default"
+        );
+        assert_eq!(Action::used_default(0).to_string(), "using default 0");
+        assert_eq!(
+            Action::RenamedTo {
+                name: "Beam (duplicate 1)".into_valid()
+            }
+            .to_string(),
+            "renamed to 'Beam (duplicate 1)'"
+        );
+        assert_eq!(
+            Action::SetToNone {
+                field: Some("ref_ft".into())
+            }
+            .to_string(),
+            "setting ref_ft to None"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_handled_problem() {
+        let mut problems = Problems::new();
+        let doc = roxmltree::Document::parse("<GDTF>\n  <whatsThis />\n</GDTF>").unwrap();
+        let node = doc.root_element().first_element_child().unwrap();
+        Problem::UnexpectedXmlNode("whatsThis".into())
+            .at(&node)
+            .handled_by(Action::IgnoredNode, &mut problems);
+
+        assert_eq!(
+            serde_json::to_value(&problems).unwrap(),
+            serde_json::json!([{
+                "code": "OG1004",
+                "message": "unexpected node <whatsThis>",
+                "line": 2,
+                "column": 3,
+                "byte_range": {"start": 9, "end": 22},
+                "xml_path": "whatsThis",
+                "attribute": null,
+                "action": "IgnoredNode",
+                "action_message": "ignoring node",
+            }])
+        );
     }
 }
@@ -0,0 +1,168 @@
+use roxmltree::Node;
+
+use crate::{
+    protocols::{ArtNet, Citp, DmxMap, OpenSoundControl, PosiStageNet, Sacn},
+    Action, ParsedGdtf, Problem,
+};
+
+use super::{
+    parse_xml::{GetXmlAttribute, GetXmlNode},
+    problems::HandleProblem,
+};
+
+impl ParsedGdtf {
+    /// Parse the Protocols node.
+    ///
+    /// Older GDTF versions don't have this node, so a missing node leaves the
+    /// protocols empty without raising a Problem.
+    pub(crate) fn parse_protocols(&mut self, fixture_type: Node) {
+        let Some(protocols) = fixture_type
+            .children()
+            .find(|n| n.has_tag_name("Protocols"))
+        else {
+            return;
+        };
+
+        for n in protocols.children().filter(|n| n.is_element()) {
+            let duplicate = match n.tag_name().name() {
+                "Art-Net" => {
+                    let maps = self.parse_dmx_maps(n);
+                    self.gdtf
+                        .protocols
+                        .art_net
+                        .replace(ArtNet { maps })
+                        .is_some()
+                }
+                "sACN" => {
+                    let maps = self.parse_dmx_maps(n);
+                    self.gdtf.protocols.sacn.replace(Sacn { maps }).is_some()
+                }
+                "PosiStageNet" => self
+                    .gdtf
+                    .protocols
+                    .posi_stage_net
+                    .replace(PosiStageNet)
+                    .is_some(),
+                "OpenSoundControl" => self
+                    .gdtf
+                    .protocols
+                    .open_sound_control
+                    .replace(OpenSoundControl)
+                    .is_some(),
+                "CITP" => self.gdtf.protocols.citp.replace(Citp).is_some(),
+                // TODO parse RDM information
+                "FTRDM" => self
+                    .gdtf
+                    .protocols
+                    .ftrdm
+                    .replace(n.verbatim().to_owned())
+                    .is_some(),
+                tag => {
+                    Problem::UnexpectedXmlNode(tag.into())
+                        .at(&n)
+                        .handled_by(Action::IgnoredNode, self);
+                    false
+                }
+            };
+            if duplicate {
+                Problem::DuplicateXmlNode(n.tag_name().name().into())
+                    .at(&n)
+                    .handled_by(Action::OverwrotePreviousValue, self);
+            }
+        }
+    }
+
+    fn parse_dmx_maps(&mut self, protocol: Node) -> Vec<DmxMap> {
+        protocol
+            .children()
+            .filter(|n| n.is_element())
+            .filter_map(|n| {
+                if !n.has_tag_name("Map") {
+                    Problem::UnexpectedXmlNode(n.tag_name().name().into())
+                        .at(&n)
+                        .handled_by(Action::IgnoredNode, self);
+                    return None;
+                }
+                let key = n
+                    .parse_required_attribute("Key")
+                    .ok_or_handled_by(Action::IgnoredNode, self)?;
+                let value = n
+                    .parse_required_attribute("Value")
+                    .ok_or_handled_by(Action::IgnoredNode, self)?;
+                Some(DmxMap { key, value })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocols::Protocols;
+
+    use super::*;
+
+    #[test]
+    fn parse_protocols() {
+        let input = r#"
+<FixtureType>
+    <Protocols>
+        <FTRDM ManufacturerID="0x0000" DeviceModelID="0x0000"/>
+        <Art-Net>
+            <Map Key="3" Value="255"/>
+            <Map Key="invalid" Value="255"/>
+            <NotAMap/>
+        </Art-Net>
+        <sACN/>
+        <CITP/>
+        <CITP/>
+        <DMX-Over-Carrier-Pigeon/>
+    </Protocols>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let mut parsed = ParsedGdtf::default();
+        parsed.parse_protocols(doc.root_element());
+
+        let mut problems = parsed.problems.iter().map(|p| p.problem());
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::InvalidAttribute { attr, .. } if attr == "Key"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::UnexpectedXmlNode(tag) if tag == "NotAMap"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::DuplicateXmlNode(tag) if tag == "CITP"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::UnexpectedXmlNode(tag) if tag == "DMX-Over-Carrier-Pigeon"
+        ));
+        assert!(problems.next().is_none());
+
+        assert_eq!(
+            parsed.gdtf.protocols,
+            Protocols {
+                art_net: Some(ArtNet {
+                    maps: vec![DmxMap { key: 3, value: 255 }]
+                }),
+                sacn: Some(Sacn::default()),
+                posi_stage_net: None,
+                open_sound_control: None,
+                citp: Some(Citp),
+                ftrdm: Some(r#"<FTRDM ManufacturerID="0x0000" DeviceModelID="0x0000"/>"#.into()),
+            }
+        );
+    }
+
+    #[test]
+    fn missing_protocols_are_empty() {
+        let doc = roxmltree::Document::parse("<FixtureType/>").unwrap();
+        let mut parsed = ParsedGdtf::default();
+        parsed.parse_protocols(doc.root_element());
+
+        assert!(parsed.problems.is_empty());
+        assert_eq!(parsed.gdtf.protocols, Protocols::default());
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt::Debug;
+
+use super::HandledProblem;
+
+/// Decides how the parser recovers from problems, set with
+/// [`ParseOptions::recovery_policy`](crate::ParseOptions::recovery_policy).
+///
+/// Every method defaults to the built-in behavior of the parser, so an
+/// implementation only overrides the categories it wants to tune. For example,
+/// to reject files with missing names:
+///
+/// ```
+/// use opengdtf::{HandledProblem, Problem, Recovery, RecoveryPolicy};
+///
+/// #[derive(Debug)]
+/// struct RequireNames;
+///
+/// impl RecoveryPolicy for RequireNames {
+///     fn recover(&self, handled: &HandledProblem) -> Recovery {
+///         match handled.problem() {
+///             Problem::XmlAttributeMissing { attr, .. } if attr == "Name" => Recovery::Abort,
+///             _ => Recovery::Continue,
+///         }
+///     }
+/// }
+/// ```
+pub trait RecoveryPolicy: Debug + Send + Sync {
+    /// Called for every problem after the parser handled it. If any problem is
+    /// answered with [`Recovery::Abort`], parsing fails with
+    /// [`Error::Aborted`](crate::Error::Aborted) for the first such problem.
+    fn recover(&self, _handled: &HandledProblem) -> Recovery {
+        Recovery::Continue
+    }
+
+    /// How geometries are handled whose name is already taken by another
+    /// geometry.
+    fn duplicate_geometry_name(&self) -> DuplicateNameRecovery {
+        DuplicateNameRecovery::Rename
+    }
+}
+
+/// The built-in recovery behavior, used if no policy is set
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultRecovery;
+
+impl RecoveryPolicy for DefaultRecovery {}
+
+/// Whether parsing goes on after a problem, see [`RecoveryPolicy::recover`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// Keep the action the parser took and continue
+    Continue,
+    /// Fail parsing
+    Abort,
+}
+
+/// See [`RecoveryPolicy::duplicate_geometry_name`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateNameRecovery {
+    /// Rename the geometry, e.g. to `Beam (duplicate 1)`, and keep its
+    /// children
+    #[default]
+    Rename,
+    /// Ignore the geometry and its children
+    Skip,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{parser::parse_description, Action, Error, ParseOptions, Problem};
+
+    use super::*;
+
+    const DESCRIPTION: &str = r#"<GDTF DataVersion="1.2">
+<FixtureType Name="Test" FixtureTypeID="00000000-0000-0000-0000-000000000000">
+    <Geometries>
+        <Geometry Name="Body">
+            <Geometry Name="Beam"/>
+        </Geometry>
+        <Geometry Name="Beam"/>
+        <Geometry/>
+    </Geometries>
+</FixtureType>
+</GDTF>"#;
+
+    #[derive(Debug)]
+    struct RequireNames;
+
+    impl RecoveryPolicy for RequireNames {
+        fn recover(&self, handled: &HandledProblem) -> Recovery {
+            match handled.problem() {
+                Problem::XmlAttributeMissing { attr, .. } if attr == "Name" => Recovery::Abort,
+                _ => Recovery::Continue,
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct SkipDuplicates;
+
+    impl RecoveryPolicy for SkipDuplicates {
+        fn duplicate_geometry_name(&self) -> DuplicateNameRecovery {
+            DuplicateNameRecovery::Skip
+        }
+    }
+
+    #[test]
+    fn default_policy_renames_duplicates() {
+        let parsed = parse_description(DESCRIPTION.into(), &Default::default()).unwrap();
+        assert!(parsed
+            .gdtf
+            .geometries
+            .get_index(&"Beam (duplicate 1)".try_into().unwrap())
+            .is_some());
+    }
+
+    #[test]
+    fn custom_policies() {
+        let options = ParseOptions {
+            recovery_policy: Some(Arc::new(RequireNames)),
+            ..Default::default()
+        };
+        let Err(Error::Aborted(handled)) = parse_description(DESCRIPTION.into(), &options) else {
+            panic!("parsing was not aborted");
+        };
+        assert!(matches!(
+            handled.problem(),
+            Problem::XmlAttributeMissing { attr, .. } if attr == "Name"
+        ));
+
+        let options = ParseOptions {
+            recovery_policy: Some(Arc::new(SkipDuplicates)),
+            ..Default::default()
+        };
+        let parsed = parse_description(DESCRIPTION.into(), &options).unwrap();
+        assert_eq!(parsed.gdtf.geometries.names().len(), 3);
+        assert!(parsed.problems.iter().any(|p| matches!(
+            (p.problem(), &p.action),
+            (Problem::DuplicateGeometryName(_), Action::IgnoredNode)
+        )));
+    }
+}
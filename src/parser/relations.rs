@@ -0,0 +1,190 @@
+use roxmltree::Node;
+
+use crate::{
+    channel::Channel,
+    dmx_modes::DmxMode,
+    geometries::Geometries,
+    name::{IntoValidName, Name},
+    relations::{Relation, RelationType},
+    Action, ParsedGdtf, Problem, ProblemAt,
+};
+
+use super::{parse_xml::GetXmlAttribute, problems::HandleProblem};
+
+impl ParsedGdtf {
+    /// Parse the optional Relations node of a DMX mode.
+    ///
+    /// Must be called after the channels of the mode were added, because
+    /// master and follower are checked to exist. Relations whose master or
+    /// follower can't be found are ignored.
+    pub(crate) fn parse_relations(
+        &mut self,
+        mode_node: Node,
+        mode_ind: usize,
+    ) -> Result<(), ProblemAt> {
+        let Some(relations) = mode_node.children().find(|n| n.has_tag_name("Relations")) else {
+            return Ok(());
+        };
+
+        for (i, n) in relations.children().filter(|n| n.is_element()).enumerate() {
+            if !n.has_tag_name("Relation") {
+                Problem::UnexpectedXmlNode(n.tag_name().name().into())
+                    .at(&n)
+                    .handled_by(Action::IgnoredNode, self);
+                continue;
+            }
+            let name = n.name(i, self);
+            let Some(relation) = self
+                .parse_relation(n, name, mode_ind)
+                .ok_or_handled_by(Action::IgnoredNode, self)
+            else {
+                continue;
+            };
+            self.gdtf
+                .dmx_mode_mut(mode_ind)
+                .map_err(|e| Problem::from(e).at(&n))?
+                .relations
+                .push(relation);
+        }
+        Ok(())
+    }
+
+    fn parse_relation(&self, n: Node, name: Name, mode_ind: usize) -> Result<Relation, ProblemAt> {
+        let mode = self
+            .gdtf
+            .dmx_mode(mode_ind)
+            .map_err(|e| Problem::from(e).at(&n))?;
+        let geometries = &self.gdtf.geometries;
+
+        let master: Name = n.parse_required_attribute("Master")?;
+        if find_channel(mode, geometries, master.as_str()).is_none() {
+            return Err(Problem::UnknownChannel(master, mode.name.clone()).at(&n));
+        }
+
+        let follower = n.required_attribute("Follower")?;
+        check_follower(mode, geometries, follower).map_err(|p| p.at(&n))?;
+
+        let relation_type: RelationType = n.parse_required_attribute("Type")?;
+
+        Ok(Relation {
+            name,
+            master,
+            follower: follower.to_owned(),
+            relation_type,
+        })
+    }
+}
+
+/// Find a channel of the mode by its GDTF name, `{Geometry}_{Attribute}`.
+/// Template channels are found by the name of the template geometry.
+fn find_channel<'a>(
+    mode: &'a DmxMode,
+    geometries: &Geometries,
+    gdtf_name: &str,
+) -> Option<&'a Channel> {
+    mode.all_channels().find(|ch| {
+        geometries
+            .get_by_index(ch.template_geometry.unwrap_or(ch.geometry))
+            .map(|g| format!("{}_{}", g.name, ch.attribute) == gdtf_name)
+            .unwrap_or(false)
+    })
+}
+
+/// Check that a link to a channel function, like `Beam_Dimmer.Dimmer.Dimmer`,
+/// resolves in the mode
+fn check_follower(mode: &DmxMode, geometries: &Geometries, link: &str) -> Result<(), Problem> {
+    let mut path = link.split('.');
+    let channel_name = path.next().unwrap_or_default();
+    let channel = find_channel(mode, geometries, channel_name)
+        .ok_or_else(|| Problem::UnknownChannel(channel_name.into_valid(), mode.name.clone()))?;
+    let (attribute, chf_name) = match (path.next(), path.next(), path.next()) {
+        (Some(attribute), Some(chf_name), None) => (attribute, chf_name),
+        _ => ("", link),
+    };
+    let found = channel.attribute == attribute
+        && channel
+            .defined_functions()
+            .iter()
+            .filter_map(|i| mode.channel_functions.node_weight(*i))
+            .any(|chf| chf.name == chf_name);
+    if !found {
+        return Err(Problem::UnknownChannelFunction {
+            name: chf_name.into_valid(),
+            mode: mode.name.clone(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::Geometry;
+
+    use super::*;
+
+    #[test]
+    fn parse_relations() {
+        let input = r#"
+<FixtureType>
+    <DMXModes>
+        <DMXMode Geometry="Body" Name="Mode 1">
+            <DMXChannels>
+                <DMXChannel DMXBreak="1" Geometry="Body" Offset="1">
+                    <LogicalChannel Attribute="Dimmer">
+                        <ChannelFunction Attribute="Dimmer" DMXFrom="0/1" Name="Dimmer"/>
+                    </LogicalChannel>
+                </DMXChannel>
+                <DMXChannel DMXBreak="1" Geometry="Body" Offset="2">
+                    <LogicalChannel Attribute="ColorAdd_R">
+                        <ChannelFunction Attribute="ColorAdd_R" DMXFrom="0/1" Name="Red"/>
+                    </LogicalChannel>
+                </DMXChannel>
+            </DMXChannels>
+            <Relations>
+                <Relation Name="Virtual Dimmer" Master="Body_Dimmer" Follower="Body_ColorAdd_R.ColorAdd_R.Red" Type="Multiply"/>
+                <Relation Name="Unknown Master" Master="Body_Shutter1" Follower="Body_ColorAdd_R.ColorAdd_R.Red" Type="Multiply"/>
+                <Relation Name="Unknown Follower" Master="Body_Dimmer" Follower="Body_ColorAdd_R.ColorAdd_R.Green" Type="Override"/>
+                <Relation Name="Invalid Type" Master="Body_Dimmer" Follower="Body_ColorAdd_R.ColorAdd_R.Red" Type="Add"/>
+            </Relations>
+        </DMXMode>
+    </DMXModes>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let mut parsed = ParsedGdtf::default();
+        parsed
+            .gdtf
+            .geometries
+            .add_top_level(Geometry {
+                name: "Body".into_valid(),
+                ..Default::default()
+            })
+            .unwrap();
+        parsed.parse_dmx_modes(doc.root_element(), &Default::default(), &Default::default());
+
+        let mut problems = parsed.problems.iter().map(|p| p.problem());
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::UnknownChannel(ch, _) if ch == "Body_Shutter1"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::UnknownChannelFunction { name, .. } if name == "Green"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::InvalidAttribute { attr, .. } if attr == "Type"
+        ));
+        assert!(problems.next().is_none());
+
+        let mode = parsed.gdtf.dmx_modes().first().unwrap();
+        assert_eq!(
+            mode.relations,
+            vec![Relation {
+                name: "Virtual Dimmer".into_valid(),
+                master: "Body_Dimmer".into_valid(),
+                follower: "Body_ColorAdd_R.ColorAdd_R.Red".into(),
+                relation_type: RelationType::Multiply,
+            }]
+        );
+    }
+}
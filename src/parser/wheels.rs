@@ -0,0 +1,151 @@
+use roxmltree::Node;
+
+use crate::{
+    wheels::{ColorCie, Slot, Wheel, WheelsError},
+    Action, ParsedGdtf, Problem,
+};
+
+use super::{
+    parse_xml::{GetXmlAttribute, GetXmlNode},
+    problems::HandleProblem,
+};
+
+impl ParsedGdtf {
+    pub(crate) fn parse_wheels(&mut self, fixture_type: Node) {
+        let wheels = match fixture_type.find_required_child("Wheels") {
+            Ok(v) => v,
+            Err(p) => {
+                p.handled_by(Action::left_empty("wheels"), self);
+                return;
+            }
+        };
+
+        for (i, n) in wheels.children().filter(|n| n.is_element()).enumerate() {
+            if !n.has_tag_name("Wheel") {
+                Problem::UnexpectedXmlNode(n.tag_name().name().into())
+                    .at(&n)
+                    .handled_by(Action::IgnoredNode, self);
+                continue;
+            }
+
+            let wheel = self.parse_wheel(n, i);
+            if let Err(WheelsError::NameAlreadyTaken(name)) = self.gdtf.wheels.add(wheel) {
+                Problem::DuplicateWheelName(name)
+                    .at(&n)
+                    .handled_by(Action::IgnoredNode, self);
+            }
+        }
+    }
+
+    fn parse_wheel(&mut self, n: Node, node_index_in_xml_parent: usize) -> Wheel {
+        let name = n.name(node_index_in_xml_parent, self);
+        let mut slots = vec![];
+        for (i, s) in n.children().filter(|n| n.is_element()).enumerate() {
+            if !s.has_tag_name("Slot") {
+                Problem::UnexpectedXmlNode(s.tag_name().name().into())
+                    .at(&s)
+                    .handled_by(Action::IgnoredNode, self);
+                continue;
+            }
+            slots.push(self.parse_slot(s, i));
+        }
+        Wheel { name, slots }
+    }
+
+    fn parse_slot(&mut self, n: Node, node_index_in_xml_parent: usize) -> Slot {
+        let name = n.name(node_index_in_xml_parent, self);
+        let color = n
+            .parse_attribute("Color")
+            .transpose()
+            .ok_or_handled_by(Action::used_default(ColorCie::WHITE), self)
+            .flatten()
+            .unwrap_or(ColorCie::WHITE);
+        let media_file_name = n
+            .attribute("MediaFileName")
+            .filter(|s| !s.is_empty())
+            .map(str::to_owned);
+        Slot {
+            name,
+            color,
+            media_file_name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::name::IntoValidName;
+
+    use super::*;
+
+    #[test]
+    fn parse_wheels() {
+        let input = r#"
+<FixtureType>
+    <Wheels>
+        <Wheel Name="Color Wheel">
+            <Slot Color="0.312700,0.329000,100.000000" Name="Open"/>
+            <Slot Color="0.640000,0.330000,21.260000" Name="Red"/>
+            <Slot Color="red" Name="Invalid"/>
+            <Filter/>
+        </Wheel>
+        <Wheel Name="Gobo Wheel">
+            <Slot MediaFileName="gobo1" Name="Gobo 1"/>
+            <Slot MediaFileName="" Name="Open"/>
+        </Wheel>
+        <Wheel Name="Color Wheel"/>
+        <NotAWheel/>
+    </Wheels>
+</FixtureType>"#;
+        let doc = roxmltree::Document::parse(input).unwrap();
+        let mut parsed = ParsedGdtf::default();
+        parsed.parse_wheels(doc.root_element());
+
+        let mut problems = parsed.problems.iter().map(|p| p.problem());
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::InvalidAttribute { attr, .. } if attr == "Color"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::UnexpectedXmlNode(tag) if tag == "Filter"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::DuplicateWheelName(name) if name == "Color Wheel"
+        ));
+        assert!(matches!(
+            problems.next().unwrap(),
+            Problem::UnexpectedXmlNode(tag) if tag == "NotAWheel"
+        ));
+        assert!(problems.next().is_none());
+
+        let wheels = &parsed.gdtf.wheels;
+        assert_eq!(wheels.len(), 2);
+        let colors = wheels.get_by_name(&"Color Wheel".into_valid()).unwrap();
+        let slot_colors: Vec<_> = colors.slots.iter().map(|s| s.color.to_string()).collect();
+        assert_eq!(
+            slot_colors,
+            [
+                "0.312700,0.329000,100.000000",
+                "0.640000,0.330000,21.260000",
+                "0.312700,0.329000,100.000000"
+            ]
+        );
+        let gobos = wheels.get_by_name(&"Gobo Wheel".into_valid()).unwrap();
+        assert_eq!(
+            gobos.slots,
+            [
+                Slot {
+                    name: "Gobo 1".into_valid(),
+                    media_file_name: Some("gobo1".into()),
+                    ..Default::default()
+                },
+                Slot {
+                    name: "Open".into_valid(),
+                    ..Default::default()
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,404 @@
+//! Decode raw DMX data into the active channel functions and physical values
+//! of a DMX mode, and encode physical values into DMX data.
+
+use std::collections::HashMap;
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef, Direction};
+
+use crate::{
+    channel::Channel,
+    dmx_break::Break,
+    dmx_modes::{ChannelFunction, DmxMode},
+    physical_value::PhysicalValue,
+};
+
+/// State of one channel for a given DMX frame
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedChannel<'a> {
+    pub channel: &'a Channel,
+    /// DMX value of the channel, combined from all its bytes
+    pub dmx_value: u32,
+    /// The first active channel function after the raw DMX channel function,
+    /// or the raw DMX channel function if no other one is active
+    pub channel_function: &'a ChannelFunction,
+    /// linearly interpolated between PhysicalFrom and PhysicalTo of
+    /// `channel_function`
+    pub physical_value: PhysicalValue,
+}
+
+/// Resolve every channel of `mode`, including subfixture channels, for one
+/// DMX frame.
+///
+/// `dmx` holds the DMX data of each break, starting at offset 1. Channels
+/// without data for all of their bytes, as well as virtual channels, use their
+/// default value.
+///
+/// A channel function is active if the DMX value of its channel is in its
+/// range and the conditions of all its ModeMasters are met, including the
+/// ModeMasters of the master channel functions.
+pub fn resolve<'a>(mode: &'a DmxMode, dmx: &HashMap<Break, &[u8]>) -> Vec<ResolvedChannel<'a>> {
+    let values: HashMap<NodeIndex, u32> = mode
+        .all_channels()
+        .flat_map(|ch| {
+            let value = dmx_value(ch, dmx);
+            ch.channel_functions.iter().map(move |i| (*i, value))
+        })
+        .collect();
+    let max_depth = mode.channel_functions.node_count();
+
+    mode.all_channels()
+        .filter_map(|ch| {
+            let index = ch
+                .defined_functions()
+                .iter()
+                .copied()
+                .find(|i| is_active(mode, &values, *i, max_depth))
+                .or_else(|| ch.raw_function())?;
+            let channel_function = mode.channel_functions.node_weight(index)?;
+            let dmx_value = *values.get(&index)?;
+            Some(ResolvedChannel {
+                channel: ch,
+                dmx_value,
+                channel_function,
+                physical_value: interpolate(channel_function, dmx_value),
+            })
+        })
+        .collect()
+}
+
+fn dmx_value(ch: &Channel, dmx: &HashMap<Break, &[u8]>) -> u32 {
+    if ch.offsets.is_empty() {
+        return ch.default;
+    }
+    let bytes = dmx.get(&ch.dmx_break);
+    ch.offsets
+        .iter()
+        .try_fold(0u32, |value, offset| {
+            let byte = bytes?.get(usize::from(*offset).checked_sub(1)?)?;
+            Some(value << 8 | u32::from(*byte))
+        })
+        .unwrap_or(ch.default)
+}
+
+/// `depth` limits the recursion into masters, in case of cyclic ModeMasters
+fn is_active(mode: &DmxMode, values: &HashMap<NodeIndex, u32>, i: NodeIndex, depth: usize) -> bool {
+    let (Some(chf), Some(value)) = (mode.channel_functions.node_weight(i), values.get(&i)) else {
+        return false;
+    };
+    if !(chf.dmx_from..=chf.dmx_to).contains(value) || depth == 0 {
+        return false;
+    }
+    mode.channel_functions
+        .edges_directed(i, Direction::Incoming)
+        .all(|edge| {
+            let (mode_master, master) = (edge.weight(), edge.source());
+            let in_range = |v: &u32| (mode_master.from..=mode_master.to).contains(v);
+            matches!(values.get(&master), Some(v) if in_range(v))
+                && is_active(mode, values, master, depth - 1)
+        })
+}
+
+pub(crate) fn interpolate(chf: &ChannelFunction, dmx_value: u32) -> PhysicalValue {
+    if chf.dmx_to <= chf.dmx_from {
+        return chf.phys_from;
+    }
+    let fraction =
+        f64::from(dmx_value.saturating_sub(chf.dmx_from)) / f64::from(chf.dmx_to - chf.dmx_from);
+    let (from, to) = (chf.phys_from.value(), chf.phys_to.value());
+    PhysicalValue::saturating(from + fraction * (to - from)).unwrap_or(chf.phys_from)
+}
+
+/// Value to encode with [`encode`]
+#[derive(Debug, Clone, Copy)]
+pub enum Target<'a> {
+    Physical(PhysicalValue),
+    /// name of a ChannelSet; the start of its DMX range is used
+    ChannelSet(&'a str),
+}
+
+/// One DMX byte to output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DmxWrite {
+    pub dmx_break: Break,
+    /// 1-based offset in the break
+    pub offset: u16,
+    pub value: u8,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EncodeError {
+    #[error("no channel function with attribute '{0}' on the geometry")]
+    UnknownAttribute(String),
+    #[error("physical value {0} is outside the range of all matching channel functions")]
+    OutOfRange(PhysicalValue),
+    #[error("no channel set named '{0}' in the matching channel functions")]
+    UnknownChannelSet(String),
+}
+
+/// Compute the DMX bytes that set `attribute` of `geometry` to `target`.
+///
+/// The first channel function with a matching attribute and range is used.
+/// If it has ModeMasters, the bytes of the master channels are included, set
+/// to the start of the required range. Virtual channels produce no bytes.
+pub fn encode(
+    mode: &DmxMode,
+    geometry: NodeIndex,
+    attribute: &str,
+    target: Target,
+) -> Result<Vec<DmxWrite>, EncodeError> {
+    let candidates: Vec<(&Channel, NodeIndex, &ChannelFunction)> = mode
+        .all_channels()
+        .filter(|ch| ch.geometry == geometry)
+        .flat_map(|ch| {
+            ch.channel_functions.iter().filter_map(move |i| {
+                let chf = mode.channel_functions.node_weight(*i)?;
+                (chf.attr == attribute).then_some((ch, *i, chf))
+            })
+        })
+        .collect();
+    if candidates.is_empty() {
+        return Err(EncodeError::UnknownAttribute(attribute.to_owned()));
+    }
+
+    let (channel, index, dmx_value) = candidates
+        .iter()
+        .find_map(|(ch, i, chf)| Some((*ch, *i, dmx_value_for(chf, target)?)))
+        .ok_or_else(|| match target {
+            Target::Physical(v) => EncodeError::OutOfRange(v),
+            Target::ChannelSet(name) => EncodeError::UnknownChannelSet(name.to_owned()),
+        })?;
+
+    let mut writes = channel_writes(channel, dmx_value);
+    let mut masters = vec![index];
+    // bounded in case of cyclic ModeMasters
+    for _ in 0..mode.channel_functions.node_count() {
+        let Some(dependent) = masters.pop() else {
+            break;
+        };
+        for edge in mode
+            .channel_functions
+            .edges_directed(dependent, Direction::Incoming)
+        {
+            let master = edge.source();
+            if let Some(ch) = mode
+                .all_channels()
+                .find(|ch| ch.channel_functions.contains(&master))
+            {
+                writes.extend(channel_writes(ch, edge.weight().from));
+            }
+            masters.push(master);
+        }
+    }
+    Ok(writes)
+}
+
+fn dmx_value_for(chf: &ChannelFunction, target: Target) -> Option<u32> {
+    match target {
+        Target::ChannelSet(name) => chf
+            .channel_sets
+            .iter()
+            .find(|set| set.name == name)
+            .map(|set| set.dmx_from),
+        Target::Physical(v) => {
+            let (from, to, v) = (chf.phys_from.value(), chf.phys_to.value(), v.value());
+            if !(from.min(to)..=from.max(to)).contains(&v) {
+                return None;
+            }
+            if from == to || chf.dmx_to <= chf.dmx_from {
+                return Some(chf.dmx_from);
+            }
+            let fraction = (v - from) / (to - from);
+            let steps = (fraction * f64::from(chf.dmx_to - chf.dmx_from)).round();
+            // fraction is in 0..=1, so the cast cannot truncate
+            Some(chf.dmx_from + steps as u32)
+        }
+    }
+}
+
+fn channel_writes(ch: &Channel, value: u32) -> Vec<DmxWrite> {
+    ch.dmx_bytes(value)
+        .map(|(offset, value)| DmxWrite {
+            dmx_break: ch.dmx_break,
+            offset,
+            value,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builder::GdtfBuilder, dmx_modes::ModeMaster, name::IntoValidName, Gdtf};
+
+    use super::*;
+
+    fn fixture() -> Gdtf {
+        let mut gdtf = GdtfBuilder::new("Manufacturer", "Fixture")
+            .unwrap()
+            .geometry("Body", None)
+            .unwrap()
+            .dmx_mode("Mode", "Body")
+            .unwrap()
+            .channel("Mode", "Body", "Dimmer", 1, vec![1, 2])
+            .unwrap()
+            .channel_function("Mode", "Body_Dimmer", "Dimmer", "Dimmer", 0, 0., 100.)
+            .unwrap()
+            .channel("Mode", "Body", "Control1", 1, vec![3])
+            .unwrap()
+            .channel_function("Mode", "Body_Control1", "Normal", "Control1", 0, 0., 1.)
+            .unwrap()
+            .channel_function("Mode", "Body_Control1", "Special", "Control1", 128, 0., 1.)
+            .unwrap()
+            .channel("Mode", "Body", "Shutter1", 1, vec![4])
+            .unwrap()
+            .channel_function("Mode", "Body_Shutter1", "Strobe", "Shutter1", 0, 1., 20.)
+            .unwrap()
+            .channel_function("Mode", "Body_Shutter1", "Pulse", "Shutter1", 1, 0., 10.)
+            .unwrap()
+            .build();
+        // Strobe requires Normal, Pulse requires Special. Both cover the whole
+        // DMX range, which the builder cannot express.
+        let mode = gdtf.dmx_mode_mut(0).unwrap();
+        let index = |ch: usize, chf: usize| {
+            *mode
+                .channels
+                .get(ch)
+                .unwrap()
+                .channel_functions
+                .get(chf)
+                .unwrap()
+        };
+        let (normal, special, strobe, pulse) = (index(1, 1), index(1, 2), index(2, 1), index(2, 2));
+        mode.channel_functions
+            .node_weight_mut(strobe)
+            .unwrap()
+            .dmx_to = 255;
+        mode.channel_functions
+            .node_weight_mut(pulse)
+            .unwrap()
+            .dmx_from = 0;
+        mode.channel_functions
+            .add_edge(
+                normal,
+                strobe,
+                ModeMaster {
+                    from: 0,
+                    to: 127,
+                    original_from: 0,
+                    original_to: 127,
+                },
+            )
+            .unwrap();
+        mode.channel_functions
+            .add_edge(
+                special,
+                pulse,
+                ModeMaster {
+                    from: 128,
+                    to: 255,
+                    original_from: 128,
+                    original_to: 255,
+                },
+            )
+            .unwrap();
+        gdtf
+    }
+
+    #[test]
+    fn resolves_channel_functions_and_physical_values() {
+        let gdtf = fixture();
+        let mode = gdtf.dmx_mode(0).unwrap();
+
+        let resolved = |data: &[u8]| {
+            resolve(mode, &HashMap::from([(1.try_into().unwrap(), data)]))
+                .into_iter()
+                .map(|r| {
+                    (
+                        r.dmx_value,
+                        r.channel_function.name.to_string(),
+                        r.physical_value.value(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            resolved(&[255, 255, 0, 255]),
+            vec![
+                (65535, "Dimmer".into(), 100.),
+                (0, "Normal".into(), 0.),
+                (255, "Strobe".into(), 20.),
+            ]
+        );
+        assert_eq!(
+            resolved(&[0, 0, 255, 0]),
+            vec![
+                (0, "Dimmer".into(), 0.),
+                (255, "Special".into(), 1.),
+                (0, "Pulse".into(), 0.),
+            ]
+        );
+        // missing data uses the default
+        assert_eq!(resolved(&[128]).first().unwrap().0, 0);
+    }
+
+    #[test]
+    fn encodes_physical_values_and_mode_masters() {
+        let gdtf = fixture();
+        let mode = gdtf.dmx_mode(0).unwrap();
+        let body = gdtf.geometries.get_index(&"Body".into_valid()).unwrap();
+        let write = |offset, value| DmxWrite {
+            dmx_break: 1.try_into().unwrap(),
+            offset,
+            value,
+        };
+
+        assert_eq!(
+            encode(
+                mode,
+                body,
+                "Dimmer",
+                Target::Physical(50.0.try_into().unwrap())
+            )
+            .unwrap(),
+            vec![write(1, 128), write(2, 0)]
+        );
+        assert_eq!(
+            encode(
+                mode,
+                body,
+                "Shutter1",
+                Target::Physical(15.0.try_into().unwrap())
+            )
+            .unwrap(),
+            vec![write(4, 188), write(3, 0)]
+        );
+        assert_eq!(
+            encode(
+                mode,
+                body,
+                "Shutter1",
+                Target::Physical(0.0.try_into().unwrap())
+            )
+            .unwrap(),
+            vec![write(4, 0), write(3, 128)]
+        );
+
+        assert!(matches!(
+            encode(
+                mode,
+                body,
+                "Shutter1",
+                Target::Physical(30.0.try_into().unwrap())
+            ),
+            Err(EncodeError::OutOfRange(_))
+        ));
+        assert!(matches!(
+            encode(mode, body, "Shutter1", Target::ChannelSet("Open")),
+            Err(EncodeError::UnknownChannelSet(_))
+        ));
+        assert!(matches!(
+            encode(mode, body, "Pan", Target::Physical(PhysicalValue::ZERO)),
+            Err(EncodeError::UnknownAttribute(_))
+        ));
+    }
+}
@@ -0,0 +1,338 @@
+//! Access to the resource files embedded in a GDTF archive, like thumbnails,
+//! 3D models and wheel media.
+
+use std::{
+    io::{self, Read, Seek},
+    path::Path,
+};
+
+use zip::{result::ZipError, ZipArchive};
+
+use crate::models::Model;
+
+/// Thumbnail of the fixture type, in the root of the archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+    /// path inside the archive
+    pub path: String,
+}
+
+/// 3D model file of a `Model` in the `models` folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Model3D {
+    /// path inside the archive
+    pub path: String,
+    pub format: Model3DFormat,
+}
+
+/// Format of a 3D model file. Variants are ordered by the precedence GDTF
+/// gives them when a model is available in several formats, the preferred
+/// format first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Model3DFormat {
+    /// glTF with JSON content, `.gltf`
+    Gltf,
+    /// binary glTF, `.glb`
+    Glb,
+    ThreeDs,
+}
+
+impl Model3DFormat {
+    /// Detect the format from the start of the file content, independent of
+    /// its path
+    pub fn probe(content: &[u8]) -> Option<Model3DFormat> {
+        if content.starts_with(b"glTF") {
+            return Some(Model3DFormat::Glb);
+        }
+        // main chunk ID 0x4D4D, little endian
+        if content.starts_with(&[0x4D, 0x4D]) {
+            return Some(Model3DFormat::ThreeDs);
+        }
+        let first = content.iter().find(|b| !b.is_ascii_whitespace())?;
+        (*first == b'{').then_some(Model3DFormat::Gltf)
+    }
+}
+
+/// Image of a wheel slot in the `wheels` folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WheelMedia {
+    /// path inside the archive
+    pub path: String,
+}
+
+/// A typed resource file in a GDTF archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resource {
+    Thumbnail(Thumbnail),
+    Model3D(Model3D),
+    WheelMedia(WheelMedia),
+}
+
+impl Resource {
+    /// path inside the archive
+    pub fn path(&self) -> &str {
+        match self {
+            Resource::Thumbnail(r) => &r.path,
+            Resource::Model3D(r) => &r.path,
+            Resource::WheelMedia(r) => &r.path,
+        }
+    }
+
+    /// Classify a file path inside a GDTF archive. Returns None for files that
+    /// are not a known kind of resource, like `description.xml`.
+    pub fn from_path(path: &str) -> Option<Resource> {
+        let path = path.strip_prefix("./").unwrap_or(path);
+        let owned = || path.to_owned();
+        if let Some(file) = path.strip_prefix("wheels/") {
+            return (!is_dir(file)).then(|| Resource::WheelMedia(WheelMedia { path: owned() }));
+        }
+        if let Some(file) = path.strip_prefix("models/3ds/") {
+            return (!is_dir(file)).then(|| {
+                Resource::Model3D(Model3D {
+                    path: owned(),
+                    format: Model3DFormat::ThreeDs,
+                })
+            });
+        }
+        if let Some(file) = path.strip_prefix("models/gltf/") {
+            let format = if extension_is(file, "glb") {
+                Model3DFormat::Glb
+            } else {
+                Model3DFormat::Gltf
+            };
+            return (!is_dir(file)).then(|| {
+                Resource::Model3D(Model3D {
+                    path: owned(),
+                    format,
+                })
+            });
+        }
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        (!path.contains('/') && matches!(extension.as_str(), "png" | "svg"))
+            .then(|| Resource::Thumbnail(Thumbnail { path: owned() }))
+    }
+}
+
+fn is_dir(file: &str) -> bool {
+    file.is_empty() || file.ends_with('/')
+}
+
+fn extension_is(file: &str, extension: &str) -> bool {
+    Path::new(file)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ResourceError {
+    #[error("zip error: {0}")]
+    InvalidZip(#[from] ZipError),
+    #[error("resource '{path}' could not be read: {source}")]
+    Read { path: String, source: io::Error },
+}
+
+/// Lists the resource files of a GDTF archive and extracts them on demand.
+pub struct ResourceMap<R: Read + Seek> {
+    zip: ZipArchive<R>,
+    /// paths in archive order
+    names: Vec<String>,
+}
+
+/// Files whose local header can't be read are not listed
+impl<R: Read + Seek> From<ZipArchive<R>> for ResourceMap<R> {
+    fn from(mut zip: ZipArchive<R>) -> Self {
+        let names = (0..zip.len())
+            .filter_map(|i| Some(zip.by_index_raw(i).ok()?.name().to_owned()))
+            .collect();
+        Self { zip, names }
+    }
+}
+
+impl<R: Read + Seek> ResourceMap<R> {
+    pub fn new(reader: R) -> Result<Self, ResourceError> {
+        let mut zip = ZipArchive::new(reader)?;
+        let names = names_in_archive_order(&mut zip)?;
+        Ok(Self { zip, names })
+    }
+
+    pub(crate) fn with_names(zip: ZipArchive<R>, names: Vec<String>) -> Self {
+        Self { zip, names }
+    }
+
+    /// All resources in the archive, in archive order
+    pub fn resources(&self) -> impl Iterator<Item = Resource> + '_ {
+        self.names
+            .iter()
+            .filter_map(|name| Resource::from_path(name))
+    }
+
+    pub fn thumbnails(&self) -> impl Iterator<Item = Thumbnail> + '_ {
+        self.resources().filter_map(|r| match r {
+            Resource::Thumbnail(t) => Some(t),
+            _ => None,
+        })
+    }
+
+    pub fn models_3d(&self) -> impl Iterator<Item = Model3D> + '_ {
+        self.resources().filter_map(|r| match r {
+            Resource::Model3D(m) => Some(m),
+            _ => None,
+        })
+    }
+
+    /// The 3D model file of a model in the preferred format, see
+    /// [`Model3DFormat`]. Files are matched by their name without extension.
+    /// `None` if the model has no file or none of the formats is in the
+    /// archive.
+    pub fn model_3d(&self, model: &Model) -> Option<Model3D> {
+        let file = model.file.as_deref()?;
+        self.models_3d()
+            .filter(|m| Path::new(&m.path).file_stem().is_some_and(|s| s == file))
+            .min_by_key(|m| m.format)
+    }
+
+    pub fn wheel_media(&self) -> impl Iterator<Item = WheelMedia> + '_ {
+        self.resources().filter_map(|r| match r {
+            Resource::WheelMedia(w) => Some(w),
+            _ => None,
+        })
+    }
+
+    /// Extract the content of a file in the archive
+    pub fn read(&mut self, path: &str) -> Result<Vec<u8>, ResourceError> {
+        // paths in the archive may be prefixed by "./"
+        let name = if self.names.iter().any(|n| n == path) {
+            path.to_owned()
+        } else {
+            format!("./{path}")
+        };
+        let mut file = self.zip.by_name(&name)?;
+        let mut buf = Vec::with_capacity(file.size().try_into().unwrap_or(0));
+        file.read_to_end(&mut buf)
+            .map_err(|source| ResourceError::Read {
+                path: path.to_owned(),
+                source,
+            })?;
+        Ok(buf)
+    }
+}
+
+/// Paths of all files in archive order. `ZipArchive::file_names` has no
+/// defined order.
+pub(crate) fn names_in_archive_order<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+) -> Result<Vec<String>, ZipError> {
+    (0..zip.len())
+        .map(|i| Ok(zip.by_index_raw(i)?.name().to_owned()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use zip::{write::FileOptions, ZipWriter};
+
+    use super::*;
+
+    fn archive(files: &[&str]) -> Cursor<Vec<u8>> {
+        let mut writer = ZipWriter::new(Cursor::new(vec![]));
+        for file in files {
+            writer.start_file(*file, FileOptions::default()).unwrap();
+            writer.write_all(file.as_bytes()).unwrap();
+        }
+        let mut cursor = writer.finish().unwrap();
+        cursor.set_position(0);
+        cursor
+    }
+
+    #[test]
+    fn lists_and_reads_resources() {
+        let mut map = ResourceMap::new(archive(&[
+            "description.xml",
+            "thumbnail.png",
+            "thumbnail.svg",
+            "wheels/gobo1.png",
+            "models/3ds/base.3ds",
+            "./models/gltf/head.glb",
+            "other/readme.txt",
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            map.thumbnails().map(|t| t.path).collect::<Vec<_>>(),
+            vec!["thumbnail.png", "thumbnail.svg"]
+        );
+        assert_eq!(
+            map.wheel_media().collect::<Vec<_>>(),
+            vec![WheelMedia {
+                path: "wheels/gobo1.png".into()
+            }]
+        );
+        assert_eq!(
+            map.models_3d().collect::<Vec<_>>(),
+            vec![
+                Model3D {
+                    path: "models/3ds/base.3ds".into(),
+                    format: Model3DFormat::ThreeDs
+                },
+                Model3D {
+                    path: "models/gltf/head.glb".into(),
+                    format: Model3DFormat::Glb
+                },
+            ]
+        );
+        assert_eq!(map.resources().count(), 5);
+
+        assert_eq!(map.read("wheels/gobo1.png").unwrap(), b"wheels/gobo1.png");
+        assert_eq!(
+            map.read("models/gltf/head.glb").unwrap(),
+            b"./models/gltf/head.glb"
+        );
+        assert!(matches!(
+            map.read("missing.png"),
+            Err(ResourceError::InvalidZip(ZipError::FileNotFound))
+        ));
+    }
+
+    #[test]
+    fn preferred_model_format() {
+        let map = ResourceMap::new(archive(&[
+            "models/3ds/head.3ds",
+            "models/gltf/head.glb",
+            "models/gltf/head.gltf",
+            "models/3ds/base.3ds",
+            "models/gltf/base.glb",
+            "models/3ds/yoke.3ds",
+        ]))
+        .unwrap();
+        let model = |file: &str| Model {
+            file: Some(file.into()),
+            ..Default::default()
+        };
+        let format = |file| map.model_3d(&model(file)).map(|m| m.format);
+
+        assert_eq!(format("head"), Some(Model3DFormat::Gltf));
+        assert_eq!(format("base"), Some(Model3DFormat::Glb));
+        assert_eq!(format("yoke"), Some(Model3DFormat::ThreeDs));
+        assert_eq!(format("missing"), None);
+        assert_eq!(map.model_3d(&Model::default()), None);
+    }
+
+    #[test]
+    fn probe_model_format() {
+        assert_eq!(
+            Model3DFormat::probe(b"glTF\x02\x00\x00\x00"),
+            Some(Model3DFormat::Glb)
+        );
+        assert_eq!(
+            Model3DFormat::probe(b"  {\"asset\": {}}"),
+            Some(Model3DFormat::Gltf)
+        );
+        assert_eq!(
+            Model3DFormat::probe(&[0x4D, 0x4D, 0x10, 0x00]),
+            Some(Model3DFormat::ThreeDs)
+        );
+        assert_eq!(Model3DFormat::probe(b"PNG"), None);
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+
+use petgraph::{graph::NodeIndex, visit::EdgeRef, Direction::Incoming};
+
+use crate::{
+    channel::Channel,
+    dmx_modes::{ChannelFunction, ChannelSet, DmxMode},
+    float::Fixed,
+    geometries::Geometries,
+    geometry::Type,
+    macros::FtMacro,
+    relations::Relation,
+    Gdtf,
+};
+
+use super::{borrowed, with_unknown, xml_writer::XmlWriter};
+
+/// Write the DMX modes back to XML.
+///
+/// Template channels are written once, from their first instance, on the
+/// geometry in the template. All channel functions of a channel are written
+/// into one LogicalChannel, since logical channels are not kept apart.
+pub(super) fn write_dmx_modes(w: &mut XmlWriter, gdtf: &Gdtf) {
+    if gdtf.dmx_modes().is_empty() {
+        w.empty("DMXModes", &[]);
+        return;
+    }
+    w.start("DMXModes", &[]);
+    for mode in gdtf.dmx_modes() {
+        write_dmx_mode(w, &gdtf.geometries, mode);
+    }
+    w.end();
+}
+
+fn write_dmx_mode(w: &mut XmlWriter, geometries: &Geometries, mode: &DmxMode) {
+    let geometry = mode
+        .geometry_name(geometries)
+        .map(|n| n.to_string())
+        .unwrap_or_default();
+    let attributes = [
+        ("Name", mode.name.as_str()),
+        ("Description", &mode.description),
+        ("Geometry", &geometry),
+    ];
+    w.start(
+        "DMXMode",
+        &with_unknown(&attributes, &mode.extensions.attributes),
+    );
+
+    let owners: HashMap<NodeIndex, &Channel> = mode
+        .all_channels()
+        .chain(mode.uninstantiated_channels())
+        .flat_map(|ch| ch.channel_functions.iter().map(move |i| (*i, ch)))
+        .collect();
+
+    // one DMXChannel per index in the mode, template channels have one
+    // instance per subfixture
+    let mut channels: Vec<&Channel> = vec![];
+    for ch in mode.all_channels().chain(mode.uninstantiated_channels()) {
+        if !channels.iter().any(|c| c.index_in_mode == ch.index_in_mode) {
+            channels.push(ch);
+        }
+    }
+    channels.sort_by_key(|ch| ch.index_in_mode);
+
+    if channels.is_empty() {
+        w.empty("DMXChannels", &[]);
+    } else {
+        w.start("DMXChannels", &[]);
+        for ch in channels {
+            write_dmx_channel(w, geometries, mode, &owners, ch);
+        }
+        w.end();
+    }
+    write_relations(w, mode);
+    write_ft_macros(w, geometries, mode);
+    w.end();
+}
+
+fn write_dmx_channel(
+    w: &mut XmlWriter,
+    geometries: &Geometries,
+    mode: &DmxMode,
+    owners: &HashMap<NodeIndex, &Channel>,
+    ch: &Channel,
+) {
+    let geometry = xml_geometry_name(geometries, ch);
+    let gdtf_name = gdtf_channel_name(geometries, ch);
+    let (dmx_break, offsets) = template_relative_offsets(geometries, ch);
+    let offsets = if offsets.is_empty() {
+        "None".to_owned()
+    } else {
+        offsets
+            .iter()
+            .map(u16::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let functions: Vec<&ChannelFunction> = ch
+        .defined_functions()
+        .iter()
+        .filter_map(|i| mode.channel_functions.node_weight(*i))
+        .collect();
+    let initial_function = ch
+        .initial_function(mode)
+        .or_else(|| {
+            functions
+                .iter()
+                .find(|chf| chf.default == ch.default)
+                .copied()
+        })
+        .map(|chf| format!("{gdtf_name}.{}.{}", ch.attribute, chf.name))
+        .unwrap_or_default();
+    let highlight = ch
+        .highlight
+        .map(|v| dmx_value(v, ch.bytes))
+        .unwrap_or_else(|| "None".to_owned());
+
+    let mut attributes = vec![
+        ("DMXBreak", dmx_break),
+        ("Offset", offsets),
+        ("Highlight", highlight),
+        ("Geometry", geometry),
+    ];
+    if !initial_function.is_empty() {
+        attributes.insert(2, ("InitialFunction", initial_function));
+    }
+    w.start(
+        "DMXChannel",
+        &with_unknown(&borrowed(&attributes), &ch.extensions.attributes),
+    );
+
+    if functions.is_empty() {
+        w.empty("LogicalChannel", &[("Attribute", ch.attribute.as_str())]);
+    } else {
+        w.start("LogicalChannel", &[("Attribute", ch.attribute.as_str())]);
+        for (i, chf) in ch.defined_functions().iter().zip(functions) {
+            write_channel_function(w, geometries, mode, owners, ch, *i, chf);
+        }
+        w.end();
+    }
+    w.end();
+}
+
+fn write_channel_function(
+    w: &mut XmlWriter,
+    geometries: &Geometries,
+    mode: &DmxMode,
+    owners: &HashMap<NodeIndex, &Channel>,
+    ch: &Channel,
+    i: NodeIndex,
+    chf: &ChannelFunction,
+) {
+    let mut attributes = vec![
+        ("Name", chf.name.to_string()),
+        ("Attribute", chf.attr.clone()),
+        ("OriginalAttribute", chf.original_attr.clone()),
+        ("DMXFrom", dmx_value(chf.dmx_from, ch.bytes)),
+        ("Default", dmx_value(chf.default, ch.bytes)),
+        ("PhysicalFrom", chf.phys_from.to_string()),
+        ("PhysicalTo", chf.phys_to.to_string()),
+        ("RealFade", Fixed(chf.real_fade).to_string()),
+        ("RealAcceleration", Fixed(chf.real_acceleration).to_string()),
+        ("Min", chf.min.to_string()),
+        ("Max", chf.max.to_string()),
+    ];
+    if let Some(wheel) = &chf.wheel {
+        attributes.push(("Wheel", wheel.to_string()));
+    }
+    if let Some(edge) = mode.channel_functions.edges_directed(i, Incoming).next() {
+        let master_index = edge.source();
+        let master = mode.channel_functions.node_weight(master_index);
+        if let (Some(master), Some(master_channel)) = (master, owners.get(&master_index)) {
+            let master_name = gdtf_channel_name(geometries, master_channel);
+            let path = if master.raw_dmx {
+                master_name
+            } else {
+                format!("{master_name}.{}.{}", master_channel.attribute, master.name)
+            };
+            attributes.extend([
+                ("ModeMaster", path),
+                (
+                    "ModeFrom",
+                    dmx_value(edge.weight().from, master_channel.bytes),
+                ),
+                ("ModeTo", dmx_value(edge.weight().to, master_channel.bytes)),
+            ]);
+        }
+    }
+
+    let attributes = borrowed(&attributes);
+    let attributes = with_unknown(&attributes, &chf.extensions.attributes);
+    if chf.channel_sets.is_empty() {
+        w.empty("ChannelFunction", &attributes);
+        return;
+    }
+    w.start("ChannelFunction", &attributes);
+    for ChannelSet {
+        name,
+        dmx_from,
+        dmx_to: _,
+        phys_from,
+        phys_to,
+        wheel_slot_index,
+    } in &chf.channel_sets
+    {
+        let mut attributes = vec![
+            ("Name", name.to_string()),
+            ("DMXFrom", dmx_value(*dmx_from, ch.bytes)),
+            ("PhysicalFrom", phys_from.to_string()),
+            ("PhysicalTo", phys_to.to_string()),
+        ];
+        if let Some(wheel_slot_index) = wheel_slot_index {
+            attributes.push(("WheelSlotIndex", wheel_slot_index.to_string()));
+        }
+        w.empty("ChannelSet", &borrowed(&attributes));
+    }
+    w.end();
+}
+
+fn write_relations(w: &mut XmlWriter, mode: &DmxMode) {
+    if mode.relations.is_empty() {
+        w.empty("Relations", &[]);
+        return;
+    }
+    w.start("Relations", &[]);
+    for Relation {
+        name,
+        master,
+        follower,
+        relation_type,
+    } in &mode.relations
+    {
+        w.empty(
+            "Relation",
+            &[
+                ("Name", name.as_str()),
+                ("Master", master.as_str()),
+                ("Follower", follower.as_str()),
+                ("Type", &relation_type.to_string()),
+            ],
+        );
+    }
+    w.end();
+}
+
+fn write_ft_macros(w: &mut XmlWriter, geometries: &Geometries, mode: &DmxMode) {
+    if mode.macros.is_empty() {
+        return;
+    }
+    let bytes: HashMap<String, u8> = mode
+        .channels
+        .iter()
+        .map(|ch| (gdtf_channel_name(geometries, ch), ch.bytes))
+        .collect();
+    w.start("FTMacros", &[]);
+    for FtMacro {
+        name,
+        channel_function,
+        steps,
+    } in &mode.macros
+    {
+        let mut attributes = vec![("Name", name.as_str())];
+        if let Some(channel_function) = channel_function {
+            attributes.push(("ChannelFunction", channel_function.as_str()));
+        }
+        if steps.is_empty() {
+            w.empty("FTMacro", &attributes);
+            continue;
+        }
+        w.start("FTMacro", &attributes);
+        w.start("MacroDMX", &[]);
+        for step in steps {
+            w.start(
+                "MacroDMXStep",
+                &[("Duration", &Fixed(step.duration).to_string())],
+            );
+            for value in &step.values {
+                let bytes = bytes.get(value.dmx_channel.as_str()).copied().unwrap_or(1);
+                w.empty(
+                    "MacroDMXValue",
+                    &[
+                        ("Value", &dmx_value(value.value, bytes)),
+                        ("DMXChannel", value.dmx_channel.as_str()),
+                    ],
+                );
+            }
+            w.end();
+        }
+        w.end();
+        w.end();
+    }
+    w.end();
+}
+
+/// Name of the geometry the DMXChannel node refers to
+fn xml_geometry_name(geometries: &Geometries, ch: &Channel) -> String {
+    geometries
+        .get_by_index(ch.template_geometry.unwrap_or(ch.geometry))
+        .map(|g| g.name.to_string())
+        .unwrap_or_default()
+}
+
+/// `{Geometry}_{Attribute}`, the name GDTF uses to reference the DMXChannel
+fn gdtf_channel_name(geometries: &Geometries, ch: &Channel) -> String {
+    format!("{}_{}", xml_geometry_name(geometries, ch), ch.attribute)
+}
+
+/// DMXBreak and offsets as written in the DMXChannel node. For template
+/// channels, the offset of the GeometryReference is subtracted again.
+fn template_relative_offsets(geometries: &Geometries, ch: &Channel) -> (String, Vec<u16>) {
+    let absolute = || (ch.dmx_break.to_string(), ch.offsets.to_vec());
+    if ch.template_geometry.is_none() {
+        return absolute();
+    }
+    let Ok(reference) = geometries.get_by_index(ch.geometry) else {
+        return absolute();
+    };
+    let Type::Reference { offsets } = &reference.t else {
+        return absolute();
+    };
+    let (dmx_break, shift) = match offsets.normal.get(&ch.dmx_break) {
+        Some(offset) => (ch.dmx_break.to_string(), *offset),
+        None => match &offsets.overwrite {
+            Some(o) if o.dmx_break == ch.dmx_break => ("Overwrite".to_owned(), o.offset),
+            _ => return absolute(),
+        },
+    };
+    let relative = ch
+        .offsets
+        .iter()
+        .map(|o| (i64::from(*o) - i64::from(shift.absolute()) + 1).clamp(1, 512) as u16)
+        .collect();
+    (dmx_break, relative)
+}
+
+fn dmx_value(value: u32, bytes: u8) -> String {
+    format!("{value}/{bytes}")
+}
@@ -0,0 +1,218 @@
+use petgraph::{graph::NodeIndex, Direction::Incoming};
+
+use crate::{
+    float::Fixed,
+    geometries::Geometries,
+    geometry::{Beam, Geometry, Laser, Offset, Offsets, Type, WiringObject},
+    Gdtf,
+};
+
+use super::{borrowed, with_unknown, xml_writer::XmlWriter};
+
+/// Write the geometry graph back to nested XML, children in the order they
+/// were added
+pub(super) fn write_geometries(w: &mut XmlWriter, gdtf: &Gdtf) {
+    let geometries = &gdtf.geometries;
+    let top_level: Vec<_> = geometries.top_level_indices().collect();
+    if top_level.is_empty() {
+        w.empty("Geometries", &[]);
+        return;
+    }
+    w.start("Geometries", &[]);
+    for i in top_level {
+        write_geometry(w, geometries, i);
+    }
+    w.end();
+}
+
+fn write_geometry(w: &mut XmlWriter, geometries: &Geometries, i: NodeIndex) {
+    let Ok(geometry) = geometries.get_by_index(i) else {
+        return;
+    };
+    let Geometry {
+        name,
+        t,
+        position,
+        model,
+        extensions,
+        source: _,
+    } = geometry;
+
+    let mut attributes = vec![
+        ("Name", name.to_string()),
+        ("Position", position.to_string()),
+        (
+            "Model",
+            model.as_ref().map(|m| m.to_string()).unwrap_or_default(),
+        ),
+    ];
+    let (tag, specific) = tag_and_attributes(geometries, i, t);
+    attributes.extend(specific);
+    let attributes = borrowed(&attributes);
+    let attributes = with_unknown(&attributes, &extensions.attributes);
+
+    if let Type::Reference { offsets } = t {
+        let breaks = breaks(offsets);
+        if breaks.is_empty() {
+            w.empty(tag, &attributes);
+        } else {
+            w.start(tag, &attributes);
+            for Offset { dmx_break, offset } in breaks {
+                w.empty(
+                    "Break",
+                    &[
+                        ("DMXOffset", &offset.absolute().to_string()),
+                        ("DMXBreak", &dmx_break.to_string()),
+                    ],
+                );
+            }
+            w.end();
+        }
+        return;
+    }
+
+    // petgraph iterates neighbors in reverse order of insertion
+    let mut children: Vec<_> = geometries.graph().neighbors(i).collect();
+    children.sort();
+    if children.is_empty() {
+        w.empty(tag, &attributes);
+    } else {
+        w.start(tag, &attributes);
+        for child in children {
+            write_geometry(w, geometries, child);
+        }
+        w.end();
+    }
+}
+
+fn tag_and_attributes(
+    geometries: &Geometries,
+    i: NodeIndex,
+    t: &Type,
+) -> (&'static str, Vec<(&'static str, String)>) {
+    match t {
+        Type::General => ("Geometry", vec![]),
+        Type::Axis => ("Axis", vec![]),
+        Type::FilterBeam => ("FilterBeam", vec![]),
+        Type::FilterColor => ("FilterColor", vec![]),
+        Type::FilterGobo => ("FilterGobo", vec![]),
+        Type::FilterShaper => ("FilterShaper", vec![]),
+        Type::Beam(beam) => ("Beam", beam_attributes(beam)),
+        Type::MediaServerLayer => ("MediaServerLayer", vec![]),
+        Type::MediaServerCamera => ("MediaServerCamera", vec![]),
+        Type::MediaServerMaster => ("MediaServerMaster", vec![]),
+        Type::Display { texture } => (
+            "Display",
+            vec![("Texture", texture.clone().unwrap_or_default())],
+        ),
+        Type::Laser(laser) => ("Laser", laser_attributes(laser)),
+        Type::WiringObject(wiring_object) => {
+            ("WiringObject", wiring_object_attributes(wiring_object))
+        }
+        Type::Inventory { count } => ("Inventory", vec![("Count", count.to_string())]),
+        Type::Structure => ("Structure", vec![]),
+        Type::Support => ("Support", vec![]),
+        Type::Magnet => ("Magnet", vec![]),
+        Type::Reference { .. } => {
+            let referenced = geometries
+                .templates()
+                .neighbors_directed(i, Incoming)
+                .next()
+                .and_then(|r| geometries.get_by_index(r).ok())
+                .map(|g| g.name.to_string())
+                .unwrap_or_default();
+            ("GeometryReference", vec![("Geometry", referenced)])
+        }
+    }
+}
+
+/// Break nodes of a GeometryReference. The parser takes the last Break as the
+/// Overwrite offset and uses it for its break if no other Break declares it,
+/// so such an entry is only written once, at the end.
+fn breaks(offsets: &Offsets) -> Vec<Offset> {
+    let mut normal: Vec<_> = offsets
+        .normal
+        .iter()
+        .map(|(dmx_break, offset)| Offset {
+            dmx_break: *dmx_break,
+            offset: *offset,
+        })
+        .filter(|o| offsets.overwrite.as_ref() != Some(o))
+        .collect();
+    normal.extend(offsets.overwrite.clone());
+    normal
+}
+
+fn beam_attributes(b: &Beam) -> Vec<(&'static str, String)> {
+    vec![
+        ("LampType", b.lamp_type.to_string()),
+        ("PowerConsumption", Fixed(b.power_consumption).to_string()),
+        ("LuminousFlux", Fixed(b.luminous_flux).to_string()),
+        ("ColorTemperature", Fixed(b.color_temperature).to_string()),
+        ("BeamAngle", Fixed(b.beam_angle).to_string()),
+        ("FieldAngle", Fixed(b.field_angle).to_string()),
+        ("ThrowRatio", Fixed(b.throw_ratio).to_string()),
+        ("RectangleRatio", Fixed(b.rectangle_ratio).to_string()),
+        ("BeamRadius", Fixed(b.beam_radius).to_string()),
+        ("BeamType", b.beam_type.to_string()),
+        ("ColorRenderingIndex", b.color_rendering_index.to_string()),
+        (
+            "EmitterSpectrum",
+            b.emitter_spectrum.clone().unwrap_or_default(),
+        ),
+    ]
+}
+
+fn laser_attributes(l: &Laser) -> Vec<(&'static str, String)> {
+    vec![
+        ("ColorType", l.color_type.to_string()),
+        ("Color", Fixed(l.color).to_string()),
+        ("OutputStrength", Fixed(l.output_strength).to_string()),
+        ("Emitter", l.emitter.clone().unwrap_or_default()),
+        ("BeamDiameter", Fixed(l.beam_diameter).to_string()),
+        (
+            "BeamDivergenceMin",
+            Fixed(l.beam_divergence_min).to_string(),
+        ),
+        (
+            "BeamDivergenceMax",
+            Fixed(l.beam_divergence_max).to_string(),
+        ),
+        ("ScanAnglePan", Fixed(l.scan_angle_pan).to_string()),
+        ("ScanAngleTilt", Fixed(l.scan_angle_tilt).to_string()),
+        ("ScanSpeed", Fixed(l.scan_speed).to_string()),
+    ]
+}
+
+fn wiring_object_attributes(o: &WiringObject) -> Vec<(&'static str, String)> {
+    let mut attributes = vec![
+        ("ConnectorType", o.connector_type.clone()),
+        ("ComponentType", o.component_type.to_string()),
+        ("SignalType", o.signal_type.clone()),
+        ("PinCount", o.pin_count.to_string()),
+        ("ElectricalPayLoad", Fixed(o.electrical_payload).to_string()),
+        ("VoltageRangeMax", Fixed(o.voltage_range_max).to_string()),
+        ("VoltageRangeMin", Fixed(o.voltage_range_min).to_string()),
+        (
+            "FrequencyRangeMax",
+            Fixed(o.frequency_range_max).to_string(),
+        ),
+        (
+            "FrequencyRangeMin",
+            Fixed(o.frequency_range_min).to_string(),
+        ),
+        ("MaxPayLoad", Fixed(o.max_payload).to_string()),
+        ("Voltage", Fixed(o.voltage).to_string()),
+        ("SignalLayer", o.signal_layer.to_string()),
+        ("CosPhi", Fixed(o.cos_phi).to_string()),
+        ("FuseCurrent", Fixed(o.fuse_current).to_string()),
+    ];
+    if let Some(fuse_rating) = o.fuse_rating {
+        attributes.push(("FuseRating", fuse_rating.to_string()));
+    }
+    if let Some(orientation) = o.orientation {
+        attributes.push(("Orientation", orientation.to_string()));
+    }
+    attributes.push(("WireGroup", o.wire_group.clone()));
+    attributes
+}
@@ -0,0 +1,485 @@
+mod dmx_modes;
+mod geometries;
+mod pretty;
+mod xml_writer;
+mod zip_writer;
+
+use crate::{
+    float::Fixed,
+    macros::FtPreset,
+    models::Model,
+    protocols::DmxMap,
+    wheels::{Slot, Wheel},
+    Gdtf,
+};
+
+pub use self::{
+    pretty::pretty_print_xml,
+    zip_writer::{write_description_archive, write_gdtf_archive, WriteError},
+};
+
+use self::{dmx_modes::write_dmx_modes, geometries::write_geometries, xml_writer::XmlWriter};
+
+/// Serialize to the content of `description.xml`.
+///
+/// Sections that are not parsed yet (AttributeDefinitions,
+/// PhysicalDescriptions, Revisions and FTRDM) are written back verbatim, as
+/// are [`Extensions`](crate::extensions::Extensions). The output still differs
+/// from the parsed file in these ways:
+/// - the logical channels of a DMX channel are merged into one and their
+///   attributes (Snap, Master, MibFade, DMXChangeTimeLimit) are dropped
+/// - attributes of PosiStageNet, OpenSoundControl and CITP are dropped
+/// - nodes the parser ignored with a [`Problem`](crate::Problem) are dropped
+pub fn serialize_gdtf(gdtf: &Gdtf) -> String {
+    let mut w = XmlWriter::new();
+    write_description(&mut w, gdtf);
+    w.finish()
+}
+
+/// Like [`serialize_gdtf`], but with the attributes of every element sorted by
+/// name, so the output does not depend on the order of unknown attributes
+pub(crate) fn serialize_normalized(gdtf: &Gdtf) -> String {
+    let mut w = XmlWriter::sorted();
+    write_description(&mut w, gdtf);
+    w.finish()
+}
+
+fn write_description(w: &mut XmlWriter, gdtf: &Gdtf) {
+    w.start("GDTF", &[("DataVersion", &gdtf.data_version.to_string())]);
+
+    let fixture_type_id = uppercase_uuid(&gdtf.fixture_type_id);
+    let ref_ft = gdtf.ref_ft.as_ref().map(uppercase_uuid).unwrap_or_default();
+    let fixture_type_attributes: [(&str, &str); 9] = [
+        ("Name", gdtf.name.as_str()),
+        ("ShortName", &gdtf.short_name),
+        ("LongName", &gdtf.long_name),
+        ("Manufacturer", &gdtf.manufacturer),
+        ("Description", &gdtf.description),
+        ("FixtureTypeID", &fixture_type_id),
+        ("Thumbnail", gdtf.thumbnail.as_deref().unwrap_or("")),
+        ("RefFT", &ref_ft),
+        ("CanHaveChildren", yes_no(gdtf.can_have_children)),
+    ];
+    w.start(
+        "FixtureType",
+        &with_unknown(&fixture_type_attributes, &gdtf.extensions.attributes),
+    );
+
+    let unparsed = &gdtf.unparsed;
+    write_unparsed(w, "AttributeDefinitions", &unparsed.attribute_definitions);
+    write_wheels(w, gdtf);
+    write_unparsed(w, "PhysicalDescriptions", &unparsed.physical_descriptions);
+    write_models(w, gdtf);
+    write_geometries(w, gdtf);
+    write_dmx_modes(w, gdtf);
+    write_unparsed(w, "Revisions", &unparsed.revisions);
+    if gdtf.ft_presets.is_empty() {
+        w.empty("FTPresets", &[]);
+    } else {
+        w.start("FTPresets", &[]);
+        for FtPreset { extensions } in &gdtf.ft_presets {
+            let attributes = with_unknown(&[], &extensions.attributes);
+            if extensions.nodes.is_empty() {
+                w.empty("FTPreset", &attributes);
+                continue;
+            }
+            w.start("FTPreset", &attributes);
+            for node in &extensions.nodes {
+                w.raw(node);
+            }
+            w.end();
+        }
+        w.end();
+    }
+    write_protocols(w, gdtf);
+    for node in &gdtf.extensions.nodes {
+        w.raw(node);
+    }
+    w.end();
+}
+
+/// Write a section that is not parsed yet as it was read, or empty if the
+/// file did not have it
+fn write_unparsed(w: &mut XmlWriter, tag: &'static str, xml: &Option<String>) {
+    match xml {
+        Some(xml) => w.raw(xml),
+        None => w.empty(tag, &[]),
+    }
+}
+
+fn write_wheels(w: &mut XmlWriter, gdtf: &Gdtf) {
+    if gdtf.wheels.is_empty() {
+        w.empty("Wheels", &[]);
+        return;
+    }
+    w.start("Wheels", &[]);
+    for Wheel { name, slots } in gdtf.wheels.iter() {
+        if slots.is_empty() {
+            w.empty("Wheel", &[("Name", name.as_str())]);
+            continue;
+        }
+        w.start("Wheel", &[("Name", name.as_str())]);
+        for Slot {
+            name,
+            color,
+            media_file_name,
+        } in slots
+        {
+            let color = color.to_string();
+            let mut attributes = vec![("Name", name.as_str()), ("Color", color.as_str())];
+            if let Some(media_file_name) = media_file_name {
+                attributes.push(("MediaFileName", media_file_name.as_str()));
+            }
+            w.empty("Slot", &attributes);
+        }
+        w.end();
+    }
+    w.end();
+}
+
+fn write_models(w: &mut XmlWriter, gdtf: &Gdtf) {
+    if gdtf.models.is_empty() {
+        w.empty("Models", &[]);
+        return;
+    }
+    w.start("Models", &[]);
+    for Model {
+        name,
+        length,
+        width,
+        height,
+        primitive_type,
+        file,
+        extensions,
+    } in gdtf.models.iter()
+    {
+        let attributes: [(&str, &str); 6] = [
+            ("Name", name.as_str()),
+            ("Length", &Fixed(*length).to_string()),
+            ("Width", &Fixed(*width).to_string()),
+            ("Height", &Fixed(*height).to_string()),
+            ("PrimitiveType", &primitive_type.to_string()),
+            ("File", file.as_deref().unwrap_or("")),
+        ];
+        let attributes = with_unknown(&attributes, &extensions.attributes);
+        if extensions.nodes.is_empty() {
+            w.empty("Model", &attributes);
+            continue;
+        }
+        w.start("Model", &attributes);
+        for node in &extensions.nodes {
+            w.raw(node);
+        }
+        w.end();
+    }
+    w.end();
+}
+
+fn write_protocols(w: &mut XmlWriter, gdtf: &Gdtf) {
+    let p = &gdtf.protocols;
+    w.start("Protocols", &[]);
+    if let Some(art_net) = &p.art_net {
+        write_maps(w, "Art-Net", &art_net.maps);
+    }
+    if let Some(sacn) = &p.sacn {
+        write_maps(w, "sACN", &sacn.maps);
+    }
+    if p.posi_stage_net.is_some() {
+        w.empty("PosiStageNet", &[]);
+    }
+    if p.open_sound_control.is_some() {
+        w.empty("OpenSoundControl", &[]);
+    }
+    if p.citp.is_some() {
+        w.empty("CITP", &[]);
+    }
+    if let Some(ftrdm) = &p.ftrdm {
+        w.raw(ftrdm);
+    }
+    w.end();
+}
+
+fn write_maps(w: &mut XmlWriter, tag: &'static str, maps: &[DmxMap]) {
+    w.start(tag, &[]);
+    for DmxMap { key, value } in maps {
+        w.empty(
+            "Map",
+            &[("Key", &key.to_string()), ("Value", &value.to_string())],
+        );
+    }
+    w.end();
+}
+
+/// Append preserved unknown attributes to the known ones
+fn with_unknown<'a>(
+    known: &[(&'a str, &'a str)],
+    unknown: &'a [(String, String)],
+) -> Vec<(&'a str, &'a str)> {
+    known
+        .iter()
+        .copied()
+        .chain(unknown.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+        .collect()
+}
+
+/// Borrow attributes with owned values for [`XmlWriter`]
+fn borrowed<'a>(attributes: &'a [(&'a str, String)]) -> Vec<(&'a str, &'a str)> {
+    attributes.iter().map(|(k, v)| (*k, v.as_str())).collect()
+}
+
+fn uppercase_uuid(uuid: &uuid::Uuid) -> String {
+    uuid.hyphenated().to_string().to_uppercase()
+}
+
+fn yes_no(b: bool) -> &'static str {
+    if b {
+        "Yes"
+    } else {
+        "No"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use proptest::{prelude::*, sample::Index};
+
+    use crate::{
+        builder::GdtfBuilder, extensions::Extensions, name::IntoValidName,
+        parser::parse_description,
+    };
+
+    use super::*;
+
+    #[test]
+    fn serialized_fixture_type_can_be_parsed() {
+        let mut gdtf = Gdtf::default();
+        gdtf.name = "Serialized <Parsed>".into_valid();
+        gdtf.manufacturer = "Manufacturer".into();
+        gdtf.description = "First line\nSecond line".into();
+        gdtf.ref_ft = Some(uuid::Uuid::nil());
+        gdtf.can_have_children = false;
+        gdtf.extensions
+            .attributes
+            .push(("VendorExtension".into(), "kept".into()));
+        gdtf.ft_presets.push(FtPreset {
+            extensions: Extensions {
+                attributes: vec![("Name".into(), "Warm".into())],
+                nodes: vec![],
+            },
+        });
+        gdtf.models
+            .add(Model {
+                name: "Body".into_valid(),
+                length: 0.5,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let parsed = parse_description(serialize_gdtf(&gdtf), &Default::default()).unwrap();
+        assert!(parsed.problems.is_empty(), "{:?}", parsed.problems);
+        assert_eq!(parsed.gdtf.name, "Serialized <Parsed>");
+        assert_eq!(parsed.gdtf.manufacturer, "Manufacturer");
+        assert_eq!(parsed.gdtf.description, "First line\nSecond line");
+        assert_eq!(parsed.gdtf.ref_ft, Some(uuid::Uuid::nil()));
+        assert!(!parsed.gdtf.can_have_children);
+        assert_eq!(parsed.gdtf.extensions, gdtf.extensions);
+        assert_eq!(parsed.gdtf.ft_presets, gdtf.ft_presets);
+        assert_eq!(parsed.gdtf.models.len(), 1);
+        assert_eq!(parsed.gdtf.models.first().unwrap().length, 0.5);
+    }
+
+    #[test]
+    fn geometries_and_dmx_modes_roundtrip() {
+        let description = r#"
+<GDTF DataVersion="1.2">
+    <FixtureType Name="Roundtrip" ShortName="RT" LongName="Roundtrip" Manufacturer="Test" Description="" FixtureTypeID="00000000-0000-0000-0000-000000000001">
+        <Geometries>
+            <Geometry Name="Body" Position="{1,0,0,0}{0,1,0,0}{0,0,1,0.5}{0,0,0,1}">
+                <Axis Name="Yoke">
+                    <Beam Name="Beam" LampType="LED" BeamAngle="20"/>
+                </Axis>
+                <GeometryReference Name="Pixel 1" Geometry="Pixel">
+                    <Break DMXBreak="1" DMXOffset="3"/>
+                </GeometryReference>
+                <GeometryReference Name="Pixel 2" Geometry="Pixel">
+                    <Break DMXBreak="1" DMXOffset="4"/>
+                </GeometryReference>
+            </Geometry>
+            <Geometry Name="Pixel">
+                <WiringObject Name="Port" ConnectorType="XLR" FuseRating="B"/>
+            </Geometry>
+        </Geometries>
+        <DMXModes>
+            <DMXMode Name="Mode" Geometry="Body">
+                <DMXChannels>
+                    <DMXChannel DMXBreak="1" Offset="1,2" InitialFunction="Beam_Dimmer.Dimmer.Dimmer" Highlight="65535/2" Geometry="Beam">
+                        <LogicalChannel Attribute="Dimmer">
+                            <ChannelFunction Name="Dimmer" Attribute="Dimmer" DMXFrom="0/2" Default="32768/2" PhysicalTo="0.5">
+                                <ChannelSet Name="Closed" DMXFrom="0/2"/>
+                                <ChannelSet Name="Open" DMXFrom="65535/2" WheelSlotIndex="1"/>
+                            </ChannelFunction>
+                        </LogicalChannel>
+                    </DMXChannel>
+                    <DMXChannel DMXBreak="1" Offset="1" Geometry="Pixel">
+                        <LogicalChannel Attribute="ColorAdd_R">
+                            <ChannelFunction Name="Red" Attribute="ColorAdd_R" DMXFrom="0/1" ModeMaster="Beam_Dimmer" ModeFrom="1/2" ModeTo="65535/2"/>
+                        </LogicalChannel>
+                    </DMXChannel>
+                </DMXChannels>
+                <FTMacros>
+                    <FTMacro Name="Full">
+                        <MacroDMX>
+                            <MacroDMXStep Duration="2">
+                                <MacroDMXValue Value="65535/2" DMXChannel="Beam_Dimmer"/>
+                            </MacroDMXStep>
+                        </MacroDMX>
+                    </FTMacro>
+                </FTMacros>
+            </DMXMode>
+        </DMXModes>
+    </FixtureType>
+</GDTF>"#;
+        let parsed = parse_description(description.into(), &Default::default()).unwrap();
+        let serialized = serialize_gdtf(&parsed.gdtf);
+
+        let reparsed = parse_description(serialized.clone(), &Default::default()).unwrap();
+        assert!(reparsed.problems.is_empty(), "{:?}", reparsed.problems);
+        assert_eq!(serialize_gdtf(&reparsed.gdtf), serialized);
+
+        let gdtf = &reparsed.gdtf;
+        assert_eq!(gdtf.geometries.names().len(), 7);
+        let pixel = gdtf.geometries.get_index(&"Pixel".into_valid()).unwrap();
+        assert_eq!(gdtf.geometries.template_references(pixel).count(), 2);
+
+        let mode = gdtf.dmx_modes().first().unwrap();
+        let channel = mode.channels.first().unwrap();
+        assert_eq!(channel.default, 32768);
+        assert_eq!(channel.highlight, Some(65535));
+        let offsets: Vec<_> = mode
+            .subfixtures
+            .iter()
+            .map(|sf| sf.channels.first().unwrap().offsets.to_vec())
+            .collect();
+        assert_eq!(offsets, [vec![3], vec![4]]);
+        for sf in &mode.subfixtures {
+            let red = *sf
+                .channels
+                .first()
+                .unwrap()
+                .defined_functions()
+                .first()
+                .unwrap();
+            let masters = mode
+                .channel_functions
+                .neighbors_directed(red, petgraph::Direction::Incoming);
+            assert_eq!(
+                masters.collect::<Vec<_>>(),
+                [channel.raw_function().unwrap()]
+            );
+        }
+        assert_eq!(mode.macros.first().unwrap().steps.len(), 1);
+    }
+
+    const ATTRIBUTES: [&str; 5] = ["Dimmer", "Pan", "Tilt", "ColorAdd_R", "Zoom"];
+
+    /// Channel on the geometry at an index, with an attribute, a resolution in
+    /// bytes and channel functions given by their DMXFrom, PhysicalFrom and
+    /// PhysicalTo
+    type ChannelParams = (Index, &'static str, u8, Vec<(u32, f64, f64)>);
+
+    fn arbitrary_channel() -> impl Strategy<Value = ChannelParams> {
+        (
+            any::<Index>(),
+            prop::sample::select(ATTRIBUTES.as_slice()),
+            1u8..=2,
+            prop::collection::vec((0u32..=255, -1000f64..1000., -1000f64..1000.), 0..4),
+        )
+    }
+
+    /// A fixture type built with [`GdtfBuilder`]. It has a tree of geometries
+    /// below the top-level geometry `Body`, some more top-level geometries and
+    /// DMX modes on `Body` whose channels are at consecutive offsets.
+    fn arbitrary_gdtf() -> impl Strategy<Value = Gdtf> {
+        let text = "[A-Za-z0-9 &<>\"'\n]{0,20}";
+        (
+            "[A-Za-z][A-Za-z0-9 _-]{0,12}",
+            text,
+            text,
+            any::<u128>(),
+            prop::collection::vec(any::<Index>(), 0..6),
+            0usize..3,
+            prop::collection::vec(prop::collection::vec(arbitrary_channel(), 0..6), 0..3),
+        )
+            .prop_map(
+                |(name, manufacturer, description, id, parents, extra_top_level, modes)| {
+                    let mut builder = GdtfBuilder::new(manufacturer, &name)
+                        .and_then(|b| b.geometry("Body", None))
+                        .unwrap();
+                    let mut geometries = vec!["Body".to_string()];
+                    for parent in parents {
+                        let parent = parent.get(&geometries).clone();
+                        let geometry = format!("Geometry {}", geometries.len());
+                        builder = builder.geometry(&geometry, Some(&parent)).unwrap();
+                        geometries.push(geometry);
+                    }
+                    for i in 1..=extra_top_level {
+                        builder = builder.geometry(&format!("Extra {i}"), None).unwrap();
+                    }
+
+                    for (i, channels) in modes.into_iter().enumerate() {
+                        let mode = format!("Mode {i}");
+                        builder = builder.dmx_mode(&mode, "Body").unwrap();
+                        let mut taken = HashSet::new();
+                        let mut offset = 1;
+                        for (geometry, attribute, bytes, mut functions) in channels {
+                            let geometry = geometry.get(&geometries);
+                            if !taken.insert((geometry, attribute)) {
+                                continue;
+                            }
+                            let offsets = (offset..offset + u16::from(bytes)).collect();
+                            offset += u16::from(bytes);
+                            builder = builder
+                                .channel(&mode, geometry, attribute, 1, offsets)
+                                .unwrap();
+
+                            functions.sort_by_key(|(dmx_from, _, _)| *dmx_from);
+                            functions.dedup_by_key(|(dmx_from, _, _)| *dmx_from);
+                            let channel = format!("{geometry}_{attribute}");
+                            for (j, (dmx_from, phys_from, phys_to)) in
+                                functions.into_iter().enumerate()
+                            {
+                                builder = builder
+                                    .channel_function(
+                                        &mode,
+                                        &channel,
+                                        &format!("Function {j}"),
+                                        attribute,
+                                        dmx_from,
+                                        phys_from,
+                                        phys_to,
+                                    )
+                                    .unwrap();
+                            }
+                        }
+                    }
+
+                    let mut gdtf = builder.build();
+                    gdtf.description = description;
+                    gdtf.fixture_type_id = uuid::Uuid::from_u128(id);
+                    gdtf
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn serialize_parse_serialize_is_stable(gdtf in arbitrary_gdtf()) {
+            let serialized = serialize_gdtf(&gdtf);
+            let parsed = parse_description(serialized.clone(), &Default::default()).unwrap();
+            prop_assert!(parsed.problems.is_empty(), "{:?}", parsed.problems);
+            prop_assert_eq!(serialize_gdtf(&parsed.gdtf), serialized);
+        }
+    }
+}
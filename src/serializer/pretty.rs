@@ -0,0 +1,95 @@
+use roxmltree::{Document, Node, NodeType};
+
+use super::xml_writer::{escape_into, XML_DECLARATION};
+
+/// Indent an XML document like GDTF Builder does, e.g. to edit
+/// `description.xml` by hand and track it in version control.
+///
+/// Whitespace between elements is replaced, elements, attributes, text and
+/// comments are kept in order. The XML declaration is rewritten and
+/// processing instructions are dropped.
+pub fn pretty_print_xml(xml: &str) -> Result<String, roxmltree::Error> {
+    let doc = Document::parse(xml)?;
+    let mut out = XML_DECLARATION.to_owned();
+    for node in doc.root().children() {
+        write_node(node, 0, &mut out);
+    }
+    Ok(out)
+}
+
+fn write_node(node: Node, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    match node.node_type() {
+        NodeType::Element => {
+            let tag = node.tag_name().name();
+            out.push_str(&indent);
+            out.push('<');
+            out.push_str(tag);
+            for attribute in node.attributes() {
+                out.push(' ');
+                out.push_str(attribute.name());
+                out.push_str("=\"");
+                escape_into(attribute.value(), out);
+                out.push('"');
+            }
+            let children: Vec<_> = node.children().filter(|c| !is_blank(c)).collect();
+            match children.as_slice() {
+                [] => out.push_str("/>\n"),
+                [text] if text.is_text() => {
+                    out.push('>');
+                    escape_into(text.text().unwrap_or_default(), out);
+                    out.push_str(&format!("</{tag}>\n"));
+                }
+                children => {
+                    out.push_str(">\n");
+                    for child in children {
+                        write_node(*child, depth + 1, out);
+                    }
+                    out.push_str(&format!("{indent}</{tag}>\n"));
+                }
+            }
+        }
+        NodeType::Text => {
+            out.push_str(&indent);
+            escape_into(node.text().unwrap_or_default().trim(), out);
+            out.push('\n');
+        }
+        NodeType::Comment => {
+            out.push_str(&format!(
+                "{indent}<!--{}-->\n",
+                node.text().unwrap_or_default()
+            ));
+        }
+        NodeType::Root | NodeType::PI => {}
+    }
+}
+
+fn is_blank(node: &Node) -> bool {
+    node.is_text() && node.text().unwrap_or_default().trim().is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indents_and_keeps_content() {
+        let pretty = pretty_print_xml(
+            r#"<?xml version="1.0"?><GDTF DataVersion="1.2"><FixtureType Name="A &amp; B">
+            <!-- note --><Geometries/><Text>some text</Text></FixtureType></GDTF>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            pretty,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\" ?>\n\
+            <GDTF DataVersion=\"1.2\">\n  \
+            <FixtureType Name=\"A &amp; B\">\n    \
+            <!-- note -->\n    \
+            <Geometries/>\n    \
+            <Text>some text</Text>\n  \
+            </FixtureType>\n\
+            </GDTF>\n"
+        );
+        assert_eq!(pretty_print_xml(&pretty).unwrap(), pretty);
+    }
+}
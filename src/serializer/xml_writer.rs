@@ -0,0 +1,143 @@
+use std::fmt::Write;
+
+pub(super) const XML_DECLARATION: &str =
+    "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\" ?>\n";
+
+/// Minimal XML writer producing indented output like GDTF Builder.
+///
+/// Attribute values are escaped, tags and attribute names are written as-is.
+#[derive(Debug, Default)]
+pub(crate) struct XmlWriter {
+    out: String,
+    open: Vec<&'static str>,
+    /// whether attributes are written sorted by name instead of in the given
+    /// order
+    sort_attributes: bool,
+}
+
+impl XmlWriter {
+    pub(crate) fn new() -> Self {
+        let mut writer = Self::default();
+        writer.out.push_str(XML_DECLARATION);
+        writer
+    }
+
+    /// Writer that sorts the attributes of every element by name, for output
+    /// that does not depend on attribute order
+    pub(crate) fn sorted() -> Self {
+        Self {
+            sort_attributes: true,
+            ..Self::new()
+        }
+    }
+
+    /// Open an element that will contain children
+    pub(crate) fn start(&mut self, tag: &'static str, attributes: &[(&str, &str)]) {
+        self.write_tag(tag, attributes);
+        self.out.push_str(">\n");
+        self.open.push(tag);
+    }
+
+    /// Write an element without children
+    pub(crate) fn empty(&mut self, tag: &'static str, attributes: &[(&str, &str)]) {
+        self.write_tag(tag, attributes);
+        self.out.push_str("/>\n");
+    }
+
+    /// Write XML text as-is, e.g. an element kept verbatim from a parsed file
+    pub(crate) fn raw(&mut self, xml: &str) {
+        self.indent();
+        self.out.push_str(xml);
+        self.out.push('\n');
+    }
+
+    /// Close the last opened element
+    pub(crate) fn end(&mut self) {
+        if let Some(tag) = self.open.pop() {
+            self.indent();
+            // writing to a String can't fail
+            let _ = writeln!(self.out, "</{tag}>");
+        }
+    }
+
+    /// Close all open elements and return the document
+    pub(crate) fn finish(mut self) -> String {
+        while !self.open.is_empty() {
+            self.end();
+        }
+        self.out
+    }
+
+    fn write_tag(&mut self, tag: &str, attributes: &[(&str, &str)]) {
+        self.indent();
+        self.out.push('<');
+        self.out.push_str(tag);
+        let mut attributes = attributes.to_vec();
+        if self.sort_attributes {
+            attributes.sort_by_key(|(name, _)| *name);
+        }
+        for (name, value) in attributes {
+            self.out.push(' ');
+            self.out.push_str(name);
+            self.out.push_str("=\"");
+            escape_into(value, &mut self.out);
+            self.out.push('"');
+        }
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.open.len() {
+            self.out.push_str("  ");
+        }
+    }
+}
+
+pub(super) fn escape_into(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // XML parsers normalize literal whitespace in attributes to spaces
+            '\n' => out.push_str("&#10;"),
+            '\r' => out.push_str("&#13;"),
+            '\t' => out.push_str("&#9;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_nested_escaped_xml() {
+        let mut w = XmlWriter::new();
+        w.start("GDTF", &[("DataVersion", "1.2")]);
+        w.empty("FixtureType", &[("Name", "A \"quoted\" <name> & more")]);
+        assert_eq!(
+            w.finish(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\" ?>\n\
+            <GDTF DataVersion=\"1.2\">\n  \
+            <FixtureType Name=\"A &quot;quoted&quot; &lt;name&gt; &amp; more\"/>\n\
+            </GDTF>\n"
+        );
+    }
+
+    #[test]
+    fn escapes_whitespace_in_attributes() {
+        let mut w = XmlWriter::new();
+        w.empty("FixtureType", &[("Description", "line 1\r\nline 2\tend")]);
+        let xml = w.finish();
+        assert!(xml.contains(r#"Description="line 1&#13;&#10;line 2&#9;end""#));
+
+        let doc = roxmltree::Document::parse(&xml).unwrap();
+        assert_eq!(
+            doc.root_element().attribute("Description"),
+            Some("line 1\r\nline 2\tend")
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::io::{self, Seek, Write};
+
+use zip::{result::ZipError, write::FileOptions, ZipWriter};
+
+use crate::Gdtf;
+
+use super::serialize_gdtf;
+
+#[derive(thiserror::Error, Debug)]
+pub enum WriteError {
+    #[error("zip error: {0}")]
+    Zip(#[from] ZipError),
+    #[error("could not write file to archive: {0}")]
+    Io(#[from] io::Error),
+    #[error("resource path '{0}' is used more than once or clashes with description.xml")]
+    DuplicatePath(String),
+}
+
+/// Write a complete GDTF archive with `description.xml` and the given
+/// resource files, given as pairs of path inside the archive and content.
+///
+/// Returns the writer after finishing the archive.
+pub fn write_gdtf_archive<W, I, P, C>(gdtf: &Gdtf, resources: I, writer: W) -> Result<W, WriteError>
+where
+    W: Write + Seek,
+    I: IntoIterator<Item = (P, C)>,
+    P: AsRef<str>,
+    C: AsRef<[u8]>,
+{
+    write_description_archive(&serialize_gdtf(gdtf), resources, writer)
+}
+
+/// Like [`write_gdtf_archive`], but with the content of `description.xml`
+/// given as is, e.g. after editing it by hand
+pub fn write_description_archive<W, I, P, C>(
+    description: &str,
+    resources: I,
+    writer: W,
+) -> Result<W, WriteError>
+where
+    W: Write + Seek,
+    I: IntoIterator<Item = (P, C)>,
+    P: AsRef<str>,
+    C: AsRef<[u8]>,
+{
+    let mut zip = ZipWriter::new(writer);
+    let options = FileOptions::default();
+
+    zip.start_file("description.xml", options)?;
+    zip.write_all(description.as_bytes())?;
+
+    let mut paths = vec!["description.xml".to_owned()];
+    for (path, content) in resources {
+        let path = path.as_ref().trim_start_matches("./").to_owned();
+        if paths.contains(&path) {
+            return Err(WriteError::DuplicatePath(path));
+        }
+        zip.start_file(path.as_str(), options)?;
+        zip.write_all(content.as_ref())?;
+        paths.push(path);
+    }
+
+    Ok(zip.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{archive::GdtfArchive, name::IntoValidName};
+
+    use super::*;
+
+    #[test]
+    fn archive_roundtrip() {
+        let mut gdtf = Gdtf::default();
+        gdtf.name = "Written".into_valid();
+
+        let mut cursor = write_gdtf_archive(
+            &gdtf,
+            [("wheels/gobo.png", b"png".as_slice())],
+            Cursor::new(vec![]),
+        )
+        .unwrap();
+        cursor.set_position(0);
+
+        let mut archive = GdtfArchive::new(cursor).unwrap();
+        assert_eq!(
+            archive.file_names().collect::<Vec<_>>(),
+            vec!["description.xml", "wheels/gobo.png"]
+        );
+        let parsed = archive.parse(&Default::default()).unwrap();
+        assert_eq!(parsed.gdtf.name, "Written");
+
+        assert!(matches!(
+            write_gdtf_archive(
+                &gdtf,
+                [("./description.xml", b"".as_slice())],
+                Cursor::new(vec![])
+            ),
+            Err(WriteError::DuplicatePath(p)) if p == "description.xml"
+        ));
+    }
+}
@@ -0,0 +1,259 @@
+//! Client for the [GDTF Share](https://gdtf-share.com) API, enabled with the
+//! `share-api` feature.
+//!
+//! An account on GDTF Share is needed to use the API.
+//!
+//! ```no_run
+//! # use opengdtf::share::Client;
+//! let client = Client::login("user", "password")?;
+//! let fixtures = client.list()?;
+//! if let Some(fixture) = fixtures.iter().find(|f| f.manufacturer == "Robe") {
+//!     let file = client.download(fixture.rid)?;
+//!     let parsed = opengdtf::parse(std::io::Cursor::new(file))?;
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::io::{self, Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+/// Base URL of the public GDTF Share API
+pub const DEFAULT_BASE_URL: &str = "https://gdtf-share.com/apis/public";
+
+/// Signature at the start of a zip archive, and so of every GDTF file
+const ZIP_MAGIC: &[u8; 4] = b"PK\x03\x04";
+
+/// A logged in session on GDTF Share
+#[derive(Debug)]
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ShareError {
+    #[error("request to GDTF Share failed")]
+    Http(#[from] Box<ureq::Error>),
+    #[error("response of GDTF Share could not be read")]
+    Io(#[from] io::Error),
+    #[error("login to GDTF Share failed: {0}")]
+    LoginFailed(String),
+    #[error("GDTF Share returned an error: {0}")]
+    Api(String),
+    #[error("GDTF Share returned a file that is not a GDTF file")]
+    NotGdtf,
+}
+
+/// A fixture revision on GDTF Share, as listed by [`Client::list`]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fixture {
+    /// revision ID, used to download the file
+    pub rid: u64,
+    /// name of the fixture type
+    pub fixture: String,
+    pub manufacturer: String,
+    /// revision text given by the uploader
+    #[serde(default)]
+    pub revision: String,
+    /// FixtureTypeID
+    #[serde(default)]
+    pub uuid: String,
+    /// GDTF DataVersion
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub creator: String,
+    #[serde(default)]
+    pub uploader: String,
+    /// Unix timestamp in seconds
+    #[serde(default)]
+    pub creation_date: u64,
+    /// Unix timestamp in seconds
+    #[serde(default)]
+    pub last_modified: u64,
+    /// size of the GDTF file in bytes
+    #[serde(default)]
+    pub filesize: u64,
+    #[serde(default)]
+    pub modes: Vec<Mode>,
+}
+
+/// A DMX mode of a [`Fixture`]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Mode {
+    pub name: String,
+    #[serde(rename = "dmxfootprint")]
+    pub dmx_footprint: u32,
+}
+
+#[derive(Serialize)]
+struct Login<'a> {
+    user: &'a str,
+    password: &'a str,
+}
+
+#[derive(Deserialize)]
+struct Response<T> {
+    result: bool,
+    #[serde(default)]
+    error: String,
+    #[serde(flatten)]
+    content: T,
+}
+
+#[derive(Deserialize)]
+struct List {
+    #[serde(default)]
+    list: Vec<Fixture>,
+}
+
+#[derive(Deserialize)]
+struct Empty {}
+
+impl Client {
+    /// Log in at [`DEFAULT_BASE_URL`]
+    pub fn login(user: &str, password: &str) -> Result<Self, ShareError> {
+        Self::login_at(DEFAULT_BASE_URL, user, password)
+    }
+
+    /// Log in at another instance of the API, e.g. a mock server
+    pub fn login_at(base_url: &str, user: &str, password: &str) -> Result<Self, ShareError> {
+        let client = Client {
+            // the session is kept in a cookie
+            agent: ureq::AgentBuilder::new().build(),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+        };
+        let response: Response<Empty> = client
+            .agent
+            .post(&client.url("login.php"))
+            .send_json(Login { user, password })
+            .map_err(Box::new)?
+            .into_json()?;
+        if !response.result {
+            return Err(ShareError::LoginFailed(response.error));
+        }
+        Ok(client)
+    }
+
+    /// All fixture revisions available on GDTF Share
+    pub fn list(&self) -> Result<Vec<Fixture>, ShareError> {
+        let response: Response<List> = self
+            .agent
+            .get(&self.url("getList.php"))
+            .call()
+            .map_err(Box::new)?
+            .into_json()?;
+        if !response.result {
+            return Err(ShareError::Api(response.error));
+        }
+        Ok(response.content.list)
+    }
+
+    /// Download the GDTF file of the revision `rid` into memory
+    pub fn download(&self, rid: u64) -> Result<Vec<u8>, ShareError> {
+        let mut buf = vec![];
+        self.download_to(rid, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Download the GDTF file of the revision `rid` into `writer`, e.g. a
+    /// file. Returns the number of bytes written.
+    ///
+    /// GDTF Share answers failed downloads with an error message, so nothing
+    /// is written unless the response starts like a zip archive.
+    pub fn download_to(&self, rid: u64, writer: impl Write) -> Result<u64, ShareError> {
+        let response = self
+            .agent
+            .get(&self.url("downloadFile.php"))
+            .query("rid", &rid.to_string())
+            .call()
+            .map_err(Box::new)?;
+        if response.content_type() == "application/json" {
+            let response: Response<Empty> = response.into_json()?;
+            return Err(ShareError::Api(response.error));
+        }
+        copy_gdtf(response.into_reader(), writer)
+    }
+
+    fn url(&self, endpoint: &str) -> String {
+        format!("{}/{endpoint}", self.base_url)
+    }
+}
+
+/// Copy `reader` to `writer` if it starts with [`ZIP_MAGIC`]
+fn copy_gdtf(mut reader: impl Read, mut writer: impl Write) -> Result<u64, ShareError> {
+    let mut magic = Vec::with_capacity(ZIP_MAGIC.len());
+    reader
+        .by_ref()
+        .take(ZIP_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+    if magic != ZIP_MAGIC {
+        return Err(ShareError::NotGdtf);
+    }
+    writer.write_all(&magic)?;
+    Ok(magic.len() as u64 + io::copy(&mut reader, &mut writer)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_list() {
+        let json = r#"{
+            "result": true,
+            "timestamp": 1675000000,
+            "list": [{
+                "rid": 12345,
+                "fixture": "Spot",
+                "manufacturer": "Test",
+                "revision": "initial",
+                "creationDate": 1600000000,
+                "lastModified": 1600000001,
+                "uploader": "Manuf.",
+                "rating": "0.00",
+                "version": "1.1",
+                "creator": "GDTF Builder",
+                "uuid": "00000000-0000-0000-0000-000000000001",
+                "filesize": 2048,
+                "modes": [{"name": "Standard", "dmxfootprint": 16}]
+            }]
+        }"#;
+        let response: Response<List> = serde_json::from_str(json).unwrap();
+        assert!(response.result);
+        let fixture = response.content.list.first().unwrap();
+        assert_eq!(fixture.rid, 12345);
+        assert_eq!(fixture.last_modified, 1600000001);
+        assert_eq!(
+            fixture.modes,
+            [Mode {
+                name: "Standard".into(),
+                dmx_footprint: 16
+            }]
+        );
+
+        let failed: Response<List> =
+            serde_json::from_str(r#"{"result": false, "error": "not logged in"}"#).unwrap();
+        assert!(!failed.result);
+        assert_eq!(failed.error, "not logged in");
+    }
+
+    #[test]
+    fn only_zip_archives_are_copied() {
+        let mut written = vec![];
+        let copied = copy_gdtf(&b"PK\x03\x04rest of the archive"[..], &mut written).unwrap();
+        assert_eq!(copied, 23);
+        assert_eq!(written, b"PK\x03\x04rest of the archive");
+
+        for response in [&b"<html>Not found</html>"[..], b"PK", b""] {
+            let mut written = vec![];
+            assert!(matches!(
+                copy_gdtf(response, &mut written),
+                Err(ShareError::NotGdtf)
+            ));
+            assert!(written.is_empty());
+        }
+    }
+}
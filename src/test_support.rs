@@ -0,0 +1,112 @@
+//! Minimal fixtures for unit tests of applications that embed opengdtf, so
+//! they don't have to ship real GDTF files. Enabled with the `test_support`
+//! feature.
+//!
+//! All fixtures have the FixtureTypeID `00000000-0000-0000-0000-000000000001`,
+//! so they are the same in every test run.
+
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use uuid::Uuid;
+
+use crate::{builder::GdtfBuilder, parser::parse_description, serialize_gdtf, Gdtf};
+
+/// FixtureTypeID of all fixtures in this module
+pub const FIXTURE_TYPE_ID: Uuid = Uuid::from_u128(1);
+
+/// A fixture with the top-level geometry `Body`, the child geometries
+/// `Cell 1` to `Cell {channels}` and the DMX mode `Mode`, which has one 8 bit
+/// `Dimmer` channel per cell at consecutive offsets in break 1.
+///
+/// Panics if `channels` is bigger than 512.
+pub fn single_mode_gdtf(channels: u16) -> Gdtf {
+    assert!(channels <= 512, "at most 512 channels fit into a break");
+    let mut builder = GdtfBuilder::new("Test", "Test Fixture")
+        .and_then(|b| b.geometry("Body", None))
+        .and_then(|b| b.dmx_mode("Mode", "Body"))
+        .expect("fixed names are valid");
+    for i in 1..=channels {
+        let cell = format!("Cell {i}");
+        builder = builder
+            .geometry(&cell, Some("Body"))
+            .and_then(|b| b.channel("Mode", &cell, "Dimmer", 1, vec![i]))
+            .expect("cells have unique names and offsets");
+    }
+    let mut gdtf = builder.build();
+    gdtf.fixture_type_id = FIXTURE_TYPE_ID;
+    gdtf
+}
+
+/// `description.xml` of [`single_mode_gdtf`]
+pub fn single_mode_description(channels: u16) -> String {
+    serialize_gdtf(&single_mode_gdtf(channels))
+}
+
+/// A pixel fixture. The top-level geometry `Body` contains the
+/// GeometryReferences `Pixel 1` to `Pixel {pixels}` to the template geometry
+/// `Pixel`. The DMX mode `Mode` has one 8 bit template channel `Dimmer` on
+/// `Pixel`, which is instantiated at offset `n` for `Pixel n`.
+///
+/// Panics if `pixels` is bigger than 512.
+pub fn pixel_template_gdtf(pixels: u16) -> Gdtf {
+    assert!(pixels <= 512, "at most 512 pixels fit into a break");
+    let references: String = (1..=pixels)
+        .map(|i| {
+            format!(
+                r#"
+                <GeometryReference Name="Pixel {i}" Geometry="Pixel">
+                    <Break DMXBreak="1" DMXOffset="{i}"/>
+                </GeometryReference>"#
+            )
+        })
+        .collect();
+    let description = format!(
+        r#"<GDTF DataVersion="1.2">
+    <FixtureType Name="Test Pixel Fixture" Manufacturer="Test" FixtureTypeID="{FIXTURE_TYPE_ID}">
+        <Geometries>
+            <Geometry Name="Body">{references}
+            </Geometry>
+            <Geometry Name="Pixel"/>
+        </Geometries>
+        <DMXModes>
+            <DMXMode Name="Mode" Geometry="Body">
+                <DMXChannels>
+                    <DMXChannel DMXBreak="1" Offset="1" Geometry="Pixel">
+                        <LogicalChannel Attribute="Dimmer">
+                            <ChannelFunction Name="Dimmer" Attribute="Dimmer" DMXFrom="0/1"/>
+                        </LogicalChannel>
+                    </DMXChannel>
+                </DMXChannels>
+            </DMXMode>
+        </DMXModes>
+    </FixtureType>
+</GDTF>"#
+    );
+    parse_description(description, &Default::default())
+        .expect("the description is valid XML with a GDTF root node")
+        .gdtf
+}
+
+/// `description.xml` of [`pixel_template_gdtf`]
+pub fn pixel_template_description(pixels: u16) -> String {
+    serialize_gdtf(&pixel_template_gdtf(pixels))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixtures_parse_without_problems() {
+        let parsed = parse_description(single_mode_description(3), &Default::default()).unwrap();
+        assert!(parsed.problems.is_empty(), "{:?}", parsed.problems);
+        let mode = parsed.gdtf.dmx_modes().first().unwrap();
+        assert_eq!(mode.channels.len(), 3);
+
+        let parsed = parse_description(pixel_template_description(4), &Default::default()).unwrap();
+        assert!(parsed.problems.is_empty(), "{:?}", parsed.problems);
+        let mode = parsed.gdtf.dmx_modes().first().unwrap();
+        let offsets: Vec<_> = mode.all_channels().map(|ch| ch.offsets.to_vec()).collect();
+        assert_eq!(offsets, [vec![1], vec![2], vec![3], vec![4]]);
+    }
+}
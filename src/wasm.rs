@@ -0,0 +1,32 @@
+//! Bindings for JavaScript, e.g. for fixture library viewers on the web.
+//! Enabled with the `wasm` feature, build with
+//! `wasm-pack build --features wasm`.
+
+use std::io::Cursor;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{parse, Gdtf, Problems};
+
+#[derive(Serialize)]
+struct Parsed<'a> {
+    gdtf: &'a Gdtf,
+    problems: &'a Problems,
+}
+
+/// Parse the bytes of a GDTF file.
+///
+/// Returns an object with the fixture model in `gdtf`, in the same form as
+/// [`Gdtf::to_json`], and the problems handled during parsing in `problems`.
+/// Throws if the file can't be parsed at all.
+#[wasm_bindgen]
+pub fn parse_gdtf_bytes(bytes: &[u8]) -> Result<JsValue, JsError> {
+    let parsed = parse(Cursor::new(bytes))?;
+    let value = Parsed {
+        gdtf: &parsed.gdtf,
+        problems: &parsed.problems,
+    }
+    .serialize(&serde_wasm_bindgen::Serializer::json_compatible())?;
+    Ok(value)
+}
@@ -1,21 +1,21 @@
 use std::collections::HashMap;
 
 use example_files::{
-    check_for_duplicate_filenames, opened_examples_iter, parse_expected_toml, parsed_examples_iter,
-    OutputEnum,
+    check_for_duplicate_filenames, opened_examples_iter, parse_expected, parsed_examples_iter,
+    OutputEnum, EXAMPLES,
 };
 use opengdtf::hash::hash_gdtf_to_string;
 use pretty_assertions::assert_eq;
 
 #[test]
-fn expected_toml_has_no_duplicate_filenames() {
-    let expected = parse_expected_toml();
+fn expected_has_no_duplicate_filenames() {
+    let expected = parse_expected();
     check_for_duplicate_filenames(expected);
 }
 
 #[test]
-fn fixtures_from_expected_toml_are_in_examples() {
-    let expected = parse_expected_toml();
+fn fixtures_from_expected_are_in_examples() {
+    let expected = parse_expected();
     let mut hashes_in_examples = HashMap::<String, String>::new();
     for (entry, file) in opened_examples_iter() {
         let key = hash_gdtf_to_string(file).unwrap();
@@ -38,15 +38,15 @@ fn fixtures_from_expected_toml_are_in_examples() {
     }
     assert!(
         missing.is_empty(),
-        "fixtures from 'expected.toml' are missing in examples:
+        "fixtures from 'expected/' are missing in examples:
 {missing:#?}
-please add these fixtures to the examples folder, e.g. by downloading them from gdtf-share.com. Alternatively, delete these entries in 'expected.toml'"
+please add these fixtures to the examples folder, e.g. by downloading them from gdtf-share.com with `cargo run -p example_files --features share-api --bin fetch_examples`. Alternatively, delete the files of these hashes in 'expected/'"
     );
 }
 
 #[test]
-fn fixtures_from_examples_are_in_expected_toml() {
-    let expected = parse_expected_toml();
+fn fixtures_from_examples_are_in_expected() {
+    let expected = parse_expected();
     let mut missing = Vec::<String>::new();
     for (entry, file) in opened_examples_iter() {
         let key = hash_gdtf_to_string(file).unwrap();
@@ -56,15 +56,15 @@ fn fixtures_from_examples_are_in_expected_toml() {
     }
     assert!(
         missing.is_empty(),
-        "fixtures from examples are missing in 'expected.toml':
+        "fixtures from examples are missing in 'expected/':
 {missing:#?}
-please add these fixtures to 'expected.toml' by running `cargo run --bin update_expected` and check the diff of 'expected.toml'"
+please add these fixtures to 'expected/' by running `cargo run --bin update_expected` and check the new files in 'expected/'"
     );
 }
 
 #[test]
-fn expected_toml_matches_examples_output() {
-    let expected = parse_expected_toml();
+fn expected_matches_examples_output() {
+    let expected = parse_expected();
     for (_entry, file, parsed_result) in parsed_examples_iter() {
         let key = hash_gdtf_to_string(file).unwrap();
         let expected_output = match expected.get(&key) {
@@ -76,3 +76,9 @@ fn expected_toml_matches_examples_output() {
         assert_eq!(expected_output, &parsed_entry);
     }
 }
+
+#[test]
+fn corpus_report_has_no_mismatches() {
+    let report = EXAMPLES.report();
+    assert!(report.mismatches.is_empty(), "{report}");
+}
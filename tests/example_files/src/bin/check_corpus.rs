@@ -0,0 +1,26 @@
+use std::{env, process::ExitCode};
+
+use example_files::Corpus;
+
+/// Check a fixture corpus against its `expected/` files, see [`Corpus`].
+///
+/// Usage: `cargo run --bin check_corpus -- <corpus dir> [--update]`
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().collect();
+    let Some(dir) = args.get(1) else {
+        eprintln!("usage: check_corpus <corpus dir> [--update]");
+        return ExitCode::FAILURE;
+    };
+    let corpus = Corpus::new(dir);
+    if args.get(2).map(String::as_str) == Some("--update") {
+        corpus.update_expected();
+        return ExitCode::SUCCESS;
+    }
+    let report = corpus.report();
+    print!("{report}");
+    if report.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,23 @@
+//! Download the example files that `expected/` lists from GDTF Share.
+//!
+//! Needs a GDTF Share account, given in the environment variables
+//! `GDTF_SHARE_USER` and `GDTF_SHARE_PASSWORD`. Run from the root of the
+//! repository with
+//! `cargo run -p example_files --features share-api --bin fetch_examples`.
+
+use std::{env, error::Error};
+
+use example_files::EXAMPLES;
+use opengdtf::share::Client;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let client = Client::login(
+        &env::var("GDTF_SHARE_USER")?,
+        &env::var("GDTF_SHARE_PASSWORD")?,
+    )?;
+    let not_fetched = EXAMPLES.fetch_missing_examples(&client)?;
+    for filename in &not_fetched {
+        println!("could not fetch '{filename}'");
+    }
+    Ok(())
+}
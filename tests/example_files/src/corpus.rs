@@ -0,0 +1,262 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::{self, create_dir_all, remove_dir_all, remove_file, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use opengdtf::{hash::hash_gdtf_to_string, parse, ParsedGdtf};
+use walkdir::{DirEntry, WalkDir};
+
+use crate::{check_for_duplicate_filenames, Expected, ExpectedEntry, OutputEnum};
+
+/// A directory of GDTF files with their expected parse outputs, checked the
+/// same way as the example files of this crate. Can be pointed at any fixture
+/// library, e.g. a private one.
+///
+/// The directory contains:
+/// - `examples/`: the GDTF files, searched recursively
+/// - `expected/`: expected outputs, one TOML file per example named after the
+///   hash of the GDTF file
+/// - `outputs/`: debug output of the parsed files, written on request
+#[derive(Debug, Clone)]
+pub struct Corpus {
+    pub examples_dir: PathBuf,
+    pub expected_dir: PathBuf,
+    pub outputs_dir: PathBuf,
+}
+
+/// Outcome of checking a corpus against its `expected/` files
+#[derive(Debug, Default)]
+pub struct Report {
+    /// file names of examples without an entry in `expected/`
+    pub missing_in_expected: Vec<String>,
+    /// file names of entries in `expected/` without an example
+    pub missing_in_examples: Vec<String>,
+    /// pairs of examples with the same hash
+    pub hash_collisions: Vec<(String, String)>,
+    /// examples whose output differs from `expected/`
+    pub mismatches: Vec<Mismatch>,
+}
+
+#[derive(Debug)]
+pub struct Mismatch {
+    pub filename: String,
+    pub expected: OutputEnum,
+    pub actual: OutputEnum,
+}
+
+impl Corpus {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        let dir = dir.as_ref();
+        Self {
+            examples_dir: dir.join("examples"),
+            expected_dir: dir.join("expected"),
+            outputs_dir: dir.join("outputs"),
+        }
+    }
+
+    /// Expected outputs, empty if `expected/` does not exist yet
+    pub fn parse_expected(&self) -> Expected {
+        let entries = match fs::read_dir(&self.expected_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Expected::new(),
+            Err(e) => panic!("could not read {:?}: {e}", self.expected_dir),
+        };
+        entries
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "toml")
+            })
+            .map(|path| {
+                let key = path.file_stem().unwrap().to_string_lossy().to_string();
+                let expected_str = fs::read_to_string(&path).unwrap();
+                let entry: ExpectedEntry = toml::from_str(&expected_str)
+                    .unwrap_or_else(|e| panic!("could not parse {path:?}: {e}"));
+                (key, entry)
+            })
+            .collect()
+    }
+
+    /// Path of the file holding the expected output for the example with hash `key`
+    pub fn expected_path(&self, key: &str) -> PathBuf {
+        self.expected_dir.join(format!("{key}.toml"))
+    }
+
+    pub fn examples_iter(&self) -> impl Iterator<Item = DirEntry> {
+        WalkDir::new(&self.examples_dir)
+            .into_iter()
+            .map(|result| result.unwrap())
+            .filter(|entry| !entry.file_type().is_dir())
+            .filter(|entry| {
+                Path::new(entry.file_name())
+                    .extension()
+                    .map_or_else(|| false, |extension| extension == "gdtf")
+            })
+    }
+
+    pub fn opened_examples_iter(&self) -> impl Iterator<Item = (DirEntry, File)> {
+        self.examples_iter().map(|entry| {
+            let file = File::open(entry.path()).unwrap();
+            (entry, file)
+        })
+    }
+
+    pub fn parsed_examples_iter(
+        &self,
+    ) -> impl Iterator<Item = (DirEntry, File, Result<ParsedGdtf, opengdtf::Error>)> {
+        self.opened_examples_iter().map(|(entry, file)| {
+            let parse_result = parse(&file);
+            (entry, file, parse_result)
+        })
+    }
+
+    /// Like [`Corpus::parsed_examples_iter`], but also writes the debug output
+    /// of each example to `outputs/`, which is cleared first
+    pub fn examples_update_output_iter(
+        &self,
+    ) -> impl Iterator<Item = (DirEntry, File, Result<ParsedGdtf, opengdtf::Error>)> + '_ {
+        if self.outputs_dir.exists() {
+            remove_dir_all(&self.outputs_dir).unwrap();
+        }
+        create_dir_all(&self.outputs_dir).unwrap();
+
+        self.parsed_examples_iter()
+            .map(|(entry, file, parse_result)| {
+                let file_name = entry.file_name().to_str().unwrap();
+
+                let mut output_file = File::create(self.outputs_dir.join(file_name)).unwrap();
+                write!(output_file, "{parse_result:#?}").unwrap();
+
+                (entry, file, parse_result)
+            })
+    }
+
+    /// Write the current outputs of all examples to `expected/`, one file per
+    /// example. Files whose output stays the same or that are absent from the
+    /// examples are not touched, except for stale files of an example that
+    /// was modified without changing its filename, which are removed.
+    pub fn update_expected(&self) {
+        let mut expected = self.parse_expected();
+        let mut current_filenames = HashMap::<String, String>::new();
+        create_dir_all(&self.expected_dir).unwrap();
+
+        for (entry, file, parsed_result) in self.parsed_examples_iter() {
+            println!("{entry:?}");
+
+            let key = hash_gdtf_to_string(file).unwrap();
+            let filename = entry.file_name().to_string_lossy().to_string();
+            current_filenames.insert(filename.clone(), key.clone());
+
+            let output_enum = parsed_result.into();
+
+            let comment = if let Some(existing_entry) = expected.get(&key) {
+                if existing_entry.output_enum == output_enum {
+                    continue;
+                }
+                existing_entry.comment.clone()
+            } else {
+                "".to_string()
+            };
+
+            let expected_entry = ExpectedEntry {
+                filename,
+                saved_on: Utc::now(),
+                comment,
+                output_enum,
+            };
+            let serialized = toml::to_string_pretty(&expected_entry).unwrap();
+            let mut output_file = File::create(self.expected_path(&key)).unwrap();
+            write!(output_file, "{}", &serialized).unwrap();
+            expected.insert(key, expected_entry);
+        }
+
+        expected.retain(|key, entry| match current_filenames.get(&entry.filename) {
+            Some(current_key) if current_key != key => {
+                println!("removing stale entry {key} of '{}'", entry.filename);
+                remove_file(self.expected_path(key)).unwrap();
+                false
+            }
+            _ => true,
+        });
+
+        check_for_duplicate_filenames(expected);
+    }
+
+    /// Parse all examples and compare them to `expected/`
+    pub fn report(&self) -> Report {
+        let mut expected = self.parse_expected();
+        let mut report = Report::default();
+        let mut filenames_by_hash = HashMap::<String, String>::new();
+
+        for (entry, file, parsed_result) in self.parsed_examples_iter() {
+            let key = hash_gdtf_to_string(file).unwrap();
+            let filename = entry.file_name().to_string_lossy().to_string();
+            if let Some(collision_filename) = filenames_by_hash.get(&key) {
+                report
+                    .hash_collisions
+                    .push((collision_filename.clone(), filename));
+                continue;
+            }
+            filenames_by_hash.insert(key.clone(), filename.clone());
+
+            let actual = OutputEnum::from(parsed_result);
+            match expected.remove(&key) {
+                Some(entry) if entry.output_enum != actual => report.mismatches.push(Mismatch {
+                    filename,
+                    expected: entry.output_enum,
+                    actual,
+                }),
+                Some(_) => {}
+                None => report.missing_in_expected.push(filename),
+            }
+        }
+        report.missing_in_examples = expected.into_values().map(|e| e.filename).collect();
+        report
+    }
+}
+
+impl Report {
+    /// Whether examples and `expected/` match completely
+    pub fn is_ok(&self) -> bool {
+        self.missing_in_expected.is_empty()
+            && self.missing_in_examples.is_empty()
+            && self.hash_collisions.is_empty()
+            && self.mismatches.is_empty()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_ok() {
+            return writeln!(f, "all examples match 'expected/'");
+        }
+        for Mismatch {
+            filename,
+            expected,
+            actual,
+        } in &self.mismatches
+        {
+            writeln!(
+                f,
+                "output of '{filename}' changed\nexpected: {expected:#?}\nactual: {actual:#?}"
+            )?;
+        }
+        for (a, b) in &self.hash_collisions {
+            writeln!(
+                f,
+                "hash collision between '{a}' and '{b}', they likely have the same content"
+            )?;
+        }
+        for filename in &self.missing_in_expected {
+            writeln!(f, "'{filename}' is missing in 'expected/'")?;
+        }
+        for filename in &self.missing_in_examples {
+            writeln!(f, "'{filename}' from 'expected/' is missing in examples")?;
+        }
+        Ok(())
+    }
+}
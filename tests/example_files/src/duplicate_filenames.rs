@@ -33,6 +33,6 @@ pub fn check_for_duplicate_filenames(expected: Expected) {
 entries with duplicate filenames:
 {duplicates:#?}
 This probably means a GDTF file was modified without changing its filename.
-The stale entries in `expected.toml` should be removed."
+The stale files in `expected/` should be removed, e.g. by running `update_expected`."
     );
 }
@@ -0,0 +1,67 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{create_dir_all, write},
+    io::Cursor,
+};
+
+use opengdtf::{
+    hash::hash_gdtf_to_string,
+    share::{Client, Fixture, ShareError},
+};
+
+use crate::Corpus;
+
+/// File name GDTF Share gives the download of `fixture`, e.g.
+/// `Ayrton@Domino_LT@V1.1_ADDStandardMode.gdtf`
+pub fn share_filename(fixture: &Fixture) -> String {
+    format!(
+        "{}@{}@{}.gdtf",
+        fixture.manufacturer, fixture.fixture, fixture.revision
+    )
+    .replace(' ', "_")
+}
+
+impl Corpus {
+    /// Download the examples that are in `expected/` but not in `examples/`
+    /// from GDTF Share. Revisions are found by file name and a download is only
+    /// kept if its hash is the one in `expected/`. Returns the file names that
+    /// could not be fetched.
+    pub fn fetch_missing_examples(&self, client: &Client) -> Result<Vec<String>, ShareError> {
+        let present: HashSet<String> = self
+            .opened_examples_iter()
+            .map(|(_, file)| hash_gdtf_to_string(file).unwrap())
+            .collect();
+        let mut missing: Vec<_> = self
+            .parse_expected()
+            .into_iter()
+            .filter(|(key, _)| !present.contains(key))
+            .collect();
+        if missing.is_empty() {
+            return Ok(vec![]);
+        }
+        missing.sort_by(|(_, a), (_, b)| a.filename.cmp(&b.filename));
+
+        let rids: HashMap<String, u64> = client
+            .list()?
+            .iter()
+            .map(|fixture| (share_filename(fixture), fixture.rid))
+            .collect();
+        create_dir_all(&self.examples_dir)?;
+
+        let mut not_fetched = vec![];
+        for (key, entry) in missing {
+            let Some(rid) = rids.get(&entry.filename) else {
+                not_fetched.push(entry.filename);
+                continue;
+            };
+            let file = client.download(*rid)?;
+            if hash_gdtf_to_string(Cursor::new(&file)).ok().as_ref() != Some(&key) {
+                not_fetched.push(entry.filename);
+                continue;
+            }
+            println!("fetched '{}'", entry.filename);
+            write(self.examples_dir.join(&entry.filename), file)?;
+        }
+        Ok(not_fetched)
+    }
+}
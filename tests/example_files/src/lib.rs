@@ -1,26 +1,30 @@
 use std::collections::BTreeMap;
-use std::io::Write;
 use std::{
-    fs::{self, create_dir_all, remove_dir_all, File},
+    fs::File,
     path::{Path, PathBuf},
 };
 
+mod corpus;
 mod duplicate_filenames;
+#[cfg(feature = "share-api")]
+mod fetch;
+pub use corpus::{Corpus, Mismatch, Report};
 pub use duplicate_filenames::check_for_duplicate_filenames;
+#[cfg(feature = "share-api")]
+pub use fetch::share_filename;
 
 use chrono::Utc;
 use once_cell::sync::Lazy;
-use opengdtf::{parse, Error, ParsedGdtf};
+use opengdtf::{Error, ParsedGdtf};
 use serde::{Deserialize, Serialize};
-use walkdir::{DirEntry, WalkDir};
+use walkdir::DirEntry;
 
 pub static EXAMPLE_FILES_DIR: Lazy<&Path> = Lazy::new(|| Path::new("tests/example_files"));
 pub static EXAMPLES_DIR: Lazy<PathBuf> = Lazy::new(|| EXAMPLE_FILES_DIR.join("examples"));
 pub static OUTPUTS_DIR: Lazy<PathBuf> = Lazy::new(|| EXAMPLE_FILES_DIR.join("outputs"));
-pub static EXPECTED_TOML_PATH: Lazy<PathBuf> =
-    Lazy::new(|| EXAMPLE_FILES_DIR.join("expected.toml"));
+pub static EXPECTED_DIR: Lazy<PathBuf> = Lazy::new(|| EXAMPLE_FILES_DIR.join("expected"));
 
-type Expected = BTreeMap<String, ExpectedEntry>;
+pub type Expected = BTreeMap<String, ExpectedEntry>;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ExpectedEntry {
@@ -82,56 +86,42 @@ impl From<Result<ParsedGdtf, Error>> for OutputEnum {
                 },
             }),
             Err(err) => OutputEnum::Err(ErrorInfo {
-                error: format!("{err}"),
+                error: {
+                    // include the causes, e.g. the zip error
+                    let mut error = format!("{err}");
+                    let mut source = std::error::Error::source(&err);
+                    while let Some(cause) = source {
+                        error.push_str(&format!(": {cause}"));
+                        source = cause.source();
+                    }
+                    error
+                },
             }),
         }
     }
 }
 
-pub fn parse_expected_toml() -> Expected {
-    let expected_str = fs::read_to_string(&*EXPECTED_TOML_PATH).unwrap();
-    toml::from_str(&expected_str).unwrap()
+/// The example files of this crate
+pub static EXAMPLES: Lazy<Corpus> = Lazy::new(|| Corpus::new(*EXAMPLE_FILES_DIR));
+
+pub fn parse_expected() -> Expected {
+    EXAMPLES.parse_expected()
 }
 
 pub fn examples_iter() -> impl Iterator<Item = DirEntry> {
-    WalkDir::new(&*EXAMPLES_DIR)
-        .into_iter()
-        .map(|result| result.unwrap())
-        .filter(|entry| !entry.file_type().is_dir())
-        .filter(|entry| {
-            Path::new(entry.file_name())
-                .extension()
-                .map_or_else(|| false, |extension| extension == "gdtf")
-        })
+    EXAMPLES.examples_iter()
 }
 
 pub fn opened_examples_iter() -> impl Iterator<Item = (DirEntry, File)> {
-    examples_iter().map(|entry| {
-        let file = File::open(entry.path()).unwrap();
-        (entry, file)
-    })
+    EXAMPLES.opened_examples_iter()
 }
 
 pub fn parsed_examples_iter(
 ) -> impl Iterator<Item = (DirEntry, File, Result<ParsedGdtf, opengdtf::Error>)> {
-    opened_examples_iter().map(|(entry, file)| {
-        let parse_result = parse(&file);
-        (entry, file, parse_result)
-    })
+    EXAMPLES.parsed_examples_iter()
 }
 
 pub fn examples_update_output_iter(
 ) -> impl Iterator<Item = (DirEntry, File, Result<ParsedGdtf, opengdtf::Error>)> {
-    // clean outputs
-    remove_dir_all(&*OUTPUTS_DIR).unwrap();
-    create_dir_all(&*OUTPUTS_DIR).unwrap();
-
-    parsed_examples_iter().map(|(entry, file, parse_result)| {
-        let file_name = entry.file_name().to_str().unwrap();
-
-        let mut output_file = File::create(OUTPUTS_DIR.join(file_name)).unwrap();
-        write!(output_file, "{parse_result:#?}").unwrap();
-
-        (entry, file, parse_result)
-    })
+    EXAMPLES.examples_update_output_iter()
 }